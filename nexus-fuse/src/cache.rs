@@ -93,6 +93,9 @@ impl Default for CacheConfig {
 #[derive(Debug, Clone)]
 pub struct CachePaths {
     pub foyer_dir: PathBuf,
+    /// Persisted inode assignments; inside `foyer_dir` so the directory
+    /// lock covers it too.
+    pub inode_file: PathBuf,
     pub sqlite_file: PathBuf,
     pub legacy_sqlite_file: PathBuf,
 }
@@ -105,8 +108,10 @@ impl CachePaths {
     /// to disk in cleartext, so it doesn't leak credentials. (#4055 R3)
     pub fn for_server(root_dir: &Path, server_url: &str, principal: &str) -> Self {
         let hash = principal_hash(server_url, principal);
+        let foyer_dir = root_dir.join(format!("nexus_{hash:016x}.foyer"));
         Self {
-            foyer_dir: root_dir.join(format!("nexus_{hash:016x}.foyer")),
+            inode_file: foyer_dir.join("inodes.bin"),
+            foyer_dir,
             sqlite_file: root_dir.join(format!("nexus_{hash:016x}.db")),
             legacy_sqlite_file: root_dir.join(format!("{}.db", legacy_server_filename(server_url))),
         }
@@ -209,6 +214,7 @@ pub struct FileCache {
     runtime: CacheRuntime,
     metadata: Mutex<HashMap<String, CacheMeta>>,
     config: CacheConfig,
+    inode_file: PathBuf,
     // Exclusive flock holder on the foyer directory's lock file.
     // Held for the lifetime of the FileCache so two daemon processes for
     // the same (server_url, principal) cannot open the same foyer dir
@@ -363,10 +369,17 @@ impl FileCache {
             runtime: CacheRuntime::new(runtime),
             metadata: Mutex::new(HashMap::new()),
             config,
+            inode_file: paths.inode_file,
             _dir_lock: dir_lock,
         })
     }
 
+    /// Where this cache namespace keeps its inode assignments (see
+    /// [`InodeStore`](crate::inode_store::InodeStore)).
+    pub fn inode_file(&self) -> &Path {
+        &self.inode_file
+    }
+
     fn now() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
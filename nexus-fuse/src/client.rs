@@ -12,14 +12,15 @@
 #![allow(dead_code)]
 
 use crate::error::NexusClientError;
-use log::debug;
+use log::{debug, info, warn};
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, IF_NONE_MATCH};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::future::Future;
-use std::sync::OnceLock;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 
 /// Default connection-pool tunables. Tuned for FUSE fan-out: enough
@@ -31,6 +32,69 @@ const TCP_KEEPALIVE_SECS: u64 = 30;
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 const CONNECT_TIMEOUT_SECS: u64 = 5;
 
+/// Upper bound on a single backoff sleep, however many retries remain.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Retry policy for idempotent requests (stat/list/read/exists/whoami).
+///
+/// Writes, mkdir, delete and rename are never retried: a request that
+/// timed out may still have been applied server-side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each subsequent one.
+    pub base_delay: Duration,
+    /// Total time budget across all attempts and sleeps. No retry is
+    /// scheduled if its backoff would end past the deadline.
+    pub deadline: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(100),
+            deadline: Duration::from_secs(REQUEST_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Jittered delay before retry number `retry` (0-based): uniform in
+    /// `[d/2, d]` where `d = base_delay * 2^retry`, capped at
+    /// `RETRY_MAX_DELAY`. Jitter keeps many FUSE threads that failed
+    /// together from hammering the server in lockstep.
+    fn backoff(&self, retry: u32) -> Duration {
+        use std::hash::{BuildHasher, Hasher};
+
+        let exp = self
+            .base_delay
+            .saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX))
+            .min(RETRY_MAX_DELAY);
+        let half = exp / 2;
+        let noise = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        let jitter_nanos = (half.as_nanos() as u64)
+            .checked_add(1)
+            .map_or(0, |n| noise % n);
+        half + Duration::from_nanos(jitter_nanos)
+    }
+}
+
+/// Whether a failed idempotent request is worth retrying: connection
+/// failures, timeouts and 5xx responses. Auth, policy and not-found
+/// errors are final.
+fn is_retryable(err: &NexusClientError) -> bool {
+    match err {
+        NexusClientError::Timeout { .. } | NexusClientError::ConnectionRefused(_) => true,
+        NexusClientError::HttpError(e) => e.is_connect() || e.is_timeout(),
+        NexusClientError::ServerError { status, .. } => (500..600).contains(status),
+        _ => false,
+    }
+}
+
 /// User information returned by whoami endpoint.
 #[derive(Debug, Deserialize)]
 pub struct UserInfo {
@@ -143,16 +207,33 @@ fn http_runtime() -> &'static Runtime {
     })
 }
 
+/// Read a bearer token from `path`, trimming surrounding whitespace.
+pub fn read_token_file(path: &Path) -> Result<String, NexusClientError> {
+    let token = std::fs::read_to_string(path).map_err(|e| {
+        NexusClientError::Other(anyhow::anyhow!(
+            "Failed to read token file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    Ok(token.trim().to_string())
+}
+
 /// Nexus HTTP client.
 ///
 /// `Clone` is cheap: `reqwest::Client` shares its connection pool via
-/// an internal `Arc`.
+/// an internal `Arc`, and clones share one bearer token so a refresh
+/// from any FUSE thread is seen by all of them.
 #[derive(Clone)]
 pub struct NexusClient {
     client: Client,
     base_url: String,
-    api_key: String,
+    api_key: Arc<RwLock<String>>,
+    /// When set, the token is re-read from this file after a 401 so
+    /// short-lived tokens can be rotated underneath a live mount.
+    token_file: Option<PathBuf>,
     agent_id: Option<String>,
+    retry: RetryConfig,
 }
 
 impl NexusClient {
@@ -178,11 +259,118 @@ impl NexusClient {
         Ok(Self {
             client,
             base_url: base_url.trim_end_matches('/').to_string(),
-            api_key: api_key.to_string(),
+            api_key: Arc::new(RwLock::new(api_key.to_string())),
+            token_file: None,
             agent_id,
+            retry: RetryConfig::default(),
         })
     }
 
+    /// Set the retry policy for idempotent requests.
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Run an idempotent request, retrying transient failures with
+    /// jittered exponential backoff per `self.retry`.
+    async fn with_retry<T, F, Fut>(&self, op: &str, attempt: F) -> Result<T, NexusClientError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, NexusClientError>>,
+    {
+        let started = Instant::now();
+        let mut retries = 0;
+        loop {
+            let err = match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            if retries >= self.retry.max_retries || !is_retryable(&err) {
+                return Err(err);
+            }
+            let delay = self.retry.backoff(retries);
+            if started.elapsed() + delay > self.retry.deadline {
+                debug!("{} retry deadline exhausted after {} retries", op, retries);
+                return Err(err);
+            }
+            retries += 1;
+            warn!(
+                "{} failed ({}); retry {}/{} in {:?}",
+                op, err, retries, self.retry.max_retries, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Create a client whose bearer token lives in `token_file`.
+    ///
+    /// The file is read once up front and lazily re-read whenever the
+    /// server answers 401; if the token changed, the request is retried
+    /// once with the new one.
+    pub fn with_token_file(
+        base_url: &str,
+        token_file: &Path,
+        agent_id: Option<String>,
+    ) -> Result<Self, NexusClientError> {
+        let token = read_token_file(token_file)?;
+        let mut client = Self::new(base_url, &token, agent_id)?;
+        client.token_file = Some(token_file.to_path_buf());
+        Ok(client)
+    }
+
+    /// Current bearer token.
+    pub fn token(&self) -> String {
+        self.api_key
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Re-read the token file. Returns `true` if a different, non-empty
+    /// token was picked up (i.e. a retry is worthwhile).
+    fn refresh_token(&self) -> bool {
+        let Some(path) = self.token_file.as_deref() else {
+            return false;
+        };
+        let fresh = match read_token_file(path) {
+            Ok(token) if !token.is_empty() => token,
+            Ok(_) => {
+                warn!(
+                    "Token file {} is empty; keeping current token",
+                    path.display()
+                );
+                return false;
+            }
+            Err(e) => {
+                warn!("{}", e);
+                return false;
+            }
+        };
+        let mut current = self.api_key.write().unwrap_or_else(|e| e.into_inner());
+        if *current == fresh {
+            return false;
+        }
+        info!("Picked up rotated token from {}", path.display());
+        *current = fresh;
+        true
+    }
+
+    /// Send a request built by `build`, which receives freshly computed
+    /// auth headers. On a 401 the token file (if any) is re-read and, if
+    /// the token rotated, the request is rebuilt and sent once more.
+    async fn send<F>(&self, build: F) -> Result<Response, NexusClientError>
+    where
+        F: Fn(HeaderMap) -> RequestBuilder,
+    {
+        let resp = build(self.headers()).send().await?;
+        if resp.status() == StatusCode::UNAUTHORIZED && self.refresh_token() {
+            debug!("Retrying {} with refreshed token", resp.url());
+            return Ok(build(self.headers()).send().await?);
+        }
+        Ok(resp)
+    }
+
     /// Run a future to completion on the shared process-wide HTTP runtime.
     ///
     /// # Contract
@@ -273,7 +461,7 @@ impl NexusClient {
         let mut headers = HeaderMap::new();
         // Note: HeaderValue::from_str can fail on non-ASCII characters
         // In practice, API keys and agent IDs should be ASCII-safe
-        if let Ok(auth_value) = HeaderValue::from_str(&format!("Bearer {}", self.api_key)) {
+        if let Ok(auth_value) = HeaderValue::from_str(&format!("Bearer {}", self.token())) {
             headers.insert(AUTHORIZATION, auth_value);
         }
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
@@ -302,11 +490,7 @@ impl NexusClient {
         debug!("POST {} {:?}", url, rpc_request);
 
         let resp = self
-            .client
-            .post(&url)
-            .headers(self.headers())
-            .json(&rpc_request)
-            .send()
+            .send(|headers| self.client.post(&url).headers(headers).json(&rpc_request))
             .await?;
 
         if !resp.status().is_success() {
@@ -331,10 +515,16 @@ impl NexusClient {
 
     /// Async core: whoami.
     pub async fn whoami_async(&self) -> Result<UserInfo, NexusClientError> {
+        self.with_retry("whoami", || self.whoami_once()).await
+    }
+
+    async fn whoami_once(&self) -> Result<UserInfo, NexusClientError> {
         let url = format!("{}/api/auth/whoami", self.base_url);
         debug!("GET {}", url);
 
-        let resp = self.client.get(&url).headers(self.headers()).send().await?;
+        let resp = self
+            .send(|headers| self.client.get(&url).headers(headers))
+            .await?;
 
         if !resp.status().is_success() {
             let status = resp.status();
@@ -395,15 +585,13 @@ impl NexusClient {
             files: Vec<DetailedEntry>,
         }
 
+        let params = json!({
+            "path": path,
+            "recursive": false,
+            "details": true
+        });
         let result: ListResult = self
-            .rpc_call_async(
-                "list",
-                json!({
-                    "path": path,
-                    "recursive": false,
-                    "details": true
-                }),
-            )
+            .with_retry("list", || self.rpc_call_async("list", params.clone()))
             .await?;
 
         // Convert to FileEntry objects - extract immediate children only
@@ -464,7 +652,10 @@ impl NexusClient {
 
     /// Async core: stat.
     pub async fn stat_async(&self, path: &str) -> Result<FileMetadata, NexusClientError> {
-        self.rpc_call_async("stat", json!({"path": path})).await
+        self.with_retry("stat", || {
+            self.rpc_call_async("stat", json!({"path": path}))
+        })
+        .await
     }
 
     /// Get file/directory metadata.
@@ -508,6 +699,15 @@ impl NexusClient {
         &self,
         path: &str,
         if_none_match: Option<&str>,
+    ) -> Result<EncodedReadResponse, NexusClientError> {
+        self.with_retry("read", || self.read_encoded_once(path, if_none_match))
+            .await
+    }
+
+    async fn read_encoded_once(
+        &self,
+        path: &str,
+        if_none_match: Option<&str>,
     ) -> Result<EncodedReadResponse, NexusClientError> {
         let url = format!("{}/api/nfs/read", self.base_url);
 
@@ -518,22 +718,18 @@ impl NexusClient {
             "params": {"path": path}
         });
 
-        let mut headers = self.headers();
-        if let Some(etag) = if_none_match {
-            headers.insert(
-                IF_NONE_MATCH,
-                HeaderValue::from_str(&format!("\"{}\"", etag)).unwrap(),
-            );
-        }
-
         debug!("POST {} (etag: {:?})", url, if_none_match);
 
         let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .json(&rpc_request)
-            .send()
+            .send(|mut headers| {
+                if let Some(etag) = if_none_match {
+                    headers.insert(
+                        IF_NONE_MATCH,
+                        HeaderValue::from_str(&format!("\"{}\"", etag)).unwrap(),
+                    );
+                }
+                self.client.post(&url).headers(headers).json(&rpc_request)
+            })
             .await?;
 
         // Handle 304 Not Modified
@@ -721,7 +917,11 @@ impl NexusClient {
             exists: bool,
         }
 
-        let result: ExistsResult = self.rpc_call_async("exists", json!({"path": path})).await?;
+        let result: ExistsResult = self
+            .with_retry("exists", || {
+                self.rpc_call_async("exists", json!({"path": path}))
+            })
+            .await?;
         Ok(result.exists)
     }
 
@@ -754,7 +954,7 @@ impl NexusClient {
 mod tests {
     use super::*;
     use base64::{engine::general_purpose::STANDARD, Engine};
-    use mockito::Server;
+    use mockito::{Mock, Server};
     use std::io;
 
     #[test]
@@ -829,4 +1029,210 @@ mod tests {
         assert!(matches!(err, NexusClientError::Other(_)));
         assert_eq!(err.to_errno(), libc::EIO);
     }
+
+    fn stat_body() -> &'static str {
+        r#"{"jsonrpc":"2.0","id":1,"result":{"size":7,"is_directory":false}}"#
+    }
+
+    #[test]
+    fn unauthorized_rereads_token_file_and_retries_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_path = dir.path().join("token");
+        std::fs::write(&token_path, "old-token\n").unwrap();
+
+        let mut server = Server::new();
+        let expired = server
+            .mock("POST", "/api/nfs/stat")
+            .match_header("authorization", "Bearer old-token")
+            .with_status(401)
+            .with_body("token expired")
+            .expect(1)
+            .create();
+        let rotated = server
+            .mock("POST", "/api/nfs/stat")
+            .match_header("authorization", "Bearer new-token")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(stat_body())
+            .expect(1)
+            .create();
+
+        let client = NexusClient::with_token_file(&server.url(), &token_path, None).unwrap();
+        assert_eq!(client.token(), "old-token");
+        std::fs::write(&token_path, "new-token\n").unwrap();
+
+        let meta = client.stat("/data/file.txt").unwrap();
+
+        assert_eq!(meta.size, 7);
+        assert_eq!(client.token(), "new-token");
+        expired.assert();
+        rotated.assert();
+    }
+
+    #[test]
+    fn unauthorized_with_unchanged_token_file_fails_without_retry() {
+        let dir = tempfile::tempdir().unwrap();
+        let token_path = dir.path().join("token");
+        std::fs::write(&token_path, "same-token").unwrap();
+
+        let mut server = Server::new();
+        let denied = server
+            .mock("POST", "/api/nfs/stat")
+            .with_status(401)
+            .with_body("token expired")
+            .expect(1)
+            .create();
+
+        let client = NexusClient::with_token_file(&server.url(), &token_path, None).unwrap();
+        let err = client.stat("/data/file.txt").unwrap_err();
+
+        assert!(matches!(err, NexusClientError::AccessDenied(_)));
+        denied.assert();
+    }
+
+    #[test]
+    fn static_api_key_does_not_retry_on_unauthorized() {
+        let mut server = Server::new();
+        let denied = server
+            .mock("GET", "/api/auth/whoami")
+            .with_status(401)
+            .expect(1)
+            .create();
+
+        let client = NexusClient::new(&server.url(), "test-key", None).unwrap();
+        let err = client.whoami().unwrap_err();
+
+        assert!(matches!(err, NexusClientError::AccessDenied(_)));
+        denied.assert();
+    }
+
+    /// Mocks `POST /api/nfs/{method}` to answer the first `failures`
+    /// requests with 503 and later ones with `body`, expecting
+    /// `successes` of those. mockito serves the first matching mock still
+    /// short of its expected hits, so the 503 mock wins until it has been
+    /// hit `failures` times.
+    fn flaky_mocks(
+        server: &mut Server,
+        method: &str,
+        failures: usize,
+        successes: usize,
+        body: &str,
+    ) -> (Mock, Mock) {
+        let path = format!("/api/nfs/{method}");
+        let unavailable = server
+            .mock("POST", path.as_str())
+            .with_status(503)
+            .with_body("try again")
+            .expect(failures)
+            .create();
+        let ok = server
+            .mock("POST", path.as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .expect(successes)
+            .create();
+        (unavailable, ok)
+    }
+
+    fn fast_retry(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            deadline: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn read_retries_transient_failures_then_returns_data() {
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":{{"__type__":"bytes","data":"{}"}}}}"#,
+            STANDARD.encode(b"eventually")
+        );
+        let mut server = Server::new();
+        let (unavailable, ok) = flaky_mocks(&mut server, "read", 2, 1, &body);
+        let client = NexusClient::new(&server.url(), "test-key", None)
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+
+        let content = client.read("/data/file.bin").unwrap();
+
+        assert_eq!(content, b"eventually");
+        unavailable.assert();
+        ok.assert();
+    }
+
+    #[test]
+    fn retries_stop_at_max_retries() {
+        let mut server = Server::new();
+        let (unavailable, ok) = flaky_mocks(&mut server, "stat", 3, 0, "");
+        let client = NexusClient::new(&server.url(), "test-key", None)
+            .unwrap()
+            .with_retry_config(fast_retry(2));
+
+        let err = client.stat("/data/file.bin").unwrap_err();
+
+        assert!(matches!(
+            err,
+            NexusClientError::ServerError { status: 503, .. }
+        ));
+        unavailable.assert();
+        ok.assert();
+    }
+
+    #[test]
+    fn retries_respect_total_deadline() {
+        let mut server = Server::new();
+        let (unavailable, ok) = flaky_mocks(&mut server, "stat", 1, 0, "");
+        let client = NexusClient::new(&server.url(), "test-key", None)
+            .unwrap()
+            .with_retry_config(RetryConfig {
+                max_retries: 10,
+                base_delay: Duration::from_secs(2),
+                deadline: Duration::from_millis(500),
+            });
+
+        assert!(client.stat("/data/file.bin").is_err());
+        unavailable.assert();
+        ok.assert();
+    }
+
+    #[test]
+    fn writes_are_not_retried() {
+        let mut server = Server::new();
+        let (unavailable, ok) = flaky_mocks(
+            &mut server,
+            "write",
+            1,
+            0,
+            r#"{"jsonrpc":"2.0","id":1,"result":{}}"#,
+        );
+        let client = NexusClient::new(&server.url(), "test-key", None)
+            .unwrap()
+            .with_retry_config(fast_retry(3));
+
+        let err = client.write("/data/file.bin", b"payload").unwrap_err();
+
+        assert!(matches!(
+            err,
+            NexusClientError::ServerError { status: 503, .. }
+        ));
+        unavailable.assert();
+        ok.assert();
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter_and_caps() {
+        let retry = RetryConfig {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            deadline: Duration::from_secs(30),
+        };
+        for (n, full) in [(0, 100), (1, 200), (2, 400)] {
+            let delay = retry.backoff(n);
+            assert!(delay >= Duration::from_millis(full / 2), "{n}: {delay:?}");
+            assert!(delay <= Duration::from_millis(full), "{n}: {delay:?}");
+        }
+        assert!(retry.backoff(40) <= RETRY_MAX_DELAY);
+    }
 }
@@ -4,6 +4,7 @@ use crate::cache::FileCache;
 use crate::cached_read::{read_with_cache, CachedReadResult};
 use crate::client::{FileEntry, NexusClient};
 use crate::error::NexusClientError;
+use crate::inode_store::InodeStore;
 use crate::metrics;
 use crate::passthrough::{ActivePassthrough, OpenAccess, PassthroughDecision, PassthroughManager};
 use fuser::{
@@ -114,14 +115,25 @@ impl OpenFileCache {
 /// lock. Eliminates the race condition in `get_or_create_inode()` (Issue 7A)
 /// where releasing one lock before acquiring another could allow duplicate
 /// allocations. LRU bounds prevent unbounded memory growth (Issue 1A).
+///
+/// With a persistent `store`, the store owns allocation and the LRU maps
+/// are a hot front for it (both bounded by `MAX_INODE_ENTRIES`): an
+/// evicted mapping is recovered from the store instead of being
+/// renumbered, and assignments survive remounts.
 struct InodeTable {
     inode_to_path: LruCache<u64, String>,
     path_to_inode: LruCache<String, u64>,
     next_inode: u64,
+    store: Option<InodeStore>,
 }
 
 impl InodeTable {
+    #[cfg(test)]
     fn new() -> Self {
+        Self::with_store(None)
+    }
+
+    fn with_store(store: Option<InodeStore>) -> Self {
         let cap = NonZeroUsize::new(MAX_INODE_ENTRIES).unwrap();
         let mut inode_to_path = LruCache::new(cap);
         let mut path_to_inode = LruCache::new(cap);
@@ -134,6 +146,7 @@ impl InodeTable {
             inode_to_path,
             path_to_inode,
             next_inode: FUSE_ROOT_ID + 1,
+            store,
         }
     }
 
@@ -152,13 +165,36 @@ impl InodeTable {
     /// counter are behind the same Mutex.
     fn get_or_create(&mut self, path: &str) -> u64 {
         if let Some(&inode) = self.path_to_inode.get(path) {
+            // Keep the store's recency in step, so it forgets cold paths
+            // rather than hot ones that never miss the front.
+            if let Some(store) = self.store.as_mut() {
+                store.touch(path);
+            }
             return inode;
         }
 
-        let inode = self.next_inode;
-        self.next_inode += 1;
+        let inode = match self.store.as_mut() {
+            Some(store) => {
+                let (inode, forgotten) = store.get_or_assign(path);
+                // The store may hand the forgotten path's inode out again
+                // after the grace period, so the front must not keep it.
+                if let Some(stale) = forgotten.and_then(|p| self.path_to_inode.pop(&p)) {
+                    self.inode_to_path.pop(&stale);
+                }
+                inode
+            }
+            None => {
+                let inode = self.next_inode;
+                self.next_inode += 1;
+                inode
+            }
+        };
+        self.insert(path, inode);
+        inode
+    }
 
-        // Insert into both maps, synchronizing evictions (Issue #3029 / Bug 4).
+    /// Insert into both maps, synchronizing evictions (Issue #3029 / Bug 4).
+    fn insert(&mut self, path: &str, inode: u64) {
         // `push` returns the evicted (key, value) if the cache was at capacity.
         if let Some((_evicted_path, evicted_inode)) =
             self.path_to_inode.push(path.to_string(), inode)
@@ -175,45 +211,40 @@ impl InodeTable {
 
         // Re-pin root after potential evictions
         self.ensure_root_pinned();
-
-        inode
     }
 
-    /// Get path for an inode.
+    /// Get path for an inode, falling back to the store after eviction.
     fn get_path(&mut self, inode: u64) -> Option<String> {
-        self.inode_to_path.get(&inode).cloned()
+        if let Some(path) = self.inode_to_path.get(&inode) {
+            return Some(path.clone());
+        }
+        let path = self.store.as_ref()?.path(inode)?.to_string();
+        self.insert(&path, inode);
+        Some(path)
     }
 
-    /// Remove a path mapping (e.g., after rename/delete).
-    #[allow(dead_code)]
+    /// Remove a path mapping after delete. A persistent store keeps the
+    /// inode reserved for its grace period before reuse.
     fn remove_path(&mut self, path: &str) -> Option<u64> {
-        if let Some(inode) = self.path_to_inode.pop(path) {
+        let cached = self.path_to_inode.pop(path);
+        if let Some(inode) = cached {
             self.inode_to_path.pop(&inode);
-            Some(inode)
-        } else {
-            None
         }
+        let stored = self.store.as_mut().and_then(|store| store.remove(path));
+        cached.or(stored)
     }
 
     /// Update path for an existing inode (rename).
     /// Uses `push` for synchronized eviction and re-pins root (Issue #3029 / Bug 4).
     fn rename_path(&mut self, old_path: &str, new_path: &str) {
+        if let Some(store) = self.store.as_mut() {
+            store.rename(old_path, new_path);
+        }
         if let Some(inode) = self.path_to_inode.pop(old_path) {
             self.inode_to_path.pop(&inode);
 
             // Re-insert with synchronized eviction (same pattern as get_or_create)
-            if let Some((_evicted_path, evicted_inode)) =
-                self.path_to_inode.push(new_path.to_string(), inode)
-            {
-                self.inode_to_path.pop(&evicted_inode);
-            }
-            if let Some((_evicted_inode, evicted_path)) =
-                self.inode_to_path.push(inode, new_path.to_string())
-            {
-                self.path_to_inode.pop(&evicted_path);
-            }
-
-            self.ensure_root_pinned();
+            self.insert(new_path, inode);
         }
     }
 
@@ -226,7 +257,10 @@ impl InodeTable {
     /// Peek inode for a path without promoting in LRU (Issue #3029 / Issue 8).
     /// Used for speculative lookups where promotion is undesirable.
     fn peek_inode(&self, path: &str) -> Option<u64> {
-        self.path_to_inode.peek(path).copied()
+        self.path_to_inode
+            .peek(path)
+            .copied()
+            .or_else(|| self.store.as_ref()?.inode(path))
     }
 }
 
@@ -282,9 +316,15 @@ impl NexusFs {
         file_cache: Option<Arc<FileCache>>,
         passthrough: Option<Arc<PassthroughManager>>,
     ) -> Self {
+        // Stable inodes across remounts ride on the persistent cache: its
+        // directory is namespaced per (server, principal) and locked.
+        let inode_store = file_cache.as_ref().map(|cache| {
+            let max_paths = NonZeroUsize::new(MAX_INODE_ENTRIES).unwrap();
+            InodeStore::open(cache.inode_file(), FUSE_ROOT_ID + 1, max_paths)
+        });
         Self {
             client: Arc::new(client),
-            inodes: Mutex::new(InodeTable::new()),
+            inodes: Mutex::new(InodeTable::with_store(inode_store)),
             attr_cache: Mutex::new(LruCache::new(NonZeroUsize::new(10000).unwrap())),
             dir_cache: Mutex::new(LruCache::new(NonZeroUsize::new(1000).unwrap())),
             file_cache,
@@ -1102,6 +1142,7 @@ impl Filesystem for NexusFs {
         match self.client.delete(&path) {
             Ok(_) => {
                 self.invalidate_path(&path);
+                self.inodes.lock().unwrap().remove_path(&path);
                 reply.ok();
             }
             Err(e) => {
@@ -1140,6 +1181,7 @@ impl Filesystem for NexusFs {
         match self.client.delete(&path) {
             Ok(_) => {
                 self.invalidate_path(&path);
+                self.inodes.lock().unwrap().remove_path(&path);
                 reply.ok();
             }
             Err(e) => {
@@ -1753,6 +1795,7 @@ mod tests {
             inode_to_path: LruCache::new(cap),
             path_to_inode: LruCache::new(cap),
             next_inode: FUSE_ROOT_ID + 1,
+            store: None,
         };
         // Manually insert root
         table.inode_to_path.put(FUSE_ROOT_ID, "/".to_string());
@@ -1785,6 +1828,7 @@ mod tests {
             inode_to_path: LruCache::new(cap),
             path_to_inode: LruCache::new(cap),
             next_inode: FUSE_ROOT_ID + 1,
+            store: None,
         };
         table.inode_to_path.put(FUSE_ROOT_ID, "/".to_string());
         table.path_to_inode.put("/".to_string(), FUSE_ROOT_ID);
@@ -1842,6 +1886,44 @@ mod tests {
         assert_eq!(table.get_path(inode), None);
     }
 
+    #[test]
+    fn test_remount_preserves_inodes() {
+        let root = tempfile::tempdir().unwrap();
+        let mount = || {
+            let config = CacheConfig::new(
+                root.path().to_path_buf(),
+                4 * 1024 * 1024,
+                64 * 1024 * 1024,
+                MAX_FILE_SIZE,
+            )
+            .unwrap();
+            let cache = FileCache::new_with_config("http://remount.test", "test", config).unwrap();
+            let client = NexusClient::new("http://remount.test", "test-key", None).unwrap();
+            NexusFs::new(client, Some(Arc::new(cache)), None)
+        };
+
+        let (kept, deleted) = {
+            let fs = mount();
+            let mut inodes = fs.inodes.lock().unwrap();
+            let kept = inodes.get_or_create("/docs/a.txt");
+            let deleted = inodes.get_or_create("/docs/b.txt");
+            inodes.remove_path("/docs/b.txt");
+            (kept, deleted)
+        }; // unmount: drops the FileCache lock and saves the inode map
+
+        let fs = mount();
+        let mut inodes = fs.inodes.lock().unwrap();
+        assert_eq!(inodes.get_or_create("/docs/a.txt"), kept);
+        assert_eq!(inodes.get_path(kept).as_deref(), Some("/docs/a.txt"));
+
+        // New files get fresh inodes that collide with neither the kept
+        // file nor the recently deleted one.
+        let fresh = inodes.get_or_create("/docs/c.txt");
+        assert_ne!(fresh, kept);
+        assert_ne!(fresh, deleted);
+        assert_ne!(fresh, FUSE_ROOT_ID);
+    }
+
     #[test]
     fn test_generation_xattr_name_list_is_nul_terminated() {
         assert_eq!(
@@ -0,0 +1,538 @@
+//! Persistent inode assignments so inodes survive remounts.
+//!
+//! `NexusFs` hands out inode numbers on first lookup. Without persistence a
+//! remount renumbers every file, which breaks applications that cache
+//! inodes and hardlink detection (`st_ino` equality). `InodeStore` keeps
+//! the path ↔ inode assignments in a sidecar file inside the foyer cache
+//! directory, so a remount of the same (server, principal) namespace
+//! reuses them — and shares the cache's exclusive directory lock.
+//!
+//! Inodes of deleted paths stay reserved for [`RECLAIM_GRACE_SECS`] so a
+//! kernel or application still holding the old number never sees it
+//! reassigned to a different file; after that they are handed out again.
+//!
+//! The store remembers at most `max_paths` assignments. Past that the least
+//! recently used path is forgotten as if deleted: its inode is reserved for
+//! the grace period and the path gets a new one if it comes back.
+//!
+//! Every change is appended to a journal next to the snapshot as it
+//! happens, so a daemon that is killed loses at most a torn last record.
+//! The journal is folded into a fresh snapshot on open, on drop, and
+//! whenever it grows past the number of stored paths.
+
+use anyhow::{Context, Result};
+use log::warn;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a deleted path's inode stays reserved before reuse (1 hour).
+pub const RECLAIM_GRACE_SECS: u64 = 3600;
+
+/// Bumped when `InodeRecords` changes shape; older files are discarded.
+const FORMAT_VERSION: u32 = 2;
+
+/// Journal records tolerated before compaction, however few paths are
+/// stored.
+const MIN_COMPACT_RECORDS: usize = 4096;
+
+/// On-disk form of the snapshot.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct InodeRecords {
+    version: u32,
+    /// Only a journal stamped with the same generation extends this
+    /// snapshot; any other journal predates it.
+    generation: u64,
+    next_inode: u64,
+    /// Assignments, least recently used first.
+    paths: Vec<(String, u64)>,
+    /// `(inode, deleted_at_secs)` of deleted paths, oldest first.
+    deleted: VecDeque<(u64, u64)>,
+    /// Reclaimed inodes, reused before `next_inode` advances.
+    free: Vec<u64>,
+}
+
+/// One journaled change. Reclaiming is not journaled: it only depends on
+/// the deletion times, so it is redone after replay.
+#[derive(Debug, Serialize, Deserialize)]
+enum JournalOp {
+    Assign {
+        path: String,
+        inode: u64,
+    },
+    /// `path` was deleted, or forgotten to stay under `max_paths`.
+    Remove {
+        path: String,
+        at_secs: u64,
+    },
+    Rename {
+        from: String,
+        to: String,
+        at_secs: u64,
+    },
+}
+
+pub struct InodeStore {
+    file: PathBuf,
+    journal_file: PathBuf,
+    /// Open journal, or `None` after a write failure; changes are then
+    /// only persisted by the next snapshot.
+    journal: Option<File>,
+    /// Changes made since the last snapshot.
+    unsaved: usize,
+    generation: u64,
+    next_inode: u64,
+    max_paths: usize,
+    /// Kept unbounded so that every eviction goes through `record`;
+    /// `max_paths` is enforced there.
+    paths: LruCache<String, u64>,
+    /// Reverse of `paths`.
+    inodes: HashMap<u64, String>,
+    deleted: VecDeque<(u64, u64)>,
+    free: Vec<u64>,
+}
+
+impl InodeStore {
+    /// Open the store backed by `file`, loading prior assignments and
+    /// replaying its journal if they exist. Fresh stores allocate from
+    /// `first_inode`. At most `max_paths` assignments are kept.
+    ///
+    /// A file that can't be read or decoded is discarded with a warning:
+    /// losing inode stability is better than failing the mount.
+    pub fn open(file: &Path, first_inode: u64, max_paths: NonZeroUsize) -> Self {
+        let fresh = || InodeRecords {
+            version: FORMAT_VERSION,
+            next_inode: first_inode,
+            ..Default::default()
+        };
+        let records = match std::fs::read(file) {
+            Ok(bytes) => match bincode::deserialize::<InodeRecords>(&bytes) {
+                Ok(records) if records.version == FORMAT_VERSION => records,
+                Ok(records) => {
+                    warn!(
+                        "Discarding inode map {} with unsupported version {}",
+                        file.display(),
+                        records.version
+                    );
+                    fresh()
+                }
+                Err(e) => {
+                    warn!("Discarding unreadable inode map {}: {}", file.display(), e);
+                    fresh()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => fresh(),
+            Err(e) => {
+                warn!("Failed to read inode map {}: {}", file.display(), e);
+                fresh()
+            }
+        };
+
+        let mut store = Self {
+            file: file.to_path_buf(),
+            journal_file: file.with_extension("journal"),
+            journal: None,
+            unsaved: 0,
+            generation: records.generation,
+            next_inode: records.next_inode,
+            max_paths: max_paths.get(),
+            paths: LruCache::unbounded(),
+            inodes: HashMap::new(),
+            deleted: records.deleted,
+            free: records.free,
+        };
+        for (path, inode) in records.paths {
+            store.inodes.insert(inode, path.clone());
+            store.paths.push(path, inode);
+        }
+        store.replay_journal();
+        let now = Self::now();
+        while store.paths.len() > store.max_paths {
+            let Some((path, _)) = store.paths.peek_lru() else {
+                break;
+            };
+            let path = path.clone();
+            store.apply(&JournalOp::Remove { path, at_secs: now });
+        }
+        if let Err(e) = store.save() {
+            warn!("Failed to persist inode map: {:#}", e);
+        }
+        store
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// Inode for `path`, assigning one if the path is new. New paths get a
+    /// reclaimed inode when one is available, else the next unused number.
+    ///
+    /// Also returns the path forgotten to make room, if any; callers that
+    /// cache assignments must drop it, since its inode may be reused once
+    /// the grace period ends.
+    pub fn get_or_assign(&mut self, path: &str) -> (u64, Option<String>) {
+        if let Some(&inode) = self.paths.get(path) {
+            return (inode, None);
+        }
+        let now = Self::now();
+        self.reclaim(now);
+        let inode = self.free.last().copied().unwrap_or(self.next_inode);
+        self.record(JournalOp::Assign {
+            path: path.to_string(),
+            inode,
+        });
+        let mut forgotten = None;
+        if self.paths.len() > self.max_paths {
+            if let Some((evicted, _)) = self.paths.peek_lru() {
+                let path = evicted.clone();
+                self.record(JournalOp::Remove {
+                    path: path.clone(),
+                    at_secs: now,
+                });
+                forgotten = Some(path);
+            }
+        }
+        (inode, forgotten)
+    }
+
+    /// Mark `path` as recently used, so it is forgotten last.
+    pub fn touch(&mut self, path: &str) {
+        self.paths.get(path);
+    }
+
+    pub fn inode(&self, path: &str) -> Option<u64> {
+        self.paths.peek(path).copied()
+    }
+
+    pub fn path(&self, inode: u64) -> Option<&str> {
+        self.inodes.get(&inode).map(String::as_str)
+    }
+
+    /// Forget `path`, reserving its inode for the grace period.
+    pub fn remove(&mut self, path: &str) -> Option<u64> {
+        let inode = self.inode(path)?;
+        self.record(JournalOp::Remove {
+            path: path.to_string(),
+            at_secs: Self::now(),
+        });
+        Some(inode)
+    }
+
+    /// Move `old_path`'s inode to `new_path`. A path already at `new_path`
+    /// is replaced, as POSIX rename does, and its inode reserved.
+    pub fn rename(&mut self, old_path: &str, new_path: &str) {
+        if !self.paths.contains(old_path) {
+            return;
+        }
+        self.record(JournalOp::Rename {
+            from: old_path.to_string(),
+            to: new_path.to_string(),
+            at_secs: Self::now(),
+        });
+    }
+
+    /// Move inodes whose grace period has passed by `now_secs` to the free
+    /// list.
+    pub fn reclaim(&mut self, now_secs: u64) {
+        while let Some(&(inode, deleted_at)) = self.deleted.front() {
+            if now_secs.saturating_sub(deleted_at) < RECLAIM_GRACE_SECS {
+                break;
+            }
+            self.deleted.pop_front();
+            self.free.push(inode);
+        }
+    }
+
+    /// Apply `op` and append it to the journal, compacting the journal into
+    /// a snapshot once it outgrows the stored paths.
+    fn record(&mut self, op: JournalOp) {
+        self.apply(&op);
+        self.unsaved += 1;
+        if let Some(journal) = self.journal.as_mut() {
+            let appended = bincode::serialize(&op)
+                .context("failed to encode inode journal record")
+                .and_then(|bytes| {
+                    let mut frame = (bytes.len() as u32).to_le_bytes().to_vec();
+                    frame.extend_from_slice(&bytes);
+                    journal
+                        .write_all(&frame)
+                        .context("failed to append to inode journal")
+                });
+            if let Err(e) = appended {
+                warn!("Inode journal disabled until the next snapshot: {:#}", e);
+                self.journal = None;
+            }
+        }
+        if self.unsaved > self.paths.len().max(MIN_COMPACT_RECORDS) {
+            if let Err(e) = self.save() {
+                warn!("Failed to persist inode map: {:#}", e);
+            }
+        }
+    }
+
+    fn apply(&mut self, op: &JournalOp) {
+        match op {
+            JournalOp::Assign { path, inode } => {
+                let inode = *inode;
+                // The inode may come from the free list, or (on replay,
+                // where reclaiming has not run yet) from the deleted queue.
+                if let Some(i) = self.free.iter().rposition(|&free| free == inode) {
+                    self.free.remove(i);
+                } else if let Some(i) = self.deleted.iter().position(|&(del, _)| del == inode) {
+                    self.deleted.remove(i);
+                }
+                self.next_inode = self.next_inode.max(inode + 1);
+                self.paths.push(path.clone(), inode);
+                self.inodes.insert(inode, path.clone());
+            }
+            JournalOp::Remove { path, at_secs } => {
+                if let Some(inode) = self.paths.pop(path) {
+                    self.inodes.remove(&inode);
+                    self.deleted.push_back((inode, *at_secs));
+                }
+            }
+            JournalOp::Rename { from, to, at_secs } => {
+                let Some(inode) = self.paths.pop(from) else {
+                    return;
+                };
+                if let Some(replaced) = self.paths.pop(to) {
+                    self.inodes.remove(&replaced);
+                    self.deleted.push_back((replaced, *at_secs));
+                }
+                self.paths.push(to.clone(), inode);
+                self.inodes.insert(inode, to.clone());
+            }
+        }
+    }
+
+    /// Apply the journal left by the previous mount, if it extends the
+    /// loaded snapshot. Replay stops at the first torn or undecodable
+    /// record.
+    fn replay_journal(&mut self) {
+        let bytes = match std::fs::read(&self.journal_file) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => {
+                warn!(
+                    "Failed to read inode journal {}: {}",
+                    self.journal_file.display(),
+                    e
+                );
+                return;
+            }
+        };
+        let Some((header, mut rest)) = bytes.split_first_chunk::<8>() else {
+            return;
+        };
+        if u64::from_le_bytes(*header) != self.generation {
+            return;
+        }
+        while let Some((len, tail)) = rest.split_first_chunk::<4>() {
+            let len = u32::from_le_bytes(*len) as usize;
+            let Some(op) = tail
+                .get(..len)
+                .and_then(|frame| bincode::deserialize::<JournalOp>(frame).ok())
+            else {
+                warn!(
+                    "Ignoring torn tail of inode journal {}",
+                    self.journal_file.display()
+                );
+                break;
+            };
+            self.apply(&op);
+            rest = &tail[len..];
+        }
+    }
+
+    /// Write all assignments to a new snapshot and start an empty journal
+    /// for it. The snapshot is replaced atomically and carries a new
+    /// generation, so a crash at any point leaves either the previous
+    /// snapshot and its journal, or the new snapshot alone.
+    pub fn save(&mut self) -> Result<()> {
+        let generation = self.generation + 1;
+        let records = InodeRecords {
+            version: FORMAT_VERSION,
+            generation,
+            next_inode: self.next_inode,
+            paths: self
+                .paths
+                .iter()
+                .rev()
+                .map(|(path, &inode)| (path.clone(), inode))
+                .collect(),
+            deleted: self.deleted.clone(),
+            free: self.free.clone(),
+        };
+        let bytes = bincode::serialize(&records).context("failed to encode inode map")?;
+        let tmp = self.file.with_extension("tmp");
+        std::fs::write(&tmp, bytes)
+            .with_context(|| format!("failed to write inode map {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.file)
+            .with_context(|| format!("failed to replace inode map {}", self.file.display()))?;
+        self.generation = generation;
+        self.unsaved = 0;
+
+        self.journal = None;
+        let mut journal = File::create(&self.journal_file).with_context(|| {
+            format!(
+                "failed to create inode journal {}",
+                self.journal_file.display()
+            )
+        })?;
+        journal
+            .write_all(&generation.to_le_bytes())
+            .context("failed to write inode journal header")?;
+        self.journal = Some(journal);
+        Ok(())
+    }
+}
+
+impl Drop for InodeStore {
+    fn drop(&mut self) {
+        if self.unsaved == 0 {
+            return;
+        }
+        if let Err(e) = self.save() {
+            warn!("Failed to persist inode map: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cap(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn test_assignments_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("inodes.bin");
+
+        let (a, b) = {
+            let mut store = InodeStore::open(&file, 2, cap(100));
+            (store.get_or_assign("/a").0, store.get_or_assign("/b").0)
+        };
+        assert_eq!((a, b), (2, 3));
+
+        let mut store = InodeStore::open(&file, 2, cap(100));
+        assert_eq!(store.inode("/a"), Some(a));
+        assert_eq!(store.path(b), Some("/b"));
+        assert_eq!(store.get_or_assign("/c").0, 4);
+    }
+
+    #[test]
+    fn test_changes_survive_a_kill_through_the_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("inodes.bin");
+
+        let mut store = InodeStore::open(&file, 2, cap(100));
+        let a = store.get_or_assign("/a").0;
+        let b = store.get_or_assign("/b").0;
+        store.rename("/a", "/moved");
+        store.remove("/b");
+        // A killed daemon never runs Drop; it may also leave a torn record.
+        std::mem::forget(store);
+        let mut journal = std::fs::OpenOptions::new()
+            .append(true)
+            .open(file.with_extension("journal"))
+            .unwrap();
+        journal.write_all(&[9, 0, 0, 0, 1]).unwrap();
+
+        let mut store = InodeStore::open(&file, 2, cap(100));
+        assert_eq!(store.inode("/moved"), Some(a));
+        assert_eq!(store.inode("/a"), None);
+        assert_eq!(store.path(b), None);
+        // `b` is still reserved, so the next path gets a fresh inode.
+        assert_eq!(store.get_or_assign("/c").0, 4);
+    }
+
+    #[test]
+    fn test_store_forgets_least_recently_used_past_capacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("inodes.bin");
+
+        let mut store = InodeStore::open(&file, 2, cap(2));
+        let a = store.get_or_assign("/a").0;
+        let b = store.get_or_assign("/b").0;
+        store.touch("/a");
+        let (c, forgotten) = store.get_or_assign("/c");
+        assert_eq!(forgotten.as_deref(), Some("/b"));
+        assert_eq!(store.inode("/b"), None);
+        assert_eq!(store.path(b), None);
+        assert_eq!((store.inode("/a"), store.inode("/c")), (Some(a), Some(c)));
+        // The forgotten inode is reserved like a deleted one.
+        assert_ne!(store.get_or_assign("/b").0, b);
+        drop(store);
+
+        let store = InodeStore::open(&file, 2, cap(1));
+        assert_eq!(store.paths.len(), 1);
+        assert_eq!(store.inodes.len(), 1);
+    }
+
+    #[test]
+    fn test_journal_is_compacted_into_the_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("inodes.bin");
+        let journal = file.with_extension("journal");
+
+        let mut store = InodeStore::open(&file, 2, cap(10));
+        for i in 0..MIN_COMPACT_RECORDS * 2 {
+            store.get_or_assign(&format!("/f{i}"));
+        }
+        assert!(store.unsaved <= MIN_COMPACT_RECORDS);
+        let len = std::fs::metadata(&journal).unwrap().len();
+        assert!(
+            len < 64 * MIN_COMPACT_RECORDS as u64,
+            "journal is {len} bytes"
+        );
+    }
+
+    #[test]
+    fn test_deleted_inode_reclaimed_only_after_grace() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = InodeStore::open(&dir.path().join("inodes.bin"), 2, cap(100));
+        let a = store.get_or_assign("/a").0;
+        assert_eq!(store.remove("/a"), Some(a));
+        assert_eq!(store.path(a), None);
+
+        // Still inside the grace period: a new path gets a fresh inode.
+        let b = store.get_or_assign("/b").0;
+        assert_ne!(b, a);
+
+        store.reclaim(InodeStore::now() + RECLAIM_GRACE_SECS);
+        assert_eq!(store.get_or_assign("/c").0, a);
+    }
+
+    #[test]
+    fn test_rename_keeps_inode_and_reserves_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = InodeStore::open(&dir.path().join("inodes.bin"), 2, cap(100));
+        let src = store.get_or_assign("/src").0;
+        let dst = store.get_or_assign("/dst").0;
+        store.rename("/src", "/dst");
+        assert_eq!(store.inode("/dst"), Some(src));
+        assert_eq!(store.inode("/src"), None);
+        assert_eq!(store.path(dst), None);
+        assert_ne!(store.get_or_assign("/new").0, dst);
+    }
+
+    #[test]
+    fn test_corrupt_file_starts_fresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("inodes.bin");
+        std::fs::write(&file, b"not an inode map").unwrap();
+        let mut store = InodeStore::open(&file, 2, cap(100));
+        assert_eq!(store.get_or_assign("/a").0, 2);
+    }
+}
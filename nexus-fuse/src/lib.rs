@@ -9,5 +9,6 @@ pub mod daemon;
 pub mod error;
 pub mod fs;
 pub mod hydrate;
+pub mod inode_store;
 pub mod metrics;
 pub mod passthrough;
@@ -7,7 +7,7 @@ use clap::{Parser, Subcommand};
 use fuser::{Config, MountOption, SessionACL};
 use log::{error, info, warn};
 use nexus_fuse::{cache, client, daemon, fs, metrics, passthrough};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 #[derive(Parser)]
@@ -39,6 +39,20 @@ enum Commands {
         #[arg(long)]
         api_key_file: Option<PathBuf>,
 
+        /// Path to a file containing a bearer token that may be rotated.
+        /// Re-read on 401 and the request retried once with the new token.
+        #[arg(long, conflicts_with_all = ["api_key", "api_key_file"])]
+        token_file: Option<PathBuf>,
+
+        /// Retries for idempotent requests (stat/list/read) on connection
+        /// errors and 5xx responses. Writes are never retried. 0 disables.
+        #[arg(long, env = "NEXUS_FUSE_MAX_RETRIES", default_value_t = 3)]
+        max_retries: u32,
+
+        /// Initial retry backoff in milliseconds; doubles per retry, with jitter
+        #[arg(long, env = "NEXUS_FUSE_RETRY_BASE_MS", default_value_t = 100)]
+        retry_base_ms: u64,
+
         /// Allow other users to access the mount
         #[arg(long, default_value = "false")]
         allow_other: bool,
@@ -309,6 +323,28 @@ fn create_passthrough_manager(
     }
 }
 
+/// Credential half of the cache namespace. An API key is stable, so it
+/// is used as-is. A `--token-file` token rotates, and keying by it would
+/// strand the cache (and the inode map persisted in it) on every
+/// rotation, so that mode keys by the token file and the principal
+/// `whoami` resolved instead.
+fn cache_credential(
+    token: &str,
+    token_file: Option<&Path>,
+    user_id: Option<&str>,
+    tenant_id: Option<&str>,
+) -> String {
+    match token_file {
+        Some(path) => format!(
+            "token-file={}|user={}|tenant={}",
+            path.display(),
+            user_id.unwrap_or(""),
+            tenant_id.unwrap_or("")
+        ),
+        None => token.to_string(),
+    }
+}
+
 fn open_file_cache(
     url: &str,
     api_key: &str,
@@ -358,6 +394,9 @@ fn main() -> anyhow::Result<()> {
             url,
             api_key,
             api_key_file,
+            token_file,
+            max_retries,
+            retry_base_ms,
             allow_other,
             foreground,
             agent_id,
@@ -372,7 +411,20 @@ fn main() -> anyhow::Result<()> {
             passthrough_backing_dir,
             metrics_addr,
         } => {
-            let api_key = resolve_api_key(api_key, api_key_file)?;
+            // Create Nexus client. Clone agent_id because open_file_cache
+            // also reads it below for the cache namespace (#4055 R9).
+            let client = match token_file.as_deref() {
+                Some(path) => client::NexusClient::with_token_file(&url, path, agent_id.clone())?,
+                None => {
+                    let api_key = resolve_api_key(api_key, api_key_file)?;
+                    client::NexusClient::new(&url, &api_key, agent_id.clone())?
+                }
+            }
+            .with_retry_config(client::RetryConfig {
+                max_retries,
+                base_delay: std::time::Duration::from_millis(retry_base_ms),
+                ..Default::default()
+            });
             let _metrics_server = if let Some(addr) = metrics_addr.as_deref() {
                 let server = metrics::start_server(addr)?;
                 info!("FUSE metrics listening on {}", server.local_addr());
@@ -385,26 +437,29 @@ fn main() -> anyhow::Result<()> {
             info!("Server URL: {}", url);
             info!("Mount point: {}", mount_point.display());
 
-            // Create Nexus client. Clone agent_id because open_file_cache
-            // also reads it below for the cache namespace (#4055 R9).
-            let client = client::NexusClient::new(&url, &api_key, agent_id.clone())?;
-
             // Verify connection
             info!("Connecting to Nexus server...");
-            match client.whoami() {
+            let user_info = match client.whoami() {
                 Ok(user_info) => {
                     let user = user_info.user_id.as_deref().unwrap_or("admin");
                     let tenant = user_info.tenant_id.as_deref().unwrap_or("default");
                     info!("Authenticated as {} (tenant: {})", user, tenant);
+                    user_info
                 }
                 Err(e) => {
                     error!("Failed to authenticate: {}", e);
                     return Err(e.into());
                 }
-            }
+            };
 
             let cache_config = build_cache_config(cache_memory_mb, cache_disk_gb, cache_dir)?;
-            let file_cache = open_file_cache(&url, &api_key, agent_id.as_deref(), cache_config);
+            let credential = cache_credential(
+                &client.token(),
+                token_file.as_deref(),
+                user_info.user_id.as_deref(),
+                user_info.tenant_id.as_deref(),
+            );
+            let file_cache = open_file_cache(&url, &credential, agent_id.as_deref(), cache_config);
 
             let passthrough = read_bool_flag_with_env(passthrough, "NEXUS_FUSE_PASSTHROUGH")?;
             let passthrough_require =
@@ -509,6 +564,37 @@ fn main() -> anyhow::Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn token_file_conflicts_with_every_api_key_source() {
+        let mount = ["nexus-fuse", "mount", "/mnt/nexus", "--url", "http://nexus"];
+        let parse = |extra: &[&str]| Cli::try_parse_from(mount.iter().chain(extra));
+
+        for extra in [
+            ["--token-file", "/run/token", "--api-key", "sk-test"],
+            ["--token-file", "/run/token", "--api-key-file", "/run/key"],
+        ] {
+            let err = parse(&extra).err().expect("conflicting auth should fail");
+            assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+        assert!(parse(&["--token-file", "/run/token"]).is_ok());
+    }
+
+    #[test]
+    fn token_file_cache_credential_survives_rotation() {
+        let file = Path::new("/run/token");
+        let before = cache_credential("tok-1", Some(file), Some("alice"), Some("acme"));
+        let after = cache_credential("tok-2", Some(file), Some("alice"), Some("acme"));
+        assert_eq!(before, after);
+        assert!(!before.contains("tok-1"));
+
+        let bob = cache_credential("tok-1", Some(file), Some("bob"), Some("acme"));
+        assert_ne!(before, bob);
+        assert_eq!(
+            cache_credential("sk-test", None, Some("alice"), None),
+            "sk-test"
+        );
+    }
+
     #[test]
     fn build_passthrough_config_keeps_disabled_default() {
         let config = build_passthrough_config(
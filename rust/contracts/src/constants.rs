@@ -163,6 +163,19 @@ pub const GRPC_TCP_KEEPALIVE_SECS: u64 = 60;
 /// running multiple zones.
 pub const MIN_SERVER_RUNTIME_WORKERS: usize = 4;
 
+/// FastCDC minimum chunk size (256 KiB) for content-addressed blobs.
+///
+/// The kernel's CAS chunker and `lib::chunk::cdc_chunk` callers that
+/// dedupe against the CAS share these bounds so equal content cuts into
+/// equal chunks. Must match Python ``nexus.backends.engines.cdc``.
+pub const CDC_MIN_CHUNK_SIZE: usize = 256 * 1024;
+
+/// FastCDC average chunk size target (1 MiB); see [`CDC_MIN_CHUNK_SIZE`].
+pub const CDC_AVG_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// FastCDC maximum chunk size (4 MiB); see [`CDC_MIN_CHUNK_SIZE`].
+pub const CDC_MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
 /// Worker-thread count for a multi-threaded tokio runtime, sized to the
 /// host's available parallelism with a floor of `min_workers`.
 ///
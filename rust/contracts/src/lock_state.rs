@@ -31,6 +31,9 @@ pub struct HolderInfo {
     pub acquired_at: u64,
     /// Unix seconds.
     pub expires_at: u64,
+    /// Fencing token issued when this holder acquired the lock; see
+    /// [`LockState::holds_fencing_token`].
+    pub fencing_token: u64,
 }
 
 /// Persistent lock record — transport type for `get_lock` / `list_locks`.
@@ -49,6 +52,40 @@ pub struct LockAcquireResult {
     pub current_holders: u32,
     pub max_holders: u32,
     pub holders: Vec<HolderInfo>,
+    /// The caller's fencing token when `acquired`, otherwise 0. Attach
+    /// it to writes made under the lock so a newer holder can fence
+    /// them off.
+    pub fencing_token: u64,
+}
+
+/// One lock in an [`LockState::apply_acquire_all`] batch — the
+/// arguments of a single `apply_acquire`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockRequest {
+    pub path: String,
+    pub lock_id: String,
+    pub max_holders: u32,
+    pub ttl_secs: u32,
+    pub holder_info: String,
+}
+
+/// Result of a `peek` — what an acquire would see right now. Computed
+/// from local state only, so it may be stale by the time an acquire is
+/// proposed; callers use it to decide whether to wait or go elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockPreview {
+    /// An `apply_acquire` by a new holder with the same `max_holders`
+    /// would succeed.
+    pub would_acquire: bool,
+    /// Unexpired holders.
+    pub current_holders: u32,
+    /// The lock's shape if held, otherwise the requested `max_holders`.
+    pub max_holders: u32,
+    /// `max_holders - current_holders`. Can be non-zero while
+    /// `would_acquire` is false (hierarchy conflict or shape mismatch).
+    pub available_slots: u32,
+    /// An ancestor or descendant path is held.
+    pub hierarchy_conflict: bool,
 }
 
 /// Per-path entry in the SSOT map. Advisory-lock only — I/O lock state
@@ -76,6 +113,9 @@ impl LockEntry {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct LockState {
     pub locks: BTreeMap<String, LockEntry>,
+    /// Last fencing token issued, across all paths. Tokens only grow, so
+    /// a later acquire always carries a larger token than an earlier one.
+    pub last_fencing_token: u64,
 }
 
 // ── Hierarchy helpers ───────────────────────────────────────────────
@@ -149,12 +189,13 @@ impl LockState {
         (entry.holders.len() as u32) < entry.max_holders
     }
 
-    fn to_result(entry: &LockEntry, acquired: bool) -> LockAcquireResult {
+    fn to_result(entry: &LockEntry, acquired: bool, fencing_token: u64) -> LockAcquireResult {
         LockAcquireResult {
             acquired,
             current_holders: entry.holders.len() as u32,
             max_holders: entry.max_holders,
             holders: entry.holders.clone(),
+            fencing_token,
         }
     }
 
@@ -166,6 +207,7 @@ impl LockState {
             current_holders: 0,
             max_holders,
             holders: Vec::new(),
+            fencing_token: 0,
         }
     }
 
@@ -174,6 +216,9 @@ impl LockState {
     /// Does hierarchy conflict detection, idempotent re-acquire, and
     /// capacity matching. `max_holders` parametrizes the lock shape:
     /// `1` is a mutex, `> 1` is a counting semaphore.
+    ///
+    /// A new holder gets the next fencing token; an idempotent
+    /// re-acquire keeps the token it already has.
     pub fn apply_acquire(
         &mut self,
         path: &str,
@@ -197,29 +242,94 @@ impl LockState {
         // Idempotent re-acquire by same lock_id — bump TTL.
         if let Some(h) = entry.holders.iter_mut().find(|h| h.lock_id == lock_id) {
             h.expires_at = expires_at;
-            return Self::to_result(entry, true);
+            let token = h.fencing_token;
+            return Self::to_result(entry, true, token);
         }
 
         // First holder seeds max_holders; subsequent holders must match.
         if entry.holders.is_empty() {
             entry.max_holders = max_holders;
         } else if entry.max_holders != max_holders {
-            return Self::to_result(entry, false);
+            return Self::to_result(entry, false, 0);
         }
 
         if Self::accepts_new_holder(entry) {
+            self.last_fencing_token += 1;
+            let token = self.last_fencing_token;
             entry.holders.push(HolderInfo {
                 lock_id: lock_id.to_string(),
                 holder_info: holder_info.to_string(),
                 acquired_at: now_secs,
                 expires_at,
+                fencing_token: token,
             });
-            Self::to_result(entry, true)
+            Self::to_result(entry, true, token)
         } else {
-            Self::to_result(entry, false)
+            Self::to_result(entry, false, 0)
         }
     }
 
+    /// Acquire every lock in `requests`, or none of them.
+    ///
+    /// Requests are applied in path order — one global order for every
+    /// batch, so two batches over overlapping paths cannot each hold part
+    /// of what the other needs. If any acquire fails, every entry the
+    /// batch touched is restored to its prior state (including TTLs
+    /// bumped by idempotent re-acquires, and the fencing-token counter)
+    /// and all results report `acquired: false`. Paths in one batch conflict with each other
+    /// like any other holders, so a batch naming both `/a` and `/a/b`
+    /// always fails. Results are in `requests` order.
+    pub fn apply_acquire_all(
+        &mut self,
+        requests: &[LockRequest],
+        now_secs: u64,
+    ) -> Vec<LockAcquireResult> {
+        let mut order: Vec<usize> = (0..requests.len()).collect();
+        order.sort_by(|&a, &b| requests[a].path.cmp(&requests[b].path));
+
+        let saved_token = self.last_fencing_token;
+        let mut saved: Vec<(&str, Option<LockEntry>)> = Vec::with_capacity(requests.len());
+        let mut results: Vec<Option<LockAcquireResult>> = vec![None; requests.len()];
+        let mut failed = false;
+        for &i in &order {
+            let req = &requests[i];
+            saved.push((&req.path, self.locks.get(&req.path).cloned()));
+            let result = self.apply_acquire(
+                &req.path,
+                &req.lock_id,
+                req.max_holders,
+                req.ttl_secs,
+                &req.holder_info,
+                now_secs,
+            );
+            failed = !result.acquired;
+            results[i] = Some(result);
+            if failed {
+                break;
+            }
+        }
+        if !failed {
+            return results.into_iter().flatten().collect();
+        }
+
+        // Roll back newest-first so a path named twice ends up with its
+        // original entry.
+        for (path, entry) in saved.into_iter().rev() {
+            match entry {
+                Some(entry) => self.locks.insert(path.to_string(), entry),
+                None => self.locks.remove(path),
+            };
+        }
+        self.last_fencing_token = saved_token;
+        requests
+            .iter()
+            .map(|req| match self.locks.get(&req.path) {
+                Some(entry) => Self::to_result(entry, false, 0),
+                None => Self::empty_result(req.max_holders),
+            })
+            .collect()
+    }
+
     /// Release one holder. Returns `true` if found.
     pub fn apply_release(&mut self, path: &str, lock_id: &str) -> bool {
         if let Some(entry) = self.locks.get_mut(path) {
@@ -276,6 +386,23 @@ impl LockState {
     // skipped the sweep, the federation-side impl did it on every
     // call).
 
+    /// Whether `token` belongs to one of `path`'s current holders, or
+    /// `None` once the path's entry is gone (released, force-released or
+    /// pruned). While the lock is held, a write carrying a token no
+    /// holder has comes from a holder that has since been superseded and
+    /// must be rejected; every co-holder of a semaphore passes.
+    ///
+    /// Because the entry — and with it the tokens — disappears when the
+    /// last holder leaves, the store being written should also remember
+    /// the highest token it has accepted per key and check against that
+    /// when this returns `None`; the two checks together make up the
+    /// fence.
+    pub fn holds_fencing_token(&self, path: &str, token: u64) -> Option<bool> {
+        self.locks
+            .get(path)
+            .map(|entry| entry.holders.iter().any(|h| h.fencing_token == token))
+    }
+
     pub fn get_lock(&self, path: &str) -> Option<LockInfo> {
         self.locks.get(path).and_then(|entry| {
             if entry.holders.is_empty() {
@@ -290,6 +417,37 @@ impl LockState {
         })
     }
 
+    /// Dry-run of `apply_acquire` for a new holder: same hierarchy,
+    /// expiry and capacity rules, no mutation.
+    pub fn peek(&self, path: &str, max_holders: u32, now_secs: u64) -> LockPreview {
+        let hierarchy_conflict = self.ancestor_conflict(path) || self.descendant_conflict(path);
+        let (current_holders, shape) = match self.locks.get(path) {
+            Some(entry) => {
+                let live = entry
+                    .holders
+                    .iter()
+                    .filter(|h| h.expires_at > now_secs)
+                    .count() as u32;
+                // An entry whose holders all expired is re-seeded on acquire.
+                let shape = if live == 0 {
+                    max_holders
+                } else {
+                    entry.max_holders
+                };
+                (live, shape)
+            }
+            None => (0, max_holders),
+        };
+        let available_slots = shape.saturating_sub(current_holders);
+        LockPreview {
+            would_acquire: !hierarchy_conflict && shape == max_holders && available_slots > 0,
+            current_holders,
+            max_holders: shape,
+            available_slots,
+            hierarchy_conflict,
+        }
+    }
+
     pub fn list_locks(&self, prefix: &str, limit: usize) -> Vec<LockInfo> {
         let mut out = Vec::new();
         for (key, entry) in self.locks.iter() {
@@ -307,6 +465,32 @@ impl LockState {
         out
     }
 
+    /// Locks with at least one holder whose `holder_info` contains
+    /// `holder_info_substr`. Each returned `LockInfo` lists only the
+    /// matching holders, so callers can release exactly those lock ids.
+    pub fn list_locks_by_holder(&self, holder_info_substr: &str, limit: usize) -> Vec<LockInfo> {
+        let mut out = Vec::new();
+        for (key, entry) in self.locks.iter() {
+            if out.len() >= limit {
+                break;
+            }
+            let holders: Vec<HolderInfo> = entry
+                .holders
+                .iter()
+                .filter(|h| h.holder_info.contains(holder_info_substr))
+                .cloned()
+                .collect();
+            if !holders.is_empty() {
+                out.push(LockInfo {
+                    path: key.clone(),
+                    max_holders: entry.max_holders,
+                    holders,
+                });
+            }
+        }
+        out
+    }
+
     /// Public read-side helper for expiry pruning (used by tests and
     /// future background reapers). apply_* paths already inline this
     /// on their own writes; this is the cold-path "compact the map"
@@ -403,6 +587,107 @@ mod tests {
         assert!(!acq(&mut s, "/a", "h2", 1, 60).acquired);
     }
 
+    fn req(path: &str, id: &str) -> LockRequest {
+        LockRequest {
+            path: path.to_string(),
+            lock_id: id.to_string(),
+            max_holders: 1,
+            ttl_secs: 60,
+            holder_info: "task".to_string(),
+        }
+    }
+
+    #[test]
+    fn acquire_all_takes_every_lock_when_clear() {
+        let mut s = LockState::new();
+        let batch = [req("/c", "t1"), req("/a", "t1"), req("/b", "t1")];
+        let results = s.apply_acquire_all(&batch, 1000);
+        assert!(results.iter().all(|r| r.acquired));
+        for path in ["/a", "/b", "/c"] {
+            assert_eq!(s.get_lock(path).unwrap().holders[0].lock_id, "t1");
+        }
+    }
+
+    #[test]
+    fn acquire_all_conflict_leaves_no_partial_holds() {
+        let mut s = LockState::new();
+        assert!(acq(&mut s, "/b", "other", 1, 60).acquired);
+        // t1 already holds /a; the batch would bump its TTL.
+        assert!(acq(&mut s, "/a", "t1", 1, 60).acquired);
+        let before = s.locks.clone();
+
+        let batch = [req("/c", "t1"), req("/a", "t1"), req("/b", "t1")];
+        let results = s.apply_acquire_all(&batch, 1030);
+        assert!(results.iter().all(|r| !r.acquired));
+        assert_eq!(s.locks, before, "rollback must restore every entry");
+        assert_eq!(results[2].holders[0].lock_id, "other");
+        assert!(s.get_lock("/c").is_none());
+
+        // Nested paths in one batch conflict with each other.
+        let nested = [req("/x", "t2"), req("/x/y", "t2")];
+        assert!(s
+            .apply_acquire_all(&nested, 1030)
+            .iter()
+            .all(|r| !r.acquired));
+        assert!(s.get_lock("/x").is_none());
+    }
+
+    #[test]
+    fn fencing_tokens_grow_across_reacquires() {
+        let mut s = LockState::new();
+        let first = s.apply_acquire("/a", "h1", 1, 10, "agent", 1000);
+        assert!(first.acquired);
+        assert_eq!(first.holders[0].fencing_token, first.fencing_token);
+        // Re-acquiring an already-held lock keeps the token.
+        assert_eq!(
+            s.apply_acquire("/a", "h1", 1, 10, "agent", 1005)
+                .fencing_token,
+            first.fencing_token
+        );
+        assert_eq!(acq(&mut s, "/a", "h2", 1, 60).fencing_token, 0);
+
+        // h1 expires and h2 takes over with a newer token.
+        let second = s.apply_acquire("/a", "h2", 1, 60, "agent", 1100);
+        assert!(second.acquired);
+        assert!(second.fencing_token > first.fencing_token);
+        assert_eq!(
+            s.holds_fencing_token("/a", second.fencing_token),
+            Some(true)
+        );
+        assert_eq!(
+            s.holds_fencing_token("/a", first.fencing_token),
+            Some(false)
+        );
+
+        // Tokens are global, so another path never reuses one.
+        let other = acq(&mut s, "/b", "h3", 1, 60);
+        assert!(other.fencing_token > second.fencing_token);
+
+        // A failed batch does not burn tokens.
+        let before = s.last_fencing_token;
+        assert!(s
+            .apply_acquire_all(&[req("/c", "t1"), req("/a", "t1")], 1100)
+            .iter()
+            .all(|r| !r.acquired));
+        assert_eq!(s.last_fencing_token, before);
+
+        assert!(s.apply_release("/a", "h2"));
+        assert_eq!(s.holds_fencing_token("/a", second.fencing_token), None);
+    }
+
+    #[test]
+    fn semaphore_co_holders_keep_their_fencing_tokens() {
+        let mut s = LockState::new();
+        let r1 = acq(&mut s, "/a", "r1", 2, 60);
+        let r2 = acq(&mut s, "/a", "r2", 2, 60);
+        assert!(r2.fencing_token > r1.fencing_token);
+        assert_eq!(s.holds_fencing_token("/a", r1.fencing_token), Some(true));
+        assert_eq!(s.holds_fencing_token("/a", r2.fencing_token), Some(true));
+
+        assert!(s.apply_release("/a", "r1"));
+        assert_eq!(s.holds_fencing_token("/a", r1.fencing_token), Some(false));
+    }
+
     #[test]
     fn semaphore_coexists_up_to_max() {
         let mut s = LockState::new();
@@ -412,6 +697,74 @@ mod tests {
         assert!(!acq(&mut s, "/a", "r4", 3, 60).acquired);
     }
 
+    #[test]
+    fn peek_reflects_full_and_available_without_mutating() {
+        let mut s = LockState::new();
+        let free = s.peek("/a", 2, 1000);
+        assert!(free.would_acquire);
+        assert_eq!((free.current_holders, free.available_slots), (0, 2));
+
+        assert!(acq(&mut s, "/a", "r1", 2, 60).acquired);
+        let one_left = s.peek("/a", 2, 1000);
+        assert!(one_left.would_acquire);
+        assert_eq!((one_left.current_holders, one_left.available_slots), (1, 1));
+
+        assert!(acq(&mut s, "/a", "r2", 2, 60).acquired);
+        let before = s.get_lock("/a");
+        let full = s.peek("/a", 2, 1000);
+        assert!(!full.would_acquire);
+        assert_eq!((full.current_holders, full.available_slots), (2, 0));
+        assert_eq!(s.get_lock("/a"), before, "peek must not touch holders");
+
+        // Peek agrees with the acquire it previews.
+        assert!(!acq(&mut s, "/a", "r3", 2, 60).acquired);
+        // Holders past their TTL don't count.
+        assert!(s.peek("/a", 2, 1060).would_acquire);
+    }
+
+    #[test]
+    fn peek_reports_shape_mismatch_and_hierarchy_conflict() {
+        let mut s = LockState::new();
+        assert!(acq(&mut s, "/a", "r1", 3, 60).acquired);
+        let mismatch = s.peek("/a", 1, 1000);
+        assert!(!mismatch.would_acquire);
+        assert_eq!(mismatch.max_holders, 3);
+        assert_eq!(mismatch.available_slots, 2);
+
+        let child = s.peek("/a/b", 1, 1000);
+        assert!(child.hierarchy_conflict);
+        assert!(!child.would_acquire);
+        assert!(s.get_lock("/a/b").is_none());
+    }
+
+    #[test]
+    fn list_locks_by_holder_returns_only_matching_holders() {
+        let mut s = LockState::new();
+        for (path, id, max, holder) in [
+            ("/a", "a1", 2, "agent:alice"),
+            ("/a", "b1", 2, "agent:bob"),
+            ("/x/y", "a2", 1, "agent:alice"),
+            ("/z", "b2", 1, "agent:bob"),
+        ] {
+            assert!(s.apply_acquire(path, id, max, 60, holder, 1000).acquired);
+        }
+
+        let mut alice = s.list_locks_by_holder("alice", 10);
+        alice.sort_by(|l, r| l.path.cmp(&r.path));
+        let found: Vec<(&str, Vec<&str>)> = alice
+            .iter()
+            .map(|l| {
+                let ids = l.holders.iter().map(|h| h.lock_id.as_str()).collect();
+                (l.path.as_str(), ids)
+            })
+            .collect();
+        assert_eq!(found, vec![("/a", vec!["a1"]), ("/x/y", vec!["a2"])]);
+        assert_eq!(alice[0].max_holders, 2);
+
+        assert_eq!(s.list_locks_by_holder("alice", 1).len(), 1);
+        assert!(s.list_locks_by_holder("carol", 10).is_empty());
+    }
+
     #[test]
     fn idempotent_reacquire_same_holder() {
         let mut s = LockState::new();
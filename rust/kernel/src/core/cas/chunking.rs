@@ -18,6 +18,7 @@
 use super::engine::CASError;
 use super::remote::RemoteChunkFetcher;
 use super::transport::LocalCASTransport;
+use contracts::{CDC_AVG_CHUNK_SIZE, CDC_MAX_CHUNK_SIZE, CDC_MIN_CHUNK_SIZE};
 use serde_json::{json, Value};
 use std::sync::Arc;
 
@@ -27,12 +28,7 @@ use std::sync::Arc;
 
 /// Minimum content size to trigger CDC chunking (16 MiB).
 pub(crate) const CDC_THRESHOLD_BYTES: usize = 16 * 1024 * 1024;
-/// FastCDC minimum chunk size (256 KiB).
-const CDC_MIN_CHUNK_SIZE: usize = 256 * 1024;
-/// FastCDC average chunk size target (1 MiB).
-const CDC_AVG_CHUNK_SIZE: usize = 1024 * 1024;
-/// FastCDC maximum chunk size (4 MiB).
-const CDC_MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+// Chunk size bounds: `contracts::CDC_{MIN,AVG,MAX}_CHUNK_SIZE`.
 
 // ---------------------------------------------------------------------------
 // ChunkAssembler trait
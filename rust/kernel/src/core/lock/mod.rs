@@ -32,6 +32,8 @@ pub struct KernelHolderInfo {
     pub holder_info: String,
     pub acquired_at_secs: u64,
     pub expires_at_secs: u64,
+    /// Fencing token issued when this holder acquired the lock.
+    pub fencing_token: u64,
 }
 
 /// Advisory lock entry returned by `get_lock_info` / `list_locks`.
@@ -196,6 +198,7 @@ fn shared_holder_to_kernel(h: &SharedHolderInfo) -> KernelHolderInfo {
         holder_info: h.holder_info.clone(),
         acquired_at_secs: h.acquired_at,
         expires_at_secs: h.expires_at,
+        fencing_token: h.fencing_token,
     }
 }
 
@@ -10,8 +10,10 @@
 //!   ``set_metastore_path``; production swaps in a real path.
 //!
 //! Remote / federation impls live in their respective neighbours:
-//! [`remote`] (gRPC proxy) and `raft::meta_store`.
+//! [`remote`] (gRPC proxy) and `raft::meta_store`. [`normalized`] wraps
+//! any impl to canonicalize path keys (`/a//b/` → `/a/b`).
 
+pub mod normalized;
 pub mod remote;
 
 // Re-export the trait surface from `abc/` so callers writing
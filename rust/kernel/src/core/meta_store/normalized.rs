@@ -0,0 +1,362 @@
+//! Key-normalizing `MetaStore` wrapper.
+//!
+//! Path keys sometimes arrive as `/a//b/` or `/A/b` from callers that
+//! mean `/a/b`; each spelling is a distinct key to the underlying store,
+//! so the lookup misses and looks like data loss. [`NormalizingMetaStore`]
+//! rewrites every path key through a [`KeyNormalization`] policy before
+//! delegating, so all spellings land on one entry.
+//!
+//! The policy changes the effective key space: entries already stored
+//! under a non-normalized key become unreachable through the wrapper, and
+//! with `case_fold` distinct paths that differ only in case collapse into
+//! one. Pick the policy when the store is created and keep it.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use super::{
+    FileMetadata, MetaStore, MetaStoreError, PaginatedList, PathEtag, PathValueStr,
+    PutIfVersionResult,
+};
+
+/// How [`NormalizingMetaStore`] rewrites path keys. The default changes
+/// nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyNormalization {
+    /// Drop trailing `/` (`/a/b/` → `/a/b`); the root stays `/`.
+    pub trim_trailing_slash: bool,
+    /// Collapse runs of `/` into one (`/a//b` → `/a/b`).
+    pub collapse_separators: bool,
+    /// Lowercase the whole key (Unicode `to_lowercase`).
+    pub case_fold: bool,
+}
+
+impl KeyNormalization {
+    /// Trim trailing slashes and collapse separators, keeping case.
+    pub fn paths() -> Self {
+        KeyNormalization {
+            trim_trailing_slash: true,
+            collapse_separators: true,
+            case_fold: false,
+        }
+    }
+
+    /// Whether the policy rewrites anything.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Normalize a full key. Borrowed when it is already normal.
+    pub fn normalize<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        let mut key = self.normalize_prefix(key);
+        if self.trim_trailing_slash && key.len() > 1 && key.ends_with('/') {
+            let trimmed = key.trim_end_matches('/');
+            let keep = if trimmed.is_empty() { 1 } else { trimmed.len() };
+            key = match key {
+                Cow::Borrowed(s) => Cow::Borrowed(&s[..keep]),
+                Cow::Owned(mut s) => {
+                    s.truncate(keep);
+                    Cow::Owned(s)
+                }
+            };
+        }
+        key
+    }
+
+    /// Normalize a `list` prefix. A trailing `/` is kept: `/a/` lists the
+    /// children of `/a`, while `/a` would also match `/ab`.
+    pub fn normalize_prefix<'a>(&self, prefix: &'a str) -> Cow<'a, str> {
+        let mut key = Cow::Borrowed(prefix);
+        if self.collapse_separators && key.contains("//") {
+            let mut collapsed = String::with_capacity(key.len());
+            for c in key.chars() {
+                if !(c == '/' && collapsed.ends_with('/')) {
+                    collapsed.push(c);
+                }
+            }
+            key = Cow::Owned(collapsed);
+        }
+        if self.case_fold && key.chars().any(char::is_uppercase) {
+            key = Cow::Owned(key.to_lowercase());
+        }
+        key
+    }
+
+    fn normalize_all(&self, keys: &[String]) -> Vec<String> {
+        keys.iter()
+            .map(|k| self.normalize(k).into_owned())
+            .collect()
+    }
+
+    fn normalize_metadata(&self, mut metadata: FileMetadata) -> FileMetadata {
+        if let Cow::Owned(path) = self.normalize(&metadata.path) {
+            metadata.path = path;
+        }
+        metadata
+    }
+}
+
+/// [`MetaStore`] that normalizes every path key with a [`KeyNormalization`]
+/// fixed at construction, then delegates to `inner`.
+///
+/// Keys are rewritten on every path-taking method, including the stored
+/// `FileMetadata::path`, so reads, writes, deletes and listings agree.
+/// Batch results that echo paths back (`get_file_metadata_bulk`,
+/// `batch_get_content_ids`) keep the caller's spelling. Stream-entry keys
+/// are kernel-internal and passed through untouched.
+pub struct NormalizingMetaStore {
+    inner: Arc<dyn MetaStore>,
+    policy: KeyNormalization,
+}
+
+impl NormalizingMetaStore {
+    pub fn new(inner: Arc<dyn MetaStore>, policy: KeyNormalization) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn policy(&self) -> KeyNormalization {
+        self.policy
+    }
+}
+
+impl MetaStore for NormalizingMetaStore {
+    fn get(&self, path: &str) -> Result<Option<FileMetadata>, MetaStoreError> {
+        self.inner.get(&self.policy.normalize(path))
+    }
+
+    fn put(&self, path: &str, metadata: FileMetadata) -> Result<(), MetaStoreError> {
+        self.inner.put(
+            &self.policy.normalize(path),
+            self.policy.normalize_metadata(metadata),
+        )
+    }
+
+    fn delete(&self, path: &str) -> Result<bool, MetaStoreError> {
+        self.inner.delete(&self.policy.normalize(path))
+    }
+
+    fn list(&self, prefix: &str) -> Result<Vec<FileMetadata>, MetaStoreError> {
+        self.inner.list(&self.policy.normalize_prefix(prefix))
+    }
+
+    fn exists(&self, path: &str) -> Result<bool, MetaStoreError> {
+        self.inner.exists(&self.policy.normalize(path))
+    }
+
+    fn put_batch(&self, items: &[(String, FileMetadata)]) -> Result<(), MetaStoreError> {
+        let items: Vec<(String, FileMetadata)> = items
+            .iter()
+            .map(|(path, meta)| {
+                (
+                    self.policy.normalize(path).into_owned(),
+                    self.policy.normalize_metadata(meta.clone()),
+                )
+            })
+            .collect();
+        self.inner.put_batch(&items)
+    }
+
+    fn get_batch(&self, paths: &[String]) -> Result<Vec<Option<FileMetadata>>, MetaStoreError> {
+        self.inner.get_batch(&self.policy.normalize_all(paths))
+    }
+
+    fn delete_batch(&self, paths: &[String]) -> Result<usize, MetaStoreError> {
+        // Two spellings of one key would otherwise count one deletion.
+        let mut paths = self.policy.normalize_all(paths);
+        paths.sort_unstable();
+        paths.dedup();
+        self.inner.delete_batch(&paths)
+    }
+
+    fn put_if_version(
+        &self,
+        metadata: FileMetadata,
+        expected_version: u32,
+    ) -> Result<PutIfVersionResult, MetaStoreError> {
+        self.inner
+            .put_if_version(self.policy.normalize_metadata(metadata), expected_version)
+    }
+
+    fn rename_path(
+        &self,
+        old_path: &str,
+        new_path: &str,
+        is_pas: bool,
+    ) -> Result<(), MetaStoreError> {
+        self.inner.rename_path(
+            &self.policy.normalize(old_path),
+            &self.policy.normalize(new_path),
+            is_pas,
+        )
+    }
+
+    fn set_file_metadata(
+        &self,
+        path: &str,
+        key: &str,
+        value: String,
+    ) -> Result<(), MetaStoreError> {
+        self.inner
+            .set_file_metadata(&self.policy.normalize(path), key, value)
+    }
+
+    fn get_file_metadata(&self, path: &str, key: &str) -> Result<Option<String>, MetaStoreError> {
+        self.inner
+            .get_file_metadata(&self.policy.normalize(path), key)
+    }
+
+    fn get_file_metadata_bulk(
+        &self,
+        paths: &[String],
+        key: &str,
+    ) -> Result<Vec<PathValueStr>, MetaStoreError> {
+        let values = self
+            .inner
+            .get_file_metadata_bulk(&self.policy.normalize_all(paths), key)?;
+        Ok(paths
+            .iter()
+            .zip(values)
+            .map(|(path, (_, value))| (path.clone(), value))
+            .collect())
+    }
+
+    fn is_implicit_directory(&self, path: &str) -> Result<bool, MetaStoreError> {
+        self.inner
+            .is_implicit_directory(&self.policy.normalize(path))
+    }
+
+    fn list_paginated(
+        &self,
+        prefix: &str,
+        recursive: bool,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<PaginatedList, MetaStoreError> {
+        self.inner.list_paginated(
+            &self.policy.normalize_prefix(prefix),
+            recursive,
+            limit,
+            cursor,
+        )
+    }
+
+    fn batch_get_content_ids(&self, paths: &[String]) -> Result<Vec<PathEtag>, MetaStoreError> {
+        let ids = self
+            .inner
+            .batch_get_content_ids(&self.policy.normalize_all(paths))?;
+        Ok(paths
+            .iter()
+            .zip(ids)
+            .map(|(path, (_, content_id))| (path.clone(), content_id))
+            .collect())
+    }
+
+    fn coherence_key(&self) -> Option<usize> {
+        self.inner.coherence_key()
+    }
+
+    fn append_stream_entry(&self, key: &str, data: &[u8]) -> Result<(), MetaStoreError> {
+        self.inner.append_stream_entry(key, data)
+    }
+
+    fn get_stream_entry(&self, key: &str) -> Result<Option<Vec<u8>>, MetaStoreError> {
+        self.inner.get_stream_entry(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::meta_store::LocalMetaStore;
+
+    fn meta(path: &str) -> FileMetadata {
+        FileMetadata {
+            path: path.to_string(),
+            size: 1,
+            version: 1,
+            ..FileMetadata::default()
+        }
+    }
+
+    fn store(dir: &tempfile::TempDir, policy: KeyNormalization) -> NormalizingMetaStore {
+        let local = LocalMetaStore::open(&dir.path().join("meta.redb")).unwrap();
+        NormalizingMetaStore::new(Arc::new(local), policy)
+    }
+
+    #[test]
+    fn test_normalize_rules() {
+        let policy = KeyNormalization::paths();
+        assert_eq!(policy.normalize("/a//b/"), "/a/b");
+        assert_eq!(policy.normalize("/a/b"), "/a/b");
+        assert!(matches!(policy.normalize("/a/b"), Cow::Borrowed(_)));
+        assert_eq!(policy.normalize("/"), "/");
+        assert_eq!(policy.normalize("///"), "/");
+        assert_eq!(policy.normalize_prefix("/a//"), "/a/");
+        assert_eq!(policy.normalize("/A/b"), "/A/b");
+
+        let folded = KeyNormalization {
+            case_fold: true,
+            ..KeyNormalization::paths()
+        };
+        assert_eq!(folded.normalize("/Docs//Readme.MD/"), "/docs/readme.md");
+        assert!(KeyNormalization::default().is_identity());
+        assert_eq!(KeyNormalization::default().normalize("/a//b/"), "/a//b/");
+    }
+
+    #[test]
+    fn test_spellings_resolve_to_one_entry_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(&dir, KeyNormalization::paths());
+
+        store.put("/a//b/", meta("/a//b/")).unwrap();
+        let got = store.get("/a/b").unwrap().unwrap();
+        assert_eq!(got.path, "/a/b");
+        assert!(store.exists("/a/b/").unwrap());
+        let listed = store.list("//a/").unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].path, "/a/b");
+
+        store.set_file_metadata("/a/b", "tag", "x".into()).unwrap();
+        assert_eq!(
+            store.get_file_metadata("/a//b", "tag").unwrap().as_deref(),
+            Some("x")
+        );
+
+        let paths = vec!["/a/b".to_string(), "/a//b/".to_string()];
+        assert_eq!(store.delete_batch(&paths).unwrap(), 1);
+        assert!(store.get("/a/b").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_spellings_stay_distinct_when_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(&dir, KeyNormalization::default());
+
+        store.put("/a//b/", meta("/a//b/")).unwrap();
+        store.put("/a/b", meta("/a/b")).unwrap();
+        assert_eq!(store.get("/a//b/").unwrap().unwrap().path, "/a//b/");
+        assert_eq!(store.get("/a/b").unwrap().unwrap().path, "/a/b");
+        assert!(store.delete("/a/b").unwrap());
+        assert!(store.get("/a//b/").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_case_fold_merges_case_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(
+            &dir,
+            KeyNormalization {
+                case_fold: true,
+                ..KeyNormalization::default()
+            },
+        );
+
+        store.put("/Docs/README", meta("/Docs/README")).unwrap();
+        assert_eq!(
+            store.get("/docs/readme").unwrap().unwrap().path,
+            "/docs/readme"
+        );
+        assert!(store.delete("/DOCS/Readme").unwrap());
+        assert!(!store.exists("/docs/readme").unwrap());
+    }
+}
@@ -11,23 +11,53 @@
 //! Inheritance-aware: `check` walks up the path hierarchy
 //! (O(depth) outer lookups) so a parent directory lease covers
 //! child files.
+//!
+//! Each path carries an LRU tick, bumped by `stamp`, by a `check` hit
+//! and by [`PermissionLeaseCache::touch`]. When the table is full of live
+//! leases, capacity eviction drops the least recently used paths instead
+//! of clearing everything like the Python table does. Ticks only order
+//! eviction; a lease still expires `ttl` after it was stamped.
+//!
+//! Capacity eviction is silent by default: logging costs a path clone per
+//! evicted entry, so it is opt-in. A caller that wants to mirror lease
+//! drops turns on a bounded log with
+//! [`PermissionLeaseCache::set_eviction_log`] and drains it itself with
+//! [`PermissionLeaseCache::take_evicted`] — a pull model, so nothing is
+//! called back from inside `stamp` on the hot path.
 
 use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
+/// Leases stamped on one path, with the path's LRU tick.
+#[derive(Default)]
+struct PathLeases {
+    /// agent_id → granted_at.
+    agents: DashMap<String, Instant>,
+    last_used: AtomicU64,
+}
+
 /// Permission lease cache — path → (agent_id → granted_at).
 ///
 /// Lock-free concurrent reads on both levels (DashMap sharded
 /// buckets). Writes are infrequent (only on lease miss → ReBAC
 /// check → stamp).
 pub struct PermissionLeaseCache {
-    leases: DashMap<String, DashMap<String, Instant>>,
+    leases: DashMap<String, PathLeases>,
     ttl: Duration,
     /// Soft cap on unique paths. When the outer map reaches 90% of
     /// this bound, [`Self::stamp`] runs an `evict_expired` pass; if
-    /// the cap is still hit, the whole table is cleared (cold-start
-    /// fallback, same strategy as the Python `PermissionLeaseTable`).
+    /// the cap is still hit, the least recently used paths are dropped
+    /// down to 75% of it.
     max_entries: usize,
+    /// Source of LRU ticks; the next tick to hand out.
+    clock: AtomicU64,
+    /// Paths dropped by capacity eviction, oldest first. Holds at most
+    /// `eviction_log_capacity` entries; 0 disables the log.
+    evicted: Mutex<VecDeque<String>>,
+    eviction_log_capacity: AtomicUsize,
 }
 
 impl PermissionLeaseCache {
@@ -37,13 +67,40 @@ impl PermissionLeaseCache {
             leases: DashMap::with_capacity(1024),
             ttl,
             max_entries,
+            clock: AtomicU64::new(1),
+            evicted: Mutex::new(VecDeque::new()),
+            eviction_log_capacity: AtomicUsize::new(0),
         }
     }
 
+    /// [`Self::set_eviction_log`] for a cache being built.
+    pub fn with_eviction_log(self, capacity: usize) -> Self {
+        self.set_eviction_log(capacity);
+        self
+    }
+
+    /// Record up to `capacity` recently evicted paths for
+    /// [`Self::take_evicted`]; 0 turns the log off and empties it. When the
+    /// log is full the oldest path is dropped. Only capacity eviction in
+    /// [`Self::stamp`] is logged; explicit `invalidate_*` calls are not.
+    pub fn set_eviction_log(&self, capacity: usize) {
+        let mut evicted = self.evicted.lock();
+        self.eviction_log_capacity
+            .store(capacity, Ordering::Relaxed);
+        let overflow = evicted.len().saturating_sub(capacity);
+        evicted.drain(..overflow);
+    }
+
+    /// Drain the paths evicted since the last call, oldest first.
+    pub fn take_evicted(&self) -> Vec<String> {
+        self.evicted.lock().drain(..).collect()
+    }
+
     /// Check whether a valid lease exists for (path, agent_id).
     ///
     /// Walks up the path hierarchy (inheritance-aware): a lease on
-    /// `/docs` covers `/docs/readme.md`. Returns true on first hit.
+    /// `/docs` covers `/docs/readme.md`. Returns true on first hit,
+    /// marking the path that held the lease as recently used.
     /// Expired entries are lazily removed on access.
     ///
     /// Hot path: zero String allocations on a hit. Both
@@ -58,14 +115,21 @@ impl PermissionLeaseCache {
         let mut current = path;
         loop {
             if let Some(inner) = self.leases.get(current) {
-                if let Some(stamped) = inner.get(agent_id) {
+                if let Some(stamped) = inner.agents.get(agent_id) {
                     if stamped.value().elapsed() < self.ttl {
+                        // Repeated hits on the most recent path skip the
+                        // shared clock.
+                        if inner.last_used.load(Ordering::Relaxed)
+                            != self.clock.load(Ordering::Relaxed) - 1
+                        {
+                            self.bump(&inner);
+                        }
                         return true;
                     }
                     // Expired — release inner Ref before write to avoid
                     // shared-vs-exclusive contention on the same shard.
                     drop(stamped);
-                    inner.remove(agent_id);
+                    inner.agents.remove(agent_id);
                 }
             }
 
@@ -88,9 +152,9 @@ impl PermissionLeaseCache {
     /// Record a successful permission check as a lease.
     ///
     /// Triggers lazy eviction at 90% of the unique-path cap: expired
-    /// entries are removed first; if still over cap, the table is
-    /// cleared (cold start equivalent — same strategy as the Python
-    /// implementation).
+    /// entries are removed first; if still over cap, the least recently
+    /// used paths are dropped until 75% of the cap is left, so the next
+    /// sweep is a while off.
     pub fn stamp(&self, path: &str, agent_id: &str) {
         if agent_id.is_empty() {
             return;
@@ -100,14 +164,39 @@ impl PermissionLeaseCache {
         if self.leases.len() >= self.max_entries * 9 / 10 {
             self.evict_expired();
             if self.leases.len() >= self.max_entries {
-                self.leases.clear();
+                self.evict_lru(self.max_entries * 3 / 4);
             }
         }
 
-        self.leases
-            .entry(path.to_string())
-            .or_default()
-            .insert(agent_id.to_string(), Instant::now());
+        let inner = self.leases.entry(path.to_string()).or_default();
+        inner.agents.insert(agent_id.to_string(), Instant::now());
+        self.bump(&inner);
+    }
+
+    /// Mark `path` as recently used, so capacity eviction in
+    /// [`Self::stamp`] drops other paths first. Returns whether `path`
+    /// had a live lease; paths holding only expired leases are left
+    /// alone.
+    ///
+    /// Only the LRU order changes: no lease is extended, so a lease still
+    /// expires `ttl` after the `stamp` that granted it.
+    pub fn touch(&self, path: &str) -> bool {
+        let Some(inner) = self.leases.get(path) else {
+            return false;
+        };
+        let live = inner
+            .agents
+            .iter()
+            .any(|stamped| stamped.value().elapsed() < self.ttl);
+        if live {
+            self.bump(&inner);
+        }
+        live
+    }
+
+    /// [`Self::touch`] for each of `paths`, in order.
+    pub fn touch_multi<S: AsRef<str>>(&self, paths: &[S]) -> Vec<bool> {
+        paths.iter().map(|p| self.touch(p.as_ref())).collect()
     }
 
     /// Invalidate all leases stamped for the exact given path.
@@ -125,7 +214,7 @@ impl PermissionLeaseCache {
     /// Invalidate all leases for a specific agent across every path.
     pub fn invalidate_agent(&self, agent_id: &str) {
         for entry in self.leases.iter() {
-            entry.value().remove(agent_id);
+            entry.value().agents.remove(agent_id);
         }
     }
 
@@ -138,9 +227,67 @@ impl PermissionLeaseCache {
     fn evict_expired(&self) {
         let ttl = self.ttl;
         for entry in self.leases.iter() {
-            entry.value().retain(|_, v| v.elapsed() < ttl);
+            entry.value().agents.retain(|_, v| v.elapsed() < ttl);
         }
-        self.leases.retain(|_, inner| !inner.is_empty());
+        let log = self.eviction_log_capacity.load(Ordering::Relaxed) > 0;
+        let mut dropped = Vec::new();
+        self.leases.retain(|path, inner| {
+            let keep = !inner.agents.is_empty();
+            if !keep && log {
+                dropped.push(path.clone());
+            }
+            keep
+        });
+        self.log_evicted(dropped);
+    }
+
+    /// Drop the least recently used paths until at most `keep` remain.
+    fn evict_lru(&self, keep: usize) {
+        let mut by_age: Vec<(u64, String)> = self
+            .leases
+            .iter()
+            .map(|entry| {
+                let tick = entry.value().last_used.load(Ordering::Relaxed);
+                (tick, entry.key().clone())
+            })
+            .collect();
+        let excess = by_age.len().saturating_sub(keep);
+        if excess == 0 {
+            return;
+        }
+        if excess < by_age.len() {
+            by_age.select_nth_unstable(excess);
+        }
+        by_age.truncate(excess);
+        let mut dropped = Vec::with_capacity(excess);
+        for (_, path) in by_age {
+            if self.leases.remove(&path).is_some() {
+                dropped.push(path);
+            }
+        }
+        if self.eviction_log_capacity.load(Ordering::Relaxed) > 0 {
+            self.log_evicted(dropped);
+        }
+    }
+
+    /// Give `leases` the newest LRU tick.
+    fn bump(&self, leases: &PathLeases) {
+        let tick = self.clock.fetch_add(1, Ordering::Relaxed);
+        leases.last_used.store(tick, Ordering::Relaxed);
+    }
+
+    /// Append `paths` to the eviction log, keeping only the newest
+    /// `eviction_log_capacity` entries.
+    fn log_evicted(&self, paths: Vec<String>) {
+        if paths.is_empty() {
+            return;
+        }
+        let mut evicted = self.evicted.lock();
+        let cap = self.eviction_log_capacity.load(Ordering::Relaxed);
+        let skip = paths.len().saturating_sub(cap);
+        evicted.extend(paths.into_iter().skip(skip));
+        let overflow = evicted.len().saturating_sub(cap);
+        evicted.drain(..overflow);
     }
 }
 
@@ -222,8 +369,99 @@ mod tests {
         }
         // This should trigger eviction check (9 >= 10*9/10=9)
         cache.stamp("/file-9", "agent-1");
-        // All entries should still be valid (none expired)
-        // but capacity was reached, so table was cleared then re-inserted
+        // Nothing expired and the cap was not hit, so every entry stays
+        assert!(cache.check("/file-0", "agent-1"));
         assert!(cache.check("/file-9", "agent-1"));
+        // The log is off by default.
+        assert!(cache.take_evicted().is_empty());
+    }
+
+    #[test]
+    fn test_eviction_log_surfaces_evicted_paths() {
+        let cache = PermissionLeaseCache::new(Duration::from_secs(30), 4).with_eviction_log(16);
+        for i in 0..4 {
+            cache.stamp(&format!("/file-{i}"), "agent-1");
+        }
+        assert!(cache.take_evicted().is_empty());
+
+        // Past capacity: the oldest path goes before `/file-4` lands,
+        // leaving 4*3/4 = 3.
+        cache.stamp("/file-4", "agent-1");
+        assert_eq!(cache.take_evicted(), ["/file-0"]);
+        assert!(cache.take_evicted().is_empty());
+        assert!(!cache.check("/file-0", "agent-1"));
+        assert!(cache.check("/file-1", "agent-1"));
+
+        // Explicit invalidation is not an eviction.
+        cache.invalidate_path("/file-4");
+        assert!(cache.take_evicted().is_empty());
+    }
+
+    #[test]
+    fn test_eviction_log_is_bounded_and_logs_expired_paths() {
+        let cache = PermissionLeaseCache::new(Duration::from_millis(1), 4).with_eviction_log(2);
+        for i in 0..3 {
+            cache.stamp(&format!("/file-{i}"), "agent-1");
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        // 3 >= 4*9/10: the expired pass drops all three paths, but only
+        // the newest two fit in the log.
+        cache.stamp("/file-3", "agent-1");
+        assert_eq!(cache.take_evicted().len(), 2);
+    }
+
+    #[test]
+    fn test_touch_protects_path_from_lru_eviction() {
+        let cache = PermissionLeaseCache::new(Duration::from_secs(30), 4).with_eviction_log(8);
+        for i in 0..4 {
+            cache.stamp(&format!("/file-{i}"), "agent-1");
+        }
+        assert!(!cache.touch("/missing"));
+        // `/file-0` is the least recently used until touched.
+        assert_eq!(cache.touch_multi(&["/file-0", "/missing"]), [true, false]);
+
+        cache.stamp("/file-4", "agent-1");
+        assert_eq!(cache.take_evicted(), ["/file-1"]);
+        assert!(cache.check("/file-0", "agent-1"));
+    }
+
+    #[test]
+    fn test_check_hit_counts_as_use() {
+        let cache = PermissionLeaseCache::new(Duration::from_secs(30), 4).with_eviction_log(8);
+        for i in 0..4 {
+            cache.stamp(&format!("/dir-{i}"), "agent-1");
+        }
+        // A hit through the inheritance walk marks the parent used.
+        assert!(cache.check("/dir-0/file", "agent-1"));
+
+        cache.stamp("/dir-4", "agent-1");
+        assert_eq!(cache.take_evicted(), ["/dir-1"]);
+    }
+
+    #[test]
+    fn test_touch_does_not_extend_leases() {
+        let cache = PermissionLeaseCache::new(Duration::from_millis(1), 100_000);
+        cache.stamp("/docs", "agent-1");
+        cache.touch("/docs");
+        std::thread::sleep(Duration::from_millis(5));
+        // Expiry still runs from the stamp, and expired leases stay dead.
+        assert!(!cache.touch("/docs"));
+        assert!(!cache.check("/docs", "agent-1"));
+    }
+
+    #[test]
+    fn test_eviction_log_can_be_switched_at_runtime() {
+        let cache = PermissionLeaseCache::new(Duration::from_secs(30), 2);
+        cache.set_eviction_log(8);
+        for i in 0..3 {
+            cache.stamp(&format!("/file-{i}"), "agent-1");
+        }
+        // Turning the log off drops what it held and stops recording.
+        cache.set_eviction_log(0);
+        assert!(cache.take_evicted().is_empty());
+        for i in 3..6 {
+            cache.stamp(&format!("/file-{i}"), "agent-1");
+        }
+        assert!(cache.take_evicted().is_empty());
     }
 }
@@ -156,6 +156,28 @@ impl Kernel {
         self.permission_lease_cache.invalidate_all();
     }
 
+    /// Mark each of `paths` as recently used so capacity eviction drops
+    /// other paths first; leases are not extended. One flag per path,
+    /// whether it had a live lease.
+    /// See [`PermissionLeaseCache::touch`](crate::core::permission_cache::PermissionLeaseCache::touch).
+    pub fn permission_lease_touch(&self, paths: &[String]) -> Vec<bool> {
+        self.permission_lease_cache.touch_multi(paths)
+    }
+
+    /// Keep up to `capacity` paths whose leases are dropped by capacity
+    /// eviction, for [`Self::permission_lease_take_evicted`]. Off (0) by
+    /// default; whoever turns it on owns draining it.
+    pub fn permission_lease_set_eviction_log(&self, capacity: usize) {
+        self.permission_lease_cache.set_eviction_log(capacity);
+    }
+
+    /// Drain paths whose leases were dropped by capacity eviction since
+    /// the last call. Always empty unless
+    /// [`Self::permission_lease_set_eviction_log`] turned the log on.
+    pub fn permission_lease_take_evicted(&self) -> Vec<String> {
+        self.permission_lease_cache.take_evicted()
+    }
+
     /// Dispatch POST-INTERCEPT hooks from NativeHookRegistry (fire-and-forget).
     /// No-op when registry is empty (zero-cost lock check).
     /// Uses ``read_unconditional`` for the same recursion reason as the pre dispatch.
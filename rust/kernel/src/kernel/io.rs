@@ -2799,7 +2799,8 @@ impl Kernel {
             }
         }
 
-        // Phase B fan-out — bounded parallelism over distinct work units.
+        // Phase B fan-out — bounded parallelism over distinct work units,
+        // on the pool `lib::pool::configure_thread_pool` sets (if any).
         use rayon::prelude::*;
 
         enum Unit {
@@ -2819,100 +2820,115 @@ impl Kernel {
         let max_conc = self.read_batch_max_concurrency().max(1);
         let chunk_size = units.len().div_ceil(max_conc).max(1);
 
-        let scattered: Vec<(usize, Result<SysReadResult, KernelError>)> = units
-            .par_chunks(chunk_size)
-            .flat_map_iter(|chunk| {
-                let mut local: Vec<(usize, Result<SysReadResult, KernelError>)> =
-                    Vec::with_capacity(chunk.len() * 2);
-                for unit in chunk {
-                    match unit {
-                        Unit::Group { indices } => {
-                            let lead = indices[0];
-                            let req = &reqs[lead];
-                            // Route stability check — if the lead's mount has
-                            // shifted since Phase A, fall back to full
-                            // sys_read_single so authz + hooks run against the
-                            // *current* mount. Otherwise use
-                            // sys_read_content_only since Phase A already
-                            // authorized this exact route.
-                            let phase_a_mount = resolved
-                                .get(lead)
-                                .and_then(|o| o.as_ref())
-                                .map(|r| r.route.mount_point.as_str())
-                                .unwrap_or("");
-                            let route_stable = self
-                                .vfs_router
-                                .route(&req.path, &ctx.zone_id)
-                                .map(|r| r.mount_point == phase_a_mount)
-                                .unwrap_or(false);
-                            let shared = if route_stable {
-                                self.sys_read_content_only(&req.path, ctx)
-                            } else {
-                                self.sys_read_single(&req.path, ctx, 1, 5000, 0)
-                            };
-                            let lead_cid = shared.as_ref().ok().and_then(|r| r.content_id.clone());
-                            for &i in indices.iter() {
-                                let consumer_route = resolved
-                                    .get(i)
-                                    .and_then(|o| o.as_ref())
-                                    .map(|r| &r.route)
-                                    .expect("resolved set in Phase A");
-                                let consumer_route_stable = self
-                                    .vfs_router
-                                    .route(&reqs[i].path, &ctx.zone_id)
-                                    .map(|r| r.mount_point == consumer_route.mount_point)
-                                    .unwrap_or(false);
-                                if !consumer_route_stable {
-                                    let r = self.sys_read_single(&reqs[i].path, ctx, 1, 5000, 0);
-                                    local.push((i, slice_read_result(r, &reqs[i])));
-                                    continue;
+        let scattered: Vec<(usize, Result<SysReadResult, KernelError>)> =
+            lib::pool::install(|| {
+                units
+                    .par_chunks(chunk_size)
+                    .flat_map_iter(|chunk| {
+                        let mut local: Vec<(usize, Result<SysReadResult, KernelError>)> =
+                            Vec::with_capacity(chunk.len() * 2);
+                        for unit in chunk {
+                            match unit {
+                                Unit::Group { indices } => {
+                                    let lead = indices[0];
+                                    let req = &reqs[lead];
+                                    // Route stability check — if the lead's mount has
+                                    // shifted since Phase A, fall back to full
+                                    // sys_read_single so authz + hooks run against the
+                                    // *current* mount. Otherwise use
+                                    // sys_read_content_only since Phase A already
+                                    // authorized this exact route.
+                                    let phase_a_mount = resolved
+                                        .get(lead)
+                                        .and_then(|o| o.as_ref())
+                                        .map(|r| r.route.mount_point.as_str())
+                                        .unwrap_or("");
+                                    let route_stable = self
+                                        .vfs_router
+                                        .route(&req.path, &ctx.zone_id)
+                                        .map(|r| r.mount_point == phase_a_mount)
+                                        .unwrap_or(false);
+                                    let shared = if route_stable {
+                                        self.sys_read_content_only(&req.path, ctx)
+                                    } else {
+                                        self.sys_read_single(&req.path, ctx, 1, 5000, 0)
+                                    };
+                                    let lead_cid =
+                                        shared.as_ref().ok().and_then(|r| r.content_id.clone());
+                                    for &i in indices.iter() {
+                                        let consumer_route = resolved
+                                            .get(i)
+                                            .and_then(|o| o.as_ref())
+                                            .map(|r| &r.route)
+                                            .expect("resolved set in Phase A");
+                                        let consumer_route_stable = self
+                                            .vfs_router
+                                            .route(&reqs[i].path, &ctx.zone_id)
+                                            .map(|r| r.mount_point == consumer_route.mount_point)
+                                            .unwrap_or(false);
+                                        if !consumer_route_stable {
+                                            let r = self.sys_read_single(
+                                                &reqs[i].path,
+                                                ctx,
+                                                1,
+                                                5000,
+                                                0,
+                                            );
+                                            local.push((i, slice_read_result(r, &reqs[i])));
+                                            continue;
+                                        }
+                                        let fresh_meta = self
+                                            .with_metastore_route(consumer_route, |ms| {
+                                                ms.get(&reqs[i].path).ok().flatten()
+                                            })
+                                            .flatten();
+                                        let consumer_cid = fresh_meta
+                                            .as_ref()
+                                            .and_then(|m| m.content_id.as_deref());
+                                        let bytes_match = match (&lead_cid, consumer_cid) {
+                                            (Some(l), Some(c)) => l == c,
+                                            _ => false,
+                                        };
+                                        if bytes_match {
+                                            local.push((
+                                                i,
+                                                clone_read_result(
+                                                    &shared,
+                                                    &reqs[i],
+                                                    fresh_meta.as_ref(),
+                                                ),
+                                            ));
+                                        } else {
+                                            let r = self.sys_read_content_only(&reqs[i].path, ctx);
+                                            local.push((i, slice_read_result(r, &reqs[i])));
+                                        }
+                                    }
                                 }
-                                let fresh_meta = self
-                                    .with_metastore_route(consumer_route, |ms| {
-                                        ms.get(&reqs[i].path).ok().flatten()
-                                    })
-                                    .flatten();
-                                let consumer_cid =
-                                    fresh_meta.as_ref().and_then(|m| m.content_id.as_deref());
-                                let bytes_match = match (&lead_cid, consumer_cid) {
-                                    (Some(l), Some(c)) => l == c,
-                                    _ => false,
-                                };
-                                if bytes_match {
-                                    local.push((
-                                        i,
-                                        clone_read_result(&shared, &reqs[i], fresh_meta.as_ref()),
-                                    ));
-                                } else {
-                                    let r = self.sys_read_content_only(&reqs[i].path, ctx);
-                                    local.push((i, slice_read_result(r, &reqs[i])));
+                                Unit::Singleton { idx } => {
+                                    let req = &reqs[*idx];
+                                    let phase_a_mount = resolved
+                                        .get(*idx)
+                                        .and_then(|o| o.as_ref())
+                                        .map(|r| r.route.mount_point.as_str())
+                                        .unwrap_or("");
+                                    let route_stable = self
+                                        .vfs_router
+                                        .route(&req.path, &ctx.zone_id)
+                                        .map(|r| r.mount_point == phase_a_mount)
+                                        .unwrap_or(false);
+                                    let r = if route_stable {
+                                        self.sys_read_content_only(&req.path, ctx)
+                                    } else {
+                                        self.sys_read_single(&req.path, ctx, 1, 5000, 0)
+                                    };
+                                    local.push((*idx, slice_read_result(r, req)));
                                 }
                             }
                         }
-                        Unit::Singleton { idx } => {
-                            let req = &reqs[*idx];
-                            let phase_a_mount = resolved
-                                .get(*idx)
-                                .and_then(|o| o.as_ref())
-                                .map(|r| r.route.mount_point.as_str())
-                                .unwrap_or("");
-                            let route_stable = self
-                                .vfs_router
-                                .route(&req.path, &ctx.zone_id)
-                                .map(|r| r.mount_point == phase_a_mount)
-                                .unwrap_or(false);
-                            let r = if route_stable {
-                                self.sys_read_content_only(&req.path, ctx)
-                            } else {
-                                self.sys_read_single(&req.path, ctx, 1, 5000, 0)
-                            };
-                            local.push((*idx, slice_read_result(r, req)));
-                        }
-                    }
-                }
-                local.into_iter()
-            })
-            .collect();
+                        local.into_iter()
+                    })
+                    .collect()
+            });
 
         for (i, r) in scattered {
             results[i] = Some(r);
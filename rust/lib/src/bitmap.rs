@@ -1,7 +1,115 @@
-//! Roaring Bitmap operations for Tiger Cache acceleration.
+//! Roaring Bitmap operations for Tiger Cache acceleration, plus
+//! `RoaringSet` — a general-purpose compressed `u32` set for id sets
+//! that are not tied to the Tiger Cache.
 
 use roaring::RoaringBitmap;
 
+/// Compressed set of `u32` ids backed by a Roaring bitmap.
+///
+/// Set algebra returns new sets and leaves both operands untouched, so the
+/// type maps directly onto an immutable-operand binding API.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoaringSet {
+    bitmap: RoaringBitmap,
+}
+
+impl RoaringSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert `id`. Returns `true` if it was not already present.
+    pub fn add(&mut self, id: u32) -> bool {
+        self.bitmap.insert(id)
+    }
+
+    /// Remove `id`. Returns `true` if it was present.
+    pub fn remove(&mut self, id: u32) -> bool {
+        self.bitmap.remove(id)
+    }
+
+    /// Check membership.
+    pub fn contains(&self, id: u32) -> bool {
+        self.bitmap.contains(id)
+    }
+
+    /// Number of ids in the set.
+    pub fn len(&self) -> u64 {
+        self.bitmap.len()
+    }
+
+    /// Returns true if empty.
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.is_empty()
+    }
+
+    /// Ids present in either set.
+    pub fn union(&self, other: &RoaringSet) -> RoaringSet {
+        RoaringSet {
+            bitmap: &self.bitmap | &other.bitmap,
+        }
+    }
+
+    /// Ids present in both sets.
+    pub fn intersect(&self, other: &RoaringSet) -> RoaringSet {
+        RoaringSet {
+            bitmap: &self.bitmap & &other.bitmap,
+        }
+    }
+
+    /// Ids present in `self` but not in `other`.
+    pub fn difference(&self, other: &RoaringSet) -> RoaringSet {
+        RoaringSet {
+            bitmap: &self.bitmap - &other.bitmap,
+        }
+    }
+
+    /// Serialize to bytes (standard RoaringFormatSpec, readable by
+    /// `deserialize_bitmap` and other Roaring implementations).
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.bitmap.serialized_size());
+        self.bitmap
+            .serialize_into(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    /// Deserialize from bytes produced by [`RoaringSet::serialize`].
+    pub fn deserialize(bytes: &[u8]) -> Result<RoaringSet, std::io::Error> {
+        Ok(RoaringSet {
+            bitmap: deserialize_bitmap(bytes)?,
+        })
+    }
+
+    /// One page of ids in ascending order, strictly greater than `after`.
+    ///
+    /// Pass the last id of the previous page as `after` to continue; a page
+    /// shorter than `limit` means the set is exhausted. Cursor-based rather
+    /// than offset-based so each page is O(page) instead of O(offset).
+    pub fn page(&self, after: Option<u32>, limit: usize) -> Vec<u32> {
+        let start = match after {
+            Some(u32::MAX) => return Vec::new(),
+            Some(id) => id + 1,
+            None => 0,
+        };
+        self.bitmap.range(start..).take(limit).collect()
+    }
+
+    /// Iterate over all ids in ascending order.
+    pub fn iter(&self) -> roaring::bitmap::Iter<'_> {
+        self.bitmap.iter()
+    }
+}
+
+impl FromIterator<u32> for RoaringSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        RoaringSet {
+            bitmap: iter.into_iter().collect(),
+        }
+    }
+}
+
 /// Filter path IDs using a deserialized Roaring Bitmap.
 ///
 /// Returns IDs present in both the input list and the bitmap.
@@ -77,6 +185,72 @@ mod tests {
         assert_eq!(bitmap, deserialized);
     }
 
+    fn sorted(set: &std::collections::BTreeSet<u32>) -> Vec<u32> {
+        set.iter().copied().collect()
+    }
+
+    #[test]
+    fn roaring_set_algebra_matches_std_set() {
+        use std::collections::BTreeSet;
+
+        let a_ids = [0, 1, 5, 70_000, 70_001, 1 << 20, u32::MAX];
+        let b_ids = [1, 2, 5, 70_001, 1 << 21, u32::MAX];
+        let a: RoaringSet = a_ids.iter().copied().collect();
+        let b: RoaringSet = b_ids.iter().copied().collect();
+        let a_std: BTreeSet<u32> = a_ids.iter().copied().collect();
+        let b_std: BTreeSet<u32> = b_ids.iter().copied().collect();
+
+        let union: Vec<u32> = a.union(&b).iter().collect();
+        let intersect: Vec<u32> = a.intersect(&b).iter().collect();
+        let difference: Vec<u32> = a.difference(&b).iter().collect();
+        assert_eq!(union, sorted(&(&a_std | &b_std)));
+        assert_eq!(intersect, sorted(&(&a_std & &b_std)));
+        assert_eq!(difference, sorted(&(&a_std - &b_std)));
+        // Operands are unchanged.
+        assert_eq!(a.len(), a_ids.len() as u64);
+        assert_eq!(b.len(), b_ids.len() as u64);
+    }
+
+    #[test]
+    fn roaring_set_add_remove_contains() {
+        let mut set = RoaringSet::new();
+        assert!(set.is_empty());
+        assert!(set.add(42));
+        assert!(!set.add(42));
+        assert!(set.contains(42));
+        assert_eq!(set.len(), 1);
+        assert!(set.remove(42));
+        assert!(!set.remove(42));
+        assert!(!set.contains(42));
+    }
+
+    #[test]
+    fn roaring_set_serialize_roundtrip() {
+        let set: RoaringSet = (0..10_000).step_by(3).chain([u32::MAX]).collect();
+        let bytes = set.serialize();
+        assert_eq!(RoaringSet::deserialize(&bytes).unwrap(), set);
+        // Wire-compatible with the Tiger Cache deserializer.
+        assert_eq!(deserialize_bitmap(&bytes).unwrap().len(), set.len());
+        assert!(RoaringSet::deserialize(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn roaring_set_pagination_covers_all_ids() {
+        let set: RoaringSet = (0..25).map(|i| i * 7).chain([u32::MAX]).collect();
+        let mut seen = Vec::new();
+        let mut after = None;
+        loop {
+            let page = set.page(after, 10);
+            seen.extend_from_slice(&page);
+            if page.len() < 10 {
+                break;
+            }
+            after = page.last().copied();
+        }
+        assert_eq!(seen, set.iter().collect::<Vec<_>>());
+        assert!(set.page(Some(u32::MAX), 10).is_empty());
+    }
+
     #[test]
     fn empty_inputs() {
         let bitmap = make_bitmap(&[1, 2, 3]);
@@ -4,6 +4,7 @@
 //! The `bloomfilter` crate has uncertain WASM support, so we implement our own.
 
 use ahash::AHasher;
+use roaring::RoaringBitmap;
 use std::hash::{Hash, Hasher};
 
 /// A Bloom filter for fast probabilistic set-membership testing.
@@ -24,16 +25,7 @@ impl BloomFilter {
     pub fn new(expected_items: usize, fp_rate: f64) -> Self {
         let expected_items = expected_items.max(1);
         let fp_rate = fp_rate.clamp(1e-10, 1.0);
-
-        // Optimal number of bits: m = -n * ln(p) / (ln(2))^2
-        let num_bits =
-            (-(expected_items as f64) * fp_rate.ln() / (2.0_f64.ln().powi(2))).ceil() as usize;
-        let num_bits = num_bits.max(64);
-
-        // Optimal number of hashes: k = (m/n) * ln(2)
-        let num_hashes = ((num_bits as f64 / expected_items as f64) * 2.0_f64.ln()).ceil() as u32;
-        let num_hashes = num_hashes.clamp(1, 30);
-
+        let (num_bits, num_hashes) = optimal_params(expected_items, fp_rate);
         let num_words = num_bits.div_ceil(64);
 
         BloomFilter {
@@ -45,6 +37,23 @@ impl BloomFilter {
         }
     }
 
+    /// Build a filter sized for `bitmap` and holding all of its ids.
+    ///
+    /// Ids are inserted as `u32`, so query with `might_contain(&id)` where
+    /// `id: u32`.
+    pub fn from_roaring(bitmap: &RoaringBitmap, fp_rate: f64) -> Self {
+        let mut bloom = Self::new(bitmap.len() as usize, fp_rate);
+        bloom.add_all(bitmap);
+        bloom
+    }
+
+    /// Add every item from `items`.
+    pub fn add_all<T: Hash>(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            self.add(&item);
+        }
+    }
+
     /// Add an item to the filter.
     pub fn add<T: Hash>(&mut self, item: &T) {
         for i in 0..self.num_hashes {
@@ -99,6 +108,22 @@ impl BloomFilter {
     }
 }
 
+/// Optimal `(num_bits, num_hashes)` for `expected_items` at `fp_rate`.
+///
+/// Callers are expected to have clamped both inputs already.
+pub(crate) fn optimal_params(expected_items: usize, fp_rate: f64) -> (usize, u32) {
+    // Optimal number of bits: m = -n * ln(p) / (ln(2))^2
+    let num_bits =
+        (-(expected_items as f64) * fp_rate.ln() / (2.0_f64.ln().powi(2))).ceil() as usize;
+    let num_bits = num_bits.max(64);
+
+    // Optimal number of hashes: k = (m/n) * ln(2)
+    let num_hashes = ((num_bits as f64 / expected_items as f64) * 2.0_f64.ln()).ceil() as u32;
+    let num_hashes = num_hashes.clamp(1, 30);
+
+    (num_bits, num_hashes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,6 +143,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_roaring_has_no_false_negatives() {
+        let bitmap: RoaringBitmap = (0..5_000u32)
+            .map(|i| i * 7 + 3)
+            .chain(1 << 20..(1 << 20) + 500)
+            .collect();
+        let bloom = BloomFilter::from_roaring(&bitmap, 0.01);
+        assert_eq!(bloom.capacity(), bitmap.len() as usize);
+        for id in &bitmap {
+            assert!(bloom.might_contain(&id), "false negative for id {id}");
+        }
+
+        // add_all over the same ids yields the same bits.
+        let mut incremental = BloomFilter::new(bitmap.len() as usize, 0.01);
+        incremental.add_all(bitmap.iter());
+        assert_eq!(incremental.bits, bloom.bits);
+    }
+
     #[test]
     fn false_positive_rate_within_bounds() {
         let n = 10_000;
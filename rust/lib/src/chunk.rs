@@ -0,0 +1,711 @@
+//! Line-aligned text chunking with pluggable token estimation.
+//!
+//! `chunk_lines()` greedily packs whole lines into chunks that stay within
+//! a token budget, so chunk sizes track downstream model limits. Lines
+//! longer than the budget on their own are split at char boundaries.
+//!
+//! Token counts come from a [`TokenEstimator`]. `Chars` (the default) is
+//! the historical behaviour; `Whitespace` and `BytesPerToken` give closer
+//! approximations for word-level and BPE tokenizers respectively.
+//!
+//! [`ChunkStrategy::ContentDefined`] instead cuts where a rolling (gear)
+//! hash of the bytes hits a mask, so boundaries follow content rather than
+//! position: an edit only moves the boundaries near it, and chunk ids
+//! ([`Chunk::id`]) elsewhere in the document survive for incremental
+//! re-embedding. [`cdc_chunk`] cuts raw bytes with FastCDC, as the
+//! content store does, for sub-file deduplication, and
+//! [`group_near_duplicates`] clusters files by the chunk hashes they share.
+
+use std::collections::{HashMap, HashSet};
+
+/// How to approximate the token count of a piece of text.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TokenEstimator {
+    /// One token per Unicode scalar value.
+    #[default]
+    Chars,
+    /// One token per whitespace-separated word.
+    Whitespace,
+    /// `ceil(utf8_bytes / ratio)` — approximates BPE tokenizers, which
+    /// average roughly 3.5–4 bytes per token on English and code.
+    BytesPerToken(f32),
+}
+
+impl TokenEstimator {
+    /// Estimate the token count of `text`.
+    pub fn count(&self, text: &str) -> usize {
+        self.tokens(&Tally::of(text))
+    }
+
+    fn tokens(&self, tally: &Tally) -> usize {
+        match *self {
+            TokenEstimator::Chars => tally.chars,
+            TokenEstimator::Whitespace => tally.words,
+            TokenEstimator::BytesPerToken(ratio) => {
+                let ratio = if ratio.is_finite() && ratio > 0.0 {
+                    ratio as f64
+                } else {
+                    1.0
+                };
+                (tally.bytes as f64 / ratio).ceil() as usize
+            }
+        }
+    }
+}
+
+/// Running measurements for incremental token estimation.
+///
+/// Every estimator is a function of these counters, so extending a
+/// chunk by one char is O(1) regardless of estimator.
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    chars: usize,
+    bytes: usize,
+    words: usize,
+    in_word: bool,
+}
+
+impl Tally {
+    fn push(&mut self, ch: char) {
+        self.chars += 1;
+        self.bytes += ch.len_utf8();
+        if ch.is_whitespace() {
+            self.in_word = false;
+        } else if !self.in_word {
+            self.in_word = true;
+            self.words += 1;
+        }
+    }
+
+    fn of(text: &str) -> Tally {
+        let mut tally = Tally::default();
+        for ch in text.chars() {
+            tally.push(ch);
+        }
+        tally
+    }
+
+    /// Tally of `self` followed by `other`.
+    ///
+    /// Exact for `chars`/`bytes`; `words` is exact when `self` ends in
+    /// whitespace, which holds for every line but the last.
+    fn merged(&self, other: &Tally) -> Tally {
+        Tally {
+            chars: self.chars + other.chars,
+            bytes: self.bytes + other.bytes,
+            words: self.words + other.words,
+            in_word: other.in_word || (other.chars == 0 && self.in_word),
+        }
+    }
+}
+
+/// A contiguous run of lines (or part of one oversized line).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// 1-indexed line of the first byte in `content`.
+    pub start_line: usize,
+    /// 1-indexed line of the last byte in `content` (inclusive).
+    pub end_line: usize,
+    pub content: String,
+    /// Estimated tokens in `content`; never exceeds the chunk budget.
+    pub tokens: usize,
+}
+
+impl Chunk {
+    /// Content address of the chunk: BLAKE3 hex of `content`.
+    pub fn id(&self) -> String {
+        crate::hash::hash_content(self.content.as_bytes())
+    }
+}
+
+/// How [`chunk`] splits a document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChunkStrategy {
+    /// Greedy line packing under a token budget; see [`chunk_lines`].
+    Lines {
+        max_tokens: usize,
+        estimator: TokenEstimator,
+    },
+    /// Content-defined boundaries; see [`chunk_content_defined`]. Sizes
+    /// are in UTF-8 bytes.
+    ContentDefined {
+        avg_size: usize,
+        min: usize,
+        max: usize,
+    },
+}
+
+/// Split `content` according to `strategy`.
+pub fn chunk(content: &str, strategy: ChunkStrategy) -> Vec<Chunk> {
+    match strategy {
+        ChunkStrategy::Lines {
+            max_tokens,
+            estimator,
+        } => chunk_lines(content, max_tokens, estimator),
+        ChunkStrategy::ContentDefined { avg_size, min, max } => {
+            chunk_content_defined(content, avg_size, min, max)
+        }
+    }
+}
+
+/// Split `content` into chunks of at most `max_tokens` estimated tokens.
+///
+/// Chunks break on line boundaries where possible and keep their trailing
+/// newlines, so concatenating `content` of every chunk reproduces the
+/// input. `max_tokens` is clamped to at least 1.
+pub fn chunk_lines(content: &str, max_tokens: usize, estimator: TokenEstimator) -> Vec<Chunk> {
+    let max_tokens = max_tokens.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tally = Tally::default();
+    let mut current_start = 1;
+
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let line_no = idx + 1;
+        let line_tally = Tally::of(line);
+        let merged = current_tally.merged(&line_tally);
+
+        if estimator.tokens(&merged) <= max_tokens {
+            if current.is_empty() {
+                current_start = line_no;
+            }
+            current.push_str(line);
+            current_tally = merged;
+            continue;
+        }
+
+        if !current.is_empty() {
+            chunks.push(Chunk {
+                start_line: current_start,
+                end_line: line_no - 1,
+                content: std::mem::take(&mut current),
+                tokens: estimator.tokens(&current_tally),
+            });
+            current_tally = Tally::default();
+        }
+
+        if estimator.tokens(&line_tally) <= max_tokens {
+            current_start = line_no;
+            current.push_str(line);
+            current_tally = line_tally;
+        } else {
+            split_long_line(line, line_no, max_tokens, estimator, &mut chunks);
+        }
+    }
+
+    if !current.is_empty() {
+        let end_line =
+            current_start + current.matches('\n').count() - usize::from(current.ends_with('\n'));
+        chunks.push(Chunk {
+            start_line: current_start,
+            end_line,
+            content: current,
+            tokens: estimator.tokens(&current_tally),
+        });
+    }
+
+    chunks
+}
+
+/// Split a single line that exceeds the budget at char boundaries.
+fn split_long_line(
+    line: &str,
+    line_no: usize,
+    max_tokens: usize,
+    estimator: TokenEstimator,
+    chunks: &mut Vec<Chunk>,
+) {
+    let mut piece_start = 0;
+    let mut tally = Tally::default();
+    for (offset, ch) in line.char_indices() {
+        let mut next = tally;
+        next.push(ch);
+        if estimator.tokens(&next) > max_tokens && offset > piece_start {
+            chunks.push(Chunk {
+                start_line: line_no,
+                end_line: line_no,
+                content: line[piece_start..offset].to_string(),
+                tokens: estimator.tokens(&tally),
+            });
+            piece_start = offset;
+            next = Tally::default();
+            next.push(ch);
+        }
+        tally = next;
+    }
+    if piece_start < line.len() {
+        chunks.push(Chunk {
+            start_line: line_no,
+            end_line: line_no,
+            content: line[piece_start..].to_string(),
+            tokens: estimator.tokens(&tally),
+        });
+    }
+}
+
+/// Gear table: one pseudo-random 64-bit value per byte (SplitMix64 from a
+/// fixed seed, so boundaries are identical across builds and platforms).
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x6E65_7875_735F_6364;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+/// Cut mask with the top `log2(avg_size - min)` bits set, so a cut after
+/// `min` bytes comes on average every `avg_size - min` bytes.
+fn gear_mask(avg_size: usize, min: usize) -> u64 {
+    let bits = (avg_size - min).max(1).next_power_of_two().trailing_zeros();
+    if bits == 0 {
+        0
+    } else {
+        u64::MAX << (64 - bits)
+    }
+}
+
+/// Split `content` at content-defined boundaries.
+///
+/// A boundary falls after a char where the gear hash of the preceding
+/// ~64 bytes has its top `log2(avg_size - min)` bits clear, subject to
+/// every chunk being between `min` and `max` bytes (the last chunk may be
+/// shorter). Boundaries always land on char boundaries, so a chunk can
+/// end mid-line; concatenating `content` reproduces the input. `tokens`
+/// is the [`TokenEstimator::Chars`] count.
+///
+/// `min` is clamped to at least 1, `max` to at least `min + 4` and
+/// `avg_size` into `min..=max`.
+pub fn chunk_content_defined(content: &str, avg_size: usize, min: usize, max: usize) -> Vec<Chunk> {
+    let min = min.max(1);
+    // Room for at least one 4-byte char past `min`.
+    let max = max.max(min + 4);
+    let mask = gear_mask(avg_size.clamp(min, max), min);
+
+    let bytes = content.as_bytes();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut start_line = 1;
+    let mut hash = 0u64;
+    let push = |chunks: &mut Vec<Chunk>, start: &mut usize, end: usize, line: &mut usize| {
+        let text = &content[*start..end];
+        let newlines = memchr::memchr_iter(b'\n', text.as_bytes()).count();
+        chunks.push(Chunk {
+            start_line: *line,
+            end_line: *line + newlines - usize::from(text.ends_with('\n')),
+            content: text.to_string(),
+            tokens: text.chars().count(),
+        });
+        *start = end;
+        *line += newlines;
+    };
+
+    for (offset, ch) in content.char_indices() {
+        let end = offset + ch.len_utf8();
+        if end - start > max {
+            push(&mut chunks, &mut start, offset, &mut start_line);
+        }
+        for &byte in &bytes[offset..end] {
+            hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        }
+        if end - start >= min && hash & mask == 0 {
+            push(&mut chunks, &mut start, end, &mut start_line);
+        }
+    }
+    if start < content.len() {
+        push(&mut chunks, &mut start, content.len(), &mut start_line);
+    }
+    chunks
+}
+
+/// One chunk from [`cdc_chunk`]: `(offset, len, blake3_hex)`.
+pub type CdcChunk = (usize, usize, String);
+
+/// Split binary `content` at content-defined boundaries and hash each
+/// chunk with BLAKE3, for deduplication across files.
+///
+/// Uses FastCDC (2020), the chunker behind the kernel's CAS store; pass
+/// `contracts::CDC_{AVG,MIN,MAX}_CHUNK_SIZE` to cut exactly as it does.
+/// Identical regions in different files yield identical chunks (and
+/// hashes) once past their first boundary. Every chunk is between `min`
+/// and `max` bytes except possibly the last. Sizes are clamped to the
+/// ranges FastCDC supports (`min` 64 B–1 MiB, `avg_size` 256 B–4 MiB,
+/// `max` 1 KiB–16 MiB), `max` to at least `min` and `avg_size` into
+/// `min..=max`.
+pub fn cdc_chunk(content: &[u8], avg_size: usize, min: usize, max: usize) -> Vec<CdcChunk> {
+    use fastcdc::v2020;
+
+    let min = min.clamp(v2020::MINIMUM_MIN, v2020::MINIMUM_MAX);
+    let max = max.clamp(v2020::MAXIMUM_MIN, v2020::MAXIMUM_MAX).max(min);
+    let avg_size = avg_size
+        .clamp(min, max)
+        .clamp(v2020::AVERAGE_MIN, v2020::AVERAGE_MAX);
+    v2020::FastCDC::new(content, min, avg_size, max)
+        .map(|chunk| {
+            let bytes = &content[chunk.offset..chunk.offset + chunk.length];
+            let hex = crate::hash::hash_content(bytes);
+            (chunk.offset, chunk.length, hex)
+        })
+        .collect()
+}
+
+/// Chunk sizes used by [`group_near_duplicates`]: small enough that a
+/// few-KiB file still yields several chunks.
+const NEAR_DUP_AVG_CHUNK: usize = 2048;
+const NEAR_DUP_MIN_CHUNK: usize = 512;
+const NEAR_DUP_MAX_CHUNK: usize = 8192;
+/// Chunks held by more files than this (runs of zeros, shared headers)
+/// say little about similarity and would cost a pair count per pair of
+/// holders, so [`group_near_duplicates`] ignores them when counting.
+const NEAR_DUP_MAX_HOLDERS: usize = 64;
+
+/// Cluster near-duplicate files by shared content-defined chunks.
+///
+/// Each file is split with [`cdc_chunk`] (2 KiB average chunks) into a
+/// set of BLAKE3 chunk hashes. Two files are linked when their sets have
+/// at least `min_shared_chunks` hashes in common (clamped to at least
+/// 1), and clusters are the connected components of those links, so a
+/// cluster can hold files that are only similar through a third.
+///
+/// With `min_shared_chunks == 1` every chunk links all of its holders
+/// directly. Otherwise shared chunks are counted per pair of files,
+/// skipping chunks held by more than 64 files, so files similar only
+/// through such common chunks are not linked.
+///
+/// Only clusters of two or more files are returned. Paths within a
+/// cluster, and clusters by their first path, keep the order of `files`.
+pub fn group_near_duplicates(
+    files: &[(String, Vec<u8>)],
+    min_shared_chunks: usize,
+) -> Vec<Vec<String>> {
+    let min_shared_chunks = min_shared_chunks.max(1);
+
+    // Chunk hash → indices of the files containing it (each file once).
+    let mut holders: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, (_, content)) in files.iter().enumerate() {
+        let hashes: HashSet<String> = cdc_chunk(
+            content,
+            NEAR_DUP_AVG_CHUNK,
+            NEAR_DUP_MIN_CHUNK,
+            NEAR_DUP_MAX_CHUNK,
+        )
+        .into_iter()
+        .map(|(_, _, hex)| hex)
+        .collect();
+        for hex in hashes {
+            holders.entry(hex).or_default().push(i);
+        }
+    }
+
+    // Union-find over linked pairs; roots are the lowest index.
+    let mut parent: Vec<usize> = (0..files.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    fn union(parent: &mut [usize], a: usize, b: usize) {
+        let (ra, rb) = (root(parent, a), root(parent, b));
+        parent[ra.max(rb)] = ra.min(rb);
+    }
+
+    if min_shared_chunks == 1 {
+        for indices in holders.values() {
+            for pair in indices.windows(2) {
+                union(&mut parent, pair[0], pair[1]);
+            }
+        }
+    } else {
+        let mut shared: HashMap<(usize, usize), usize> = HashMap::new();
+        for indices in holders.values() {
+            if indices.len() > NEAR_DUP_MAX_HOLDERS {
+                continue;
+            }
+            for (n, &a) in indices.iter().enumerate() {
+                for &b in &indices[n + 1..] {
+                    *shared.entry((a, b)).or_default() += 1;
+                }
+            }
+        }
+        for (&(a, b), &count) in &shared {
+            if count >= min_shared_chunks {
+                union(&mut parent, a, b);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<String>> = Vec::new();
+    let mut cluster_of: HashMap<usize, usize> = HashMap::new();
+    for (i, (path, _)) in files.iter().enumerate() {
+        let r = root(&mut parent, i);
+        let slot = *cluster_of.entry(r).or_insert_with(|| {
+            clusters.push(Vec::new());
+            clusters.len() - 1
+        });
+        clusters[slot].push(path.clone());
+    }
+    clusters.retain(|cluster| cluster.len() > 1);
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str =
+        "fn main() {\n    let greeting = \"hello world\";\n\n    println!(\"{greeting}\");\n}\n";
+
+    fn estimators() -> [TokenEstimator; 3] {
+        [
+            TokenEstimator::Chars,
+            TokenEstimator::Whitespace,
+            TokenEstimator::BytesPerToken(4.0),
+        ]
+    }
+
+    #[test]
+    fn estimators_count_differently() {
+        assert_eq!(TokenEstimator::Chars.count("héllo wörld"), 11);
+        assert_eq!(TokenEstimator::Whitespace.count("héllo wörld"), 2);
+        // 13 UTF-8 bytes / 4 → 4 tokens.
+        assert_eq!(TokenEstimator::BytesPerToken(4.0).count("héllo wörld"), 4);
+        assert_eq!(TokenEstimator::default(), TokenEstimator::Chars);
+    }
+
+    #[test]
+    fn same_text_chunks_differently_per_estimator() {
+        let counts: Vec<usize> = estimators()
+            .iter()
+            .map(|&e| chunk_lines(TEXT, 12, e).len())
+            .collect();
+        assert_eq!(counts[1], 1, "whole snippet is under 12 words");
+        assert!(counts[0] > counts[2], "chars: {counts:?}");
+        assert!(counts[2] > counts[1], "bytes-per-token: {counts:?}");
+    }
+
+    #[test]
+    fn chunks_never_exceed_budget_and_reassemble() {
+        let long_line = "x".repeat(50) + " word".repeat(30).as_str() + "\n";
+        let text = format!("{TEXT}{long_line}tail");
+        for estimator in estimators() {
+            for budget in [1, 3, 7, 20] {
+                let chunks = chunk_lines(&text, budget, estimator);
+                for chunk in &chunks {
+                    assert!(chunk.tokens <= budget, "{estimator:?} budget {budget}");
+                    assert_eq!(chunk.tokens, estimator.count(&chunk.content));
+                }
+                let rebuilt: String = chunks.iter().map(|c| c.content.as_str()).collect();
+                assert_eq!(rebuilt, text);
+            }
+        }
+    }
+
+    #[test]
+    fn line_numbers_track_chunk_boundaries() {
+        let chunks = chunk_lines("a\nb\nc\nd\n", 4, TokenEstimator::Chars);
+        let spans: Vec<(usize, usize)> =
+            chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(spans, vec![(1, 2), (3, 4)]);
+
+        let chunks = chunk_lines("abcdef", 4, TokenEstimator::Chars);
+        let spans: Vec<(usize, usize)> =
+            chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(spans, vec![(1, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn empty_content_has_no_chunks() {
+        assert!(chunk_lines("", 10, TokenEstimator::Chars).is_empty());
+        assert!(chunk_content_defined("", 64, 16, 256).is_empty());
+    }
+
+    fn document(lines: usize) -> String {
+        (0..lines)
+            .map(|i| format!("line {i}: value = {} // note {}\n", i * 7919 % 1000, i % 13))
+            .collect()
+    }
+
+    #[test]
+    fn content_defined_respects_bounds_and_reassembles() {
+        let text = document(400) + "ünïcödé tail ✓";
+        let chunks = chunk(
+            &text,
+            ChunkStrategy::ContentDefined {
+                avg_size: 256,
+                min: 64,
+                max: 1024,
+            },
+        );
+        assert!(chunks.len() > 10, "only {} chunks", chunks.len());
+        let (last, rest) = chunks.split_last().unwrap();
+        for c in rest {
+            assert!(
+                (64..=1024).contains(&c.content.len()),
+                "{}",
+                c.content.len()
+            );
+        }
+        assert!(last.content.len() <= 1024);
+        let rebuilt: String = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(rebuilt, text);
+
+        // Line numbers agree with the chunk's position in the text.
+        let mut offset = 0;
+        for c in &chunks {
+            let line = text[..offset].matches('\n').count() + 1;
+            assert_eq!(c.start_line, line);
+            offset += c.content.len();
+        }
+    }
+
+    #[test]
+    fn inserting_a_line_only_changes_nearby_chunk_ids() {
+        let strategy = ChunkStrategy::ContentDefined {
+            avg_size: 256,
+            min: 64,
+            max: 1024,
+        };
+        let original = document(400);
+        let mid = original.match_indices('\n').nth(200).unwrap().0 + 1;
+        let edited = format!("{}an inserted line\n{}", &original[..mid], &original[mid..]);
+
+        let ids =
+            |text: &str| -> Vec<String> { chunk(text, strategy).iter().map(Chunk::id).collect() };
+        let before = ids(&original);
+        let after = ids(&edited);
+
+        // Everything well before and well after the edit keeps its id.
+        let common_prefix = before
+            .iter()
+            .zip(&after)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let common_suffix = before
+            .iter()
+            .rev()
+            .zip(after.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let changed = before.len() - common_prefix - common_suffix;
+        assert!(changed <= 3, "{changed} of {} chunks changed", before.len());
+        assert!(common_prefix > before.len() / 3);
+        assert!(common_suffix > before.len() / 3);
+    }
+
+    /// Deterministic pseudo-random bytes (xorshift), so tests don't
+    /// depend on text structure.
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cdc_chunk_covers_content_within_bounds() {
+        let data = noise(7, 100_000);
+        let chunks = cdc_chunk(&data, 2048, 512, 8192);
+        assert!(chunks.len() > 10);
+        let mut expected_offset = 0;
+        for (i, (offset, len, hex)) in chunks.iter().enumerate() {
+            assert_eq!(*offset, expected_offset);
+            assert!(*len <= 8192);
+            assert!(*len >= 512 || i == chunks.len() - 1);
+            assert_eq!(
+                *hex,
+                crate::hash::hash_content(&data[*offset..offset + len])
+            );
+            expected_offset += len;
+        }
+        assert_eq!(expected_offset, data.len());
+        assert!(cdc_chunk(&[], 2048, 512, 8192).is_empty());
+    }
+
+    #[test]
+    fn cdc_chunk_shares_hashes_across_files_with_common_region() {
+        let shared = noise(42, 64_000);
+        let file_a = [noise(1, 3_000), shared.clone(), noise(2, 5_000)].concat();
+        let file_b = [noise(3, 11_111), shared.clone()].concat();
+
+        let hashes = |data: &[u8]| -> Vec<String> {
+            cdc_chunk(data, 2048, 512, 8192)
+                .into_iter()
+                .map(|(_, _, hex)| hex)
+                .collect()
+        };
+        let (a, b) = (hashes(&file_a), hashes(&file_b));
+        let common = a.iter().filter(|h| b.contains(h)).count();
+        // Boundaries resync inside the shared region, so nearly all of
+        // its chunks are shared.
+        assert!(
+            common * 2048 > shared.len() / 2,
+            "only {common} shared chunks"
+        );
+
+        // Content-stable: same input, same boundaries.
+        assert_eq!(hashes(&file_a), a);
+    }
+
+    #[test]
+    fn near_duplicates_cluster_by_shared_chunks() {
+        let shared = noise(42, 64_000);
+        let files = vec![
+            (
+                "a.bin".to_string(),
+                [noise(1, 3_000), shared.clone()].concat(),
+            ),
+            ("unrelated.bin".to_string(), noise(9, 60_000)),
+            (
+                "b.bin".to_string(),
+                [shared.clone(), noise(2, 5_000)].concat(),
+            ),
+            ("empty.bin".to_string(), Vec::new()),
+        ];
+        assert_eq!(
+            group_near_duplicates(&files, 5),
+            vec![vec!["a.bin".to_string(), "b.bin".to_string()]]
+        );
+        // Demanding more shared chunks than the files have splits them.
+        assert!(group_near_duplicates(&files, 10_000).is_empty());
+    }
+
+    #[test]
+    fn near_duplicates_skip_chunks_common_to_many_files() {
+        let header = noise(5, 16_000);
+        let shared = noise(42, 32_000);
+        let mut files: Vec<(String, Vec<u8>)> = (0..70)
+            .map(|i| {
+                let body = [header.clone(), noise(1_000 + 2 * i, 4_000)].concat();
+                (format!("f{i}.bin"), body)
+            })
+            .collect();
+        files.push((
+            "x.bin".to_string(),
+            [noise(1, 3_000), shared.clone()].concat(),
+        ));
+        files.push(("y.bin".to_string(), [shared, noise(2, 3_000)].concat()));
+
+        // One shared chunk links files directly, header included.
+        let clusters = group_near_duplicates(&files, 1);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].len(), 70);
+        // Counting pairs ignores the header's chunks: only x and y remain.
+        assert_eq!(
+            group_near_duplicates(&files, 2),
+            vec![vec!["x.bin".to_string(), "y.bin".to_string()]]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Consistent hashing for sharding keys across nodes.
+//!
+//! [`HashRing`] places `weight × VNODES_PER_WEIGHT` virtual nodes per node
+//! on a 64-bit ring (BLAKE3 point hashes) and routes a key to the first
+//! virtual node clockwise from the key's point. Adding or removing a node
+//! only moves the keys in the arcs that node gains or loses — about
+//! `1/N` of them — while every other key keeps its owner.
+
+use std::collections::BTreeMap;
+
+use ahash::AHashMap;
+
+/// Virtual nodes per unit of weight. Enough for a few-percent load
+/// spread at typical cluster sizes without bloating the ring.
+pub const VNODES_PER_WEIGHT: u32 = 128;
+
+/// Consistent-hash ring of weighted nodes.
+#[derive(Debug, Clone, Default)]
+pub struct HashRing {
+    /// Ring point → owning node id.
+    ring: BTreeMap<u64, String>,
+    /// Node id → weight.
+    nodes: AHashMap<String, u32>,
+}
+
+/// 64-bit ring position of `bytes`.
+fn point(bytes: &[u8]) -> u64 {
+    let hash = blake3::hash(bytes);
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&hash.as_bytes()[..8]);
+    u64::from_le_bytes(head)
+}
+
+fn vnode_point(id: &str, replica: u32) -> u64 {
+    point(format!("{id}#{replica}").as_bytes())
+}
+
+impl HashRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `id` with `weight` (share of keys relative to other nodes).
+    ///
+    /// Re-adding an existing node replaces its weight. A weight of 0 is
+    /// the same as removing the node.
+    pub fn add_node(&mut self, id: &str, weight: u32) {
+        self.remove_node(id);
+        if weight == 0 {
+            return;
+        }
+        for replica in 0..weight.saturating_mul(VNODES_PER_WEIGHT) {
+            // On a (vanishingly rare) point collision the first owner
+            // keeps the point, so routing doesn't depend on insert order
+            // of later nodes.
+            self.ring
+                .entry(vnode_point(id, replica))
+                .or_insert_with(|| id.to_string());
+        }
+        self.nodes.insert(id.to_string(), weight);
+    }
+
+    /// Remove `id`; returns false if it was not on the ring.
+    pub fn remove_node(&mut self, id: &str) -> bool {
+        let Some(weight) = self.nodes.remove(id) else {
+            return false;
+        };
+        for replica in 0..weight.saturating_mul(VNODES_PER_WEIGHT) {
+            let p = vnode_point(id, replica);
+            if self.ring.get(&p).is_some_and(|owner| owner == id) {
+                self.ring.remove(&p);
+            }
+        }
+        true
+    }
+
+    /// Node that owns `key`, or `None` if the ring is empty.
+    pub fn route(&self, key: &str) -> Option<&str> {
+        let p = point(key.as_bytes());
+        self.ring
+            .range(p..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, id)| id.as_str())
+    }
+
+    pub fn contains_node(&self, id: &str) -> bool {
+        self.nodes.contains_key(id)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: usize = 20_000;
+
+    fn ring_of(n: usize) -> HashRing {
+        let mut ring = HashRing::new();
+        for i in 0..n {
+            ring.add_node(&format!("node-{i}"), 1);
+        }
+        ring
+    }
+
+    fn assignments(ring: &HashRing) -> Vec<String> {
+        (0..KEYS)
+            .map(|i| ring.route(&format!("/zone/file-{i}")).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn empty_ring_routes_nowhere() {
+        let mut ring = HashRing::new();
+        assert_eq!(ring.route("/a"), None);
+        ring.add_node("solo", 1);
+        assert_eq!(ring.route("/a"), Some("solo"));
+        assert!(ring.remove_node("solo"));
+        assert!(!ring.remove_node("solo"));
+        assert_eq!(ring.route("/a"), None);
+    }
+
+    #[test]
+    fn removing_one_of_n_moves_only_its_keys() {
+        let n = 10;
+        let mut ring = ring_of(n);
+        let before = assignments(&ring);
+
+        ring.remove_node("node-3");
+        let after = assignments(&ring);
+
+        let mut moved = 0;
+        for (old, new) in before.iter().zip(&after) {
+            if old == "node-3" {
+                assert_ne!(new, "node-3");
+                moved += 1;
+            } else {
+                assert_eq!(old, new, "key on a surviving node was reassigned");
+            }
+        }
+        let fraction = moved as f64 / KEYS as f64;
+        assert!(
+            (0.5 / n as f64..1.5 / n as f64).contains(&fraction),
+            "moved {fraction:.3} of keys"
+        );
+    }
+
+    #[test]
+    fn adding_a_node_only_pulls_keys_onto_it() {
+        let mut ring = ring_of(9);
+        let before = assignments(&ring);
+
+        ring.add_node("node-new", 1);
+        let after = assignments(&ring);
+
+        let moved = before
+            .iter()
+            .zip(&after)
+            .filter(|(old, new)| {
+                if old != new {
+                    assert_eq!(new.as_str(), "node-new");
+                }
+                old != new
+            })
+            .count();
+        let fraction = moved as f64 / KEYS as f64;
+        assert!((0.05..0.15).contains(&fraction), "moved {fraction:.3}");
+    }
+
+    #[test]
+    fn weight_scales_share_of_keys() {
+        let mut ring = HashRing::new();
+        ring.add_node("small", 1);
+        ring.add_node("big", 3);
+        let big = assignments(&ring).iter().filter(|n| *n == "big").count();
+        let share = big as f64 / KEYS as f64;
+        assert!((0.65..0.85).contains(&share), "big got {share:.3}");
+
+        // Re-adding replaces the weight rather than stacking it.
+        ring.add_node("big", 1);
+        assert_eq!(ring.node_count(), 2);
+        let big = assignments(&ring).iter().filter(|n| *n == "big").count();
+        let share = big as f64 / KEYS as f64;
+        assert!((0.4..0.6).contains(&share), "big got {share:.3}");
+    }
+}
@@ -0,0 +1,331 @@
+//! Content-type sniffing for search and chunking pipelines.
+//!
+//! `detect_content_type()` decides whether a blob is worth grepping or
+//! chunking as text, and offers a best-effort language guess so callers
+//! can pick a chunking strategy. `decode_text()` turns a text blob into
+//! UTF-8 so line-oriented consumers never split on raw bytes. Pure byte
+//! inspection — no file I/O, no extra dependencies, WASM-safe.
+
+use std::borrow::Cow;
+
+/// How many leading bytes are inspected. Matches git's binary heuristic.
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Magic prefixes of common binary formats that can otherwise pass the
+/// NUL/UTF-8 checks on a short sample.
+const BINARY_SIGNATURES: &[&[u8]] = &[
+    b"\x89PNG\r\n\x1a\n",
+    b"\xFF\xD8\xFF", // JPEG
+    b"GIF87a",
+    b"GIF89a",
+    b"%PDF-",
+    b"PK\x03\x04",       // ZIP / JAR / DOCX
+    b"\x1F\x8B",         // gzip
+    b"\x28\xB5\x2F\xFD", // zstd
+    b"\x7FELF",
+    b"\0asm", // WebAssembly
+];
+
+/// Text encoding classification of a blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// Not text (NUL bytes, invalid encoding, or a known binary signature).
+    Binary,
+    /// Valid UTF-8 (with or without BOM).
+    Utf8Text,
+    /// UTF-16, detected via BOM or an alternating-NUL pattern.
+    Utf16Text,
+}
+
+/// Result of [`detect_content_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentType {
+    pub kind: ContentKind,
+    /// Lower-case language name (e.g. `"rust"`, `"python"`), or `None`
+    /// when the blob is binary or nothing recognisable was found.
+    pub language: Option<&'static str>,
+}
+
+impl ContentType {
+    /// Whether the blob should be treated as text.
+    pub fn is_text(&self) -> bool {
+        self.kind != ContentKind::Binary
+    }
+}
+
+/// Classify `content` and guess its language.
+///
+/// `filename` is an optional hint; its extension wins over content
+/// signatures (shebangs, `<?php`, doctype) when both are available.
+pub fn detect_content_type(content: &[u8], filename: Option<&str>) -> ContentType {
+    let kind = detect_kind(content);
+    let language = match kind {
+        ContentKind::Binary => None,
+        _ => filename
+            .and_then(language_from_filename)
+            .or_else(|| language_from_signature(content)),
+    };
+    ContentType { kind, language }
+}
+
+fn detect_kind(content: &[u8]) -> ContentKind {
+    if content.starts_with(&[0xFF, 0xFE]) || content.starts_with(&[0xFE, 0xFF]) {
+        return ContentKind::Utf16Text;
+    }
+    if BINARY_SIGNATURES.iter().any(|sig| content.starts_with(sig)) {
+        return ContentKind::Binary;
+    }
+
+    let sample = &content[..content.len().min(SNIFF_LEN)];
+    if memchr::memchr(0, sample).is_some() {
+        return if looks_like_bomless_utf16(sample) {
+            ContentKind::Utf16Text
+        } else {
+            ContentKind::Binary
+        };
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => ContentKind::Utf8Text,
+        // A multi-byte char cut off by the sample boundary is still text.
+        Err(e) if e.error_len().is_none() && sample.len() < content.len() => ContentKind::Utf8Text,
+        Err(_) => ContentKind::Binary,
+    }
+}
+
+/// BOM-less UTF-16 of mostly-ASCII text has NULs in every other byte.
+fn looks_like_bomless_utf16(sample: &[u8]) -> bool {
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return false;
+    }
+    let (mut even_nuls, mut odd_nuls) = (0usize, 0usize);
+    for pair in sample.chunks_exact(2) {
+        even_nuls += (pair[0] == 0) as usize;
+        odd_nuls += (pair[1] == 0) as usize;
+    }
+    // One lane nearly all NUL (LE: odd, BE: even), the other nearly none.
+    let mostly = pairs * 9 / 10;
+    let rarely = pairs / 10;
+    (odd_nuls >= mostly && even_nuls <= rarely) || (even_nuls >= mostly && odd_nuls <= rarely)
+}
+
+/// Decode a text blob to UTF-8 for line-oriented processing.
+///
+/// UTF-16 (BOM or BOM-less) is transcoded, so lines split on code-point
+/// newlines — a `0x0A` byte inside a UTF-16 code unit is not a line break.
+/// A UTF-8 BOM is stripped. Invalid sequences become U+FFFD. Returns
+/// `None` for binary blobs.
+pub fn decode_text(content: &[u8]) -> Option<Cow<'_, str>> {
+    match detect_kind(content) {
+        ContentKind::Binary => None,
+        ContentKind::Utf8Text => {
+            let body = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+            Some(String::from_utf8_lossy(body))
+        }
+        ContentKind::Utf16Text => Some(Cow::Owned(decode_utf16(content))),
+    }
+}
+
+fn decode_utf16(content: &[u8]) -> String {
+    let (body, little_endian) = match content {
+        [0xFF, 0xFE, rest @ ..] => (rest, true),
+        [0xFE, 0xFF, rest @ ..] => (rest, false),
+        // BOM-less: mostly-ASCII text has its NULs in the high byte,
+        // which comes second in little-endian.
+        _ => {
+            let sample = &content[..content.len().min(SNIFF_LEN)];
+            let (mut even_nuls, mut odd_nuls) = (0usize, 0usize);
+            for pair in sample.chunks_exact(2) {
+                even_nuls += (pair[0] == 0) as usize;
+                odd_nuls += (pair[1] == 0) as usize;
+            }
+            (content, odd_nuls >= even_nuls)
+        }
+    };
+    let units = body.chunks_exact(2).map(|pair| {
+        let pair = [pair[0], pair[1]];
+        if little_endian {
+            u16::from_le_bytes(pair)
+        } else {
+            u16::from_be_bytes(pair)
+        }
+    });
+    char::decode_utf16(units)
+        .map(|ch| ch.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn language_from_filename(filename: &str) -> Option<&'static str> {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    match name {
+        "Makefile" | "GNUmakefile" => return Some("make"),
+        "Dockerfile" => return Some("dockerfile"),
+        _ => {}
+    }
+    let (_, ext) = name.rsplit_once('.')?;
+    let lang = match ext.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "py" | "pyi" => "python",
+        "js" | "mjs" | "cjs" | "jsx" => "javascript",
+        "ts" | "mts" | "cts" | "tsx" => "typescript",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "swift" => "swift",
+        "sh" | "bash" | "zsh" => "shell",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "md" | "markdown" => "markdown",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "xml" => "xml",
+        "proto" => "protobuf",
+        _ => return None,
+    };
+    Some(lang)
+}
+
+fn language_from_signature(content: &[u8]) -> Option<&'static str> {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    if let Some(rest) = content.strip_prefix(b"#!") {
+        let line_end = memchr::memchr(b'\n', rest).unwrap_or(rest.len());
+        let line = &rest[..line_end];
+        // `#!/usr/bin/env python3` and `#!/usr/bin/python3` both name the
+        // interpreter in the last path component / argument.
+        let interpreter = line
+            .split(|&b| b == b'/' || b == b' ')
+            .rfind(|part| !part.is_empty())?;
+        return match interpreter {
+            i if i.starts_with(b"python") => Some("python"),
+            i if i.starts_with(b"node") => Some("javascript"),
+            b"sh" | b"bash" | b"zsh" | b"dash" => Some("shell"),
+            i if i.starts_with(b"ruby") => Some("ruby"),
+            i if i.starts_with(b"perl") => Some("perl"),
+            _ => None,
+        };
+    }
+    if content.starts_with(b"<?php") {
+        return Some("php");
+    }
+    if content.starts_with(b"<?xml") {
+        return Some("xml");
+    }
+    let head = &content[..content.len().min(64)];
+    let head = head.to_ascii_lowercase();
+    if head.starts_with(b"<!doctype html") || head.starts_with(b"<html") {
+        return Some("html");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_header_is_binary() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\x01\0";
+        let ct = detect_content_type(png, Some("logo.png"));
+        assert_eq!(ct.kind, ContentKind::Binary);
+        assert_eq!(ct.language, None);
+        assert!(!ct.is_text());
+    }
+
+    #[test]
+    fn utf8_source_is_text_with_language() {
+        let src = "fn main() {\n    println!(\"héllo\");\n}\n".as_bytes();
+        let ct = detect_content_type(src, Some("src/main.rs"));
+        assert_eq!(ct.kind, ContentKind::Utf8Text);
+        assert_eq!(ct.language, Some("rust"));
+    }
+
+    #[test]
+    fn utf16_bom_is_utf16_text() {
+        let mut le = vec![0xFF, 0xFE];
+        le.extend("hello".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        assert_eq!(detect_content_type(&le, None).kind, ContentKind::Utf16Text);
+
+        let mut be = vec![0xFE, 0xFF];
+        be.extend("hello".encode_utf16().flat_map(|u| u.to_be_bytes()));
+        let ct = detect_content_type(&be, Some("notes.md"));
+        assert_eq!(ct.kind, ContentKind::Utf16Text);
+        assert_eq!(ct.language, Some("markdown"));
+    }
+
+    #[test]
+    fn bomless_utf16_detected_by_nul_lanes() {
+        let le: Vec<u8> = "plain ascii text"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        assert_eq!(detect_content_type(&le, None).kind, ContentKind::Utf16Text);
+    }
+
+    #[test]
+    fn decode_text_transcodes_utf16_and_strips_bom() {
+        let text = "a\u{010A}b\nline two";
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(text.encode_utf16().flat_map(|u| u.to_be_bytes()));
+        let bomless: Vec<u8> = text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        for bytes in [&le, &be, &bomless] {
+            assert_eq!(decode_text(bytes).as_deref(), Some(text));
+        }
+
+        assert_eq!(decode_text(b"\xEF\xBB\xBFhi\n").as_deref(), Some("hi\n"));
+        assert!(matches!(
+            decode_text(b"plain"),
+            Some(Cow::Borrowed("plain"))
+        ));
+        assert_eq!(decode_text(b"\x89PNG\r\n\x1a\n\0\0"), None);
+    }
+
+    #[test]
+    fn stray_nul_is_binary() {
+        let data = b"abc\0\x01\x02\xFFdef";
+        assert_eq!(detect_content_type(data, None).kind, ContentKind::Binary);
+    }
+
+    #[test]
+    fn invalid_utf8_is_binary() {
+        let data = b"abc\xC3\x28def";
+        assert_eq!(detect_content_type(data, None).kind, ContentKind::Binary);
+    }
+
+    #[test]
+    fn multibyte_char_split_at_sniff_boundary_is_text() {
+        let mut data = vec![b'a'; SNIFF_LEN - 1];
+        data.extend("é and more".as_bytes());
+        assert_eq!(detect_content_type(&data, None).kind, ContentKind::Utf8Text);
+    }
+
+    #[test]
+    fn shebang_language_without_filename() {
+        let ct = detect_content_type(b"#!/usr/bin/env python3\nprint(1)\n", None);
+        assert_eq!(ct.language, Some("python"));
+        let ct = detect_content_type(b"#!/bin/bash\necho hi\n", None);
+        assert_eq!(ct.language, Some("shell"));
+    }
+
+    #[test]
+    fn extension_wins_over_signature() {
+        let ct = detect_content_type(b"#!/usr/bin/env node\n", Some("tool.ts"));
+        assert_eq!(ct.language, Some("typescript"));
+    }
+
+    #[test]
+    fn unknown_text_has_no_language() {
+        let ct = detect_content_type(b"just some words\n", Some("README"));
+        assert_eq!(ct.kind, ContentKind::Utf8Text);
+        assert_eq!(ct.language, None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Whole-file reads that avoid needless copies.
+//!
+//! [`read_file_if_changed`] is the "not modified" shortcut for sync
+//! clients: a large file is hashed straight from a memory map and only
+//! copied out when its hash differs from the one the caller already holds.
+
+use std::fs::File;
+use std::io::{self, Read};
+
+use crate::hash::{hash_content, hash_content_smart};
+use crate::search::mmap::MAP_MIN_BYTES;
+
+/// Contents of `path`, or `None` if its hash equals `known_hash`.
+///
+/// The file is hashed with BLAKE3 — [`hash_content`], or
+/// [`hash_content_smart`] when `smart` is set. Files under
+/// [`MAP_MIN_BYTES`] are read once, and the returned bytes are exactly the
+/// ones hashed. Larger files are mapped and hashed in place (with `smart`,
+/// only the sampled pages are faulted in), so an unchanged file is never
+/// copied; a changed one is copied out of the same mapping afterwards, so
+/// a concurrent writer can make the returned bytes differ from the hashed
+/// ones. A mapped file must not be truncated during the call: touching the
+/// lost pages raises `SIGBUS`. `known_hash` is compared as hex, ignoring
+/// case.
+pub fn read_file_if_changed(
+    path: &str,
+    known_hash: &str,
+    smart: bool,
+) -> io::Result<Option<Vec<u8>>> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a regular file: {path}"),
+        ));
+    }
+    let unchanged = |bytes: &[u8]| {
+        let hash = if smart {
+            hash_content_smart(bytes)
+        } else {
+            hash_content(bytes)
+        };
+        hash.eq_ignore_ascii_case(known_hash)
+    };
+    if metadata.len() < MAP_MIN_BYTES {
+        let mut bytes = Vec::with_capacity(metadata.len() as usize);
+        (&file).read_to_end(&mut bytes)?;
+        return Ok((!unchanged(&bytes)).then_some(bytes));
+    }
+    // SAFETY: the mapping is read-only and dropped before returning, but
+    // the file is not locked: a truncation by another process while it is
+    // mapped raises SIGBUS on the lost pages. The doc comment puts that
+    // case out of contract; other concurrent writes only change the bytes
+    // seen.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    Ok((!unchanged(&mmap)).then(|| mmap.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_file_is_not_returned() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.txt");
+        std::fs::write(&path, b"version one").unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let known = hash_content(b"version one");
+
+        assert!(read_file_if_changed(&path, &known, false)
+            .unwrap()
+            .is_none());
+        assert!(read_file_if_changed(&path, &known.to_uppercase(), false)
+            .unwrap()
+            .is_none());
+        let smart = hash_content_smart(b"version one");
+        assert!(read_file_if_changed(&path, &smart, true).unwrap().is_none());
+
+        std::fs::write(&path, b"version two").unwrap();
+        assert_eq!(
+            read_file_if_changed(&path, &known, false).unwrap().unwrap(),
+            b"version two"
+        );
+
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(
+            read_file_if_changed(&path, &known, false).unwrap(),
+            Some(Vec::new())
+        );
+        assert!(read_file_if_changed(&path, &hash_content(&[]), false)
+            .unwrap()
+            .is_none());
+        assert!(read_file_if_changed(&dir.path().to_string_lossy(), &known, false).is_err());
+    }
+
+    #[test]
+    fn large_files_are_hashed_from_a_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big");
+        let contents = vec![b'x'; MAP_MIN_BYTES as usize + 1];
+        std::fs::write(&path, &contents).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let smart = hash_content_smart(&contents);
+        assert!(read_file_if_changed(&path, &smart, true).unwrap().is_none());
+        let copied = read_file_if_changed(&path, &hash_content(b"old"), false).unwrap();
+        assert_eq!(copied.unwrap(), contents);
+    }
+}
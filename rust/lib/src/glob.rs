@@ -1,6 +1,8 @@
 //! Glob pattern matching using the `globset` crate.
 
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::fmt;
+
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
 
 /// Build a `GlobSet` from a list of glob patterns.
 pub fn build_globset(patterns: &[String]) -> Result<GlobSet, globset::Error> {
@@ -11,6 +13,18 @@ pub fn build_globset(patterns: &[String]) -> Result<GlobSet, globset::Error> {
     builder.build()
 }
 
+/// Check each pattern on its own: `None` if it compiles, else a
+/// human-readable reason (e.g. "unclosed character class; missing ']'").
+///
+/// Unlike [`build_globset`], which stops at the first bad pattern, this
+/// reports every one, by position, so config loaders can point at them.
+pub fn validate_globs(patterns: &[String]) -> Vec<Option<String>> {
+    patterns
+        .iter()
+        .map(|pattern| Glob::new(pattern).err().map(|e| e.kind().to_string()))
+        .collect()
+}
+
 /// Filter paths by glob patterns — return paths that match any pattern.
 pub fn glob_match(patterns: &[String], paths: &[String]) -> Result<Vec<String>, globset::Error> {
     let globset = build_globset(patterns)?;
@@ -40,6 +54,153 @@ pub fn filter_paths_exclude(
         .collect())
 }
 
+/// Keep paths matching any include pattern (every path if `include_patterns`
+/// is empty), then drop those matching any exclude pattern.
+///
+/// Mirrors ripgrep's `-g` / `-g '!…'` pair. Unlike [`filter_paths_exclude`],
+/// both sets match against the full path only, never the bare file name.
+pub fn filter_paths_include_exclude(
+    paths: &[String],
+    include_patterns: &[String],
+    exclude_patterns: &[String],
+) -> Result<Vec<String>, globset::Error> {
+    let include = build_globset(include_patterns)?;
+    let exclude = build_globset(exclude_patterns)?;
+    Ok(paths
+        .iter()
+        .filter(|path| {
+            (include_patterns.is_empty() || include.is_match(path.as_str()))
+                && !exclude.is_match(path.as_str())
+        })
+        .cloned()
+        .collect())
+}
+
+/// Every pattern that matches `path`, as `(index, pattern)` in the
+/// order of `patterns`, so the first entry is the "first match wins" rule.
+///
+/// The per-path complement to [`glob_match`]: one path against a whole
+/// ruleset, compiled once into a `GlobSet`. Fails if any pattern is
+/// invalid.
+pub fn matching_patterns(
+    path: &str,
+    patterns: &[String],
+) -> Result<Vec<(usize, String)>, globset::Error> {
+    let globset = build_globset(patterns)?;
+    let mut indices = globset.matches(path);
+    indices.sort_unstable();
+    Ok(indices
+        .into_iter()
+        .map(|i| (i, patterns[i].clone()))
+        .collect())
+}
+
+/// Why a pattern did (or did not) match in [`glob_explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobReason {
+    /// Matched as-is.
+    Matched,
+    /// The pattern failed to compile.
+    InvalidPattern(String),
+    /// Would match if compared case-insensitively.
+    CaseMismatch,
+    /// Matches the file name but not the full path (needs `**/`).
+    BasenameOnly,
+    /// Would match without the path's leading `/` (or with one added).
+    LeadingSlash,
+    /// Would match with `\` separators converted to `/`.
+    SeparatorStyle,
+    /// No near-miss found.
+    NoMatch,
+}
+
+impl fmt::Display for GlobReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobReason::Matched => write!(f, "matched"),
+            GlobReason::InvalidPattern(e) => write!(f, "invalid pattern: {}", e),
+            GlobReason::CaseMismatch => write!(f, "case mismatch (matching is case-sensitive)"),
+            GlobReason::BasenameOnly => {
+                write!(f, "basename-only: matches the file name, prefix with **/")
+            }
+            GlobReason::LeadingSlash => write!(f, "leading '/' differs between pattern and path"),
+            GlobReason::SeparatorStyle => write!(f, "path uses '\\' separators, pattern uses '/'"),
+            GlobReason::NoMatch => write!(f, "no match"),
+        }
+    }
+}
+
+/// Per-pattern result of [`glob_explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobExplanation {
+    pub pattern: String,
+    pub matched: bool,
+    pub reason: GlobReason,
+}
+
+/// Explain, for each pattern, whether `path` matches and if not, the
+/// nearest miss.
+///
+/// Debugging aid for "why isn't my file matched": each failing pattern
+/// is retried against variations of the path and matcher options
+/// (case-insensitive, basename, leading slash, `\` separators) and the
+/// first variation that matches is reported. Uses the same default
+/// options as [`build_globset`], so `matched` agrees with [`glob_match`].
+pub fn glob_explain(patterns: &[String], path: &str) -> Vec<GlobExplanation> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let reason = explain_one(pattern, path);
+            GlobExplanation {
+                pattern: pattern.clone(),
+                matched: reason == GlobReason::Matched,
+                reason,
+            }
+        })
+        .collect()
+}
+
+fn explain_one(pattern: &str, path: &str) -> GlobReason {
+    let matcher = match Glob::new(pattern) {
+        Ok(glob) => glob.compile_matcher(),
+        Err(e) => return GlobReason::InvalidPattern(e.kind().to_string()),
+    };
+    if matcher.is_match(path) {
+        return GlobReason::Matched;
+    }
+
+    let folded = GlobBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map(|g| g.compile_matcher());
+    if folded.is_ok_and(|m| m.is_match(path)) {
+        return GlobReason::CaseMismatch;
+    }
+
+    let forward = path.replace('\\', "/");
+    if forward != path && matcher.is_match(&forward) {
+        return GlobReason::SeparatorStyle;
+    }
+    let toggled = match forward.strip_prefix('/') {
+        Some(rest) => rest.to_string(),
+        None => format!("/{}", forward),
+    };
+    if matcher.is_match(&toggled) {
+        return GlobReason::LeadingSlash;
+    }
+    if matches_basename(&matcher, &forward) {
+        return GlobReason::BasenameOnly;
+    }
+    GlobReason::NoMatch
+}
+
+fn matches_basename(matcher: &GlobMatcher, path: &str) -> bool {
+    match path.rsplit_once('/') {
+        Some((_, name)) => !name.is_empty() && matcher.is_match(name),
+        None => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,4 +273,148 @@ mod tests {
         let filtered = filter_paths_exclude(&paths, &exclude).unwrap();
         assert_eq!(filtered, vec!["src\\main.rs", "docs\\readme.md"]);
     }
+
+    #[test]
+    fn include_exclude_filter() {
+        let paths = [
+            "src/main.rs",
+            "src/gen/schema.rs",
+            "docs/readme.md",
+            "main.rs",
+        ]
+        .map(String::from);
+        let include_only =
+            filter_paths_include_exclude(&paths, &["src/**/*.rs"].map(String::from), &[]).unwrap();
+        assert_eq!(include_only, vec!["src/main.rs", "src/gen/schema.rs"]);
+
+        let exclude_only =
+            filter_paths_include_exclude(&paths, &[], &["src/gen/**"].map(String::from)).unwrap();
+        assert_eq!(
+            exclude_only,
+            vec!["src/main.rs", "docs/readme.md", "main.rs"]
+        );
+
+        let combined = filter_paths_include_exclude(
+            &paths,
+            &["src/**/*.rs", "*.md"].map(String::from),
+            &["src/gen/**"].map(String::from),
+        )
+        .unwrap();
+        // `*` crosses `/` under the default globset options.
+        assert_eq!(combined, vec!["src/main.rs", "docs/readme.md"]);
+    }
+
+    #[test]
+    fn include_exclude_matches_full_path_not_basename() {
+        let paths = ["main.rs", "src/main.rs", "src/.hidden"].map(String::from);
+        // `main.rs` names only the top-level file; `.*` excludes nothing
+        // nested because the full path does not start with a dot.
+        let filtered = filter_paths_include_exclude(
+            &paths,
+            &["main.rs"].map(String::from),
+            &[".*"].map(String::from),
+        )
+        .unwrap();
+        assert_eq!(filtered, vec!["main.rs"]);
+        let kept = filter_paths_include_exclude(&paths, &[], &[".*"].map(String::from)).unwrap();
+        assert_eq!(kept, paths);
+    }
+
+    #[test]
+    fn matching_patterns_reports_overlaps_in_input_order() {
+        let rules = ["docs/**", "**/*.md", "*.rs", "docs/*.md", "**"].map(String::from);
+        let matched = matching_patterns("docs/guide.md", &rules).unwrap();
+        assert_eq!(
+            matched,
+            vec![
+                (0, "docs/**".to_string()),
+                (1, "**/*.md".to_string()),
+                (3, "docs/*.md".to_string()),
+                (4, "**".to_string()),
+            ]
+        );
+        assert_eq!(
+            matching_patterns("src/lib.rs", &rules).unwrap()[0],
+            (2, "*.rs".to_string())
+        );
+        assert!(matching_patterns("x", &["*.rs"].map(String::from))
+            .unwrap()
+            .is_empty());
+        assert!(matching_patterns("x", &["[bad"].map(String::from)).is_err());
+    }
+
+    fn reasons(patterns: &[&str], path: &str) -> Vec<GlobReason> {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        glob_explain(&patterns, path)
+            .into_iter()
+            .map(|e| e.reason)
+            .collect()
+    }
+
+    #[test]
+    fn explain_flags_case_mismatch() {
+        let patterns = vec!["*.PNG".to_string(), "*.png".to_string()];
+        let explained = glob_explain(&patterns, "assets/logo.png");
+        assert!(!explained[0].matched);
+        assert_eq!(explained[0].reason, GlobReason::CaseMismatch);
+        assert!(explained[0].reason.to_string().contains("case"));
+        assert!(explained[1].matched);
+        assert_eq!(explained[1].reason, GlobReason::Matched);
+    }
+
+    #[test]
+    fn explain_agrees_with_glob_match() {
+        let patterns = vec!["src/**/*.rs".to_string(), "*.md".to_string()];
+        let path = "src/a/b.rs".to_string();
+        let matched = glob_match(&patterns, std::slice::from_ref(&path)).unwrap();
+        assert_eq!(matched.len(), 1);
+        assert!(glob_explain(&patterns, &path).iter().any(|e| e.matched));
+    }
+
+    #[test]
+    fn explain_near_misses() {
+        assert_eq!(
+            reasons(&["Makefile"], "build/Makefile"),
+            vec![GlobReason::BasenameOnly]
+        );
+        assert_eq!(
+            reasons(&["src/*.rs"], "/src/main.rs"),
+            vec![GlobReason::LeadingSlash]
+        );
+        assert_eq!(
+            reasons(&["src/*.rs"], "src\\main.rs"),
+            vec![GlobReason::SeparatorStyle]
+        );
+        assert_eq!(
+            reasons(&["docs/**"], "src/main.rs"),
+            vec![GlobReason::NoMatch]
+        );
+        assert!(matches!(
+            reasons(&["a[b"], "ab")[..],
+            [GlobReason::InvalidPattern(_)]
+        ));
+    }
+
+    #[test]
+    fn validate_globs_reports_each_invalid_pattern() {
+        let patterns: Vec<String> = ["src/**/*.rs", "src/[", "*.{md,txt}", "a{b", ""]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let errors = validate_globs(&patterns);
+        assert_eq!(errors.len(), patterns.len());
+        assert_eq!(errors[0], None);
+        assert!(errors[1]
+            .as_deref()
+            .unwrap()
+            .contains("unclosed character class"));
+        assert_eq!(errors[2], None);
+        assert!(errors[3]
+            .as_deref()
+            .unwrap()
+            .contains("unclosed alternate group"));
+        assert_eq!(errors[4], None);
+        // Any reported error is one build_globset would fail on.
+        assert!(build_globset(&patterns).is_err());
+    }
 }
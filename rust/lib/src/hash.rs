@@ -1,4 +1,21 @@
-//! BLAKE3 content hashing for content-addressable storage.
+//! Content hashing for content-addressable storage.
+//!
+//! BLAKE3 is the native content address. SHA-256 is available through
+//! [`hash_content_with`] for interop with systems that address content by
+//! SHA-256 (OCI registries, Git).
+
+#[cfg(feature = "mmap")]
+use std::{fs::File, io, path::Path};
+
+use sha2::{Digest, Sha256};
+
+/// Digest algorithm for [`hash_content_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlgo {
+    #[default]
+    Blake3,
+    Sha256,
+}
 
 /// Compute BLAKE3 hash of content (full hash).
 ///
@@ -7,6 +24,25 @@ pub fn hash_content(content: &[u8]) -> String {
     blake3::hash(content).to_hex().to_string()
 }
 
+/// Compute the full hash of content with the chosen algorithm.
+///
+/// Returns a lowercase hex digest; both algorithms produce 256 bits
+/// (64 hex chars). `HashAlgo::Blake3` is identical to [`hash_content`].
+pub fn hash_content_with(content: &[u8], algo: HashAlgo) -> String {
+    match algo {
+        HashAlgo::Blake3 => hash_content(content),
+        HashAlgo::Sha256 => {
+            use std::fmt::Write;
+            Sha256::digest(content)
+                .iter()
+                .fold(String::with_capacity(64), |mut hex, byte| {
+                    let _ = write!(hex, "{byte:02x}");
+                    hex
+                })
+        }
+    }
+}
+
 /// Compute BLAKE3 hash with strategic sampling for large files.
 ///
 /// For files < 256KB: full hash (same as `hash_content`)
@@ -15,7 +51,9 @@ pub fn hash_content(content: &[u8]) -> String {
 /// ~10x speedup for large files while maintaining good collision resistance.
 ///
 /// NOTE: Not suitable for cryptographic integrity verification —
-/// only for content-addressable storage fingerprinting.
+/// only for content-addressable storage fingerprinting. Always BLAKE3:
+/// a sampled digest is not a SHA-256 content address, so there is no
+/// algorithm choice here.
 pub fn hash_content_smart(content: &[u8]) -> String {
     const THRESHOLD: usize = 256 * 1024;
     const SAMPLE_SIZE: usize = 64 * 1024;
@@ -43,10 +81,141 @@ pub fn hash_content_smart(content: &[u8]) -> String {
     }
 }
 
+/// [`hash_content`] of the NFC form of `content`, so text that differs
+/// only in Unicode normalization (precomposed `é` vs `e` + combining
+/// acute) gets one address.
+///
+/// Only valid UTF-8 is normalized; anything else, binary included, is
+/// hashed byte for byte. Content already in NFC hashes exactly like
+/// [`hash_content`] after a single checking pass; other text is copied
+/// once to compose it.
+pub fn hash_content_normalized(content: &[u8]) -> String {
+    match std::str::from_utf8(content) {
+        Ok(text) => hash_content(crate::search::normalize_nfc(text).as_bytes()),
+        Err(_) => hash_content(content),
+    }
+}
+
+/// Hash each file in `paths` by path, in parallel, without copying its
+/// contents to the caller.
+///
+/// Files are memory-mapped and hashed with [`hash_content`] (or
+/// [`hash_content_smart`] when `smart` is set, which then only faults in
+/// the sampled pages). Results line up with `paths`; a file that cannot
+/// be opened or mapped yields its own error without failing the batch.
+/// A file must not be truncated while it is being hashed: touching the
+/// unmapped pages raises `SIGBUS`.
+///
+/// Behind the `mmap` feature (file mapping is not WASM-safe).
+#[cfg(feature = "mmap")]
+pub fn hash_files_by_path<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    smart: bool,
+) -> Vec<io::Result<String>> {
+    use rayon::prelude::*;
+
+    crate::pool::install(|| {
+        paths
+            .par_iter()
+            .map(|path| hash_file(path.as_ref(), smart))
+            .collect()
+    })
+}
+
+#[cfg(feature = "mmap")]
+fn hash_file(path: &Path, smart: bool) -> io::Result<String> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a regular file: {}", path.display()),
+        ));
+    }
+    // Zero-length mappings are rejected by the OS.
+    if metadata.len() == 0 {
+        return Ok(hash_content(&[]));
+    }
+    // SAFETY: read-only mapping dropped before returning. The file is not
+    // locked, so a truncation by another process while it is mapped raises
+    // SIGBUS; the doc comment puts that out of contract. Other concurrent
+    // writes only change the bytes hashed.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    Ok(if smart {
+        hash_content_smart(&mmap)
+    } else {
+        hash_content(&mmap)
+    })
+}
+
+/// Deterministic `fraction` sample of `paths`, in input order.
+///
+/// A path is kept iff the first 8 bytes of `blake3(seed || path)`, read as
+/// a little-endian `u64`, fall below `fraction * 2^64`. Each decision
+/// depends only on the seed and the path itself, so the sample is the
+/// same across runs, machines and input orderings, and a larger fraction
+/// under the same seed keeps a superset. `fraction` is clamped to
+/// `[0, 1]`.
+pub fn sample_paths(paths: &[String], fraction: f64, seed: u64) -> Vec<String> {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction >= 1.0 {
+        return paths.to_vec();
+    }
+    // `as` saturates, so a fraction rounding up to 2^64 still fits.
+    let threshold = (fraction * 2f64.powi(64)) as u64;
+    paths
+        .iter()
+        .filter(|path| {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(&seed.to_le_bytes());
+            hasher.update(path.as_bytes());
+            let digest = hasher.finalize();
+            let mut head = [0u8; 8];
+            head.copy_from_slice(&digest.as_bytes()[..8]);
+            u64::from_le_bytes(head) < threshold
+        })
+        .cloned()
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn sample_paths_is_deterministic_and_order_independent() {
+        let paths: Vec<String> = (0..10_000).map(|i| format!("src/file_{i}.rs")).collect();
+        let sample = sample_paths(&paths, 0.1, 42);
+        // Binomial(10000, 0.1): sd = 30, so this is a > 6 sigma window.
+        assert!((800..=1200).contains(&sample.len()), "{}", sample.len());
+        assert_eq!(sample_paths(&paths, 0.1, 42), sample);
+        assert_ne!(sample_paths(&paths, 0.1, 43), sample);
+
+        let mut reversed = paths.clone();
+        reversed.reverse();
+        let mut sample_rev = sample_paths(&reversed, 0.1, 42);
+        sample_rev.reverse();
+        assert_eq!(sample_rev, sample);
+
+        // Growing the fraction only adds paths.
+        let wider = sample_paths(&paths, 0.2, 42);
+        assert!(sample.iter().all(|p| wider.contains(p)));
+        assert!(sample_paths(&paths, 0.0, 42).is_empty());
+        assert_eq!(sample_paths(&paths, 1.0, 42), paths);
+    }
+
+    #[test]
+    fn normalized_hash_unifies_nfc_and_nfd() {
+        let nfc = "caf\u{e9}".as_bytes();
+        let nfd = "cafe\u{301}".as_bytes();
+        assert_ne!(hash_content(nfc), hash_content(nfd));
+        assert_eq!(hash_content_normalized(nfc), hash_content_normalized(nfd));
+        assert_eq!(hash_content_normalized(nfc), hash_content(nfc));
+        // Invalid UTF-8 is hashed as is.
+        let binary = b"\xFFcafe\xCC\x81";
+        assert_eq!(hash_content_normalized(binary), hash_content(binary));
+    }
+
     #[test]
     fn deterministic_hash() {
         let content = b"hello world";
@@ -56,6 +225,26 @@ mod tests {
         assert_eq!(h1.len(), 64); // 256-bit = 64 hex chars
     }
 
+    #[test]
+    fn hash_content_with_known_vectors() {
+        assert_eq!(
+            hash_content_with(b"", HashAlgo::Sha256),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hash_content_with(b"abc", HashAlgo::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_content_with(b"", HashAlgo::Blake3),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hash_content_with(b"abc", HashAlgo::default()),
+            hash_content(b"abc")
+        );
+    }
+
     #[test]
     fn different_content_different_hash() {
         let h1 = hash_content(b"hello");
@@ -101,4 +290,50 @@ mod tests {
         let expected = hasher.finalize().to_hex().to_string();
         assert_eq!(hash_content_smart(&content), expected);
     }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn hash_files_by_path_matches_read_then_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let contents: [&[u8]; 3] = [b"", b"hello world", &[9u8; 512 * 1024]];
+        let paths: Vec<_> = contents
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let path = dir.path().join(format!("f{i}"));
+                std::fs::write(&path, content).unwrap();
+                path
+            })
+            .collect();
+
+        for smart in [false, true] {
+            let hashes = hash_files_by_path(&paths, smart);
+            for (path, hash) in paths.iter().zip(hashes) {
+                let bytes = std::fs::read(path).unwrap();
+                let expected = if smart {
+                    hash_content_smart(&bytes)
+                } else {
+                    hash_content(&bytes)
+                };
+                assert_eq!(hash.unwrap(), expected, "{}", path.display());
+            }
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn hash_files_by_path_reports_errors_per_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good");
+        std::fs::write(&good, b"data").unwrap();
+        let paths = [good, dir.path().join("missing"), dir.path().to_path_buf()];
+
+        let hashes = hash_files_by_path(&paths, false);
+        assert_eq!(hashes[0].as_ref().unwrap(), &hash_content(b"data"));
+        assert_eq!(
+            hashes[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert!(hashes[2].is_err());
+    }
 }
@@ -5,25 +5,55 @@
 //!
 //! Modules:
 //! - `types` — domain types (Entity, Permission, etc.)
-//! - `rebac` — Relationship-Based Access Control engine
-//! - `search` — line-oriented text search (literal + regex)
+//! - `rebac` — Relationship-Based Access Control engine (portable graph
+//!   snapshots in `rebac::snapshot`; seeded fuzz corpus generators in
+//!   `rebac::testing` behind the `testing` feature)
+//! - `search` — line-oriented text search (literal + regex; incremental
+//!   mmap file tailing behind the `mmap` feature, windowed streaming grep
+//!   behind the `fs` feature)
 //! - `bloom` — Bloom filter for fast set-membership checks
-//! - `hash` — BLAKE3 content hashing
+//! - `hash` — BLAKE3 (or SHA-256) content hashing (plus by-path file
+//!   hashing behind the `mmap` feature) and seeded path sampling
 //! - `glob` — Glob pattern matching
 //! - `bitmap` — Roaring Bitmap operations
+//! - `simd` — vector similarity kernels (cosine / dot / L2) + top-k
+//! - `chunk` — line-aligned chunking under a pluggable token estimator,
+//!   or content-defined (gear hash) chunking with stable BLAKE3 ids;
+//!   `cdc_chunk` cuts binary content with FastCDC like the CAS store, and
+//!   `group_near_duplicates` clusters files by shared chunks
+//! - `content_type` — binary/text sniffing + language guess for chunking
+//! - `consistent_hash` — BLAKE3 hash ring for sharding keys across nodes
+//! - `mmap_bloom` — persistent, memory-mapped Bloom filter shared across
+//!   processes. Behind the `mmap` feature (file mapping is not WASM-safe).
+//! - `files` — hash-guarded reads that skip unchanged files. Behind the
+//!   `mmap` feature.
+//! - `pool` — optional dedicated rayon pool bounding the parallel file
+//!   helpers. Behind the `fs` feature.
 //! - `transport_primitives` — gRPC TLS / pool / addressing / TOFU trust
 //!   store / `PeerBlobClient` trait. Behind the `transport` feature;
 //!   brings tonic + tokio-light deps that pure-algo callers (WASM, edge
 //!   profile) can skip.
+//! - `warmup` — process-start priming of the ReBAC and regex search paths
 
 pub mod bitmap;
 pub mod bloom;
+pub mod chunk;
+pub mod consistent_hash;
+pub mod content_type;
 pub mod glob;
 pub mod hash;
 pub mod rebac;
 pub mod search;
+pub mod simd;
 pub mod trigram;
 pub mod types;
+pub mod warmup;
 
+#[cfg(feature = "mmap")]
+pub mod files;
+#[cfg(feature = "mmap")]
+pub mod mmap_bloom;
+#[cfg(feature = "fs")]
+pub mod pool;
 #[cfg(feature = "transport")]
 pub mod transport_primitives;
@@ -0,0 +1,354 @@
+//! Persistent, memory-mapped Bloom filter.
+//!
+//! [`MmapBloomFilter`] keeps its bit array in a file mapped with
+//! `MAP_SHARED`, so every process that opens the same path shares one
+//! OS page-cache copy and startup costs an `mmap` call instead of a
+//! rebuild. Sizing parameters live in a fixed header so `open(path)`
+//! needs no other input.
+//!
+//! Layout:
+//! ```text
+//! ┌─────────────────────────────────────────────┐
+//! │ Header (48 bytes)                           │
+//! │  magic: [u8; 4] = "NBLM"                    │
+//! │  version: u32 = 1                           │
+//! │  num_hashes: u32                            │
+//! │  reserved: u32                              │
+//! │  num_bits: u64                              │
+//! │  capacity: u64                              │
+//! │  fp_rate: f64                               │
+//! │  reserved: u32                              │
+//! │  header_crc32: u32                          │
+//! ├─────────────────────────────────────────────┤
+//! │ Bit array (ceil(num_bits / 8) bytes)        │
+//! └─────────────────────────────────────────────┘
+//! ```
+//!
+//! Unlike [`crate::bloom::BloomFilter`], bit positions are derived from
+//! BLAKE3 rather than `ahash`: `ahash` keys are fixed per build, which
+//! would make a file written by one binary unreadable by the next.
+//!
+//! # Concurrency
+//!
+//! Reads (`might_contain`) are lock-free and may run concurrently from
+//! any number of threads or processes. Writes (`add`, `clear`) are plain
+//! byte stores into the shared mapping — callers must serialize writers
+//! externally (e.g. a file lock or a single builder process). A reader
+//! racing a writer may miss a key that is mid-insert, but never sees a
+//! false negative for a key whose `add` completed before the read.
+//!
+//! Gated behind the `mmap` feature: file mapping is unavailable on
+//! `wasm32-unknown-unknown`.
+
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+
+use memmap2::MmapMut;
+
+use crate::bloom::optimal_params;
+
+/// Magic bytes identifying a persistent Bloom filter file.
+pub const MAGIC: [u8; 4] = *b"NBLM";
+
+/// Current format version.
+pub const VERSION: u32 = 1;
+
+/// Header size in bytes (fixed).
+pub const HEADER_SIZE: usize = 48;
+
+/// A Bloom filter whose bit array is a memory-mapped file.
+///
+/// Same guarantees as [`crate::bloom::BloomFilter`]: false positives are
+/// possible, false negatives are not.
+pub struct MmapBloomFilter {
+    mmap: MmapMut,
+    num_bits: u64,
+    num_hashes: u32,
+    capacity: usize,
+    fp_rate: f64,
+}
+
+impl MmapBloomFilter {
+    /// Create (or truncate) the file at `path` and size it for
+    /// `expected_items` items at target `fp_rate`.
+    pub fn create(path: impl AsRef<Path>, expected_items: usize, fp_rate: f64) -> io::Result<Self> {
+        let expected_items = expected_items.max(1);
+        let fp_rate = fp_rate.clamp(1e-10, 1.0);
+        let (num_bits, num_hashes) = optimal_params(expected_items, fp_rate);
+        let num_bits = num_bits as u64;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(HEADER_SIZE as u64 + num_bits.div_ceil(8))?;
+
+        // SAFETY: the file was just sized above and is owned by this
+        // mapping for its lifetime; concurrent writers are a documented
+        // caller obligation.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        mmap[..HEADER_SIZE].copy_from_slice(&encode_header(
+            num_bits,
+            num_hashes,
+            expected_items as u64,
+            fp_rate,
+        ));
+        mmap.flush_range(0, HEADER_SIZE)?;
+
+        Ok(MmapBloomFilter {
+            mmap,
+            num_bits,
+            num_hashes,
+            capacity: expected_items,
+            fp_rate,
+        })
+    }
+
+    /// Open an existing filter file, reading sizing from its header.
+    ///
+    /// Fails with `InvalidData` if the magic, CRC, version or file length
+    /// do not match.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        // SAFETY: see `create`.
+        let mmap = unsafe { MmapMut::map_mut(&file)? };
+        let (num_bits, num_hashes, capacity, fp_rate) = decode_header(&mmap)?;
+
+        let expected_len = (HEADER_SIZE as u64).checked_add(num_bits.div_ceil(8));
+        if expected_len != Some(mmap.len() as u64) {
+            return Err(invalid_data(format!(
+                "bloom file length {} does not match header (num_bits={num_bits})",
+                mmap.len()
+            )));
+        }
+
+        Ok(MmapBloomFilter {
+            mmap,
+            num_bits,
+            num_hashes,
+            capacity: capacity as usize,
+            fp_rate,
+        })
+    }
+
+    /// Add an item to the filter.
+    ///
+    /// Requires external synchronization against other writers.
+    pub fn add(&mut self, item: impl AsRef<[u8]>) {
+        let (h1, h2) = hash_pair(item.as_ref());
+        for i in 0..self.num_hashes {
+            let bit = self.bit_index(h1, h2, i);
+            self.mmap[HEADER_SIZE + (bit / 8) as usize] |= 1u8 << (bit % 8);
+        }
+    }
+
+    /// Check if an item might exist in the filter.
+    ///
+    /// Returns `false` if the item is definitely absent,
+    /// `true` if it might be present (possible false positive).
+    pub fn might_contain(&self, item: impl AsRef<[u8]>) -> bool {
+        let (h1, h2) = hash_pair(item.as_ref());
+        (0..self.num_hashes).all(|i| {
+            let bit = self.bit_index(h1, h2, i);
+            self.mmap[HEADER_SIZE + (bit / 8) as usize] & (1u8 << (bit % 8)) != 0
+        })
+    }
+
+    /// Clear all entries, resetting to empty.
+    ///
+    /// Requires external synchronization against other writers.
+    pub fn clear(&mut self) {
+        self.mmap[HEADER_SIZE..].fill(0);
+    }
+
+    /// Flush dirty pages to the backing file.
+    pub fn flush(&self) -> io::Result<()> {
+        self.mmap.flush()
+    }
+
+    /// Expected item capacity.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Target false positive rate.
+    pub fn fp_rate(&self) -> f64 {
+        self.fp_rate
+    }
+
+    /// Size of the mapped bit array in bytes (excluding header).
+    pub fn memory_bytes(&self) -> usize {
+        self.mmap.len() - HEADER_SIZE
+    }
+
+    /// Kirsch–Mitzenmacher double hashing: `h1 + i * h2 mod m`.
+    fn bit_index(&self, h1: u64, h2: u64, i: u32) -> u64 {
+        h1.wrapping_add((i as u64).wrapping_mul(h2)) % self.num_bits
+    }
+}
+
+/// Two independent 64-bit hashes of `item`, stable across builds.
+fn hash_pair(item: &[u8]) -> (u64, u64) {
+    let digest = blake3::hash(item);
+    let bytes = digest.as_bytes();
+    let h1 = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+    // Force h2 odd so successive probes never collapse onto h1.
+    let h2 = u64::from_le_bytes(bytes[8..16].try_into().unwrap()) | 1;
+    (h1, h2)
+}
+
+fn encode_header(num_bits: u64, num_hashes: u32, capacity: u64, fp_rate: f64) -> [u8; HEADER_SIZE] {
+    let mut buf = [0u8; HEADER_SIZE];
+    buf[0..4].copy_from_slice(&MAGIC);
+    buf[4..8].copy_from_slice(&VERSION.to_le_bytes());
+    buf[8..12].copy_from_slice(&num_hashes.to_le_bytes());
+    buf[16..24].copy_from_slice(&num_bits.to_le_bytes());
+    buf[24..32].copy_from_slice(&capacity.to_le_bytes());
+    buf[32..40].copy_from_slice(&fp_rate.to_le_bytes());
+    let crc = crc32fast::hash(&buf[..44]);
+    buf[44..48].copy_from_slice(&crc.to_le_bytes());
+    buf
+}
+
+/// Parse `(num_bits, num_hashes, capacity, fp_rate)` from a header.
+fn decode_header(data: &[u8]) -> io::Result<(u64, u32, u64, f64)> {
+    if data.len() < HEADER_SIZE {
+        return Err(invalid_data("bloom file shorter than header".to_string()));
+    }
+    if data[0..4] != MAGIC {
+        return Err(invalid_data("invalid bloom file magic".to_string()));
+    }
+    let stored_crc = u32::from_le_bytes(data[44..48].try_into().unwrap());
+    if stored_crc != crc32fast::hash(&data[..44]) {
+        return Err(invalid_data("bloom header CRC mismatch".to_string()));
+    }
+    let version = u32::from_le_bytes(data[4..8].try_into().unwrap());
+    if version != VERSION {
+        return Err(invalid_data(format!(
+            "bloom version mismatch: expected {VERSION}, found {version}"
+        )));
+    }
+
+    let num_hashes = u32::from_le_bytes(data[8..12].try_into().unwrap());
+    let num_bits = u64::from_le_bytes(data[16..24].try_into().unwrap());
+    let capacity = u64::from_le_bytes(data[24..32].try_into().unwrap());
+    let fp_rate = f64::from_le_bytes(data[32..40].try_into().unwrap());
+    if num_bits == 0 || num_hashes == 0 {
+        return Err(invalid_data(
+            "bloom header has zero-sized parameters".to_string(),
+        ));
+    }
+    Ok((num_bits, num_hashes, capacity, fp_rate))
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_add_reopen_contains() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paths.bloom");
+
+        {
+            let mut bloom = MmapBloomFilter::create(&path, 1000, 0.01).unwrap();
+            for i in 0..100 {
+                bloom.add(format!("/zone/file-{i}"));
+            }
+            bloom.flush().unwrap();
+        }
+
+        // Simulate a second process: fresh mapping, sizing from header only.
+        let reopened = MmapBloomFilter::open(&path).unwrap();
+        assert_eq!(reopened.capacity(), 1000);
+        assert!((reopened.fp_rate() - 0.01).abs() < f64::EPSILON);
+        for i in 0..100 {
+            assert!(
+                reopened.might_contain(format!("/zone/file-{i}")),
+                "false negative for file-{i} after reopen"
+            );
+        }
+        assert!(!reopened.might_contain("/zone/never-added"));
+    }
+
+    #[test]
+    fn concurrent_mappings_share_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.bloom");
+
+        let mut writer = MmapBloomFilter::create(&path, 100, 0.01).unwrap();
+        let reader = MmapBloomFilter::open(&path).unwrap();
+        assert!(!reader.might_contain("late"));
+
+        writer.add("late");
+        assert!(reader.might_contain("late"));
+    }
+
+    #[test]
+    fn false_positive_rate_within_bounds() {
+        let dir = tempfile::tempdir().unwrap();
+        let n = 10_000;
+        let target_fp = 0.01;
+        let mut bloom = MmapBloomFilter::create(dir.path().join("fp.bloom"), n, target_fp).unwrap();
+        for i in 0..n {
+            bloom.add(i.to_le_bytes());
+        }
+
+        let false_positives = (n..2 * n)
+            .filter(|i| bloom.might_contain(i.to_le_bytes()))
+            .count();
+        let actual_fp_rate = false_positives as f64 / n as f64;
+        assert!(
+            actual_fp_rate < target_fp * 3.0,
+            "FP rate {actual_fp_rate:.4} exceeds 3x target {target_fp}"
+        );
+    }
+
+    #[test]
+    fn clear_persists_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clear.bloom");
+
+        let mut bloom = MmapBloomFilter::create(&path, 100, 0.01).unwrap();
+        bloom.add("hello");
+        bloom.clear();
+        bloom.flush().unwrap();
+        drop(bloom);
+
+        assert!(!MmapBloomFilter::open(&path).unwrap().might_contain("hello"));
+    }
+
+    #[test]
+    fn open_rejects_corrupt_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupt.bloom");
+        drop(MmapBloomFilter::create(&path, 100, 0.01).unwrap());
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[16] ^= 0xFF; // Corrupt num_bits without fixing the CRC.
+        std::fs::write(&path, &bytes).unwrap();
+
+        let err = MmapBloomFilter::open(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn open_rejects_truncated_bit_array() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("short.bloom");
+        drop(MmapBloomFilter::create(&path, 1000, 0.01).unwrap());
+
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(HEADER_SIZE as u64 + 1).unwrap();
+
+        let err = MmapBloomFilter::open(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
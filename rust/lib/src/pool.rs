@@ -0,0 +1,93 @@
+//! Process-wide thread pool for the crate's rayon-parallel functions.
+//!
+//! By default parallel helpers (`hash::hash_files_by_path`,
+//! `search::mmap`, both behind the `mmap` feature) run on rayon's global
+//! pool, one thread per core. Dependent crates route their own rayon work
+//! through [`install`] so it shares the same bound. A server that already runs a worker process
+//! per core oversubscribes with that; [`configure_thread_pool`] gives them
+//! a dedicated, smaller pool instead. Results never depend on pool size.
+
+use std::sync::{Arc, RwLock};
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+static POOL: RwLock<Option<Arc<ThreadPool>>> = RwLock::new(None);
+
+/// Run this crate's parallel functions on a dedicated pool of
+/// `num_threads` threads, replacing any previously configured pool.
+/// `0` drops the dedicated pool and falls back to rayon's global pool.
+///
+/// Calls already running keep the pool they started on.
+pub fn configure_thread_pool(num_threads: usize) -> Result<(), ThreadPoolBuildError> {
+    let pool = match num_threads {
+        0 => None,
+        n => Some(Arc::new(ThreadPoolBuilder::new().num_threads(n).build()?)),
+    };
+    *POOL.write().unwrap_or_else(|e| e.into_inner()) = pool;
+    Ok(())
+}
+
+/// Threads in the configured pool, or `None` when using the global pool.
+pub fn configured_threads() -> Option<usize> {
+    POOL.read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|pool| pool.current_num_threads())
+}
+
+/// Run `op` on the configured pool, or directly (on the global pool)
+/// if none is set. Any rayon iterators `op` drives run on that pool.
+pub fn install<R: Send>(op: impl FnOnce() -> R + Send) -> R {
+    // Clone the handle so the lock is not held while `op` runs.
+    let pool = POOL.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::hash::hash_files_by_path;
+
+    #[test]
+    fn pool_size_does_not_change_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<_> = (0..16)
+            .map(|i| {
+                let path = dir.path().join(format!("f{i}"));
+                let mut file = std::fs::File::create(&path).unwrap();
+                write!(file, "content {i}").unwrap();
+                path
+            })
+            .collect();
+        let hashes = |paths: &[std::path::PathBuf]| -> Vec<String> {
+            hash_files_by_path(paths, false)
+                .into_iter()
+                .map(Result::unwrap)
+                .collect()
+        };
+
+        configure_thread_pool(1).unwrap();
+        assert_eq!(configured_threads(), Some(1));
+        assert_eq!(install(rayon::current_num_threads), 1);
+        let sequential = hashes(&paths);
+
+        configure_thread_pool(4).unwrap();
+        let parallel = hashes(&paths);
+
+        configure_thread_pool(0).unwrap();
+        assert_eq!(configured_threads(), None);
+        let global = hashes(&paths);
+
+        assert_eq!(sequential, parallel);
+        assert_eq!(sequential, global);
+        let expected: Vec<String> = (0..16)
+            .map(|i| crate::hash::hash_content(format!("content {i}").as_bytes()))
+            .collect();
+        assert_eq!(sequential, expected);
+    }
+}
@@ -1,15 +1,135 @@
 //! Namespace configuration deserialization helpers.
 //!
 //! This module re-exports the config types from `types.rs` and provides
-//! convenience functions for parsing namespace configs from JSON.
+//! convenience functions for parsing namespace configs from JSON, plus
+//! static checks over them.
 
-use crate::types::NamespaceConfig;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::types::{NamespaceConfig, RelationConfig};
 
 /// Parse a namespace config from a JSON string.
 pub fn parse_namespace_config(json: &str) -> Result<NamespaceConfig, serde_json::Error> {
     serde_json::from_str(json)
 }
 
+/// Namespace configs parsed once, for reuse across many checks.
+///
+/// Cloning is cheap (the map is shared), and it derefs to the
+/// `AHashMap<String, NamespaceConfig>` every check and expand entry point
+/// takes, so a caller issuing repeated bulk calls against the same schema
+/// pays for the JSON parse once instead of per call.
+#[derive(Debug, Clone, Default)]
+pub struct PreparedNamespaces(Arc<AHashMap<String, NamespaceConfig>>);
+
+impl Deref for PreparedNamespaces {
+    type Target = AHashMap<String, NamespaceConfig>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Parse `(object_type, config JSON)` pairs into a [`PreparedNamespaces`].
+/// Fails on the first config that doesn't parse.
+pub fn prepare_namespaces<I, K, V>(
+    namespace_configs: I,
+) -> Result<PreparedNamespaces, serde_json::Error>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: AsRef<str>,
+{
+    let namespaces = namespace_configs
+        .into_iter()
+        .map(|(object_type, json)| Ok((object_type.into(), parse_namespace_config(json.as_ref())?)))
+        .collect::<Result<_, serde_json::Error>>()?;
+    Ok(PreparedNamespaces(Arc::new(namespaces)))
+}
+
+/// Find definitional cycles in namespace configs.
+///
+/// Follows the edges `compute_permission` takes on the *same object*:
+/// permission → its usersets, union relation → its members. A cycle there
+/// (`a` unions `b`, `b` unions `a`) means every check through it hits
+/// cycle detection and returns `false`. tupleToUserset hops always move
+/// to a different object (`viewer` via `parent` is ordinary hierarchy
+/// recursion), so they are not followed.
+///
+/// Each cycle is reported once as `type#relation` names in traversal
+/// order, rotated to start at its smallest name. Output is sorted.
+pub fn find_config_cycles(namespaces: &AHashMap<String, NamespaceConfig>) -> Vec<Vec<String>> {
+    let mut cycles: AHashSet<Vec<String>> = AHashSet::new();
+
+    let mut types: Vec<&String> = namespaces.keys().collect();
+    types.sort();
+    for object_type in types {
+        let config = &namespaces[object_type];
+        let mut names: Vec<&String> = config
+            .permissions
+            .keys()
+            .chain(config.relations.keys())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        // Per-type DFS; `done` nodes have had every cycle through them found.
+        let mut done: AHashSet<&str> = AHashSet::new();
+        for root in names {
+            let mut path: Vec<&str> = Vec::new();
+            let mut stack: Vec<(&str, Vec<&str>)> = vec![(root, definition_edges(config, root))];
+            path.push(root);
+            while let Some((_, children)) = stack.last_mut() {
+                let Some(child) = children.pop() else {
+                    let (node, _) = stack.pop().expect("non-empty stack");
+                    path.pop();
+                    done.insert(node);
+                    continue;
+                };
+                if let Some(pos) = path.iter().position(|n| *n == child) {
+                    cycles.insert(canonical_cycle(object_type, &path[pos..]));
+                } else if !done.contains(child) {
+                    path.push(child);
+                    stack.push((child, definition_edges(config, child)));
+                }
+            }
+        }
+    }
+
+    let mut cycles: Vec<Vec<String>> = cycles.into_iter().collect();
+    cycles.sort();
+    cycles
+}
+
+/// Same-object definition edges out of `name`, mirroring the lookup
+/// order of `compute_permission` (permissions shadow relations).
+fn definition_edges<'a>(config: &'a NamespaceConfig, name: &str) -> Vec<&'a str> {
+    if let Some(usersets) = config.permissions.get(name) {
+        return usersets.iter().map(String::as_str).collect();
+    }
+    match config.relations.get(name) {
+        Some(RelationConfig::Union { union }) => union.iter().map(String::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn canonical_cycle(object_type: &str, members: &[&str]) -> Vec<String> {
+    let start = members
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, name)| **name)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    members[start..]
+        .iter()
+        .chain(&members[..start])
+        .map(|name| format!("{object_type}#{name}"))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +188,141 @@ mod tests {
             other => panic!("expected TupleToUserset, got {:?}", other),
         }
     }
+
+    fn namespaces(entries: &[(&str, &str)]) -> AHashMap<String, NamespaceConfig> {
+        entries
+            .iter()
+            .map(|(t, json)| (t.to_string(), parse_namespace_config(json).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn find_config_cycles_reports_two_node_union_cycle() {
+        let ns = namespaces(&[(
+            "doc",
+            r#"{"relations":{"a":{"union":["b"]},"b":{"union":["a","owner"]},"owner":"direct"},
+                "permissions":{"read":["a"]}}"#,
+        )]);
+        assert_eq!(find_config_cycles(&ns), vec![vec!["doc#a", "doc#b"]]);
+    }
+
+    #[test]
+    fn find_config_cycles_follows_permissions_and_self_loops() {
+        let ns = namespaces(&[
+            (
+                "doc",
+                r#"{"relations":{"editor":{"union":["write"]},"owner":"direct"},
+                    "permissions":{"write":["editor","owner"]}}"#,
+            ),
+            (
+                "group",
+                r#"{"relations":{"member":{"union":["member"]}},"permissions":{}}"#,
+            ),
+        ]);
+        assert_eq!(
+            find_config_cycles(&ns),
+            vec![vec!["doc#editor", "doc#write"], vec!["group#member"]]
+        );
+    }
+
+    #[test]
+    fn find_config_cycles_clean_config_and_tuple_to_userset_recursion() {
+        let ns = namespaces(&[(
+            "file",
+            r#"{"relations":{
+                    "parent":"direct",
+                    "owner":"direct",
+                    "editor":{"union":["owner"]},
+                    "viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+                },
+                "permissions":{"read":["viewer","editor"],"write":["editor"]}}"#,
+        )]);
+        assert!(find_config_cycles(&ns).is_empty());
+    }
+
+    #[test]
+    fn prepared_namespaces_match_per_call_parsing() {
+        use crate::rebac::{compute_permission, expand_permission, ReBACGraph};
+        use crate::types::{Entity, MemoCache, ReBACTuple};
+
+        let folder = r#"{"relations":{"viewer":"direct"},"permissions":{"read":["viewer"]}}"#;
+        let file = r#"{
+            "relations":{
+                "parent":"direct",
+                "viewer":"direct",
+                "parent_read":{"tupleToUserset":{"tupleset":"parent","computedUserset":"read"}}
+            },
+            "permissions":{"read":["viewer","parent_read"]}
+        }"#;
+        let raw = [("folder", folder), ("file", file)];
+        let prepared = prepare_namespaces(raw).unwrap();
+        let parsed: AHashMap<String, NamespaceConfig> = raw
+            .iter()
+            .map(|(t, json)| (t.to_string(), parse_namespace_config(json).unwrap()))
+            .collect();
+        assert_eq!(prepared.len(), 2);
+
+        let tuple = |subject: (&str, &str), relation: &str, object: (&str, &str)| ReBACTuple {
+            subject_type: subject.0.to_string(),
+            subject_id: subject.1.to_string(),
+            subject_relation: None,
+            relation: relation.to_string(),
+            object_type: object.0.to_string(),
+            object_id: object.1.to_string(),
+        };
+        let graph = ReBACGraph::from_tuples(&[
+            tuple(("user", "alice"), "viewer", ("folder", "docs")),
+            tuple(("folder", "docs"), "parent", ("file", "a")),
+            tuple(("user", "bob"), "viewer", ("file", "b")),
+        ]);
+        let entity = |t: &str, id: &str| Entity {
+            entity_type: t.to_string(),
+            entity_id: id.to_string(),
+        };
+
+        // The handle is built once and reused across calls.
+        let handle = prepared.clone();
+        for (user, file_id) in [("alice", "a"), ("alice", "b"), ("bob", "a"), ("bob", "b")] {
+            let check = |namespaces: &AHashMap<String, NamespaceConfig>| {
+                compute_permission(
+                    &entity("user", user),
+                    "read",
+                    &entity("file", file_id),
+                    &graph,
+                    namespaces,
+                    &mut MemoCache::new(),
+                    &mut Default::default(),
+                    0,
+                )
+            };
+            assert_eq!(check(&handle), check(&parsed), "{user} read file:{file_id}");
+
+            let expand = |namespaces: &AHashMap<String, NamespaceConfig>| {
+                let mut subjects = AHashSet::new();
+                expand_permission(
+                    "read",
+                    &entity("file", file_id),
+                    &graph,
+                    namespaces,
+                    &mut subjects,
+                    &mut AHashSet::new(),
+                    0,
+                );
+                subjects
+            };
+            assert_eq!(expand(&handle), expand(&parsed));
+        }
+        assert!(
+            Arc::ptr_eq(&handle.0, &prepared.0),
+            "clones share one parse"
+        );
+    }
+
+    #[test]
+    fn prepare_namespaces_rejects_invalid_json() {
+        assert!(prepare_namespaces([("file", "{\"relations\": ")]).is_err());
+        assert!(prepare_namespaces(Vec::<(String, String)>::new())
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,253 @@
+//! Schema migration helpers: rename a relation across tuples and configs.
+//!
+//! A rename has to touch every place a relation name appears — the
+//! tuple's own relation, userset subjects (`group:eng#member`), union
+//! members, tupleToUserset references and permission lists. Doing it in
+//! one place keeps migrations from missing a reference.
+
+use std::fmt;
+
+use crate::types::{NamespaceConfig, ReBACTuple, RelationConfig};
+
+/// Errors from [`rewrite_namespace_relation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrateError {
+    /// The target name is already a relation in the config.
+    RelationExists { relation: String },
+    /// The target name is a permission, which would shadow the renamed
+    /// relation during evaluation.
+    PermissionExists { permission: String },
+    /// The target name already carries obligations, which the renamed
+    /// relation's obligations would replace.
+    ObligationsExist { name: String },
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrateError::RelationExists { relation } => {
+                write!(f, "Relation '{}' already exists", relation)
+            }
+            MigrateError::PermissionExists { permission } => {
+                write!(f, "'{}' is already a permission", permission)
+            }
+            MigrateError::ObligationsExist { name } => {
+                write!(f, "'{}' already has obligations", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MigrateError {}
+
+fn rename(name: &mut String, from: &str, to: &str) {
+    if name == from {
+        *name = to.to_string();
+    }
+}
+
+/// Copy of `tuples` with relation `from` renamed to `to`, in both the
+/// `relation` and `subject_relation` fields. Order is preserved.
+pub fn rewrite_relation(tuples: &[ReBACTuple], from: &str, to: &str) -> Vec<ReBACTuple> {
+    tuples
+        .iter()
+        .cloned()
+        .map(|mut tuple| {
+            rename(&mut tuple.relation, from, to);
+            if let Some(subject_relation) = tuple.subject_relation.as_mut() {
+                rename(subject_relation, from, to);
+            }
+            tuple
+        })
+        .collect()
+}
+
+/// Copy of `config` with relation `from` renamed to `to`: the relation's
+/// own definition, union members, both sides of every tupleToUserset and
+/// every permission's userset list.
+///
+/// `computedUserset` names a relation on the *tupleset target's* type, so
+/// this assumes the rename is applied to every type's config, as a schema
+/// migration would. Permission names are not relations and are left as is.
+///
+/// Fails rather than overwrite when `config` already defines relation
+/// `to`, when `to` names a permission (permissions shadow relations of the
+/// same name, so the rename would change check results), or when both
+/// `from` and `to` carry obligations.
+pub fn rewrite_namespace_relation(
+    config: &NamespaceConfig,
+    from: &str,
+    to: &str,
+) -> Result<NamespaceConfig, MigrateError> {
+    let mut config = config.clone();
+    if from == to {
+        return Ok(config);
+    }
+    if config.relations.contains_key(from) {
+        if config.relations.contains_key(to) {
+            return Err(MigrateError::RelationExists {
+                relation: to.to_string(),
+            });
+        }
+        if config.permissions.contains_key(to) {
+            return Err(MigrateError::PermissionExists {
+                permission: to.to_string(),
+            });
+        }
+        if config.obligations.contains_key(from) && config.obligations.contains_key(to) {
+            return Err(MigrateError::ObligationsExist {
+                name: to.to_string(),
+            });
+        }
+    }
+    if let Some(definition) = config.relations.remove(from) {
+        config.relations.insert(to.to_string(), definition);
+        if let Some(obligations) = config.obligations.remove(from) {
+            config.obligations.insert(to.to_string(), obligations);
+        }
+    }
+    for definition in config.relations.values_mut() {
+        match definition {
+            RelationConfig::Union { union } => {
+                union.iter_mut().for_each(|member| rename(member, from, to));
+            }
+            RelationConfig::TupleToUserset { tuple_to_userset } => {
+                rename(&mut tuple_to_userset.tupleset, from, to);
+                rename(&mut tuple_to_userset.computed_userset, from, to);
+            }
+            RelationConfig::Direct(_) | RelationConfig::EmptyDict(_) => {}
+        }
+    }
+    for usersets in config.permissions.values_mut() {
+        usersets
+            .iter_mut()
+            .for_each(|userset| rename(userset, from, to));
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rebac::config::parse_namespace_config;
+
+    fn tuple(subject: &str, subject_relation: Option<&str>, relation: &str) -> ReBACTuple {
+        let (subject_type, subject_id) = subject.split_once(':').unwrap();
+        ReBACTuple {
+            subject_type: subject_type.to_string(),
+            subject_id: subject_id.to_string(),
+            subject_relation: subject_relation.map(str::to_string),
+            relation: relation.to_string(),
+            object_type: "file".to_string(),
+            object_id: "readme".to_string(),
+        }
+    }
+
+    #[test]
+    fn rewrite_relation_renames_relation_and_subject_relation() {
+        let tuples = vec![
+            tuple("user:alice", None, "viewer"),
+            tuple("folder:root", Some("viewer"), "viewer"),
+            tuple("group:eng", Some("member"), "editor"),
+        ];
+        let rewritten = rewrite_relation(&tuples, "viewer", "reader");
+        let summary: Vec<(&str, Option<&str>)> = rewritten
+            .iter()
+            .map(|t| (t.relation.as_str(), t.subject_relation.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("reader", None),
+                ("reader", Some("reader")),
+                ("editor", Some("member")),
+            ]
+        );
+        assert_eq!(rewritten[0].subject_id, "alice");
+        // The input is untouched.
+        assert_eq!(tuples[0].relation, "viewer");
+    }
+
+    #[test]
+    fn rewrite_namespace_relation_updates_every_reference() {
+        let config = parse_namespace_config(
+            r#"{
+                "relations": {
+                    "parent": "direct",
+                    "owner": "direct",
+                    "viewer": "direct",
+                    "editor": {"union": ["owner", "viewer"]},
+                    "parent_viewer": {"tupleToUserset": {"tupleset": "parent", "computedUserset": "viewer"}},
+                    "viewer_owner": {"tupleToUserset": {"tupleset": "viewer", "computedUserset": "owner"}}
+                },
+                "permissions": {"read": ["viewer", "editor"], "write": ["owner"], "viewer": ["owner"]}
+            }"#,
+        )
+        .unwrap();
+        let rewritten = rewrite_namespace_relation(&config, "viewer", "reader").unwrap();
+
+        assert!(!rewritten.relations.contains_key("viewer"));
+        assert!(matches!(
+            rewritten.relations["reader"],
+            RelationConfig::Direct(_)
+        ));
+        match &rewritten.relations["editor"] {
+            RelationConfig::Union { union } => assert_eq!(union, &["owner", "reader"]),
+            other => panic!("expected Union, got {other:?}"),
+        }
+        let ttu = |name: &str| match &rewritten.relations[name] {
+            RelationConfig::TupleToUserset { tuple_to_userset } => (
+                tuple_to_userset.tupleset.as_str(),
+                tuple_to_userset.computed_userset.as_str(),
+            ),
+            other => panic!("expected TupleToUserset, got {other:?}"),
+        };
+        assert_eq!(ttu("parent_viewer"), ("parent", "reader"));
+        assert_eq!(ttu("viewer_owner"), ("reader", "owner"));
+        assert_eq!(rewritten.permissions["read"], ["reader", "editor"]);
+        // Unrelated entries, and the permission *named* viewer, are untouched.
+        assert_eq!(rewritten.permissions["write"], ["owner"]);
+        assert_eq!(rewritten.permissions["viewer"], ["owner"]);
+        assert!(matches!(
+            rewritten.relations["owner"],
+            RelationConfig::Direct(_)
+        ));
+        assert_eq!(rewritten.relations.len(), config.relations.len());
+    }
+
+    #[test]
+    fn rewrite_namespace_relation_refuses_to_overwrite() {
+        let config = parse_namespace_config(
+            r#"{
+                "relations": {"viewer": "direct", "reader": "direct", "owner": "direct"},
+                "permissions": {"read": ["viewer"]},
+                "obligations": {"viewer": ["audit"], "legacy": ["watermark"]}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rewrite_namespace_relation(&config, "viewer", "reader").unwrap_err(),
+            MigrateError::RelationExists {
+                relation: "reader".to_string()
+            }
+        );
+        assert_eq!(
+            rewrite_namespace_relation(&config, "viewer", "read").unwrap_err(),
+            MigrateError::PermissionExists {
+                permission: "read".to_string()
+            }
+        );
+        assert_eq!(
+            rewrite_namespace_relation(&config, "viewer", "legacy").unwrap_err(),
+            MigrateError::ObligationsExist {
+                name: "legacy".to_string()
+            }
+        );
+        // Without a collision the obligations move with the relation.
+        let rewritten = rewrite_namespace_relation(&config, "owner", "admin").unwrap();
+        assert!(rewritten.relations.contains_key("admin"));
+        let rewritten = rewrite_namespace_relation(&config, "viewer", "watcher").unwrap();
+        assert_eq!(rewritten.obligations["watcher"], ["audit"]);
+    }
+}
@@ -5,6 +5,15 @@
 
 pub mod config;
 pub mod graph;
+pub mod migrate;
+pub mod snapshot;
+pub mod stats;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod validate;
+
+use std::borrow::Cow;
+use std::cell::Cell;
 
 use ahash::{AHashMap, AHashSet};
 
@@ -186,6 +195,16 @@ impl ReBACGraph {
     }
 }
 
+/// Per-check state threaded through the recursive evaluators.
+#[derive(Default)]
+struct CheckContext<'a> {
+    overrides: Option<&'a ObjectOverrides>,
+    /// A branch was cut at [`MAX_DEPTH`].
+    depth_exceeded: Cell<bool>,
+    /// A branch was cut because it re-entered a check still in progress.
+    cycle_detected: Cell<bool>,
+}
+
 /// Compute a single permission check with memoization (string-keyed).
 #[allow(clippy::too_many_arguments)]
 pub fn compute_permission(
@@ -197,8 +216,128 @@ pub fn compute_permission(
     memo_cache: &mut MemoCache,
     visited: &mut VisitedSet,
     depth: u32,
+) -> bool {
+    compute_permission_inner(
+        subject,
+        permission,
+        object,
+        graph,
+        namespaces,
+        &CheckContext::default(),
+        memo_cache,
+        visited,
+        depth,
+    )
+}
+
+/// [`compute_permission`] with object-scoped namespace overrides merged
+/// on top of the type-level configs (see [`ObjectNamespaceOverride`] for
+/// the merge rules). `memo_cache` must not be shared with checks that use
+/// different overrides.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_permission_with_overrides(
+    subject: &Entity,
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    overrides: &ObjectOverrides,
+    memo_cache: &mut MemoCache,
+    visited: &mut VisitedSet,
+    depth: u32,
+) -> bool {
+    compute_permission_inner(
+        subject,
+        permission,
+        object,
+        graph,
+        namespaces,
+        &CheckContext {
+            overrides: Some(overrides),
+            ..CheckContext::default()
+        },
+        memo_cache,
+        visited,
+        depth,
+    )
+}
+
+/// Namespace for `object`: its type's config with any object-scoped
+/// override merged on top (per key, override wins).
+fn effective_namespace<'a>(
+    object: &Entity,
+    namespaces: &'a AHashMap<String, NamespaceConfig>,
+    overrides: Option<&ObjectOverrides>,
+) -> Option<Cow<'a, NamespaceConfig>> {
+    let type_config = namespaces.get(&object.entity_type);
+    let object_override = overrides.and_then(|overrides| {
+        overrides.get(&(object.entity_type.clone(), object.entity_id.clone()))
+    });
+    let Some(object_override) = object_override else {
+        return type_config.map(Cow::Borrowed);
+    };
+    let mut merged = type_config.cloned().unwrap_or_else(|| NamespaceConfig {
+        relations: Default::default(),
+        permissions: Default::default(),
+        obligations: Default::default(),
+    });
+    merged.relations.extend(
+        object_override
+            .relations
+            .iter()
+            .map(|(name, config)| (name.clone(), config.clone())),
+    );
+    merged.permissions.extend(
+        object_override
+            .permissions
+            .iter()
+            .map(|(name, usersets)| (name.clone(), usersets.clone())),
+    );
+    Some(Cow::Owned(merged))
+}
+
+/// Whether an object-scoped override lists `subject` (or `*:*`) as an
+/// implicit holder of `relation` on `object`.
+fn has_implicit_grant(
+    subject: &Entity,
+    relation: &str,
+    object: &Entity,
+    overrides: Option<&ObjectOverrides>,
+) -> bool {
+    let Some(object_override) = overrides.and_then(|overrides| {
+        overrides.get(&(object.entity_type.clone(), object.entity_id.clone()))
+    }) else {
+        return false;
+    };
+    object_override
+        .implicit_subjects
+        .get(relation)
+        .is_some_and(|holders| {
+            holders.iter().any(|holder| {
+                holder == "*:*"
+                    || holder
+                        .split_once(':')
+                        .is_some_and(|(entity_type, entity_id)| {
+                            entity_type == subject.entity_type && entity_id == subject.entity_id
+                        })
+            })
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn compute_permission_inner(
+    subject: &Entity,
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    ctx: &CheckContext<'_>,
+    memo_cache: &mut MemoCache,
+    visited: &mut VisitedSet,
+    depth: u32,
 ) -> bool {
     if depth > MAX_DEPTH {
+        ctx.depth_exceeded.set(true);
         return false;
     }
 
@@ -215,15 +354,16 @@ pub fn compute_permission(
     }
 
     if visited.contains(&memo_key) {
+        ctx.cycle_detected.set(true);
         return false;
     }
     visited.insert(memo_key.clone());
 
-    let namespace = match namespaces.get(&object.entity_type) {
+    let namespace = match effective_namespace(object, namespaces, ctx.overrides) {
         Some(ns) => ns,
         None => {
-            let result = check_relation_with_usersets(
-                subject, permission, object, graph, namespaces, memo_cache, visited, depth,
+            let result = check_relation_inner(
+                subject, permission, object, graph, namespaces, ctx, memo_cache, visited, depth,
             );
             memo_cache.insert(memo_key, result);
             return result;
@@ -233,12 +373,13 @@ pub fn compute_permission(
     let result = if let Some(usersets) = namespace.permissions.get(permission) {
         let mut allowed = false;
         for userset in usersets {
-            if compute_permission(
+            if compute_permission_inner(
                 subject,
                 userset,
                 object,
                 graph,
                 namespaces,
+                ctx,
                 memo_cache,
                 visited,
                 depth + 1,
@@ -250,20 +391,19 @@ pub fn compute_permission(
         allowed
     } else if let Some(relation_config) = namespace.relations.get(permission) {
         match relation_config {
-            RelationConfig::Direct(_) | RelationConfig::EmptyDict(_) => {
-                check_relation_with_usersets(
-                    subject, permission, object, graph, namespaces, memo_cache, visited, depth,
-                )
-            }
+            RelationConfig::Direct(_) | RelationConfig::EmptyDict(_) => check_relation_inner(
+                subject, permission, object, graph, namespaces, ctx, memo_cache, visited, depth,
+            ),
             RelationConfig::Union { union } => {
                 let mut allowed = false;
                 for rel in union {
-                    if compute_permission(
+                    if compute_permission_inner(
                         subject,
                         rel,
                         object,
                         graph,
                         namespaces,
+                        ctx,
                         memo_cache,
                         visited,
                         depth + 1,
@@ -290,12 +430,13 @@ pub fn compute_permission(
                 let forward_targets =
                     graph.find_related_objects(object, &tuple_to_userset.tupleset);
                 for target in &forward_targets {
-                    if compute_permission(
+                    if compute_permission_inner(
                         subject,
                         &tuple_to_userset.computed_userset,
                         target,
                         graph,
                         namespaces,
+                        ctx,
                         memo_cache,
                         visited,
                         depth + 1,
@@ -325,12 +466,13 @@ pub fn compute_permission(
                     let reverse_targets =
                         graph.find_subjects_for_object(object, &tuple_to_userset.tupleset);
                     for target in &reverse_targets {
-                        if compute_permission(
+                        if compute_permission_inner(
                             subject,
                             &tuple_to_userset.computed_userset,
                             target,
                             graph,
                             namespaces,
+                            ctx,
                             memo_cache,
                             visited,
                             depth + 1,
@@ -343,16 +485,17 @@ pub fn compute_permission(
 
                 // Also check direct relations — Zanzibar: direct tuples always apply
                 if !allowed {
-                    allowed = check_relation_with_usersets(
-                        subject, permission, object, graph, namespaces, memo_cache, visited, depth,
+                    allowed = check_relation_inner(
+                        subject, permission, object, graph, namespaces, ctx, memo_cache, visited,
+                        depth,
                     );
                 }
                 allowed
             }
         }
     } else {
-        check_relation_with_usersets(
-            subject, permission, object, graph, namespaces, memo_cache, visited, depth,
+        check_relation_inner(
+            subject, permission, object, graph, namespaces, ctx, memo_cache, visited, depth,
         )
     };
 
@@ -372,7 +515,34 @@ pub fn check_relation_with_usersets(
     visited: &mut VisitedSet,
     depth: u32,
 ) -> bool {
-    if graph.check_direct_relation(subject, relation, object) {
+    check_relation_inner(
+        subject,
+        relation,
+        object,
+        graph,
+        namespaces,
+        &CheckContext::default(),
+        memo_cache,
+        visited,
+        depth,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_relation_inner(
+    subject: &Entity,
+    relation: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    ctx: &CheckContext<'_>,
+    memo_cache: &mut MemoCache,
+    visited: &mut VisitedSet,
+    depth: u32,
+) -> bool {
+    if graph.check_direct_relation(subject, relation, object)
+        || has_implicit_grant(subject, relation, object, ctx.overrides)
+    {
         return true;
     }
 
@@ -382,12 +552,13 @@ pub fn check_relation_with_usersets(
             entity_id: userset.subject_id.clone(),
         };
 
-        if compute_permission(
+        if compute_permission_inner(
             subject,
             &userset.subject_relation,
             &userset_entity,
             graph,
             namespaces,
+            ctx,
             memo_cache,
             visited,
             depth + 1,
@@ -399,6 +570,242 @@ pub fn check_relation_with_usersets(
     false
 }
 
+/// Check whether `subject` holds ANY of `permissions` on `object`.
+///
+/// Evaluates in order and stops at the first grant. All checks share
+/// `memo_cache`, so sub-relations resolved for an earlier permission are
+/// reused by later ones. An empty `permissions` list is denied.
+pub fn check_any(
+    subject: &Entity,
+    permissions: &[String],
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    memo_cache: &mut MemoCache,
+) -> bool {
+    permissions.iter().any(|permission| {
+        compute_permission(
+            subject,
+            permission,
+            object,
+            graph,
+            namespaces,
+            memo_cache,
+            &mut AHashSet::new(),
+            0,
+        )
+    })
+}
+
+/// Check whether `subject` holds ALL of `permissions` on `object`.
+///
+/// Evaluates in order and stops at the first denial, sharing `memo_cache`
+/// like [`check_any`]. An empty `permissions` list is granted.
+pub fn check_all(
+    subject: &Entity,
+    permissions: &[String],
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    memo_cache: &mut MemoCache,
+) -> bool {
+    permissions.iter().all(|permission| {
+        compute_permission(
+            subject,
+            permission,
+            object,
+            graph,
+            namespaces,
+            memo_cache,
+            &mut AHashSet::new(),
+            0,
+        )
+    })
+}
+
+/// Check every permission in `permissions` for `subject` on every object
+/// in `objects`: `grid[i][j]` is whether `subject` holds `permissions[j]`
+/// on `objects[i]`.
+///
+/// The whole matrix shares `memo_cache`, not just the permissions of one
+/// object: with a single subject, sub-results such as group memberships
+/// or a common parent's relations are resolved once and reused by every
+/// later cell. Pass a cache kept from earlier calls on the same graph to
+/// reuse its results too.
+pub fn check_subject_matrix(
+    subject: &Entity,
+    permissions: &[String],
+    objects: &[Entity],
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    memo_cache: &mut MemoCache,
+) -> Vec<Vec<bool>> {
+    objects
+        .iter()
+        .map(|object| {
+            permissions
+                .iter()
+                .map(|permission| {
+                    compute_permission(
+                        subject,
+                        permission,
+                        object,
+                        graph,
+                        namespaces,
+                        memo_cache,
+                        &mut AHashSet::new(),
+                        0,
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Why [`check_with_reason`] denied a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenialReason {
+    /// Evaluation completed and no tuple or rewrite granted the permission.
+    NoMatchingRelation,
+    /// A branch hit [`MAX_DEPTH`] and was cut; a grant deeper in the graph
+    /// would not have been seen.
+    DepthExceeded,
+    /// A branch re-entered a check that was still being evaluated (e.g.
+    /// mutually recursive relations or group memberships) and was cut.
+    CycleDetected,
+}
+
+/// [`compute_permission`] that says why a denial happened.
+///
+/// Returns `Ok(())` when granted. When several branches were cut, a depth
+/// cut wins over a cycle cut, since it may have hidden a real grant;
+/// `NoMatchingRelation` means every branch ran to completion. Uses a fresh
+/// memo cache so the classification reflects this check alone.
+pub fn check_with_reason(
+    subject: &Entity,
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> Result<(), DenialReason> {
+    let ctx = CheckContext::default();
+    let granted = compute_permission_inner(
+        subject,
+        permission,
+        object,
+        graph,
+        namespaces,
+        &ctx,
+        &mut MemoCache::new(),
+        &mut AHashSet::new(),
+        0,
+    );
+    if granted {
+        Ok(())
+    } else if ctx.depth_exceeded.get() {
+        Err(DenialReason::DepthExceeded)
+    } else if ctx.cycle_detected.get() {
+        Err(DenialReason::CycleDetected)
+    } else {
+        Err(DenialReason::NoMatchingRelation)
+    }
+}
+
+/// Result of [`check_with_obligations`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObligatedDecision {
+    pub allowed: bool,
+    /// Obligations of every relation and permission on the granting path,
+    /// outermost first, without duplicates. Empty when denied.
+    pub obligations: Vec<String>,
+}
+
+/// [`compute_permission`] that also returns the obligations the grant
+/// carries (see [`NamespaceConfig::obligations`]).
+///
+/// The granting path is the one evaluation stops on: the first userset,
+/// union member or tupleToUserset target that grants, in the same order
+/// [`compute_permission`] tries them, down to the direct tuple. Each name
+/// on it contributes the obligations its object's namespace lists.
+pub fn check_with_obligations(
+    subject: &Entity,
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> ObligatedDecision {
+    let mut memo_cache = MemoCache::new();
+    let mut grants = |name: &str, target: &Entity| {
+        compute_permission(
+            subject,
+            name,
+            target,
+            graph,
+            namespaces,
+            &mut memo_cache,
+            &mut AHashSet::new(),
+            0,
+        )
+    };
+    if !grants(permission, object) {
+        return ObligatedDecision::default();
+    }
+
+    let mut obligations: Vec<String> = Vec::new();
+    let mut name = permission.to_string();
+    let mut current = object.clone();
+    for _ in 0..=MAX_DEPTH {
+        let Some(namespace) = namespaces.get(&current.entity_type) else {
+            break;
+        };
+        for obligation in namespace.obligations.get(&name).into_iter().flatten() {
+            if !obligations.contains(obligation) {
+                obligations.push(obligation.clone());
+            }
+        }
+        // The next hop on the granting path, if this name is a rewrite.
+        let next = if let Some(usersets) = namespace.permissions.get(&name) {
+            usersets
+                .iter()
+                .find(|userset| grants(userset, &current))
+                .map(|userset| (userset.clone(), current.clone()))
+        } else {
+            match namespace.relations.get(&name) {
+                Some(RelationConfig::Union { union }) => union
+                    .iter()
+                    .find(|member| grants(member, &current))
+                    .map(|member| (member.clone(), current.clone())),
+                Some(RelationConfig::TupleToUserset { tuple_to_userset }) => {
+                    let computed = &tuple_to_userset.computed_userset;
+                    let mut targets =
+                        graph.find_related_objects(&current, &tuple_to_userset.tupleset);
+                    if tuple_to_userset.tupleset != "parent" {
+                        targets.extend(
+                            graph.find_subjects_for_object(&current, &tuple_to_userset.tupleset),
+                        );
+                    }
+                    targets
+                        .into_iter()
+                        .find(|target| grants(computed, target))
+                        .map(|target| (computed.clone(), target))
+                }
+                _ => None,
+            }
+        };
+        match next {
+            Some((next_name, next_object)) => {
+                name = next_name;
+                current = next_object;
+            }
+            None => break,
+        }
+    }
+    ObligatedDecision {
+        allowed: true,
+        obligations,
+    }
+}
+
 /// Expand subjects: find all subjects with a permission on an object.
 pub fn expand_permission(
     permission: &str,
@@ -409,7 +816,194 @@ pub fn expand_permission(
     visited: &mut AHashSet<(String, String, String)>,
     depth: u32,
 ) {
-    if depth > MAX_DEPTH {
+    let mut truncated = false;
+    expand_permission_inner(
+        permission,
+        object,
+        graph,
+        namespaces,
+        subjects,
+        visited,
+        depth,
+        usize::MAX,
+        &mut truncated,
+    );
+}
+
+/// Result of [`expand_permission_bounded`].
+#[derive(Debug, Clone, Default)]
+pub struct BoundedExpansion {
+    /// Subjects found (all of them unless `truncated`).
+    pub subjects: AHashSet<(String, String)>,
+    /// Expansion nodes (permission/relation × object) visited.
+    pub nodes_visited: usize,
+    /// The budget ran out before the expansion finished; `subjects` is a
+    /// subset of the full answer.
+    pub truncated: bool,
+}
+
+/// [`expand_permission`] that stops after visiting `max_nodes_visited`
+/// expansion nodes and returns what it found so far, so interactive
+/// callers get a bounded answer on dense graphs.
+pub fn expand_permission_bounded(
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    max_nodes_visited: usize,
+) -> BoundedExpansion {
+    let mut result = BoundedExpansion::default();
+    let mut visited = AHashSet::new();
+    expand_permission_inner(
+        permission,
+        object,
+        graph,
+        namespaces,
+        &mut result.subjects,
+        &mut visited,
+        0,
+        max_nodes_visited,
+        &mut result.truncated,
+    );
+    result.nodes_visited = visited.len();
+    result
+}
+
+/// Every subject holding one permission on one object, materialized by
+/// [`precompute_closure`] for O(1) membership checks on hot objects.
+#[derive(Debug, Clone, Default)]
+pub struct PermissionClosure {
+    subjects: AHashSet<(String, String)>,
+}
+
+impl PermissionClosure {
+    /// Whether `subject` holds the permission: it is in the closure, or
+    /// the closure contains the `*:*` wildcard.
+    pub fn contains(&self, subject: &Entity) -> bool {
+        let wildcard = ("*".to_string(), "*".to_string());
+        self.subjects.contains(&wildcard)
+            || self
+                .subjects
+                .contains(&(subject.entity_type.clone(), subject.entity_id.clone()))
+    }
+
+    /// Number of subjects, counting `*:*` as one.
+    pub fn len(&self) -> usize {
+        self.subjects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subjects.is_empty()
+    }
+
+    /// The subjects as `(type, id)`, sorted.
+    pub fn to_sorted_vec(&self) -> Vec<(String, String)> {
+        let mut subjects: Vec<(String, String)> = self.subjects.iter().cloned().collect();
+        subjects.sort_unstable();
+        subjects
+    }
+}
+
+/// Collects concrete subjects and queues userset subjects for expansion.
+#[derive(Default)]
+struct ClosureSink {
+    subjects: AHashSet<(String, String)>,
+    usersets: Vec<(String, Entity)>,
+}
+
+impl ExpansionSink for ClosureSink {
+    fn direct(&mut self, subject: Entity, _granted_on: &Entity) {
+        self.subjects
+            .insert((subject.entity_type, subject.entity_id));
+    }
+
+    fn userset(&mut self, userset: &UsersetEntry, _granted_on: &Entity) {
+        self.usersets.push((
+            userset.subject_relation.clone(),
+            Entity {
+                entity_type: userset.subject_type.clone(),
+                entity_id: userset.subject_id.clone(),
+            },
+        ));
+    }
+}
+
+/// Materialize every subject with `permission` on `object`.
+///
+/// Like [`expand_permission`], but userset subjects (`group:eng#member`)
+/// are expanded into their members recursively, so the result holds only
+/// concrete subjects (plus `*:*` when a wildcard grants). Expansion nodes
+/// are shared across the whole closure, so each is expanded once.
+///
+/// The closure is a snapshot of `graph`: any tuple written or deleted
+/// afterwards — on `object`, on anything it inherits from through
+/// tupleToUserset, or on any group in the chain — can make it stale.
+/// Callers caching it for a hot object must rebuild it on tuple changes
+/// (or bound its lifetime) rather than treat it as authoritative.
+pub fn precompute_closure(
+    object: &Entity,
+    permission: &str,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> PermissionClosure {
+    let mut sink = ClosureSink::default();
+    let mut visited = AHashSet::new();
+    let mut pending = vec![(permission.to_string(), object.clone())];
+    while let Some((permission, object)) = pending.pop() {
+        let mut truncated = false;
+        expand_permission_inner(
+            &permission,
+            &object,
+            graph,
+            namespaces,
+            &mut sink,
+            &mut visited,
+            0,
+            usize::MAX,
+            &mut truncated,
+        );
+        pending.append(&mut sink.usersets);
+    }
+    PermissionClosure {
+        subjects: sink.subjects,
+    }
+}
+
+/// Receives the subjects found by [`expand_permission_inner`], along with
+/// the object whose tuples granted them.
+trait ExpansionSink {
+    fn direct(&mut self, subject: Entity, granted_on: &Entity);
+    fn userset(&mut self, userset: &UsersetEntry, granted_on: &Entity);
+}
+
+/// The `(type, id)` subject set returned by [`expand_permission`];
+/// usersets appear as `("type#relation", id)`.
+impl ExpansionSink for AHashSet<(String, String)> {
+    fn direct(&mut self, subject: Entity, _granted_on: &Entity) {
+        self.insert((subject.entity_type, subject.entity_id));
+    }
+
+    fn userset(&mut self, userset: &UsersetEntry, _granted_on: &Entity) {
+        self.insert((
+            format!("{}#{}", userset.subject_type, userset.subject_relation),
+            userset.subject_id.clone(),
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn expand_permission_inner<S: ExpansionSink>(
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    subjects: &mut S,
+    visited: &mut AHashSet<(String, String, String)>,
+    depth: u32,
+    max_nodes: usize,
+    truncated: &mut bool,
+) {
+    if depth > MAX_DEPTH || *truncated {
         return;
     }
 
@@ -421,6 +1015,10 @@ pub fn expand_permission(
     if visited.contains(&visit_key) {
         return;
     }
+    if visited.len() >= max_nodes {
+        *truncated = true;
+        return;
+    }
     visited.insert(visit_key);
 
     let namespace = match namespaces.get(&object.entity_type) {
@@ -433,7 +1031,7 @@ pub fn expand_permission(
 
     if let Some(usersets) = namespace.permissions.get(permission) {
         for userset in usersets {
-            expand_permission(
+            expand_permission_inner(
                 userset,
                 object,
                 graph,
@@ -441,6 +1039,8 @@ pub fn expand_permission(
                 subjects,
                 visited,
                 depth + 1,
+                max_nodes,
+                truncated,
             );
         }
         return;
@@ -453,7 +1053,17 @@ pub fn expand_permission(
             }
             RelationConfig::Union { union } => {
                 for rel in union {
-                    expand_permission(rel, object, graph, namespaces, subjects, visited, depth + 1);
+                    expand_permission_inner(
+                        rel,
+                        object,
+                        graph,
+                        namespaces,
+                        subjects,
+                        visited,
+                        depth + 1,
+                        max_nodes,
+                        truncated,
+                    );
                 }
             }
             RelationConfig::TupleToUserset { tuple_to_userset } => {
@@ -461,7 +1071,7 @@ pub fn expand_permission(
                 let forward_targets =
                     graph.find_related_objects(object, &tuple_to_userset.tupleset);
                 for target in &forward_targets {
-                    expand_permission(
+                    expand_permission_inner(
                         &tuple_to_userset.computed_userset,
                         target,
                         graph,
@@ -469,6 +1079,8 @@ pub fn expand_permission(
                         subjects,
                         visited,
                         depth + 1,
+                        max_nodes,
+                        truncated,
                     );
                 }
 
@@ -479,7 +1091,7 @@ pub fn expand_permission(
                     let reverse_targets =
                         graph.find_subjects_for_object(object, &tuple_to_userset.tupleset);
                     for target in &reverse_targets {
-                        expand_permission(
+                        expand_permission_inner(
                             &tuple_to_userset.computed_userset,
                             target,
                             graph,
@@ -487,6 +1099,8 @@ pub fn expand_permission(
                             subjects,
                             visited,
                             depth + 1,
+                            max_nodes,
+                            truncated,
                         );
                     }
                 }
@@ -502,22 +1116,192 @@ pub fn expand_permission(
 }
 
 /// Add all direct subjects that have a relation on an object.
-fn add_direct_subjects(
+fn add_direct_subjects<S: ExpansionSink>(
     relation: &str,
     object: &Entity,
     graph: &ReBACGraph,
-    subjects: &mut AHashSet<(String, String)>,
+    subjects: &mut S,
 ) {
     for entity in graph.find_direct_subjects_for_object(object, relation) {
-        subjects.insert((entity.entity_type, entity.entity_id));
+        subjects.direct(entity, object);
     }
 
     for userset in graph.get_usersets(object, relation) {
-        subjects.insert((
-            format!("{}#{}", userset.subject_type, userset.subject_relation),
-            userset.subject_id.clone(),
-        ));
+        subjects.userset(userset, object);
+    }
+}
+
+/// How an [`AuditGrant`]'s subject came to hold the permission.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GrantSource {
+    /// A tuple on the audited object names the subject itself.
+    Direct,
+    /// The subject is a member of this group (or other userset subject),
+    /// which a tuple grants the permission to.
+    Group(Entity),
+    /// A tuple on this other object (reached via tupleToUserset, e.g. a
+    /// parent folder) names the subject directly.
+    Inherited(Entity),
+}
+
+/// One subject holding the audited permission, and how.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuditGrant {
+    pub subject: Entity,
+    pub source: GrantSource,
+}
+
+/// Subjects found by a traced expansion, with the object that granted each.
+#[derive(Default)]
+struct TracedSubjects {
+    direct: Vec<(Entity, Entity)>,
+    usersets: Vec<UsersetEntry>,
+}
+
+impl ExpansionSink for TracedSubjects {
+    fn direct(&mut self, subject: Entity, granted_on: &Entity) {
+        self.direct.push((subject, granted_on.clone()));
+    }
+
+    fn userset(&mut self, userset: &UsersetEntry, _granted_on: &Entity) {
+        self.usersets.push(userset.clone());
+    }
+}
+
+fn expand_traced(
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> TracedSubjects {
+    let mut traced = TracedSubjects::default();
+    expand_permission_inner(
+        permission,
+        object,
+        graph,
+        namespaces,
+        &mut traced,
+        &mut AHashSet::new(),
+        0,
+        usize::MAX,
+        &mut false,
+    );
+    traced
+}
+
+/// Concrete (non-userset) members of `relation` on `group`, following
+/// nested usersets.
+fn userset_members(
+    relation: &str,
+    group: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    seen: &mut AHashSet<(String, String, String)>,
+    members: &mut AHashSet<Entity>,
+) {
+    let key = (
+        relation.to_string(),
+        group.entity_type.clone(),
+        group.entity_id.clone(),
+    );
+    if !seen.insert(key) {
+        return;
+    }
+    let traced = expand_traced(relation, group, graph, namespaces);
+    members.extend(traced.direct.into_iter().map(|(subject, _)| subject));
+    for userset in &traced.usersets {
+        let nested = Entity {
+            entity_type: userset.subject_type.clone(),
+            entity_id: userset.subject_id.clone(),
+        };
+        userset_members(
+            &userset.subject_relation,
+            &nested,
+            graph,
+            namespaces,
+            seen,
+            members,
+        );
+    }
+}
+
+/// [`expand_permission`] that also reports how each subject holds
+/// `permission` on `object` — e.g. for a sharing UI that shows
+/// "via group:eng".
+///
+/// Runs the same traversal but records where each subject was found: a
+/// tuple on the object itself is [`GrantSource::Direct`], one on another
+/// object reached through tupleToUserset is [`GrantSource::Inherited`],
+/// and members of a userset subject (nested usersets flattened) are
+/// [`GrantSource::Group`] of the userset named in the tuple, wherever that
+/// tuple lives. A subject with several routes appears once per distinct
+/// source. Sorted by subject, then source.
+pub fn expand_permission_with_sources(
+    permission: &str,
+    object: &Entity,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> Vec<AuditGrant> {
+    let traced = expand_traced(permission, object, graph, namespaces);
+    let mut grants: AHashSet<AuditGrant> = AHashSet::new();
+    for (subject, granted_on) in traced.direct {
+        let source = if &granted_on == object {
+            GrantSource::Direct
+        } else {
+            GrantSource::Inherited(granted_on)
+        };
+        grants.insert(AuditGrant { subject, source });
+    }
+    for userset in &traced.usersets {
+        let group = Entity {
+            entity_type: userset.subject_type.clone(),
+            entity_id: userset.subject_id.clone(),
+        };
+        let mut members = AHashSet::new();
+        userset_members(
+            &userset.subject_relation,
+            &group,
+            graph,
+            namespaces,
+            &mut AHashSet::new(),
+            &mut members,
+        );
+        grants.extend(members.into_iter().map(|subject| AuditGrant {
+            subject,
+            source: GrantSource::Group(group.clone()),
+        }));
     }
+
+    let mut grants: Vec<AuditGrant> = grants.into_iter().collect();
+    grants.sort_by_cached_key(|grant| {
+        let (rank, via) = match &grant.source {
+            GrantSource::Direct => (0, None),
+            GrantSource::Group(group) => (1, Some(group)),
+            GrantSource::Inherited(via) => (2, Some(via)),
+        };
+        (
+            grant.subject.entity_type.clone(),
+            grant.subject.entity_id.clone(),
+            rank,
+            via.map(|e| (e.entity_type.clone(), e.entity_id.clone())),
+        )
+    });
+    grants
+}
+
+/// For each of `objects`, list the subjects holding `permission` and the
+/// source of each grant; see [`expand_permission_with_sources`]. Results
+/// are in `objects` order.
+pub fn audit_objects(
+    objects: &[Entity],
+    permission: &str,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> Vec<Vec<AuditGrant>> {
+    objects
+        .iter()
+        .map(|object| expand_permission_with_sources(permission, object, graph, namespaces))
+        .collect()
 }
 
 /// Get all relations that can grant a permission.
@@ -759,6 +1543,146 @@ fn add_tuple_to_userset_candidates(
     }
 }
 
+/// Relations `subject` directly holds on `object`, sorted and deduplicated.
+///
+/// Covers direct tuples, wildcard (`*:*`) grants, and userset tuples whose
+/// subject set is rooted at `subject` (`group:eng#member` counts for
+/// `group:eng`). No namespace expansion — this is what the tuple store
+/// says about the pair, not what permissions it implies.
+pub fn direct_relations(subject: &Entity, object: &Entity, graph: &ReBACGraph) -> Vec<String> {
+    let mut relations: Vec<String> = graph
+        .tuple_index
+        .iter()
+        .filter(|(ot, oid, _, st, sid)| {
+            *ot == object.entity_type
+                && *oid == object.entity_id
+                && ((*st == subject.entity_type && *sid == subject.entity_id)
+                    || (st == "*" && sid == "*"))
+        })
+        .map(|(_, _, relation, _, _)| relation.clone())
+        .collect();
+
+    for ((ot, oid, relation), entries) in &graph.userset_index {
+        if *ot == object.entity_type
+            && *oid == object.entity_id
+            && entries
+                .iter()
+                .any(|e| e.subject_type == subject.entity_type && e.subject_id == subject.entity_id)
+        {
+            relations.push(relation.clone());
+        }
+    }
+
+    relations.sort();
+    relations.dedup();
+    relations
+}
+
+/// For each candidate, whether `graph` already holds exactly that tuple.
+///
+/// Direct candidates are looked up in `tuple_index`, userset candidates
+/// (`subject_relation` set) in `userset_index`, matching subject relation
+/// included — so `group:eng#member` and `group:eng#admin` are different
+/// tuples. Pure membership: no wildcard or permission expansion. Build the
+/// graph once from the existing tuples and reuse it across batches.
+pub fn tuples_exist(candidates: &[ReBACTuple], graph: &ReBACGraph) -> Vec<bool> {
+    candidates
+        .iter()
+        .map(|t| match &t.subject_relation {
+            None => graph.tuple_index.contains(&(
+                t.object_type.clone(),
+                t.object_id.clone(),
+                t.relation.clone(),
+                t.subject_type.clone(),
+                t.subject_id.clone(),
+            )),
+            Some(subject_relation) => graph
+                .get_usersets(
+                    &Entity {
+                        entity_type: t.object_type.clone(),
+                        entity_id: t.object_id.clone(),
+                    },
+                    &t.relation,
+                )
+                .iter()
+                .any(|e| {
+                    e.subject_type == t.subject_type
+                        && e.subject_id == t.subject_id
+                        && e.subject_relation == *subject_relation
+                }),
+        })
+        .collect()
+}
+
+/// Distinct objects of `object_type` with at least one subject (direct,
+/// wildcard or userset) for `relation`, sorted by object id and paginated
+/// by `offset` / `limit`.
+pub fn objects_with_relation(
+    object_type: &str,
+    relation: &str,
+    graph: &ReBACGraph,
+    limit: usize,
+    offset: usize,
+) -> Vec<Entity> {
+    // reverse_adjacency holds one key per (object, relation) across direct
+    // and userset tuples, so each object appears at most once.
+    let mut ids: Vec<&String> = graph
+        .reverse_adjacency
+        .keys()
+        .filter(|(ot, _, rel)| ot == object_type && rel == relation)
+        .map(|(_, oid, _)| oid)
+        .collect();
+    ids.sort_unstable();
+    ids.into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|oid| Entity {
+            entity_type: object_type.to_string(),
+            entity_id: oid.clone(),
+        })
+        .collect()
+}
+
+/// Which end of a tuple [`related_objects`] starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationDirection {
+    /// Tuples where the given entity is the subject: `file:x parent
+    /// folder:y` leads from the file to the folder.
+    Outgoing,
+    /// Tuples where the given entity is the object: from the folder back
+    /// to every file naming it as `parent`.
+    Incoming,
+}
+
+/// Entities linked to `object` by `relation` in `direction`, distinct and
+/// sorted by type then id.
+///
+/// A read-only walk over the adjacency indexes for graph tooling: no
+/// namespace rewrites are applied, and an incoming userset tuple
+/// (`group:eng#member`) yields its group entity.
+pub fn related_objects(
+    object: &Entity,
+    relation: &str,
+    graph: &ReBACGraph,
+    direction: RelationDirection,
+) -> Vec<Entity> {
+    let key = (
+        object.entity_type.clone(),
+        object.entity_id.clone(),
+        relation.to_string(),
+    );
+    let index = match direction {
+        RelationDirection::Outgoing => &graph.adjacency_list,
+        RelationDirection::Incoming => &graph.reverse_adjacency,
+    };
+    let mut related: Vec<Entity> = index.get(&key).cloned().unwrap_or_default();
+    related.sort_unstable_by(|a, b| {
+        (&a.entity_type, &a.entity_id).cmp(&(&b.entity_type, &b.entity_id))
+    });
+    related.dedup();
+    related
+}
+
 /// Find all groups that a subject belongs to.
 pub fn find_subject_groups(subject: &Entity, graph: &ReBACGraph) -> Vec<Entity> {
     let mut groups = Vec::new();
@@ -860,5 +1784,252 @@ pub fn collect_candidate_objects_for_subjects(
     }
 }
 
+/// Objects a subject can access for one `(object_type, permission)` pair.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessSummaryEntry {
+    /// Accessible object ids, sorted, capped at the summary's `limit`.
+    pub object_ids: Vec<String>,
+    /// Number of accessible objects before capping.
+    pub total: usize,
+}
+
+/// Summarize what `subject` can do across several object types.
+///
+/// Returns `object_type → permission → entry` for every combination of
+/// `object_types` × `permissions`. Each entry is the candidate set from
+/// [`collect_candidate_objects_for_subject`] filtered by
+/// [`compute_permission`], with one `MemoCache` shared across all pairs
+/// so sub-relations resolved for one permission are reused by the next.
+pub fn access_summary(
+    subject: &Entity,
+    object_types: &[String],
+    permissions: &[String],
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    limit: usize,
+) -> AHashMap<String, AHashMap<String, AccessSummaryEntry>> {
+    let mut memo_cache: MemoCache = AHashMap::new();
+    let mut summary = AHashMap::with_capacity(object_types.len());
+
+    for object_type in object_types {
+        let by_permission: &mut AHashMap<String, AccessSummaryEntry> =
+            summary.entry(object_type.clone()).or_default();
+        for permission in permissions {
+            let mut candidates = AHashSet::new();
+            collect_candidate_objects_for_subject(
+                subject,
+                permission,
+                object_type,
+                graph,
+                namespaces,
+                &mut candidates,
+            );
+
+            let mut object_ids: Vec<String> = candidates
+                .into_iter()
+                .filter(|object| {
+                    compute_permission(
+                        subject,
+                        permission,
+                        object,
+                        graph,
+                        namespaces,
+                        &mut memo_cache,
+                        &mut AHashSet::new(),
+                        0,
+                    )
+                })
+                .map(|object| object.entity_id)
+                .collect();
+            object_ids.sort_unstable();
+            let total = object_ids.len();
+            object_ids.truncate(limit);
+            by_permission.insert(permission.clone(), AccessSummaryEntry { object_ids, total });
+        }
+    }
+    summary
+}
+
+/// Objects of `object_type` on which `subject` holds at least one of
+/// `permissions`, each annotated with the ones it holds (in the order
+/// given). Sorted by object id.
+///
+/// Candidates for every permission are pooled first, then each object is
+/// checked against all of `permissions` with one shared `MemoCache`, so
+/// relations common to several permissions are resolved once per object.
+pub fn list_objects_with_permissions(
+    subject: &Entity,
+    object_type: &str,
+    permissions: &[String],
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> Vec<(Entity, Vec<String>)> {
+    let mut candidates = AHashSet::new();
+    for permission in permissions {
+        collect_candidate_objects_for_subject(
+            subject,
+            permission,
+            object_type,
+            graph,
+            namespaces,
+            &mut candidates,
+        );
+    }
+
+    let mut memo_cache: MemoCache = AHashMap::new();
+    let mut annotated: Vec<(Entity, Vec<String>)> = candidates
+        .into_iter()
+        .filter_map(|object| {
+            let held: Vec<String> = permissions
+                .iter()
+                .filter(|permission| {
+                    compute_permission(
+                        subject,
+                        permission,
+                        &object,
+                        graph,
+                        namespaces,
+                        &mut memo_cache,
+                        &mut AHashSet::new(),
+                        0,
+                    )
+                })
+                .cloned()
+                .collect();
+            (!held.is_empty()).then_some((object, held))
+        })
+        .collect();
+    annotated.sort_unstable_by(|(a, _), (b, _)| a.entity_id.cmp(&b.entity_id));
+    annotated
+}
+
+/// Objects of one type partitioned by which of two subjects holds a
+/// permission on them; see [`diff_subject_access`]. Each list is sorted
+/// by object id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessDiff {
+    /// Accessible to `subject_a` only.
+    pub only_a: Vec<Entity>,
+    /// Accessible to `subject_b` only.
+    pub only_b: Vec<Entity>,
+    /// Accessible to both.
+    pub both: Vec<Entity>,
+}
+
+/// Compare what two subjects can do: the objects of `object_type` on
+/// which only `subject_a`, only `subject_b`, or both hold `permission`.
+///
+/// Candidates for both subjects are gathered in one pass over `graph`
+/// (sharing the userset and tupleToUserset indexes), then each candidate
+/// is checked for both subjects with one shared `MemoCache`, so
+/// sub-relations common to the two are resolved once. Objects neither
+/// subject can access appear nowhere.
+pub fn diff_subject_access(
+    subject_a: &Entity,
+    subject_b: &Entity,
+    object_type: &str,
+    permission: &str,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> AccessDiff {
+    let mut candidates = AHashSet::new();
+    collect_candidate_objects_for_subjects(
+        &[subject_a.clone(), subject_b.clone()],
+        permission,
+        object_type,
+        graph,
+        namespaces,
+        &mut candidates,
+    );
+
+    let mut memo_cache: MemoCache = AHashMap::new();
+    let mut allowed = |subject: &Entity, object: &Entity| {
+        compute_permission(
+            subject,
+            permission,
+            object,
+            graph,
+            namespaces,
+            &mut memo_cache,
+            &mut AHashSet::new(),
+            0,
+        )
+    };
+    let mut diff = AccessDiff::default();
+    for object in candidates {
+        match (allowed(subject_a, &object), allowed(subject_b, &object)) {
+            (true, true) => diff.both.push(object),
+            (true, false) => diff.only_a.push(object),
+            (false, true) => diff.only_b.push(object),
+            (false, false) => {}
+        }
+    }
+    for objects in [&mut diff.only_a, &mut diff.only_b, &mut diff.both] {
+        objects.sort_unstable_by(|a, b| a.entity_id.cmp(&b.entity_id));
+    }
+    diff
+}
+
+/// Whether `subject` has `permission` on at least one object of
+/// `object_type`.
+///
+/// Stops at the first accessible object instead of listing them all.
+/// Objects the subject (or `*:*`) holds a granting relation on directly
+/// are checked first, straight off the adjacency index; the userset and
+/// tupleToUserset candidate indexes are only built when none of those
+/// grant.
+pub fn has_permission_on_any(
+    subject: &Entity,
+    permission: &str,
+    object_type: &str,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> bool {
+    let mut memo_cache: MemoCache = AHashMap::new();
+    let mut grants = |object: &Entity| {
+        object.entity_type == object_type
+            && compute_permission(
+                subject,
+                permission,
+                object,
+                graph,
+                namespaces,
+                &mut memo_cache,
+                &mut AHashSet::new(),
+                0,
+            )
+    };
+
+    let relations = get_permission_relations(permission, object_type, namespaces);
+    for relation in &relations {
+        for (entity_type, entity_id) in [
+            (subject.entity_type.as_str(), subject.entity_id.as_str()),
+            ("*", "*"),
+        ] {
+            let adj_key = (
+                entity_type.to_string(),
+                entity_id.to_string(),
+                relation.clone(),
+            );
+            if let Some(objects) = graph.adjacency_list.get(&adj_key) {
+                if objects.iter().any(&mut grants) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    let mut candidates = AHashSet::new();
+    collect_candidate_objects_for_subject(
+        subject,
+        permission,
+        object_type,
+        graph,
+        namespaces,
+        &mut candidates,
+    );
+    candidates.iter().any(grants)
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,319 @@
+//! Portable snapshots of the interned ReBAC graph.
+//!
+//! Building an [`InternedGraph`] means hashing every tuple string into an
+//! interner, which each process of a worker pool would otherwise repeat.
+//! [`serialize_graph`] does that once and writes the symbol table plus the
+//! symbol-encoded tuples; [`load_graph`] restores the interner without
+//! re-deriving it and rebuilds the indexes from plain `u32` tuples.
+//!
+//! Layout (all integers little-endian):
+//! ```text
+//! magic: [u8; 4] = "RBGR"
+//! version: u32 = 1
+//! tuple_version: u64
+//! symbol_count: u32
+//! tuple_count: u32
+//! symbols: symbol_count × (len: u32, utf8 bytes)
+//! tuples: tuple_count × 6 × u32
+//!   subject_type, subject_id, subject_relation (symbol + 1, 0 = none),
+//!   relation, object_type, object_id
+//! crc32: u32 over everything before it
+//! ```
+//! The encoding depends only on the tuple order, so the same input always
+//! produces the same bytes.
+
+use std::fmt;
+
+use ahash::AHashMap;
+use string_interner::{DefaultStringInterner, Symbol};
+
+use super::graph::{compute_permission_interned, InternedGraph};
+use crate::types::*;
+
+/// Magic bytes identifying a graph snapshot.
+pub const MAGIC: [u8; 4] = *b"RBGR";
+
+/// Current snapshot format version.
+pub const VERSION: u32 = 1;
+
+/// magic + version + tuple_version + symbol_count + tuple_count.
+const HEADER_SIZE: usize = 24;
+
+/// Errors from [`load_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphLoadError {
+    /// The bytes do not start with [`MAGIC`].
+    InvalidMagic,
+    /// The snapshot was written by another format version.
+    VersionMismatch { expected: u32, found: u32 },
+    /// The trailing checksum does not match the contents.
+    ChecksumMismatch,
+    /// The snapshot is truncated or internally inconsistent.
+    Corrupt { reason: String },
+}
+
+impl fmt::Display for GraphLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphLoadError::InvalidMagic => write!(f, "Invalid magic bytes in graph snapshot"),
+            GraphLoadError::VersionMismatch { expected, found } => write!(
+                f,
+                "Graph snapshot version mismatch: expected {}, found {}",
+                expected, found
+            ),
+            GraphLoadError::ChecksumMismatch => write!(f, "Graph snapshot checksum mismatch"),
+            GraphLoadError::Corrupt { reason } => write!(f, "Corrupt graph snapshot: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for GraphLoadError {}
+
+/// An interned graph together with the interner that resolves its symbols.
+#[derive(Debug, Clone)]
+pub struct GraphHandle {
+    interner: DefaultStringInterner,
+    tuples: Vec<InternedTuple>,
+    graph: InternedGraph,
+    tuple_version: u64,
+}
+
+impl GraphHandle {
+    /// Intern `tuples` and build the graph in this process.
+    pub fn from_tuples(tuples: &[ReBACTuple], tuple_version: u64) -> Self {
+        let mut interner: DefaultStringInterner = DefaultStringInterner::new();
+        let tuples: Vec<InternedTuple> = tuples
+            .iter()
+            .map(|t| InternedTuple {
+                subject_type: interner.get_or_intern(&t.subject_type),
+                subject_id: interner.get_or_intern(&t.subject_id),
+                subject_relation: t
+                    .subject_relation
+                    .as_ref()
+                    .map(|r| interner.get_or_intern(r)),
+                relation: interner.get_or_intern(&t.relation),
+                object_type: interner.get_or_intern(&t.object_type),
+                object_id: interner.get_or_intern(&t.object_id),
+            })
+            .collect();
+        Self::from_interned(interner, tuples, tuple_version)
+    }
+
+    fn from_interned(
+        mut interner: DefaultStringInterner,
+        tuples: Vec<InternedTuple>,
+        tuple_version: u64,
+    ) -> Self {
+        let graph = InternedGraph::from_tuples(&tuples, &mut interner);
+        GraphHandle {
+            interner,
+            tuples,
+            graph,
+            tuple_version,
+        }
+    }
+
+    /// Version of the tuple set this graph was built from.
+    pub fn tuple_version(&self) -> u64 {
+        self.tuple_version
+    }
+
+    pub fn graph(&self) -> &InternedGraph {
+        &self.graph
+    }
+
+    pub fn interner(&self) -> &DefaultStringInterner {
+        &self.interner
+    }
+
+    /// Intern `namespaces` against this graph's symbol table.
+    pub fn intern_namespaces(
+        &mut self,
+        namespaces: &AHashMap<String, NamespaceConfig>,
+    ) -> AHashMap<Sym, InternedNamespaceConfig> {
+        namespaces
+            .iter()
+            .map(|(object_type, config)| {
+                let key = self.interner.get_or_intern(object_type);
+                let config = InternedNamespaceConfig::from_config(config, &mut self.interner);
+                (key, config)
+            })
+            .collect()
+    }
+
+    /// Check `subject` has `permission` on `object`. `namespaces` must come
+    /// from [`Self::intern_namespaces`] on this handle; `memo_cache` may be
+    /// shared across checks against the same handle.
+    pub fn check(
+        &mut self,
+        subject: &Entity,
+        permission: &str,
+        object: &Entity,
+        namespaces: &AHashMap<Sym, InternedNamespaceConfig>,
+        memo_cache: &mut InternedMemoCache,
+    ) -> bool {
+        let subject = self.intern_entity(subject);
+        let object = self.intern_entity(object);
+        let permission = self.interner.get_or_intern(permission);
+        compute_permission_interned(
+            subject,
+            permission,
+            object,
+            &self.graph,
+            namespaces,
+            memo_cache,
+            &mut InternedVisitedSet::new(),
+            0,
+        )
+    }
+
+    fn intern_entity(&mut self, entity: &Entity) -> InternedEntity {
+        InternedEntity {
+            entity_type: self.interner.get_or_intern(&entity.entity_type),
+            entity_id: self.interner.get_or_intern(&entity.entity_id),
+        }
+    }
+
+    /// Encode this graph in the snapshot format.
+    ///
+    /// Symbols interned after construction (by checks or namespaces) are
+    /// written too, so a loaded handle resolves them identically.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // The default backend hands out dense symbols in interning order,
+        // so re-interning the table in iteration order recreates them.
+        let symbols: Vec<&str> = self.interner.iter().map(|(_, s)| s).collect();
+        let symbol_bytes: usize = symbols.iter().map(|s| 4 + s.len()).sum();
+        let mut buf = Vec::with_capacity(HEADER_SIZE + symbol_bytes + self.tuples.len() * 24 + 4);
+        buf.extend_from_slice(&MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        buf.extend_from_slice(&self.tuple_version.to_le_bytes());
+        buf.extend_from_slice(&(symbols.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.tuples.len() as u32).to_le_bytes());
+        for symbol in symbols {
+            buf.extend_from_slice(&(symbol.len() as u32).to_le_bytes());
+            buf.extend_from_slice(symbol.as_bytes());
+        }
+        let index = |sym: Sym| sym.to_usize() as u32;
+        for t in &self.tuples {
+            let subject_relation = t.subject_relation.map_or(0, |r| index(r) + 1);
+            for value in [
+                index(t.subject_type),
+                index(t.subject_id),
+                subject_relation,
+                index(t.relation),
+                index(t.object_type),
+                index(t.object_id),
+            ] {
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        let crc = crc32fast::hash(&buf);
+        buf.extend_from_slice(&crc.to_le_bytes());
+        buf
+    }
+}
+
+/// Build the interned graph for `tuples` and encode it, symbol table
+/// included, for [`load_graph`] in another process.
+pub fn serialize_graph(tuples: &[ReBACTuple], tuple_version: u64) -> Vec<u8> {
+    GraphHandle::from_tuples(tuples, tuple_version).to_bytes()
+}
+
+/// Restore a graph written by [`serialize_graph`] or [`GraphHandle::to_bytes`].
+pub fn load_graph(bytes: &[u8]) -> Result<GraphHandle, GraphLoadError> {
+    if bytes.len() < HEADER_SIZE + 4 {
+        return Err(corrupt("shorter than header"));
+    }
+    if bytes[0..4] != MAGIC {
+        return Err(GraphLoadError::InvalidMagic);
+    }
+    let (body, crc) = bytes.split_at(bytes.len() - 4);
+    if crc32fast::hash(body) != u32::from_le_bytes(crc.try_into().unwrap()) {
+        return Err(GraphLoadError::ChecksumMismatch);
+    }
+
+    let mut reader = Reader { data: body, pos: 4 };
+    let version = reader.u32()?;
+    if version != VERSION {
+        return Err(GraphLoadError::VersionMismatch {
+            expected: VERSION,
+            found: version,
+        });
+    }
+    let tuple_version = reader.u64()?;
+    let symbol_count = reader.u32()? as usize;
+    let tuple_count = reader.u32()? as usize;
+
+    let mut interner: DefaultStringInterner = DefaultStringInterner::new();
+    for i in 0..symbol_count {
+        let len = reader.u32()? as usize;
+        let text = std::str::from_utf8(reader.take(len)?)
+            .map_err(|_| corrupt(format!("symbol {i} is not UTF-8")))?;
+        if interner.get_or_intern(text).to_usize() != i {
+            return Err(corrupt(format!("duplicate symbol {text:?}")));
+        }
+    }
+
+    let known = |index: u32| (index as usize) < symbol_count;
+    let mut tuples = Vec::with_capacity(tuple_count.min(body.len() / 24));
+    for i in 0..tuple_count {
+        let mut fields = [0u32; 6];
+        for field in &mut fields {
+            *field = reader.u32()?;
+        }
+        let [subject_type, subject_id, subject_relation, relation, object_type, object_id] = fields;
+        let relation_ok = subject_relation == 0 || known(subject_relation - 1);
+        if !relation_ok
+            || ![subject_type, subject_id, relation, object_type, object_id]
+                .into_iter()
+                .all(known)
+        {
+            return Err(corrupt(format!("tuple {i} references an unknown symbol")));
+        }
+        let sym = |index: u32| Sym::try_from_usize(index as usize).expect("checked above");
+        tuples.push(InternedTuple {
+            subject_type: sym(subject_type),
+            subject_id: sym(subject_id),
+            subject_relation: (subject_relation != 0).then(|| sym(subject_relation - 1)),
+            relation: sym(relation),
+            object_type: sym(object_type),
+            object_id: sym(object_id),
+        });
+    }
+    if reader.pos != body.len() {
+        return Err(corrupt("trailing bytes after tuples"));
+    }
+    Ok(GraphHandle::from_interned(interner, tuples, tuple_version))
+}
+
+fn corrupt(reason: impl Into<String>) -> GraphLoadError {
+    GraphLoadError::Corrupt {
+        reason: reason.into(),
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], GraphLoadError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| corrupt("unexpected end of data"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, GraphLoadError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, GraphLoadError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
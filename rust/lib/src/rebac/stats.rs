@@ -0,0 +1,229 @@
+//! Graph-shape diagnostics for slow permission checks.
+//!
+//! `graph_stats()` summarizes a tuple set (counts, types, fan-out) and runs
+//! a dry-run traversal over every relation node to find the longest
+//! relation-expansion chain — the quantity that `MAX_DEPTH` bounds during
+//! `compute_permission`.
+
+use ahash::{AHashMap, AHashSet};
+
+use super::{ReBACGraph, MAX_DEPTH};
+use crate::types::*;
+
+/// Chains at or beyond this length are flagged as approaching `MAX_DEPTH`.
+pub const DEPTH_WARNING_THRESHOLD: u32 = MAX_DEPTH * 3 / 4;
+
+/// Shape metrics for a ReBAC graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub tuple_count: usize,
+    /// Tuples whose subject is a userset (`group:eng#member`).
+    pub userset_tuple_count: usize,
+    /// Distinct object types, sorted.
+    pub object_types: Vec<String>,
+    /// Distinct subject types, sorted.
+    pub subject_types: Vec<String>,
+    /// Most tuples pointing at a single object (across all relations).
+    pub max_fanout: usize,
+    /// Object with `max_fanout` tuples (lowest type/id on ties).
+    pub max_fanout_object: Option<Entity>,
+    /// Longest relation-expansion chain, counted in nodes: a direct
+    /// relation is 1, one tupleToUserset hop on top of it is 2, etc.
+    pub max_expansion_depth: u32,
+    /// `max_expansion_depth >= DEPTH_WARNING_THRESHOLD`.
+    pub near_max_depth: bool,
+}
+
+/// Build the graph for `tuples` and report its shape.
+pub fn graph_stats(
+    tuples: &[ReBACTuple],
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> GraphStats {
+    let graph = ReBACGraph::from_tuples(tuples);
+
+    let mut object_types: AHashSet<&str> = AHashSet::new();
+    let mut subject_types: AHashSet<&str> = AHashSet::new();
+    let mut fanout: AHashMap<(&str, &str), usize> = AHashMap::new();
+    let mut userset_tuple_count = 0;
+    for tuple in tuples {
+        object_types.insert(&tuple.object_type);
+        subject_types.insert(&tuple.subject_type);
+        *fanout
+            .entry((&tuple.object_type, &tuple.object_id))
+            .or_default() += 1;
+        userset_tuple_count += tuple.subject_relation.is_some() as usize;
+    }
+
+    let max_fanout_entry = fanout
+        .iter()
+        .max_by(|(ka, ca), (kb, cb)| ca.cmp(cb).then_with(|| kb.cmp(ka)));
+    let max_fanout = max_fanout_entry.map(|(_, &c)| c).unwrap_or(0);
+    let max_fanout_object = max_fanout_entry.map(|(&(t, id), _)| Entity {
+        entity_type: t.to_string(),
+        entity_id: id.to_string(),
+    });
+
+    let max_expansion_depth = longest_expansion_chain(&graph, namespaces);
+
+    let mut object_types: Vec<String> = object_types.into_iter().map(String::from).collect();
+    let mut subject_types: Vec<String> = subject_types.into_iter().map(String::from).collect();
+    object_types.sort();
+    subject_types.sort();
+
+    GraphStats {
+        tuple_count: tuples.len(),
+        userset_tuple_count,
+        object_types,
+        subject_types,
+        max_fanout,
+        max_fanout_object,
+        max_expansion_depth,
+        near_max_depth: max_expansion_depth >= DEPTH_WARNING_THRESHOLD,
+    }
+}
+
+/// Expansion node: (relation or permission, object_type, object_id).
+type Node = (String, String, String);
+
+struct Frame {
+    node: Node,
+    children: Vec<Node>,
+    next: usize,
+    best: u32,
+}
+
+/// Longest chain over every relation/permission node in the graph.
+///
+/// Memoized DFS with an explicit stack, so chain length is bounded by
+/// memory rather than thread stack. Edges back onto the current path
+/// (cycles) contribute nothing — `compute_permission` rejects them too.
+fn longest_expansion_chain(
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> u32 {
+    let mut roots: Vec<Node> = graph
+        .reverse_adjacency
+        .keys()
+        .chain(graph.userset_index.keys())
+        .map(|(ot, oid, rel)| (rel.clone(), ot.clone(), oid.clone()))
+        .collect();
+    // Permissions can be checked on any entity in the graph, including
+    // ones that only appear as subjects (e.g. a file pointing at its parent).
+    let entities: AHashSet<(&String, &String)> = graph
+        .reverse_adjacency
+        .keys()
+        .chain(graph.adjacency_list.keys())
+        .map(|(t, id, _)| (t, id))
+        .collect();
+    for (ot, oid) in entities {
+        if let Some(ns) = namespaces.get(ot) {
+            for perm in ns.permissions.keys() {
+                roots.push((perm.clone(), ot.clone(), oid.clone()));
+            }
+        }
+    }
+
+    // `None` marks a node on the current DFS path.
+    let mut memo: AHashMap<Node, Option<u32>> = AHashMap::new();
+    let mut deepest = 0;
+
+    for root in roots {
+        if memo.contains_key(&root) {
+            continue;
+        }
+        memo.insert(root.clone(), None);
+        let mut stack = vec![Frame {
+            children: expansion_children(&root, graph, namespaces),
+            node: root,
+            next: 0,
+            best: 0,
+        }];
+
+        while let Some(frame) = stack.last_mut() {
+            if frame.next < frame.children.len() {
+                let child = frame.children[frame.next].clone();
+                frame.next += 1;
+                match memo.get(&child) {
+                    Some(Some(depth)) => frame.best = frame.best.max(*depth),
+                    Some(None) => {}
+                    None => {
+                        memo.insert(child.clone(), None);
+                        stack.push(Frame {
+                            children: expansion_children(&child, graph, namespaces),
+                            node: child,
+                            next: 0,
+                            best: 0,
+                        });
+                    }
+                }
+            } else {
+                let frame = stack.pop().expect("non-empty stack");
+                let depth = frame.best + 1;
+                deepest = deepest.max(depth);
+                memo.insert(frame.node, Some(depth));
+                if let Some(parent) = stack.last_mut() {
+                    parent.best = parent.best.max(depth);
+                }
+            }
+        }
+    }
+
+    deepest
+}
+
+/// Nodes `compute_permission` would recurse into from `node`.
+fn expansion_children(
+    node: &Node,
+    graph: &ReBACGraph,
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> Vec<Node> {
+    let (permission, object_type, object_id) = node;
+    let object = Entity {
+        entity_type: object_type.clone(),
+        entity_id: object_id.clone(),
+    };
+    let at = |rel: &String, target: &Entity| {
+        (
+            rel.clone(),
+            target.entity_type.clone(),
+            target.entity_id.clone(),
+        )
+    };
+    let mut children = Vec::new();
+
+    let namespace = namespaces.get(object_type);
+    if let Some(usersets) = namespace.and_then(|ns| ns.permissions.get(permission)) {
+        children.extend(usersets.iter().map(|u| at(u, &object)));
+        return children;
+    }
+
+    match namespace.and_then(|ns| ns.relations.get(permission)) {
+        Some(RelationConfig::Union { union }) => {
+            children.extend(union.iter().map(|rel| at(rel, &object)));
+            return children;
+        }
+        Some(RelationConfig::TupleToUserset { tuple_to_userset }) => {
+            let computed = &tuple_to_userset.computed_userset;
+            for target in graph.find_related_objects(&object, &tuple_to_userset.tupleset) {
+                children.push(at(computed, &target));
+            }
+            // Same Bug A guard as compute_permission (nexi-lab/nexus#3733).
+            if tuple_to_userset.tupleset != "parent" {
+                for target in graph.find_subjects_for_object(&object, &tuple_to_userset.tupleset) {
+                    children.push(at(computed, &target));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // Direct relation with usersets (also the TupleToUserset fallback).
+    for userset in graph.get_usersets(&object, permission) {
+        children.push((
+            userset.subject_relation.clone(),
+            userset.subject_type.clone(),
+            userset.subject_id.clone(),
+        ));
+    }
+    children
+}
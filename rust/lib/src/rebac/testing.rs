@@ -0,0 +1,293 @@
+//! Reproducible random ReBAC corpora for differential testing.
+//!
+//! [`generate_graph`] and [`generate_checks`] derive everything from a
+//! seed, so a failing case is replayed by its seed alone.
+//! [`brute_force_check`] is a deliberately naive reference evaluator
+//! (linear scans over the tuple list, no indexes, no memoization) to diff
+//! the optimized engines against.
+//!
+//! Generated graphs are acyclic: every object-to-object edge the
+//! evaluator can follow (`parent` forward, userset subjects) points to a
+//! higher-numbered object, so the reference evaluator always terminates.
+//! Each object hop costs the engine about two levels of depth, so keep
+//! `num_objects` to 20 or so to stay within [`MAX_DEPTH`](super::MAX_DEPTH).
+//!
+//! Behind the `testing` feature (always on for this crate's own tests).
+
+use ahash::AHashMap;
+use serde_json::json;
+
+use crate::types::*;
+
+/// Relations every generated type defines directly.
+const DIRECT_RELATIONS: [&str; 3] = ["owner", "editor", "member"];
+
+/// Names checked by [`generate_checks`]: permissions, rewritten relations
+/// and direct relations, so every evaluation path is exercised.
+const CHECKED_NAMES: [&str; 6] = ["read", "write", "viewer", "owner", "editor", "member"];
+
+/// SplitMix64 — tiny, seedable, and good enough for test corpora.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+fn object_type(index: usize, num_types: usize) -> String {
+    format!("t{}", index % num_types)
+}
+
+fn object(index: usize, num_types: usize) -> (String, String) {
+    (object_type(index, num_types), format!("o{index}"))
+}
+
+fn tuple(
+    subject: (String, String),
+    subject_relation: Option<&str>,
+    relation: &str,
+    object: (String, String),
+) -> ReBACTuple {
+    ReBACTuple {
+        subject_type: subject.0,
+        subject_id: subject.1,
+        subject_relation: subject_relation.map(str::to_string),
+        relation: relation.to_string(),
+        object_type: object.0,
+        object_id: object.1,
+    }
+}
+
+fn generate_namespace(rng: &mut Rng) -> NamespaceConfig {
+    let mut viewer: Vec<&str> = ["owner", "editor"]
+        .into_iter()
+        .filter(|_| rng.chance(70))
+        .collect();
+    viewer.push("parent_viewer");
+    let mut write: Vec<&str> = ["owner", "editor"]
+        .into_iter()
+        .filter(|_| rng.chance(60))
+        .collect();
+    if write.is_empty() {
+        write.push("owner");
+    }
+    serde_json::from_value(json!({
+        "relations": {
+            "owner": "direct",
+            "editor": "direct",
+            "member": "direct",
+            "parent": "direct",
+            "viewer": {"union": viewer},
+            "parent_viewer": {"tupleToUserset": {"tupleset": "parent", "computedUserset": "viewer"}},
+        },
+        "permissions": {"read": ["viewer"], "write": write},
+    }))
+    .expect("generated namespace config is valid")
+}
+
+/// Generate `num_tuples` tuples over `num_objects` objects spread across
+/// `num_types` types (`t0`, `t1`, ...), plus a namespace config per type.
+///
+/// Users are `user:u0..u7`. The mix covers direct grants, `*:*` wildcard
+/// grants, `parent` links (resolved via tupleToUserset) and userset
+/// grants (`tN:oK#member`). Types with no objects still get a config.
+pub fn generate_graph(
+    seed: u64,
+    num_types: usize,
+    num_objects: usize,
+    num_tuples: usize,
+) -> (Vec<ReBACTuple>, AHashMap<String, NamespaceConfig>) {
+    assert!(
+        num_types > 0 && num_objects > 0,
+        "need at least one type and object"
+    );
+    let mut rng = Rng(seed);
+    let namespaces = (0..num_types)
+        .map(|t| (format!("t{t}"), generate_namespace(&mut rng)))
+        .collect();
+
+    let user = |rng: &mut Rng| ("user".to_string(), format!("u{}", rng.below(8)));
+    let mut tuples = Vec::with_capacity(num_tuples);
+    while tuples.len() < num_tuples {
+        let target = rng.below(num_objects);
+        let obj = object(target, num_types);
+        // Edges between objects always point to a higher index.
+        let higher =
+            (target + 1 < num_objects).then(|| target + 1 + rng.below(num_objects - target - 1));
+        match (rng.below(10), higher) {
+            (0..=4, _) => {
+                let relation = rng.pick(&["owner", "editor", "member", "viewer"]);
+                tuples.push(tuple(user(&mut rng), None, relation, obj));
+            }
+            (5, _) => {
+                let relation = rng.pick(&["editor", "viewer"]);
+                let wildcard = ("*".to_string(), "*".to_string());
+                tuples.push(tuple(wildcard, None, relation, obj));
+            }
+            (6 | 7, Some(parent)) => {
+                tuples.push(tuple(obj, None, "parent", object(parent, num_types)));
+            }
+            (8 | 9, Some(group)) => {
+                let relation = rng.pick(&DIRECT_RELATIONS);
+                let group = object(group, num_types);
+                tuples.push(tuple(group, Some("member"), relation, obj));
+            }
+            // Highest object: no edge fits; retry with a fresh draw.
+            _ => {}
+        }
+    }
+    (tuples, namespaces)
+}
+
+/// Generate `n` `(subject, permission, object)` checks against the
+/// entities that appear in `tuples`.
+///
+/// Subjects are drawn from the users plus one never-granted outsider;
+/// objects from every object-side entity.
+pub fn generate_checks(
+    seed: u64,
+    tuples: &[ReBACTuple],
+    n: usize,
+) -> Vec<(Entity, String, Entity)> {
+    let mut rng = Rng(seed);
+    let entity = |entity_type: &str, entity_id: &str| Entity {
+        entity_type: entity_type.to_string(),
+        entity_id: entity_id.to_string(),
+    };
+    let mut subjects: Vec<Entity> = tuples
+        .iter()
+        .filter(|t| t.subject_type == "user")
+        .map(|t| entity(&t.subject_type, &t.subject_id))
+        .collect();
+    subjects.push(entity("user", "outsider"));
+    subjects.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+    subjects.dedup();
+    let mut objects: Vec<Entity> = tuples
+        .iter()
+        .map(|t| entity(&t.object_type, &t.object_id))
+        .collect();
+    objects.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+    objects.dedup();
+    if objects.is_empty() {
+        return Vec::new();
+    }
+
+    (0..n)
+        .map(|_| {
+            let subject = subjects[rng.below(subjects.len())].clone();
+            let permission = rng.pick(&CHECKED_NAMES).to_string();
+            let object = objects[rng.below(objects.len())].clone();
+            (subject, permission, object)
+        })
+        .collect()
+}
+
+fn is(entity: &Entity, entity_type: &str, entity_id: &str) -> bool {
+    entity.entity_type == entity_type && entity.entity_id == entity_id
+}
+
+/// Reference evaluator: does `subject` hold `permission` on `object`?
+///
+/// Implements the engine's documented rules straight from the tuple
+/// list: permissions shadow relations; unions; tupleToUserset forward
+/// (object as subject of the tupleset) and reverse (subjects of the
+/// tupleset on the object, skipped for `parent`), plus the direct-tuple
+/// fallback; direct tuples match the subject or `*:*`; userset tuples
+/// recurse into the userset. Exponential time — acyclic inputs only.
+pub fn brute_force_check(
+    subject: &Entity,
+    permission: &str,
+    object: &Entity,
+    tuples: &[ReBACTuple],
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> bool {
+    let recurse = |relation: &str, object: &Entity| {
+        brute_force_check(subject, relation, object, tuples, namespaces)
+    };
+    let Some(namespace) = namespaces.get(&object.entity_type) else {
+        return brute_force_direct(subject, permission, object, tuples, namespaces);
+    };
+    if let Some(usersets) = namespace.permissions.get(permission) {
+        return usersets.iter().any(|userset| recurse(userset, object));
+    }
+    match namespace.relations.get(permission) {
+        Some(RelationConfig::Union { union }) => union.iter().any(|rel| recurse(rel, object)),
+        Some(RelationConfig::TupleToUserset { tuple_to_userset }) => {
+            let ttu = tuple_to_userset;
+            let forward = tuples.iter().any(|t| {
+                t.relation == ttu.tupleset
+                    && is(object, &t.subject_type, &t.subject_id)
+                    && recurse(
+                        &ttu.computed_userset,
+                        &Entity {
+                            entity_type: t.object_type.clone(),
+                            entity_id: t.object_id.clone(),
+                        },
+                    )
+            });
+            let reverse = ttu.tupleset != "parent"
+                && tuples.iter().any(|t| {
+                    t.relation == ttu.tupleset
+                        && is(object, &t.object_type, &t.object_id)
+                        && recurse(
+                            &ttu.computed_userset,
+                            &Entity {
+                                entity_type: t.subject_type.clone(),
+                                entity_id: t.subject_id.clone(),
+                            },
+                        )
+                });
+            forward
+                || reverse
+                || brute_force_direct(subject, permission, object, tuples, namespaces)
+        }
+        _ => brute_force_direct(subject, permission, object, tuples, namespaces),
+    }
+}
+
+fn brute_force_direct(
+    subject: &Entity,
+    relation: &str,
+    object: &Entity,
+    tuples: &[ReBACTuple],
+    namespaces: &AHashMap<String, NamespaceConfig>,
+) -> bool {
+    tuples.iter().any(|t| {
+        if t.relation != relation || !is(object, &t.object_type, &t.object_id) {
+            return false;
+        }
+        match &t.subject_relation {
+            None => {
+                is(subject, &t.subject_type, &t.subject_id)
+                    || (t.subject_type == "*" && t.subject_id == "*")
+            }
+            Some(subject_relation) => brute_force_check(
+                subject,
+                subject_relation,
+                &Entity {
+                    entity_type: t.subject_type.clone(),
+                    entity_id: t.subject_id.clone(),
+                },
+                tuples,
+                namespaces,
+            ),
+        }
+    })
+}
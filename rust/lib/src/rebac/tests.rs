@@ -4,6 +4,7 @@ use ahash::{AHashMap, AHashSet};
 use string_interner::DefaultStringInterner;
 
 use crate::rebac::graph::*;
+use crate::rebac::stats::*;
 use crate::rebac::*;
 
 // ============================================================================
@@ -485,6 +486,127 @@ fn interned_permission_computation() {
     assert!(result);
 }
 
+// ============================================================================
+// check_any / check_all
+// ============================================================================
+
+fn memo_has(memo: &MemoCache, subject: &Entity, permission: &str, object: &Entity) -> bool {
+    memo.contains_key(&(
+        subject.entity_type.clone(),
+        subject.entity_id.clone(),
+        permission.to_string(),
+        object.entity_type.clone(),
+        object.entity_id.clone(),
+    ))
+}
+
+#[test]
+fn check_any_grants_on_second_permission_and_stops() {
+    let tuples = vec![tuple_direct("user", "alice", "viewer", "file", "doc")];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let namespaces = AHashMap::new();
+    let mut memo = MemoCache::new();
+    let (alice, doc) = (entity("user", "alice"), entity("file", "doc"));
+
+    let result = check_any(
+        &alice,
+        &["owner", "viewer", "editor"].map(String::from),
+        &doc,
+        &graph,
+        &namespaces,
+        &mut memo,
+    );
+
+    assert!(result);
+    assert!(memo_has(&memo, &alice, "owner", &doc));
+    assert!(memo_has(&memo, &alice, "viewer", &doc));
+    // Short-circuited: the third permission was never evaluated.
+    assert!(!memo_has(&memo, &alice, "editor", &doc));
+}
+
+#[test]
+fn check_any_denies_when_none_granted() {
+    let tuples = vec![tuple_direct("user", "alice", "viewer", "file", "doc")];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let namespaces = AHashMap::new();
+    let mut memo = MemoCache::new();
+
+    assert!(!check_any(
+        &entity("user", "bob"),
+        &["owner", "viewer"].map(String::from),
+        &entity("file", "doc"),
+        &graph,
+        &namespaces,
+        &mut memo,
+    ));
+    assert!(!check_any(
+        &entity("user", "alice"),
+        &[],
+        &entity("file", "doc"),
+        &graph,
+        &namespaces,
+        &mut memo,
+    ));
+}
+
+#[test]
+fn check_all_denies_on_first_denial_and_stops() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "viewer", "file", "doc"),
+        tuple_direct("user", "alice", "editor", "file", "doc"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let namespaces = AHashMap::new();
+    let mut memo = MemoCache::new();
+    let (alice, doc) = (entity("user", "alice"), entity("file", "doc"));
+
+    let result = check_all(
+        &alice,
+        &["owner", "viewer", "editor"].map(String::from),
+        &doc,
+        &graph,
+        &namespaces,
+        &mut memo,
+    );
+
+    assert!(!result);
+    assert!(memo_has(&memo, &alice, "owner", &doc));
+    assert!(!memo_has(&memo, &alice, "viewer", &doc));
+    assert!(!memo_has(&memo, &alice, "editor", &doc));
+}
+
+#[test]
+fn check_all_grants_when_every_permission_held() {
+    let ns = ns_config(
+        r#"{
+            "relations": {"owner": "direct", "viewer": {"union": ["owner"]}},
+            "permissions": {"read": ["viewer"], "write": ["owner"]}
+        }"#,
+    );
+    let mut namespaces = AHashMap::new();
+    namespaces.insert("file".to_string(), ns);
+    let tuples = vec![tuple_direct("user", "alice", "owner", "file", "doc")];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut memo = MemoCache::new();
+
+    assert!(check_all(
+        &entity("user", "alice"),
+        &["read", "write"].map(String::from),
+        &entity("file", "doc"),
+        &graph,
+        &namespaces,
+        &mut memo,
+    ));
+    assert!(check_all(
+        &entity("user", "bob"),
+        &[],
+        &entity("file", "doc"),
+        &graph,
+        &namespaces,
+        &mut memo,
+    ));
+}
+
 // ============================================================================
 // expand_permission / find_subject_groups / collect_candidate_objects
 // ============================================================================
@@ -515,6 +637,548 @@ fn expand_subjects_finds_direct() {
     assert_eq!(subjects.len(), 2);
 }
 
+#[test]
+fn precomputed_closure_agrees_with_check() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "member", "group", "eng"),
+        tuple_direct("user", "bob", "member", "group", "platform"),
+        tuple_userset("group", "platform", "member", "member", "group", "eng"),
+        tuple_userset("group", "eng", "member", "direct_viewer", "folder", "root"),
+        tuple_direct("user", "carol", "owner", "folder", "root"),
+        tuple_direct("user", "dave", "direct_viewer", "folder", "other"),
+        tuple_direct("folder", "root", "parent", "folder", "docs"),
+        tuple_direct("user", "erin", "direct_viewer", "folder", "docs"),
+        tuple_direct("*", "*", "direct_viewer", "folder", "public"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "folder".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "owner":"direct",
+                "parent":"direct",
+                "direct_viewer":"direct",
+                "viewer":{"union":["direct_viewer","parent_viewer"]},
+                "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+            },"permissions":{"read":["viewer","owner"]}}"#,
+        ),
+    );
+    namespaces.insert(
+        "group".to_string(),
+        ns_config(r#"{"relations":{"member":"direct"},"permissions":{}}"#),
+    );
+
+    let mut subjects: Vec<Entity> = tuples
+        .iter()
+        .flat_map(|t| {
+            [
+                entity(&t.subject_type, &t.subject_id),
+                entity(&t.object_type, &t.object_id),
+            ]
+        })
+        .collect();
+    subjects.push(entity("user", "stranger"));
+
+    for object in ["root", "docs", "other", "public"] {
+        let object = entity("folder", object);
+        let closure = precompute_closure(&object, "read", &graph, &namespaces);
+        for subject in &subjects {
+            let checked = compute_permission(
+                subject,
+                "read",
+                &object,
+                &graph,
+                &namespaces,
+                &mut MemoCache::new(),
+                &mut AHashSet::new(),
+                0,
+            );
+            assert_eq!(
+                closure.contains(subject),
+                checked,
+                "{subject:?} read {object:?}"
+            );
+        }
+    }
+
+    let root = precompute_closure(&entity("folder", "root"), "read", &graph, &namespaces);
+    assert_eq!(
+        root.to_sorted_vec(),
+        vec![
+            ("user".to_string(), "alice".to_string()),
+            ("user".to_string(), "bob".to_string()),
+            ("user".to_string(), "carol".to_string()),
+            ("user".to_string(), "erin".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn subject_matrix_matches_independent_checks_and_reuses_memo() {
+    let mut tuples = vec![
+        tuple_direct("user", "alice", "member", "group", "eng"),
+        tuple_userset("group", "eng", "member", "member", "group", "staff"),
+        tuple_userset("group", "staff", "member", "viewer", "folder", "root"),
+        tuple_direct("user", "alice", "editor", "folder", "drafts"),
+    ];
+    for i in 0..12 {
+        let parent = if i % 3 == 0 { "drafts" } else { "root" };
+        tuples.push(tuple_direct(
+            "file",
+            &format!("f{i}"),
+            "parent",
+            "folder",
+            parent,
+        ));
+    }
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "folder".to_string(),
+        ns_config(r#"{"relations":{"viewer":"direct","editor":"direct"},"permissions":{}}"#),
+    );
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "parent":"direct",
+                "viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}},
+                "editor":{"tupleToUserset":{"tupleset":"parent","computedUserset":"editor"}}
+            },"permissions":{"read":["viewer","editor"],"write":["editor"]}}"#,
+        ),
+    );
+    namespaces.insert(
+        "group".to_string(),
+        ns_config(r#"{"relations":{"member":"direct"},"permissions":{}}"#),
+    );
+
+    let alice = entity("user", "alice");
+    let permissions = vec!["read".to_string(), "write".to_string()];
+    let mut objects: Vec<Entity> = (0..12).map(|i| entity("file", &format!("f{i}"))).collect();
+    objects.push(entity("file", "orphan"));
+
+    let mut shared = MemoCache::new();
+    let grid = check_subject_matrix(
+        &alice,
+        &permissions,
+        &objects,
+        &graph,
+        &namespaces,
+        &mut shared,
+    );
+    assert_eq!(grid.len(), objects.len());
+
+    let mut independent_evaluations = 0;
+    for (object, row) in objects.iter().zip(&grid) {
+        for (permission, &cell) in permissions.iter().zip(row) {
+            let mut fresh = MemoCache::new();
+            let expected = compute_permission(
+                &alice,
+                permission,
+                object,
+                &graph,
+                &namespaces,
+                &mut fresh,
+                &mut AHashSet::new(),
+                0,
+            );
+            independent_evaluations += fresh.len();
+            assert_eq!(cell, expected, "{permission} on {object:?}");
+        }
+    }
+    // Files under root are readable through the group chain; files under
+    // drafts are readable and writable through alice's own editor tuple.
+    assert_eq!(grid[0], vec![true, true]);
+    assert_eq!(grid[1], vec![true, false]);
+    assert_eq!(grid[12], vec![false, false]);
+
+    // Folder and group sub-results are evaluated once for the whole
+    // matrix instead of once per cell.
+    assert!(
+        shared.len() < independent_evaluations,
+        "shared memo: {} entries, independent checks: {} evaluations",
+        shared.len(),
+        independent_evaluations
+    );
+}
+
+#[test]
+fn object_override_implicit_wildcard_grants_without_tuples() {
+    let graph = ReBACGraph::from_tuples(&[tuple_direct(
+        "user", "alice", "viewer", "folder", "/private",
+    )]);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "folder".to_string(),
+        ns_config(r#"{"relations":{"viewer":"direct"},"permissions":{"read":["viewer"]}}"#),
+    );
+    let mut overrides = ObjectOverrides::new();
+    overrides.insert(
+        ("folder".to_string(), "/public".to_string()),
+        serde_json::from_str(r#"{"implicit_subjects":{"viewer":["*:*"]}}"#).unwrap(),
+    );
+
+    let check = |subject: &Entity, permission: &str, object: &Entity| {
+        compute_permission_with_overrides(
+            subject,
+            permission,
+            object,
+            &graph,
+            &namespaces,
+            &overrides,
+            &mut MemoCache::new(),
+            &mut AHashSet::new(),
+            0,
+        )
+    };
+    let bob = entity("user", "bob");
+    let public = entity("folder", "/public");
+    let private = entity("folder", "/private");
+    assert!(!compute_permission(
+        &bob,
+        "read",
+        &public,
+        &graph,
+        &namespaces,
+        &mut MemoCache::new(),
+        &mut AHashSet::new(),
+        0,
+    ));
+    assert!(check(&bob, "read", &public));
+    // Other objects of the type keep the type-level behavior.
+    assert!(!check(&bob, "read", &private));
+    let alice = entity("user", "alice");
+    assert!(check(&alice, "read", &private));
+}
+
+#[test]
+fn object_override_replaces_named_permission_and_inherits_the_rest() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "owner", "file", "/doc"),
+        tuple_direct("user", "bob", "editor", "file", "/doc"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{"owner":"direct","editor":"direct"},
+                "permissions":{"write":["owner"],"read":["owner","editor"]}}"#,
+        ),
+    );
+    let mut overrides = ObjectOverrides::new();
+    overrides.insert(
+        ("file".to_string(), "/doc".to_string()),
+        serde_json::from_str(r#"{"permissions":{"write":["editor"]}}"#).unwrap(),
+    );
+
+    let check = |subject: &Entity, permission: &str, object: &Entity| {
+        compute_permission_with_overrides(
+            subject,
+            permission,
+            object,
+            &graph,
+            &namespaces,
+            &overrides,
+            &mut MemoCache::new(),
+            &mut AHashSet::new(),
+            0,
+        )
+    };
+    let alice = entity("user", "alice");
+    let bob = entity("user", "bob");
+    let doc = entity("file", "/doc");
+    // `write` is replaced, not unioned: the owner loses it, the editor gains it.
+    assert!(check(&bob, "write", &doc));
+    assert!(!check(&alice, "write", &doc));
+    // `read` is not overridden and still comes from the type config.
+    assert!(check(&alice, "read", &doc));
+}
+
+#[test]
+fn audit_objects_distinguishes_grant_sources() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "owner", "file", "/doc"),
+        tuple_userset("group", "eng", "member", "editor", "file", "/doc"),
+        tuple_direct("user", "bob", "member", "group", "eng"),
+        // Nested group: sre members are eng members.
+        tuple_userset("group", "sre", "member", "member", "group", "eng"),
+        tuple_direct("user", "carol", "member", "group", "sre"),
+        tuple_direct("file", "/doc", "parent", "file", "/folder"),
+        tuple_direct("user", "dave", "owner", "file", "/folder"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "owner":"direct","editor":"direct","parent":"direct",
+                "writer":{"union":["owner","editor","parent_writer"]},
+                "parent_writer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"writer"}}
+            },"permissions":{"write":["writer"]}}"#,
+        ),
+    );
+    namespaces.insert(
+        "group".to_string(),
+        ns_config(r#"{"relations":{"member":"direct"},"permissions":{}}"#),
+    );
+
+    let objects = [entity("file", "/doc"), entity("file", "/folder")];
+    let audit = audit_objects(&objects, "write", &graph, &namespaces);
+    assert_eq!(audit.len(), 2);
+
+    let grant = |subject: &str, source: GrantSource| AuditGrant {
+        subject: entity("user", subject),
+        source,
+    };
+    let eng = GrantSource::Group(entity("group", "eng"));
+    assert_eq!(
+        audit[0],
+        vec![
+            grant("alice", GrantSource::Direct),
+            grant("bob", eng.clone()),
+            grant("carol", eng),
+            grant("dave", GrantSource::Inherited(entity("file", "/folder"))),
+        ]
+    );
+    assert_eq!(audit[1], vec![grant("dave", GrantSource::Direct)]);
+}
+
+#[test]
+fn expand_with_sources_attributes_each_path() {
+    // bob is an editor through group:eng and also a direct viewer.
+    let tuples = vec![
+        tuple_direct("user", "bob", "viewer", "file", "/plan"),
+        tuple_userset("group", "eng", "member", "editor", "file", "/plan"),
+        tuple_direct("user", "bob", "member", "group", "eng"),
+        tuple_direct("user", "erin", "member", "group", "eng"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{"viewer":"direct","editor":"direct"},
+                "permissions":{"read":["viewer","editor"],"write":["editor"]}}"#,
+        ),
+    );
+
+    let plan = entity("file", "/plan");
+    let via_eng = GrantSource::Group(entity("group", "eng"));
+    let sources = |permission: &str| -> Vec<(String, GrantSource)> {
+        expand_permission_with_sources(permission, &plan, &graph, &namespaces)
+            .into_iter()
+            .map(|grant| (grant.subject.entity_id, grant.source))
+            .collect()
+    };
+
+    assert_eq!(
+        sources("read"),
+        vec![
+            ("bob".to_string(), GrantSource::Direct),
+            ("bob".to_string(), via_eng.clone()),
+            ("erin".to_string(), via_eng.clone()),
+        ]
+    );
+    // Write only comes through the group, so bob is attributed to it.
+    assert_eq!(
+        sources("write"),
+        vec![
+            ("bob".to_string(), via_eng.clone()),
+            ("erin".to_string(), via_eng),
+        ]
+    );
+
+    // The flat expansion sees the same subjects, minus the attribution.
+    let mut flat = AHashSet::new();
+    expand_permission(
+        "write",
+        &plan,
+        &graph,
+        &namespaces,
+        &mut flat,
+        &mut AHashSet::new(),
+        0,
+    );
+    assert!(flat.contains(&("group#member".to_string(), "eng".to_string())));
+}
+
+fn parent_chain(len: usize) -> (ReBACGraph, AHashMap<String, NamespaceConfig>) {
+    // file:d0 --parent--> file:d1 --parent--> ... ; user:u{i} views d{i}
+    let mut tuples = Vec::new();
+    for i in 0..len {
+        let doc = format!("d{i}");
+        tuples.push(tuple_direct(
+            "user",
+            &format!("u{i}"),
+            "viewer",
+            "file",
+            &doc,
+        ));
+        if i + 1 < len {
+            tuples.push(tuple_direct(
+                "file",
+                &doc,
+                "parent",
+                "file",
+                &format!("d{}", i + 1),
+            ));
+        }
+    }
+    let config_json = r#"{"relations":{
+        "parent":"direct",
+        "viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+    },"permissions":{"read":["viewer"]}}"#;
+    let mut namespaces = AHashMap::new();
+    namespaces.insert("file".to_string(), ns_config(config_json));
+    (ReBACGraph::from_tuples(&tuples), namespaces)
+}
+
+#[test]
+fn check_with_obligations_follows_granting_path() {
+    let tuples = vec![
+        tuple_direct("file", "doc", "parent", "folder", "docs"),
+        tuple_direct("user", "alice", "viewer", "folder", "docs"),
+        tuple_direct("user", "bob", "owner", "file", "doc"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "parent":"direct",
+                "owner":"direct",
+                "viewer":{"union":["owner","parent_viewer"]},
+                "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+            },"permissions":{"read":["viewer"]},
+            "obligations":{"read":["audit"],"parent_viewer":["inherited"]}}"#,
+        ),
+    );
+    namespaces.insert(
+        "folder".to_string(),
+        ns_config(
+            r#"{"relations":{"viewer":"direct"},"permissions":{},
+            "obligations":{"viewer":["watermark","audit"]}}"#,
+        ),
+    );
+    let doc = entity("file", "doc");
+
+    let decision =
+        check_with_obligations(&entity("user", "alice"), "read", &doc, &graph, &namespaces);
+    assert!(decision.allowed);
+    assert_eq!(
+        decision.obligations,
+        vec!["audit", "inherited", "watermark"]
+    );
+
+    // Bob's grant comes from owning the file; the inherited path's
+    // obligations don't apply.
+    let decision =
+        check_with_obligations(&entity("user", "bob"), "read", &doc, &graph, &namespaces);
+    assert!(decision.allowed);
+    assert_eq!(decision.obligations, vec!["audit"]);
+
+    assert_eq!(
+        check_with_obligations(
+            &entity("user", "mallory"),
+            "read",
+            &doc,
+            &graph,
+            &namespaces
+        ),
+        ObligatedDecision::default()
+    );
+}
+
+#[test]
+fn check_with_reason_classifies_denials() {
+    let (graph, namespaces) = parent_chain(60);
+    let d0 = entity("file", "d0");
+    assert_eq!(
+        check_with_reason(&entity("user", "u3"), "read", &d0, &graph, &namespaces),
+        Ok(())
+    );
+    // u59's grant sits 59 parent hops up, past MAX_DEPTH.
+    assert_eq!(
+        check_with_reason(&entity("user", "u59"), "read", &d0, &graph, &namespaces),
+        Err(DenialReason::DepthExceeded)
+    );
+    // Short chain, unknown user: every branch completes.
+    let (graph, namespaces) = parent_chain(3);
+    assert_eq!(
+        check_with_reason(&entity("user", "mallory"), "read", &d0, &graph, &namespaces),
+        Err(DenialReason::NoMatchingRelation)
+    );
+
+    // group:a and group:b are each other's members.
+    let tuples = vec![
+        tuple_userset("group", "b", "member", "member", "group", "a"),
+        tuple_userset("group", "a", "member", "member", "group", "b"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "group".to_string(),
+        ns_config(r#"{"relations":{"member":"direct"},"permissions":{}}"#),
+    );
+    assert_eq!(
+        check_with_reason(
+            &entity("user", "alice"),
+            "member",
+            &entity("group", "a"),
+            &graph,
+            &namespaces
+        ),
+        Err(DenialReason::CycleDetected)
+    );
+}
+
+#[test]
+fn bounded_expand_truncates_on_tiny_budget() {
+    let (graph, namespaces) = parent_chain(10);
+
+    let partial = expand_permission_bounded("read", &entity("file", "d0"), &graph, &namespaces, 3);
+    assert!(partial.truncated);
+    assert_eq!(partial.nodes_visited, 3);
+    assert!(!partial.subjects.is_empty());
+    assert!(partial.subjects.len() < 10);
+}
+
+#[test]
+fn bounded_expand_with_ample_budget_matches_full_expand() {
+    let (graph, namespaces) = parent_chain(10);
+    let mut full = AHashSet::new();
+    expand_permission(
+        "read",
+        &entity("file", "d0"),
+        &graph,
+        &namespaces,
+        &mut full,
+        &mut AHashSet::new(),
+        0,
+    );
+    assert_eq!(full.len(), 10);
+
+    let bounded =
+        expand_permission_bounded("read", &entity("file", "d0"), &graph, &namespaces, 1000);
+    assert!(!bounded.truncated);
+    assert_eq!(bounded.subjects, full);
+
+    // A budget of exactly the nodes needed is not a truncation.
+    let exact = expand_permission_bounded(
+        "read",
+        &entity("file", "d0"),
+        &graph,
+        &namespaces,
+        bounded.nodes_visited,
+    );
+    assert!(!exact.truncated);
+    assert_eq!(exact.subjects, full);
+}
+
 #[test]
 fn find_groups_for_subject() {
     let tuples = vec![
@@ -750,6 +1414,264 @@ fn collect_candidates_include_transitive_userset_subject_chain() {
     assert!(candidates.contains(&entity("file", "/doc")));
 }
 
+#[test]
+fn access_summary_lists_accessible_objects_per_pair() {
+    // alice owns /a, views /b through group:eng, and views /a/x via parent.
+    // folder:f1 is shared with everyone.
+    let tuples = vec![
+        tuple_direct("user", "alice", "owner", "file", "/a"),
+        tuple_direct("user", "alice", "member", "group", "eng"),
+        tuple_userset("group", "eng", "member", "direct_viewer", "file", "/b"),
+        tuple_direct("file", "/a/x", "parent", "file", "/a"),
+        tuple_direct("user", "bob", "owner", "file", "/c"),
+        tuple_direct("*", "*", "viewer", "folder", "f1"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "owner":"direct","direct_viewer":"direct","parent":"direct",
+                "viewer":{"union":["owner","direct_viewer","parent_viewer"]},
+                "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+            },"permissions":{"read":["viewer"],"write":["owner"]}}"#,
+        ),
+    );
+    namespaces.insert(
+        "folder".to_string(),
+        ns_config(r#"{"relations":{"viewer":"direct"},"permissions":{"read":["viewer"]}}"#),
+    );
+
+    let alice = entity("user", "alice");
+    let object_types = vec!["file".to_string(), "folder".to_string()];
+    let permissions = vec!["read".to_string(), "write".to_string()];
+    let summary = access_summary(&alice, &object_types, &permissions, &graph, &namespaces, 10);
+
+    let expected: [(&str, &str, &[&str]); 4] = [
+        ("file", "read", &["/a", "/a/x", "/b"]),
+        ("file", "write", &["/a"]),
+        ("folder", "read", &["f1"]),
+        ("folder", "write", &[]),
+    ];
+    for (object_type, permission, ids) in expected {
+        let entry = &summary[object_type][permission];
+        assert_eq!(entry.object_ids, ids, "{object_type}#{permission}");
+        assert_eq!(entry.total, ids.len(), "{object_type}#{permission}");
+    }
+    assert_eq!(summary.len(), 2);
+    assert!(summary
+        .values()
+        .all(|by_permission| by_permission.len() == 2));
+
+    // The cap trims ids but keeps the full count.
+    let capped = access_summary(&alice, &object_types, &permissions, &graph, &namespaces, 1);
+    assert_eq!(capped["file"]["read"].object_ids, ["/a"]);
+    assert_eq!(capped["file"]["read"].total, 3);
+}
+
+#[test]
+fn list_objects_with_permissions_matches_individual_checks() {
+    // alice owns /a, edits /b, views /c through group:eng and /a/x via parent.
+    let tuples = vec![
+        tuple_direct("user", "alice", "owner", "file", "/a"),
+        tuple_direct("user", "alice", "editor", "file", "/b"),
+        tuple_direct("user", "alice", "member", "group", "eng"),
+        tuple_userset("group", "eng", "member", "direct_viewer", "file", "/c"),
+        tuple_direct("file", "/a/x", "parent", "file", "/a"),
+        tuple_direct("user", "bob", "owner", "file", "/d"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "owner":"direct","editor":"direct","direct_viewer":"direct","parent":"direct",
+                "viewer":{"union":["owner","editor","direct_viewer","parent_viewer"]},
+                "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+            },"permissions":{"read":["viewer"],"write":["owner","editor"],"delete":["owner"]}}"#,
+        ),
+    );
+
+    let alice = entity("user", "alice");
+    let permissions = vec![
+        "write".to_string(),
+        "read".to_string(),
+        "delete".to_string(),
+    ];
+    let listed = list_objects_with_permissions(&alice, "file", &permissions, &graph, &namespaces);
+    let summary: Vec<(&str, Vec<&str>)> = listed
+        .iter()
+        .map(|(object, held)| {
+            (
+                object.entity_id.as_str(),
+                held.iter().map(String::as_str).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("/a", vec!["write", "read", "delete"]),
+            ("/a/x", vec!["read"]),
+            ("/b", vec!["write", "read"]),
+            ("/c", vec!["read"]),
+        ]
+    );
+
+    for (object, held) in &listed {
+        for permission in &permissions {
+            let allowed = compute_permission(
+                &alice,
+                permission,
+                object,
+                &graph,
+                &namespaces,
+                &mut MemoCache::new(),
+                &mut AHashSet::new(),
+                0,
+            );
+            assert_eq!(
+                held.contains(permission),
+                allowed,
+                "{object:?} {permission}"
+            );
+        }
+    }
+}
+
+#[test]
+fn diff_subject_access_partitions_objects_by_holder() {
+    // alice and bob share /shared (directly) and /team (via group:eng);
+    // alice alone owns /a and inherits /a/x; bob alone edits /b.
+    let tuples = vec![
+        tuple_direct("user", "alice", "owner", "file", "/shared"),
+        tuple_direct("user", "bob", "editor", "file", "/shared"),
+        tuple_direct("user", "alice", "member", "group", "eng"),
+        tuple_direct("user", "bob", "member", "group", "eng"),
+        tuple_userset("group", "eng", "member", "direct_viewer", "file", "/team"),
+        tuple_direct("user", "alice", "owner", "file", "/a"),
+        tuple_direct("file", "/a/x", "parent", "file", "/a"),
+        tuple_direct("user", "bob", "editor", "file", "/b"),
+        tuple_direct("user", "carol", "owner", "file", "/c"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "owner":"direct","editor":"direct","direct_viewer":"direct","parent":"direct",
+                "viewer":{"union":["owner","editor","direct_viewer","parent_viewer"]},
+                "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+            },"permissions":{"read":["viewer"],"write":["owner","editor"]}}"#,
+        ),
+    );
+    let alice = entity("user", "alice");
+    let bob = entity("user", "bob");
+    let ids = |objects: &[Entity]| -> Vec<String> {
+        objects.iter().map(|o| o.entity_id.clone()).collect()
+    };
+
+    let diff = diff_subject_access(&alice, &bob, "file", "read", &graph, &namespaces);
+    assert_eq!(ids(&diff.only_a), ["/a", "/a/x"]);
+    assert_eq!(ids(&diff.only_b), ["/b"]);
+    assert_eq!(ids(&diff.both), ["/shared", "/team"]);
+
+    // The partitions are disjoint and agree with per-object checks.
+    let mut seen = AHashSet::new();
+    for (objects, a_expected, b_expected) in [
+        (&diff.only_a, true, false),
+        (&diff.only_b, false, true),
+        (&diff.both, true, true),
+    ] {
+        for object in objects {
+            assert!(seen.insert(object.clone()), "{object:?} listed twice");
+            for (subject, expected) in [(&alice, a_expected), (&bob, b_expected)] {
+                let allowed = compute_permission(
+                    subject,
+                    "read",
+                    object,
+                    &graph,
+                    &namespaces,
+                    &mut MemoCache::new(),
+                    &mut AHashSet::new(),
+                    0,
+                );
+                assert_eq!(allowed, expected, "{subject:?} on {object:?}");
+            }
+        }
+    }
+    assert!(!seen.contains(&entity("file", "/c")));
+
+    // Swapping the subjects swaps the one-sided partitions.
+    let swapped = diff_subject_access(&bob, &alice, "file", "read", &graph, &namespaces);
+    assert_eq!(swapped.only_a, diff.only_b);
+    assert_eq!(swapped.only_b, diff.only_a);
+    assert_eq!(swapped.both, diff.both);
+
+    // write: /shared is held by both, /team by neither.
+    let write = diff_subject_access(&alice, &bob, "file", "write", &graph, &namespaces);
+    assert_eq!(ids(&write.only_a), ["/a"]);
+    assert_eq!(ids(&write.only_b), ["/b"]);
+    assert_eq!(ids(&write.both), ["/shared"]);
+}
+
+#[test]
+fn has_permission_on_any_finds_direct_and_indirect_access() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "admin", "org", "acme"),
+        tuple_direct("user", "bob", "member", "group", "ops"),
+        tuple_userset("group", "ops", "member", "direct_viewer", "file", "/ops"),
+        tuple_direct("file", "/ops/log", "parent", "file", "/ops"),
+        tuple_direct("user", "carol", "viewer", "folder", "f1"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        "org".to_string(),
+        ns_config(r#"{"relations":{"admin":"direct"},"permissions":{"manage":["admin"]}}"#),
+    );
+    namespaces.insert(
+        "file".to_string(),
+        ns_config(
+            r#"{"relations":{
+                "parent":"direct","direct_viewer":"direct",
+                "viewer":{"union":["direct_viewer"]},
+                "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"read"}}
+            },"permissions":{"read":["viewer","parent_viewer"]}}"#,
+        ),
+    );
+
+    let any = |subject: &str, permission: &str, object_type: &str| {
+        has_permission_on_any(
+            &entity("user", subject),
+            permission,
+            object_type,
+            &graph,
+            &namespaces,
+        )
+    };
+    assert!(any("alice", "manage", "org"));
+    assert!(!any("bob", "manage", "org"));
+    // Userset grant, with no direct edge from bob to any file.
+    assert!(any("bob", "read", "file"));
+    assert!(!any("alice", "read", "file"));
+    // carol's grant is on a different object type.
+    assert!(!any("carol", "read", "file"));
+
+    // A public grant is access for everyone.
+    let public = ReBACGraph::from_tuples(&[tuple_direct("*", "*", "admin", "org", "demo")]);
+    assert!(has_permission_on_any(
+        &entity("user", "dave"),
+        "manage",
+        "org",
+        &public,
+        &namespaces,
+    ));
+}
+
 // ============================================================================
 // Cross-implementation parity: string-keyed vs interned must agree
 // ============================================================================
@@ -761,12 +1683,20 @@ fn assert_parity(
     namespaces_json: &[(&str, &str)],
     checks: &[(&str, &str, &str, &str, &str, bool)],
 ) {
-    // --- String-keyed setup ---
-    let graph = ReBACGraph::from_tuples(tuples);
     let mut namespaces: AHashMap<String, NamespaceConfig> = AHashMap::new();
     for (name, json) in namespaces_json {
         namespaces.insert(name.to_string(), ns_config(json));
     }
+    assert_parity_with_configs(tuples, &namespaces, checks);
+}
+
+fn assert_parity_with_configs(
+    tuples: &[ReBACTuple],
+    namespaces: &AHashMap<String, NamespaceConfig>,
+    checks: &[(&str, &str, &str, &str, &str, bool)],
+) {
+    // --- String-keyed setup ---
+    let graph = ReBACGraph::from_tuples(tuples);
 
     // --- Interned setup ---
     let mut interner = DefaultStringInterner::new();
@@ -786,10 +1716,9 @@ fn assert_parity(
         .collect();
     let interned_graph = InternedGraph::from_tuples(&interned_tuples, &mut interner);
     let mut interned_ns: AHashMap<Sym, InternedNamespaceConfig> = AHashMap::new();
-    for (name, json) in namespaces_json {
-        let config: NamespaceConfig = serde_json::from_str(json).unwrap();
-        let interned_config = InternedNamespaceConfig::from_config(&config, &mut interner);
-        interned_ns.insert(interner.get_or_intern(*name), interned_config);
+    for (name, config) in namespaces {
+        let interned_config = InternedNamespaceConfig::from_config(config, &mut interner);
+        interned_ns.insert(interner.get_or_intern(name), interned_config);
     }
 
     // --- Run checks against both ---
@@ -805,7 +1734,7 @@ fn assert_parity(
             permission,
             &object,
             &graph,
-            &namespaces,
+            namespaces,
             &mut memo,
             &mut visited,
             0,
@@ -1026,3 +1955,433 @@ fn parity_shared_visited_cycle_path() {
         ],
     );
 }
+
+#[test]
+fn fuzz_engines_agree_with_brute_force() {
+    use crate::rebac::testing::*;
+
+    let mut allowed = 0;
+    for seed in 0..40 {
+        let (tuples, namespaces) = generate_graph(seed, 3, 12, 40);
+        let generated = generate_checks(seed, &tuples, 60);
+        let checks: Vec<_> = generated
+            .iter()
+            .map(|(subject, permission, object)| {
+                let expected = brute_force_check(subject, permission, object, &tuples, &namespaces);
+                (
+                    subject.entity_type.as_str(),
+                    subject.entity_id.as_str(),
+                    permission.as_str(),
+                    object.entity_type.as_str(),
+                    object.entity_id.as_str(),
+                    expected,
+                )
+            })
+            .collect();
+        allowed += checks.iter().filter(|c| c.5).count();
+        assert_parity_with_configs(&tuples, &namespaces, &checks);
+    }
+    // The corpus must exercise grants, not just denials.
+    assert!(allowed > 100, "only {allowed} of 2400 checks allowed");
+
+    // Same seed, same corpus.
+    let (a, _) = generate_graph(7, 3, 12, 40);
+    let (b, _) = generate_graph(7, 3, 12, 40);
+    assert_eq!(format!("{a:?}"), format!("{b:?}"));
+    assert_eq!(generate_checks(7, &a, 20), generate_checks(7, &b, 20));
+}
+
+// ============================================================================
+// graph_stats
+// ============================================================================
+
+fn folder_namespaces() -> AHashMap<String, NamespaceConfig> {
+    let json = r#"{
+        "relations": {
+            "parent": "direct",
+            "owner": "direct",
+            "viewer": {"tupleToUserset": {"tupleset": "parent", "computedUserset": "viewer"}}
+        },
+        "permissions": {"read": ["viewer", "owner"]}
+    }"#;
+    let mut namespaces = AHashMap::new();
+    namespaces.insert("file".to_string(), ns_config(json));
+    namespaces.insert("folder".to_string(), ns_config(json));
+    namespaces
+}
+
+#[test]
+fn graph_stats_counts_and_tuple_to_userset_chain() {
+    // file:a -parent-> folder:f1 -parent-> folder:f2 -parent-> folder:root
+    let tuples = vec![
+        tuple_direct("file", "a", "parent", "folder", "f1"),
+        tuple_direct("folder", "f1", "parent", "folder", "f2"),
+        tuple_direct("folder", "f2", "parent", "folder", "root"),
+        tuple_direct("user", "alice", "owner", "folder", "root"),
+        tuple_direct("user", "bob", "viewer", "folder", "root"),
+        tuple_userset("group", "eng", "member", "viewer", "folder", "root"),
+        tuple_direct("user", "alice", "member", "group", "eng"),
+    ];
+
+    let stats = graph_stats(&tuples, &folder_namespaces());
+
+    assert_eq!(stats.tuple_count, 7);
+    assert_eq!(stats.userset_tuple_count, 1);
+    assert_eq!(stats.object_types, vec!["folder", "group"]);
+    assert_eq!(stats.subject_types, vec!["file", "folder", "group", "user"]);
+    // folder:root has parent(from f2) + owner + viewer + userset viewer.
+    assert_eq!(stats.max_fanout, 4);
+    assert_eq!(stats.max_fanout_object, Some(entity("folder", "root")));
+    // read(file:a) → viewer(file:a) → viewer(f1) → viewer(f2)
+    //   → viewer(root) → member(group:eng) = 6 nodes.
+    assert_eq!(stats.max_expansion_depth, 6);
+    assert!(!stats.near_max_depth);
+}
+
+#[test]
+fn graph_stats_flags_chains_near_max_depth() {
+    let depth = DEPTH_WARNING_THRESHOLD as usize;
+    let tuples: Vec<ReBACTuple> = (0..depth)
+        .map(|i| {
+            tuple_direct(
+                "folder",
+                &i.to_string(),
+                "parent",
+                "folder",
+                &(i + 1).to_string(),
+            )
+        })
+        .collect();
+
+    let stats = graph_stats(&tuples, &folder_namespaces());
+
+    assert!(stats.max_expansion_depth >= DEPTH_WARNING_THRESHOLD);
+    assert!(stats.near_max_depth);
+}
+
+#[test]
+fn graph_stats_survives_cycles_and_empty_input() {
+    let tuples = vec![
+        tuple_userset("group", "a", "member", "member", "group", "b"),
+        tuple_userset("group", "b", "member", "member", "group", "a"),
+    ];
+    let stats = graph_stats(&tuples, &AHashMap::new());
+    assert_eq!(stats.max_expansion_depth, 2);
+
+    let empty = graph_stats(&[], &AHashMap::new());
+    assert_eq!(empty.tuple_count, 0);
+    assert_eq!(empty.max_fanout, 0);
+    assert_eq!(empty.max_fanout_object, None);
+    assert_eq!(empty.max_expansion_depth, 0);
+}
+
+// ============================================================================
+// direct_relations
+// ============================================================================
+
+#[test]
+fn direct_relations_lists_every_direct_tuple() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "editor", "file", "x"),
+        tuple_direct("user", "alice", "commenter", "file", "x"),
+        tuple_direct("user", "alice", "owner", "file", "y"),
+        tuple_direct("user", "bob", "viewer", "file", "x"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+
+    assert_eq!(
+        direct_relations(&entity("user", "alice"), &entity("file", "x"), &graph),
+        vec!["commenter", "editor"]
+    );
+}
+
+#[test]
+fn direct_relations_include_wildcard_and_userset_grants() {
+    let tuples = vec![
+        tuple_direct("*", "*", "viewer", "file", "x"),
+        tuple_direct("user", "alice", "viewer", "file", "x"),
+        tuple_userset("group", "eng", "member", "editor", "file", "x"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+
+    assert_eq!(
+        direct_relations(&entity("user", "alice"), &entity("file", "x"), &graph),
+        vec!["viewer"]
+    );
+    assert_eq!(
+        direct_relations(&entity("group", "eng"), &entity("file", "x"), &graph),
+        vec!["editor", "viewer"]
+    );
+}
+
+#[test]
+fn direct_relations_empty_when_pair_unrelated() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "editor", "file", "x"),
+        tuple_direct("user", "bob", "member", "group", "eng"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+
+    assert!(direct_relations(&entity("user", "bob"), &entity("file", "x"), &graph).is_empty());
+    assert!(direct_relations(&entity("user", "alice"), &entity("file", "z"), &graph).is_empty());
+}
+
+// ============================================================================
+// objects_with_relation
+// ============================================================================
+
+#[test]
+fn objects_with_relation_lists_each_object_once() {
+    let tuples = vec![
+        tuple_direct("user", "alice", "editor", "file", "b"),
+        tuple_direct("user", "bob", "editor", "file", "b"),
+        tuple_userset("group", "eng", "member", "editor", "file", "a"),
+        tuple_direct("*", "*", "editor", "file", "c"),
+        tuple_direct("user", "alice", "viewer", "file", "d"),
+        tuple_direct("user", "alice", "editor", "folder", "e"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+
+    let ids: Vec<String> = objects_with_relation("file", "editor", &graph, 100, 0)
+        .into_iter()
+        .map(|e| e.entity_id)
+        .collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+    assert!(objects_with_relation("file", "owner", &graph, 100, 0).is_empty());
+}
+
+#[test]
+fn objects_with_relation_pages_are_stable_and_disjoint() {
+    let tuples: Vec<ReBACTuple> = (0..25)
+        .rev()
+        .flat_map(|i| {
+            let id = format!("f{i:02}");
+            [
+                tuple_direct("user", "alice", "editor", "file", &id),
+                tuple_direct("user", "bob", "editor", "file", &id),
+            ]
+        })
+        .collect();
+    let graph = ReBACGraph::from_tuples(&tuples);
+
+    let mut paged = Vec::new();
+    for offset in (0..30).step_by(10) {
+        let page = objects_with_relation("file", "editor", &graph, 10, offset);
+        assert_eq!(
+            page,
+            objects_with_relation("file", "editor", &graph, 10, offset)
+        );
+        paged.extend(page.into_iter().map(|e| e.entity_id));
+    }
+    let expected: Vec<String> = (0..25).map(|i| format!("f{i:02}")).collect();
+    assert_eq!(paged, expected);
+}
+
+// ============================================================================
+// Graph snapshots
+// ============================================================================
+
+#[test]
+fn loaded_graph_snapshot_matches_fresh_build() {
+    use crate::rebac::snapshot::*;
+
+    let tuples = vec![
+        tuple_direct("user", "alice", "member", "group", "eng"),
+        tuple_userset("group", "eng", "member", "viewer", "folder", "root"),
+        tuple_direct("user", "bob", "editor", "folder", "root"),
+        tuple_direct("file", "readme", "parent", "folder", "root"),
+        tuple_direct("user", "carol", "viewer", "file", "notes"),
+        tuple_direct("*", "*", "viewer", "file", "public"),
+    ];
+    let folder = ns_config(
+        r#"{"relations":{
+            "viewer":"direct",
+            "editor":"direct"
+        },"permissions":{"read":["viewer","editor"],"write":["editor"]}}"#,
+    );
+    let file = ns_config(
+        r#"{"relations":{
+            "viewer":"direct",
+            "parent":"direct",
+            "parent_viewer":{"tupleToUserset":{"tupleset":"parent","computedUserset":"viewer"}}
+        },"permissions":{"read":["viewer","parent_viewer"]}}"#,
+    );
+    let namespaces: AHashMap<String, NamespaceConfig> =
+        [("folder".to_string(), folder), ("file".to_string(), file)]
+            .into_iter()
+            .collect();
+
+    let bytes = serialize_graph(&tuples, 7);
+    assert_eq!(bytes, serialize_graph(&tuples, 7), "encoding is stable");
+    let mut loaded = load_graph(&bytes).unwrap();
+    assert_eq!(loaded.tuple_version(), 7);
+    let mut fresh = GraphHandle::from_tuples(&tuples, 7);
+    let loaded_ns = loaded.intern_namespaces(&namespaces);
+    let fresh_ns = fresh.intern_namespaces(&namespaces);
+
+    let subjects = ["alice", "bob", "carol", "stranger"].map(|id| entity("user", id));
+    let objects = [
+        entity("folder", "root"),
+        entity("file", "readme"),
+        entity("file", "notes"),
+        entity("file", "public"),
+    ];
+    let mut granted = 0;
+    for subject in &subjects {
+        for object in &objects {
+            for permission in ["read", "write"] {
+                let want = fresh.check(
+                    subject,
+                    permission,
+                    object,
+                    &fresh_ns,
+                    &mut InternedMemoCache::new(),
+                );
+                let got = loaded.check(
+                    subject,
+                    permission,
+                    object,
+                    &loaded_ns,
+                    &mut InternedMemoCache::new(),
+                );
+                assert_eq!(got, want, "{subject:?} {permission} {object:?}");
+                granted += usize::from(got);
+            }
+        }
+    }
+    assert!(granted > 0);
+    let alice = entity("user", "alice");
+    let readme = entity("file", "readme");
+    assert!(loaded.check(
+        &alice,
+        "read",
+        &readme,
+        &loaded_ns,
+        &mut InternedMemoCache::new()
+    ));
+
+    // A reloaded snapshot round-trips byte for byte.
+    assert_eq!(load_graph(&bytes).unwrap().to_bytes(), bytes);
+
+    let mut flipped = bytes.clone();
+    flipped[30] ^= 1;
+    assert_eq!(
+        load_graph(&flipped).unwrap_err(),
+        GraphLoadError::ChecksumMismatch
+    );
+    assert_eq!(
+        load_graph(b"nope").unwrap_err(),
+        GraphLoadError::Corrupt {
+            reason: "shorter than header".to_string()
+        }
+    );
+    let mut future = bytes[..bytes.len() - 4].to_vec();
+    future[4] = 2;
+    let crc = crc32fast::hash(&future);
+    future.extend_from_slice(&crc.to_le_bytes());
+    assert_eq!(
+        load_graph(&future).unwrap_err(),
+        GraphLoadError::VersionMismatch {
+            expected: 1,
+            found: 2
+        }
+    );
+}
+
+// ============================================================================
+// related_objects
+// ============================================================================
+
+#[test]
+fn related_objects_walks_parent_links_both_ways() {
+    let tuples = vec![
+        tuple_direct("file", "a", "parent", "folder", "docs"),
+        tuple_direct("file", "b", "parent", "folder", "docs"),
+        tuple_direct("file", "b", "parent", "folder", "shared"),
+        tuple_direct("file", "b", "parent", "folder", "shared"),
+        tuple_direct("folder", "docs", "parent", "folder", "root"),
+        tuple_direct("user", "alice", "viewer", "folder", "docs"),
+    ];
+    let graph = ReBACGraph::from_tuples(&tuples);
+    let ids = |entities: Vec<Entity>| -> Vec<String> {
+        entities
+            .into_iter()
+            .map(|e| format!("{}:{}", e.entity_type, e.entity_id))
+            .collect()
+    };
+
+    let containing = related_objects(
+        &entity("file", "b"),
+        "parent",
+        &graph,
+        RelationDirection::Outgoing,
+    );
+    assert_eq!(ids(containing), ["folder:docs", "folder:shared"]);
+
+    let children = related_objects(
+        &entity("folder", "docs"),
+        "parent",
+        &graph,
+        RelationDirection::Incoming,
+    );
+    assert_eq!(ids(children), ["file:a", "file:b"]);
+    let up = related_objects(
+        &entity("folder", "docs"),
+        "parent",
+        &graph,
+        RelationDirection::Outgoing,
+    );
+    assert_eq!(ids(up), ["folder:root"]);
+
+    // Other relations and unknown entities are not followed.
+    assert!(related_objects(
+        &entity("file", "a"),
+        "viewer",
+        &graph,
+        RelationDirection::Outgoing
+    )
+    .is_empty());
+    assert!(related_objects(
+        &entity("folder", "nowhere"),
+        "parent",
+        &graph,
+        RelationDirection::Incoming
+    )
+    .is_empty());
+}
+
+// ============================================================================
+// tuples_exist
+// ============================================================================
+
+#[test]
+fn tuples_exist_reports_direct_and_userset_membership() {
+    let existing = vec![
+        tuple_direct("user", "alice", "editor", "file", "readme"),
+        tuple_userset("group", "eng", "member", "viewer", "folder", "docs"),
+        tuple_direct("*", "*", "viewer", "file", "public"),
+    ];
+    let graph = ReBACGraph::from_tuples(&existing);
+
+    let candidates = vec![
+        // Present.
+        tuple_direct("user", "alice", "editor", "file", "readme"),
+        tuple_userset("group", "eng", "member", "viewer", "folder", "docs"),
+        tuple_direct("*", "*", "viewer", "file", "public"),
+        // Absent: other relation, other subject, other object.
+        tuple_direct("user", "alice", "viewer", "file", "readme"),
+        tuple_direct("user", "bob", "editor", "file", "readme"),
+        tuple_direct("user", "alice", "editor", "file", "other"),
+        // A wildcard grant is not a stored tuple for a concrete user.
+        tuple_direct("user", "bob", "viewer", "file", "public"),
+        // Userset forms differ from the direct form and by relation.
+        tuple_direct("group", "eng", "viewer", "folder", "docs"),
+        tuple_userset("group", "eng", "admin", "viewer", "folder", "docs"),
+        tuple_userset("user", "alice", "member", "editor", "file", "readme"),
+    ];
+    assert_eq!(
+        tuples_exist(&candidates, &graph),
+        [true, true, true, false, false, false, false, false, false, false]
+    );
+    assert!(tuples_exist(&[], &graph).is_empty());
+}
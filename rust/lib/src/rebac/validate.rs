@@ -0,0 +1,241 @@
+//! Input checks for tuples before graph construction.
+//!
+//! `ReBACGraph::from_tuples` trusts its input, so a stray space in an id
+//! or a `#` that reads like userset notation silently changes which
+//! checks pass. `validate_tuples()` reports such tuples by position;
+//! `normalize_tuples()` fixes the mechanical ones (whitespace, empty
+//! subject relations, duplicates).
+
+use std::fmt;
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::types::ReBACTuple;
+
+/// Something wrong with one tuple, reported by [`validate_tuples`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TupleIssue {
+    /// A required field is empty (or only whitespace).
+    EmptyField { field: &'static str },
+    /// `subject_relation` is `Some("")`; a direct tuple should use `None`.
+    EmptySubjectRelation,
+    /// A field has leading or trailing whitespace.
+    Whitespace { field: &'static str },
+    /// A field contains `#`, which collides with `type:id#relation`
+    /// userset notation.
+    EmbeddedHash { field: &'static str },
+    /// A wildcard subject (`*`) used as a userset; wildcards only grant
+    /// directly.
+    WildcardUserset,
+    /// Same tuple (after normalization) as the one at index `first`.
+    Duplicate { first: usize },
+}
+
+impl fmt::Display for TupleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TupleIssue::EmptyField { field } => write!(f, "{} is empty", field),
+            TupleIssue::EmptySubjectRelation => {
+                write!(f, "subject_relation is empty; omit it for a direct tuple")
+            }
+            TupleIssue::Whitespace { field } => {
+                write!(f, "{} has leading or trailing whitespace", field)
+            }
+            TupleIssue::EmbeddedHash { field } => {
+                write!(f, "{} contains '#', which reads as userset notation", field)
+            }
+            TupleIssue::WildcardUserset => write!(f, "wildcard subject cannot be a userset"),
+            TupleIssue::Duplicate { first } => write!(f, "duplicate of tuple {}", first),
+        }
+    }
+}
+
+/// The fields of `tuple` in declaration order, with their names.
+fn fields(tuple: &ReBACTuple) -> [(&'static str, &str); 5] {
+    [
+        ("subject_type", &tuple.subject_type),
+        ("subject_id", &tuple.subject_id),
+        ("relation", &tuple.relation),
+        ("object_type", &tuple.object_type),
+        ("object_id", &tuple.object_id),
+    ]
+}
+
+/// Trimmed identity of a tuple, for duplicate detection.
+type TupleKey<'a> = (&'a str, &'a str, Option<&'a str>, &'a str, &'a str, &'a str);
+
+fn tuple_key(tuple: &ReBACTuple) -> TupleKey<'_> {
+    (
+        tuple.subject_type.trim(),
+        tuple.subject_id.trim(),
+        tuple
+            .subject_relation
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty()),
+        tuple.relation.trim(),
+        tuple.object_type.trim(),
+        tuple.object_id.trim(),
+    )
+}
+
+/// Every issue in `tuples` as `(index, issue)`, ordered by index, then
+/// by field. A clean batch returns an empty list.
+///
+/// Duplicates are judged after normalization, so `" eng"` and `"eng"`
+/// count as the same id; each repeat points at the first occurrence.
+pub fn validate_tuples(tuples: &[ReBACTuple]) -> Vec<(usize, TupleIssue)> {
+    let mut issues = Vec::new();
+    let mut seen: AHashMap<TupleKey<'_>, usize> = AHashMap::new();
+    for (index, tuple) in tuples.iter().enumerate() {
+        let subject_relation = tuple
+            .subject_relation
+            .as_deref()
+            .map(|r| ("subject_relation", r));
+        for (field, value) in fields(tuple).into_iter().chain(subject_relation) {
+            if value.trim().is_empty() {
+                issues.push((
+                    index,
+                    if field == "subject_relation" {
+                        TupleIssue::EmptySubjectRelation
+                    } else {
+                        TupleIssue::EmptyField { field }
+                    },
+                ));
+                continue;
+            }
+            if value.trim() != value {
+                issues.push((index, TupleIssue::Whitespace { field }));
+            }
+            if value.contains('#') {
+                issues.push((index, TupleIssue::EmbeddedHash { field }));
+            }
+        }
+        let key = tuple_key(tuple);
+        if key.1 == "*" && key.2.is_some() {
+            issues.push((index, TupleIssue::WildcardUserset));
+        }
+        match seen.get(&key) {
+            Some(&first) => issues.push((index, TupleIssue::Duplicate { first })),
+            None => {
+                seen.insert(key, index);
+            }
+        }
+    }
+    issues
+}
+
+/// Copy of `tuples` with every field trimmed, empty subject relations
+/// turned into direct tuples (`None`) and later duplicates dropped.
+/// Order of first occurrences is preserved.
+///
+/// Issues that need a human decision (empty fields, embedded `#`,
+/// wildcard usersets) are left for [`validate_tuples`] to report.
+pub fn normalize_tuples(tuples: &[ReBACTuple]) -> Vec<ReBACTuple> {
+    let mut seen = AHashSet::new();
+    tuples
+        .iter()
+        .filter(|tuple| seen.insert(tuple_key(tuple)))
+        .map(|tuple| {
+            let (subject_type, subject_id, subject_relation, relation, object_type, object_id) =
+                tuple_key(tuple);
+            ReBACTuple {
+                subject_type: subject_type.to_string(),
+                subject_id: subject_id.to_string(),
+                subject_relation: subject_relation.map(str::to_string),
+                relation: relation.to_string(),
+                object_type: object_type.to_string(),
+                object_id: object_id.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuple(subject: (&str, &str), relation: &str, object: (&str, &str)) -> ReBACTuple {
+        ReBACTuple {
+            subject_type: subject.0.to_string(),
+            subject_id: subject.1.to_string(),
+            subject_relation: None,
+            relation: relation.to_string(),
+            object_type: object.0.to_string(),
+            object_id: object.1.to_string(),
+        }
+    }
+
+    fn userset(subject: (&str, &str, &str), relation: &str, object: (&str, &str)) -> ReBACTuple {
+        ReBACTuple {
+            subject_relation: Some(subject.2.to_string()),
+            ..tuple((subject.0, subject.1), relation, object)
+        }
+    }
+
+    #[test]
+    fn clean_batch_has_no_issues() {
+        let tuples = vec![
+            tuple(("user", "alice"), "member", ("group", "eng")),
+            userset(("group", "eng", "member"), "viewer", ("file", "readme")),
+            tuple(("user", "*"), "viewer", ("file", "public")),
+        ];
+        assert!(validate_tuples(&tuples).is_empty());
+        assert_eq!(normalize_tuples(&tuples).len(), 3);
+    }
+
+    #[test]
+    fn each_malformed_case_is_reported() {
+        let tuples = vec![
+            tuple(("user", ""), "member", ("group", "eng")),
+            tuple(("user", "alice "), "member", ("group", "eng")),
+            tuple(("user", "alice"), "member", ("group", "eng#member")),
+            userset(("group", "eng", ""), "viewer", ("file", "a")),
+            userset(("user", "*", "member"), "viewer", ("file", "a")),
+            tuple(("user", "alice"), "member", ("group", "eng")),
+        ];
+        assert_eq!(
+            validate_tuples(&tuples),
+            vec![
+                (
+                    0,
+                    TupleIssue::EmptyField {
+                        field: "subject_id"
+                    }
+                ),
+                (
+                    1,
+                    TupleIssue::Whitespace {
+                        field: "subject_id"
+                    }
+                ),
+                (2, TupleIssue::EmbeddedHash { field: "object_id" }),
+                (3, TupleIssue::EmptySubjectRelation),
+                (4, TupleIssue::WildcardUserset),
+                (5, TupleIssue::Duplicate { first: 1 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_trims_and_dedups() {
+        let tuples = vec![
+            tuple((" user", "alice\t"), "member ", ("group", " eng")),
+            userset(("group", "eng", " "), "viewer", ("file", "a")),
+            tuple(("user", "alice"), "member", ("group", "eng")),
+        ];
+        let normalized = normalize_tuples(&tuples);
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(
+            (
+                normalized[0].subject_type.as_str(),
+                normalized[0].subject_id.as_str(),
+                normalized[0].relation.as_str(),
+                normalized[0].object_id.as_str(),
+            ),
+            ("user", "alice", "member", "eng")
+        );
+        assert_eq!(normalized[1].subject_relation, None);
+        assert!(validate_tuples(&normalized).is_empty());
+    }
+}
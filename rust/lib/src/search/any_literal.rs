@@ -0,0 +1,157 @@
+//! Multi-literal search: which of many literals occur, and where.
+//!
+//! All literals are compiled into one Aho-Corasick automaton and the
+//! content is scanned once, so cost is independent of the number of
+//! literals — unlike a regex alternation or per-literal `memmem` passes.
+
+use aho_corasick::{AhoCorasick, MatchKind};
+
+/// One occurrence of a literal found by [`search_any_literal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralMatch {
+    /// Index of the matched literal in the `literals` argument.
+    pub literal: usize,
+    pub file: String,
+    /// 1-indexed line of the match start.
+    pub line: usize,
+    /// 1-indexed byte column of the match start within the line.
+    pub column: usize,
+    /// Absolute byte offset of the match start in `content`.
+    pub offset: usize,
+    /// The matched text as it appears in `content`.
+    pub match_text: String,
+}
+
+/// Find every occurrence of every literal in `content` in a single pass.
+///
+/// Overlapping occurrences are all reported (`"he"` and `"hello"` both
+/// match in `"hello"`), ordered by offset, then literal index. Empty
+/// literals match nothing. `ignore_case` folds ASCII letters only, like
+/// [`CaseFolding::Ascii`](super::CaseFolding::Ascii).
+pub fn search_any_literal(
+    file_path: &str,
+    literals: &[String],
+    content: &str,
+    ignore_case: bool,
+) -> Result<Vec<LiteralMatch>, aho_corasick::BuildError> {
+    // Automaton pattern id → index into `literals`, skipping empties.
+    let indices: Vec<usize> = (0..literals.len())
+        .filter(|&i| !literals[i].is_empty())
+        .collect();
+    if indices.is_empty() {
+        return Ok(Vec::new());
+    }
+    let automaton = AhoCorasick::builder()
+        .match_kind(MatchKind::Standard)
+        .ascii_case_insensitive(ignore_case)
+        .build(indices.iter().map(|&i| &literals[i]))?;
+
+    let mut spans: Vec<(usize, usize, usize)> = automaton
+        .find_overlapping_iter(content)
+        .map(|m| (m.start(), indices[m.pattern().as_usize()], m.end()))
+        .collect();
+    // Overlapping matches arrive in end order; line numbering needs starts.
+    spans.sort_unstable();
+
+    let bytes = content.as_bytes();
+    let (mut line, mut line_start, mut scanned) = (1, 0, 0);
+    Ok(spans
+        .into_iter()
+        .map(|(start, literal, end)| {
+            for newline in memchr::memchr_iter(b'\n', &bytes[scanned..start]) {
+                line += 1;
+                line_start = scanned + newline + 1;
+            }
+            scanned = start;
+            LiteralMatch {
+                literal,
+                file: file_path.to_string(),
+                line,
+                column: start - line_start + 1,
+                offset: start,
+                match_text: content[start..end].to_string(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_overlapping_literals_with_positions() {
+        let lits = ["hello", "he", "", "lo w", "world"].map(String::from);
+        let content = "say hello\nhello world";
+        let found = search_any_literal("f.txt", &lits, content, false).unwrap();
+        let summary: Vec<(usize, usize, usize, &str)> = found
+            .iter()
+            .map(|m| (m.literal, m.line, m.column, m.match_text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, 1, 5, "hello"),
+                (1, 1, 5, "he"),
+                (0, 2, 1, "hello"),
+                (1, 2, 1, "he"),
+                (3, 2, 4, "lo w"),
+                (4, 2, 7, "world"),
+            ]
+        );
+        assert_eq!(found[2].offset, 10);
+        assert!(found.iter().all(|m| m.file == "f.txt"));
+    }
+
+    #[test]
+    fn ignore_case_folds_ascii() {
+        let lits = ["SECRET", "token"].map(String::from);
+        let found = search_any_literal("f", &lits, "a Secret TOKEN", true).unwrap();
+        let texts: Vec<(usize, &str)> = found
+            .iter()
+            .map(|m| (m.literal, m.match_text.as_str()))
+            .collect();
+        assert_eq!(texts, vec![(0, "Secret"), (1, "TOKEN")]);
+        assert!(search_any_literal("f", &lits, "a Secret TOKEN", false)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn dense_matches_agree_with_per_literal_scan() {
+        // 500 literals over a small alphabet, so matches are dense and
+        // heavily overlapping.
+        let lits: Vec<String> = (0..500)
+            .map(|i: usize| {
+                let len = 1 + i % 4;
+                (0..len)
+                    .map(|j| (b'a' + ((i / 4 + j * 7) % 3) as u8) as char)
+                    .collect()
+            })
+            .collect();
+        let content: String = (0..2_000)
+            .map(|i: usize| match i % 41 {
+                40 => '\n',
+                _ => (b'a' + ((i * 31 + i / 7) % 3) as u8) as char,
+            })
+            .collect();
+
+        let mut expected: Vec<(usize, usize)> = Vec::new();
+        for offset in 0..content.len() {
+            for (index, lit) in lits.iter().enumerate() {
+                if content[offset..].starts_with(lit.as_str()) {
+                    expected.push((offset, index));
+                }
+            }
+        }
+
+        let found = search_any_literal("f", &lits, &content, false).unwrap();
+        let got: Vec<(usize, usize)> = found.iter().map(|m| (m.offset, m.literal)).collect();
+        assert_eq!(got, expected);
+        for m in &found {
+            let line_start = content[..m.offset].rfind('\n').map_or(0, |i| i + 1);
+            assert_eq!(m.line, content[..m.offset].matches('\n').count() + 1);
+            assert_eq!(m.column, m.offset - line_start + 1);
+        }
+    }
+}
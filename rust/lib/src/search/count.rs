@@ -0,0 +1,89 @@
+//! Multi-pattern occurrence counts, without materializing matches.
+
+use std::collections::HashMap;
+
+use regex::{Regex, RegexBuilder, RegexSetBuilder};
+
+use super::SearchError;
+
+/// Total occurrences of each regex in `patterns` across every file in
+/// `file_contents` (path → content), indexed like `patterns`.
+///
+/// Files are scanned line by line, like the rest of `search`. A
+/// `RegexSet` first picks which patterns occur on a line, and only those
+/// are run to count their non-overlapping matches. Empty matches are not
+/// counted, so a pattern like `x*` counts only its non-empty runs. An
+/// invalid pattern fails the whole call with
+/// [`SearchError::InvalidPattern`].
+pub fn grep_multi_counts(
+    patterns: &[String],
+    file_contents: &HashMap<String, String>,
+    ignore_case: bool,
+) -> Result<Vec<usize>, SearchError> {
+    let set = RegexSetBuilder::new(patterns)
+        .case_insensitive(ignore_case)
+        .build()?;
+    let regexes = patterns
+        .iter()
+        .map(|pattern| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+        })
+        .collect::<Result<Vec<Regex>, _>>()?;
+
+    let mut counts = vec![0; patterns.len()];
+    for content in file_contents.values() {
+        for line in content.lines() {
+            for i in set.matches(line).iter() {
+                counts[i] += regexes[i].find_iter(line).filter(|m| !m.is_empty()).count();
+            }
+        }
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(items: &[(&str, &str)]) -> HashMap<String, String> {
+        items
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn counts_each_pattern_across_files() {
+        let input = files(&[
+            ("a.rs", "x.unwrap();\ny.unwrap().unwrap();\ntodo!()\n"),
+            ("b.rs", "// TODO: drop unwrap\nprintln!(\"hi\");\n"),
+            ("c.rs", "clean\n"),
+        ]);
+        let patterns = [r"\.unwrap\(\)", "unwrap", r"println!", "todo", "absent"].map(String::from);
+        assert_eq!(
+            grep_multi_counts(&patterns, &input, false).unwrap(),
+            vec![3, 4, 1, 1, 0]
+        );
+        assert_eq!(
+            grep_multi_counts(&patterns, &input, true).unwrap(),
+            vec![3, 4, 1, 2, 0]
+        );
+    }
+
+    #[test]
+    fn empty_matches_and_bad_patterns() {
+        let input = files(&[("a.txt", "aa b aaa\n")]);
+        assert_eq!(
+            grep_multi_counts(&["a*".to_string()], &input, false).unwrap(),
+            vec![2]
+        );
+        let bad = ["ok", "(unclosed"].map(String::from);
+        assert!(matches!(
+            grep_multi_counts(&bad, &input, false),
+            Err(SearchError::InvalidPattern(_))
+        ));
+        assert!(grep_multi_counts(&[], &input, false).unwrap().is_empty());
+    }
+}
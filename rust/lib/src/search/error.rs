@@ -0,0 +1,51 @@
+//! Error types for text search.
+
+use std::fmt;
+
+use super::grep::GrepMatch;
+
+/// Errors that can occur while building or running a search, kept apart so
+/// callers can tell a user's bad pattern from a problem with the content.
+#[derive(Debug)]
+pub enum SearchError {
+    /// The pattern is not a valid regex.
+    InvalidPattern(regex::Error),
+    /// The content could not be decoded as text (binary, or an encoding
+    /// `decode_text` does not recognize).
+    EncodingError { file: String },
+    /// More matches exist than `max_results`; `matches` holds the first
+    /// `max_results` of them.
+    Truncated {
+        max_results: usize,
+        matches: Vec<GrepMatch>,
+    },
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::InvalidPattern(e) => write!(f, "Invalid pattern: {}", e),
+            SearchError::EncodingError { file } => {
+                write!(f, "Cannot decode {} as text", file)
+            }
+            SearchError::Truncated { max_results, .. } => {
+                write!(f, "Results truncated at {} matches", max_results)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SearchError::InvalidPattern(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<regex::Error> for SearchError {
+    fn from(e: regex::Error) -> Self {
+        SearchError::InvalidPattern(e)
+    }
+}
@@ -1,10 +1,15 @@
 //! Grep match result type.
 
-/// A single grep match with file, line, content, and match text.
+/// A single grep match with file, line, column, content, and match text.
 #[derive(Debug, Clone)]
 pub struct GrepMatch {
     pub file: String,
     pub line: usize,
+    /// 1-indexed byte column of the match start within the line.
+    pub column: usize,
+    /// Absolute byte offset of the match start in the searched buffer.
+    pub offset: usize,
+    /// The matching line, or just the match in only-matching mode.
     pub content: String,
     pub match_text: String,
 }
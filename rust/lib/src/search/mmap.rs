@@ -0,0 +1,451 @@
+//! Incremental grep over memory-mapped files.
+//!
+//! `grep_files_mmap_from()` tails append-only files: each call searches
+//! only the bytes past a caller-held cursor and hands back the new cursor,
+//! so polling a growing log never re-scans what it has already seen.
+//! `grep_files_mmap_grouped()` searches whole files and returns matches
+//! already partitioned by file. Both honour
+//! [`SearchOptions::max_file_bytes`], checking the length before mapping
+//! so a giant generated file is never scanned. Behind the `mmap` feature
+//! (file mapping is not WASM-safe).
+//!
+//! Only regions of at least [`MAP_MIN_BYTES`] are mapped; smaller ones
+//! are read into memory. A mapping is not protected against other
+//! processes: if a mapped file is truncated mid-search, touching the lost
+//! pages raises `SIGBUS` and kills the process. Large files that may be
+//! truncated while being searched (e.g. by a copy-truncate log rotation)
+//! belong with [`super::stream`], which only ever calls `read()`.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Deref;
+use std::path::Path;
+
+use super::grep::GrepMatch;
+use super::{search_bytes_with, search_lossy_with, SearchMode, SearchOptions};
+
+/// Per-file result of [`grep_files_mmap_from`].
+#[derive(Debug, Clone)]
+pub struct IncrementalGrep {
+    /// Matches in the newly scanned region. `offset` is absolute within
+    /// the file; `line` counts from 1 at the first line past the cursor.
+    pub matches: Vec<GrepMatch>,
+    /// Cursor to pass on the next call: just past the last complete line.
+    pub next_offset: u64,
+    /// The file had shrunk below the given cursor, so it was rescanned
+    /// from the start.
+    pub truncated: bool,
+    /// The file exceeds `options.max_file_bytes` and was not searched;
+    /// `next_offset` is the cursor that was passed in.
+    pub skipped: bool,
+}
+
+/// Result of [`grep_files_mmap_grouped`].
+#[derive(Debug)]
+pub struct GroupedGrep {
+    /// `(path, matches)` for files that matched or failed to open, in
+    /// input order.
+    pub files: Vec<(String, io::Result<Vec<GrepMatch>>)>,
+    /// Files larger than `options.max_file_bytes`, in input order.
+    pub skipped: Vec<String>,
+}
+
+/// File regions smaller than this are read into memory instead of
+/// mapped: 1 MiB.
+pub const MAP_MIN_BYTES: u64 = 1 << 20;
+
+/// Search each `(path, offset)` from `offset` to the last complete line.
+///
+/// A trailing line without a newline is left for the next call, so a
+/// line that is still being written is never matched half-way. If a file
+/// is now shorter than its cursor it is treated as truncated (rotated)
+/// and searched from 0. `options.max_results` applies per file. Files are
+/// searched in parallel; results are in input order.
+///
+/// New regions of [`MAP_MIN_BYTES`] or more are mapped, so a file must
+/// not be truncated while such a region is being searched (see the
+/// module docs).
+pub fn grep_files_mmap_from<P: AsRef<Path> + Sync>(
+    files: &[(P, u64)],
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> Vec<io::Result<IncrementalGrep>> {
+    use rayon::prelude::*;
+
+    crate::pool::install(|| {
+        files
+            .par_iter()
+            .map(|(path, offset)| grep_file_from(path.as_ref(), *offset, search_mode, options))
+            .collect()
+    })
+}
+
+/// Search every file in `paths` in full, returning `(path, matches)` only
+/// for files that matched or failed to open, in input order. Files over
+/// `options.max_file_bytes` are listed in [`GroupedGrep::skipped`]
+/// instead of being searched.
+///
+/// Matches within a file keep their scan order, so callers rendering
+/// per-file sections need no regrouping. Content is decoded like
+/// [`search_bytes_with`] (binary files never match, the last line needs
+/// no trailing newline). `options.max_results` applies per file. Files
+/// are searched in parallel. Files of [`MAP_MIN_BYTES`] or more are
+/// mapped and must not be truncated while being searched.
+pub fn grep_files_mmap_grouped<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> GroupedGrep {
+    use rayon::prelude::*;
+
+    let results: Vec<_> = crate::pool::install(|| {
+        paths
+            .par_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                let matches = grep_file(path, search_mode, options);
+                (path.to_string_lossy().into_owned(), matches)
+            })
+            .collect()
+    });
+    let mut grouped = GroupedGrep {
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (path, matches) in results {
+        match matches {
+            Ok(None) => grouped.skipped.push(path),
+            Ok(Some(m)) if m.is_empty() => {}
+            Ok(Some(m)) => grouped.files.push((path, Ok(m))),
+            Err(e) => grouped.files.push((path, Err(e))),
+        }
+    }
+    grouped
+}
+
+/// Whether a file of `len` bytes is over `options.max_file_bytes`.
+fn too_large(len: u64, options: &SearchOptions) -> bool {
+    options.max_file_bytes.is_some_and(|max| len > max)
+}
+
+/// Matches in `path`, or `None` if it is over `options.max_file_bytes`.
+fn grep_file(
+    path: &Path,
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> io::Result<Option<Vec<GrepMatch>>> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if too_large(len, options) {
+        return Ok(None);
+    }
+    // Empty files cannot be mapped, and hold no matches anyway.
+    if len == 0 {
+        return Ok(Some(Vec::new()));
+    }
+    let bytes = load_region(&file, 0, len)?;
+    Ok(Some(search_bytes_with(
+        &path.to_string_lossy(),
+        &bytes,
+        search_mode,
+        options,
+    )))
+}
+
+fn grep_file_from(
+    path: &Path,
+    offset: u64,
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> io::Result<IncrementalGrep> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if too_large(len, options) {
+        return Ok(IncrementalGrep {
+            matches: Vec::new(),
+            next_offset: offset,
+            truncated: false,
+            skipped: true,
+        });
+    }
+    let truncated = offset > len;
+    let start = if truncated { 0 } else { offset };
+    let mut result = IncrementalGrep {
+        matches: Vec::new(),
+        next_offset: start,
+        truncated,
+        skipped: false,
+    };
+    // Nothing new (this also covers empty files, which cannot be mapped).
+    if start == len {
+        return Ok(result);
+    }
+
+    // Bytes past the cursor may still be growing, which is why only
+    // complete lines are searched.
+    let region = load_region(&file, start, len)?;
+    let Some(last_newline) = memchr::memrchr(b'\n', &region) else {
+        return Ok(result);
+    };
+    let end = last_newline + 1;
+
+    result.matches = search_lossy_with(
+        &path.to_string_lossy(),
+        &region[..end],
+        search_mode,
+        options,
+    );
+    for m in &mut result.matches {
+        m.offset += start as usize;
+    }
+    result.next_offset = start + end as u64;
+    Ok(result)
+}
+
+/// File contents held either in memory or in a mapping.
+enum Region {
+    Read(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for Region {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Region::Read(bytes) => bytes,
+            Region::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+/// Bytes `start..len` of `file`, where `len` is its size as of `stat`
+/// and `start < len`.
+///
+/// Regions under [`MAP_MIN_BYTES`] are read, so a concurrent truncation
+/// just shortens the result. Larger ones are mapped.
+fn load_region(file: &File, start: u64, len: u64) -> io::Result<Region> {
+    let size = len - start;
+    if size < MAP_MIN_BYTES {
+        let mut bytes = Vec::with_capacity(size as usize);
+        let mut reader = file;
+        reader.seek(SeekFrom::Start(start))?;
+        reader.take(size).read_to_end(&mut bytes)?;
+        return Ok(Region::Read(bytes));
+    }
+    // SAFETY: the mapping is read-only and lives for one search, but the
+    // file is not locked: if another process truncates it below
+    // `start + size` before the mapping is dropped, reading the lost pages
+    // raises SIGBUS. Callers are documented as not supporting that; a
+    // concurrent append or in-place write only changes the bytes seen.
+    let mmap = unsafe {
+        memmap2::MmapOptions::new()
+            .offset(start)
+            .len(size as usize)
+            .map(file)?
+    };
+    Ok(Region::Mapped(mmap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::build_search_mode;
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn poll(path: &Path, offset: u64) -> IncrementalGrep {
+        let mode = build_search_mode("ERROR", false).unwrap();
+        grep_files_mmap_from(&[(path, offset)], &mode, &SearchOptions::default())
+            .pop()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn second_poll_returns_only_appended_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        append(&log, "ERROR one\ninfo\nERROR two\n");
+
+        let first = poll(&log, 0);
+        let texts: Vec<&str> = first.matches.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(texts, ["ERROR one", "ERROR two"]);
+        assert_eq!(first.next_offset, 25);
+
+        append(&log, "info\nERROR three\nERROR part");
+        let second = poll(&log, first.next_offset);
+        assert_eq!(second.matches.len(), 1);
+        let m = &second.matches[0];
+        assert_eq!((m.content.as_str(), m.line), ("ERROR three", 2));
+        let bytes = std::fs::read(&log).unwrap();
+        assert_eq!(&bytes[m.offset..m.offset + 5], b"ERROR");
+        // The unterminated trailing line waits for its newline.
+        assert_eq!(second.next_offset, 42);
+
+        append(&log, "ial\n");
+        let third = poll(&log, second.next_offset);
+        assert_eq!(third.matches[0].content, "ERROR partial");
+        assert_eq!(third.next_offset, bytes.len() as u64 + 4);
+        assert!(poll(&log, third.next_offset).matches.is_empty());
+    }
+
+    #[test]
+    fn truncated_file_is_rescanned_from_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        append(&log, "ERROR old\nERROR old again\n");
+        let cursor = poll(&log, 0).next_offset;
+
+        std::fs::write(&log, "ERROR new\n").unwrap();
+        let after = poll(&log, cursor);
+        assert!(after.truncated);
+        assert_eq!(after.matches.len(), 1);
+        assert_eq!(after.matches[0].content, "ERROR new");
+        assert_eq!(after.next_offset, 10);
+    }
+
+    #[test]
+    fn offsets_index_raw_bytes_past_invalid_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        std::fs::write(&log, b"bad \xff\xfe bytes\nx\xc3 ERROR one\n").unwrap();
+
+        let first = poll(&log, 0);
+        let bytes = std::fs::read(&log).unwrap();
+        assert_eq!(first.next_offset, bytes.len() as u64);
+        let m = &first.matches[0];
+        assert_eq!(&bytes[m.offset..m.offset + 5], b"ERROR");
+        assert_eq!(m.column, 4);
+
+        append(&log, "ERROR two\n");
+        let second = poll(&log, first.next_offset);
+        assert_eq!(second.matches[0].content, "ERROR two");
+        assert_eq!(second.matches[0].offset as u64, first.next_offset);
+    }
+
+    #[test]
+    fn large_regions_are_mapped_from_an_unaligned_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("big.log");
+        append(&log, "ERROR head\n");
+        let cursor = poll(&log, 0).next_offset;
+        let filler = "info\n".repeat(MAP_MIN_BYTES as usize / 5 + 1);
+        append(&log, &format!("{filler}ERROR tail\n"));
+
+        let tail = poll(&log, cursor);
+        assert_eq!(tail.matches.len(), 1);
+        let m = &tail.matches[0];
+        assert_eq!(m.content, "ERROR tail");
+        let bytes = std::fs::read(&log).unwrap();
+        assert_eq!(&bytes[m.offset..m.offset + 10], b"ERROR tail");
+        assert_eq!(tail.next_offset, bytes.len() as u64);
+    }
+
+    #[test]
+    fn grouped_matches_partition_per_file_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.log");
+        let b = dir.path().join("b.log");
+        let quiet = dir.path().join("quiet.log");
+        let empty = dir.path().join("empty.log");
+        append(&a, "ERROR one\ninfo\nERROR two\n");
+        append(&b, "info\nERROR three");
+        append(&quiet, "info\ninfo\n");
+        std::fs::write(&empty, b"").unwrap();
+        let missing = dir.path().join("missing.log");
+        let paths = [&a, &quiet, &b, &empty, &missing];
+
+        let mode = build_search_mode("ERROR", false).unwrap();
+        let options = SearchOptions::default();
+        let grouped = grep_files_mmap_grouped(&paths, &mode, &options);
+        assert!(grouped.skipped.is_empty());
+        let grouped = grouped.files;
+
+        let names: Vec<&str> = grouped.iter().map(|(p, _)| p.as_str()).collect();
+        let expected_names: Vec<String> = [&a, &b, &missing]
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, expected_names);
+
+        for ((name, matches), path) in grouped.iter().zip([&a, &b]) {
+            let flat = search_bytes_with(name, &std::fs::read(path).unwrap(), &mode, &options);
+            let got: Vec<(usize, usize, &str)> = matches
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|m| (m.line, m.offset, m.content.as_str()))
+                .collect();
+            let want: Vec<(usize, usize, &str)> = flat
+                .iter()
+                .map(|m| (m.line, m.offset, m.content.as_str()))
+                .collect();
+            assert_eq!(got, want);
+            assert!(matches.as_ref().unwrap().iter().all(|m| &m.file == name));
+        }
+        // The unterminated last line of b.log is searched too.
+        assert_eq!(grouped[1].1.as_ref().unwrap()[0].content, "ERROR three");
+        assert_eq!(
+            grouped[2].1.as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn oversized_files_are_skipped_and_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let small = dir.path().join("small.log");
+        let huge = dir.path().join("bundle.min.js");
+        append(&small, "ERROR one\n");
+        append(&huge, &"ERROR minified;".repeat(100));
+        let mode = build_search_mode("ERROR", false).unwrap();
+        let options = SearchOptions {
+            max_file_bytes: Some(64),
+            ..SearchOptions::default()
+        };
+
+        let grouped = grep_files_mmap_grouped(&[&huge, &small], &mode, &options);
+        assert_eq!(grouped.skipped, [huge.to_string_lossy()]);
+        assert_eq!(grouped.files.len(), 1);
+        assert_eq!(grouped.files[0].0, small.to_string_lossy());
+        assert_eq!(grouped.files[0].1.as_ref().unwrap().len(), 1);
+
+        let tailed = grep_files_mmap_from(&[(&huge, 15), (&small, 0)], &mode, &options);
+        let huge_result = tailed[0].as_ref().unwrap();
+        assert!(huge_result.skipped && huge_result.matches.is_empty());
+        assert_eq!(huge_result.next_offset, 15);
+        let small_result = tailed[1].as_ref().unwrap();
+        assert!(!small_result.skipped);
+        assert_eq!(small_result.matches.len(), 1);
+
+        // Without the limit the large file is searched like any other.
+        let unlimited = grep_files_mmap_grouped(&[&huge], &mode, &SearchOptions::default());
+        assert!(unlimited.skipped.is_empty());
+        assert_eq!(unlimited.files[0].1.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn errors_are_reported_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.log");
+        std::fs::write(&empty, b"").unwrap();
+        let mode = build_search_mode("x", false).unwrap();
+        let files = [(empty, 0), (dir.path().join("missing.log"), 0)];
+
+        let results = grep_files_mmap_from(&files, &mode, &SearchOptions::default());
+        assert_eq!(results[0].as_ref().unwrap().next_offset, 0);
+        assert_eq!(
+            results[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -2,27 +2,99 @@
 //!
 //! Provides `search_lines()` — a unified search function that automatically
 //! selects SIMD-accelerated literal search or regex depending on the pattern.
+//! `search_bytes_with()` is the raw-file entry point: it decodes UTF-16 and
+//! BOM-prefixed content first so line numbers count real newlines.
+//! `mmap::grep_files_mmap_from()` (behind the `mmap` feature) tails files
+//! incrementally from a saved byte cursor; `mmap::grep_files_mmap_grouped()`
+//! searches whole files with results partitioned per file;
+//! `stream::grep_file_streaming()` (behind the `fs` feature) scans one
+//! arbitrarily large file through a bounded window, reporting matches
+//! through a callback.
+//! `any_literal::search_any_literal()` scans for many literals at once;
+//! `count::grep_multi_counts()` tallies occurrences of many regexes without
+//! collecting matches. `replace::grep_replace_preview()` previews a regex
+//! substitution across files without writing them. Failures are
+//! reported as [`SearchError`].
+//!
+//! Unicode normalization is opt-in: [`build_search_mode_nfc`] plus
+//! [`SearchOptions::normalize_nfc`] compare pattern and content in NFC, so
+//! a precomposed `é` matches `e` + combining acute.
 
+pub mod any_literal;
+pub mod count;
+pub mod error;
 pub mod grep;
 pub mod literal;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod replace;
+#[cfg(feature = "fs")]
+pub mod stream;
+
+use std::borrow::Cow;
 
+pub use error::SearchError;
 use grep::GrepMatch;
 use literal::is_literal_pattern;
 
+use crate::content_type::decode_text;
+
 /// Search mode — either SIMD-accelerated literal or full regex.
 pub enum SearchMode {
     /// Case-sensitive literal search using memchr.
     Literal { pattern: String },
-    /// Case-insensitive literal search.
+    /// Case-insensitive literal search with Unicode folding: each line is
+    /// lowercased with `to_lowercase`, which allocates per line but treats
+    /// pairs like `İ`/`i̇` and `K` (Kelvin sign)/`k` as equal.
     LiteralIgnoreCase { pattern_lower: String },
+    /// Case-insensitive literal search folding only ASCII letters. Byte
+    /// lengths are preserved, so each line is folded into a reused buffer
+    /// and spans need no remapping. Non-ASCII text must match exactly.
+    LiteralAsciiIgnoreCase { pattern_lower: String },
     /// Full regex search for complex patterns.
     Regex(regex::bytes::Regex),
 }
 
+/// Case folding for case-insensitive literal patterns. Regex patterns
+/// always use the regex engine's Unicode-aware folding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseFolding {
+    /// ASCII folding for ASCII patterns, Unicode folding otherwise.
+    #[default]
+    Auto,
+    /// Always fold ASCII letters only ([`SearchMode::LiteralAsciiIgnoreCase`]).
+    Ascii,
+    /// Always fold with `to_lowercase` ([`SearchMode::LiteralIgnoreCase`]).
+    Unicode,
+}
+
 /// Build a `SearchMode` from a pattern string.
-pub fn build_search_mode(pattern: &str, ignore_case: bool) -> Result<SearchMode, regex::Error> {
+///
+/// Uses [`CaseFolding::Auto`], so an ASCII pattern does not match the
+/// rare non-ASCII characters whose lowercase contains ASCII letters (`İ`,
+/// the Kelvin sign); pass [`CaseFolding::Unicode`] to
+/// [`build_search_mode_with`] when that matters.
+pub fn build_search_mode(pattern: &str, ignore_case: bool) -> Result<SearchMode, SearchError> {
+    build_search_mode_with(pattern, ignore_case, CaseFolding::Auto)
+}
+
+/// [`build_search_mode`] with an explicit [`CaseFolding`].
+pub fn build_search_mode_with(
+    pattern: &str,
+    ignore_case: bool,
+    folding: CaseFolding,
+) -> Result<SearchMode, SearchError> {
     if is_literal_pattern(pattern) {
-        if ignore_case {
+        let ascii = match folding {
+            CaseFolding::Auto => pattern.is_ascii(),
+            CaseFolding::Ascii => true,
+            CaseFolding::Unicode => false,
+        };
+        if ignore_case && ascii {
+            Ok(SearchMode::LiteralAsciiIgnoreCase {
+                pattern_lower: pattern.to_ascii_lowercase(),
+            })
+        } else if ignore_case {
             Ok(SearchMode::LiteralIgnoreCase {
                 pattern_lower: pattern.to_lowercase(),
             })
@@ -39,17 +111,41 @@ pub fn build_search_mode(pattern: &str, ignore_case: bool) -> Result<SearchMode,
     }
 }
 
-/// Map byte offsets in a lowercased string back to the corresponding substring
+/// [`build_search_mode_with`] on the NFC form of `pattern`. Pair it with
+/// [`SearchOptions::normalize_nfc`] so content is normalized the same way.
+///
+/// Regex patterns are normalized as text, so a character class listing a
+/// base letter and a combining mark separately (`[e\u{301}]`) is composed
+/// into one character like any other text.
+pub fn build_search_mode_nfc(
+    pattern: &str,
+    ignore_case: bool,
+    folding: CaseFolding,
+) -> Result<SearchMode, SearchError> {
+    build_search_mode_with(&normalize_nfc(pattern), ignore_case, folding)
+}
+
+/// NFC form of `text`, borrowed when it is already normalized.
+///
+/// Checking costs one pass; text that needs composing is copied once.
+pub fn normalize_nfc(text: &str) -> Cow<'_, str> {
+    icu_normalizer::ComposingNormalizerBorrowed::new_nfc().normalize(text)
+}
+
+/// Map a byte span in a lowercased string back to the corresponding span
 /// in the original string. Handles cases where `to_lowercase()` changes byte
-/// lengths (e.g., Turkish İ → i̇, German ß → ss).
-pub fn extract_original_match(
+/// lengths in either direction (Turkish İ → i̇ grows by a byte, capital
+/// sharp s ẞ → ß shrinks by one). `ß` itself lowercases to `ß`, not `ss`.
+///
+/// Returns `None` if the span is empty or out of range.
+pub fn map_lowered_span(
     original: &str,
     lowered: &str,
     byte_start: usize,
     byte_end: usize,
-) -> String {
+) -> Option<(usize, usize)> {
     if byte_start >= byte_end || byte_start >= lowered.len() {
-        return String::new();
+        return None;
     }
     let clamped_end = byte_end.min(lowered.len());
 
@@ -83,15 +179,110 @@ pub fn extract_original_match(
         orig_pos = orig_next;
     }
 
-    let start = match orig_start {
-        Some(s) => s,
-        None => return String::new(),
-    };
+    let start = orig_start?;
     let end = orig_end.unwrap_or(original.len());
     if start > end || end > original.len() {
-        return String::new();
+        return None;
+    }
+    Some((start, end))
+}
+
+/// Map byte offsets in a lowercased string back to the corresponding substring
+/// in the original string. See [`map_lowered_span`].
+pub fn extract_original_match(
+    original: &str,
+    lowered: &str,
+    byte_start: usize,
+    byte_end: usize,
+) -> String {
+    map_lowered_span(original, lowered, byte_start, byte_end)
+        .map(|(start, end)| original[start..end].to_string())
+        .unwrap_or_default()
+}
+
+/// Options for [`search_lines_with`].
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Stop after this many results.
+    pub max_results: usize,
+    /// Emit one result per match (every match on a line, like `rg -o`)
+    /// with `content` set to the matched text, instead of one result per
+    /// matching line.
+    pub only_matching: bool,
+    /// Only search lines inside these 1-indexed inclusive `(start, end)`
+    /// ranges, numbered like [`GrepMatch::line`]. Ranges may overlap or
+    /// come in any order; empty means every line. The scan seeks from one
+    /// range to the next without splitting or matching the lines in
+    /// between, and stops after the last range.
+    pub line_ranges: Vec<(usize, usize)>,
+    /// Skip files larger than this many bytes, like `rg --max-filesize`.
+    /// Only the whole-file searches in `mmap` consult it; the size is
+    /// checked before mapping and skipped files are reported by path.
+    pub max_file_bytes: Option<u64>,
+    /// Normalize content to NFC before matching, for patterns built with
+    /// [`build_search_mode_nfc`]. Costs a check per search and a copy of
+    /// content that is not already NFC; `offset`, `column` and `content`
+    /// then refer to the normalized text. Binary content still never
+    /// matches.
+    pub normalize_nfc: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            max_results: usize::MAX,
+            only_matching: false,
+            line_ranges: Vec::new(),
+            max_file_bytes: None,
+            normalize_nfc: false,
+        }
+    }
+}
+
+/// Sort `ranges` by start and merge overlapping or adjacent ones, dropping
+/// empty ranges, so a scan can walk them with a single cursor.
+fn normalize_line_ranges(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut sorted: Vec<(usize, usize)> = ranges
+        .iter()
+        .map(|&(start, end)| (start.max(1), end))
+        .filter(|&(start, end)| start <= end)
+        .collect();
+    sorted.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(sorted.len());
+    for (start, end) in sorted {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
     }
-    original[start..end].to_string()
+    merged
+}
+
+/// The 0-indexed numbers and text of the lines inside `ranges`, as
+/// returned by [`normalize_line_ranges`]. Seeks to each range start by
+/// counting newlines with `memchr` instead of splitting the lines in
+/// between, so a narrow range near the end of a large file stays cheap.
+fn ranged_lines<'a>(
+    content: &'a str,
+    ranges: &'a [(usize, usize)],
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    // Byte offset where line `cursor_line` (1-indexed) starts.
+    let mut cursor = 0;
+    let mut cursor_line = 1;
+    ranges.iter().flat_map(move |&(start, end)| {
+        if let Some(skip) = (start - cursor_line).checked_sub(1) {
+            cursor = match memchr::memchr_iter(b'\n', &content.as_bytes()[cursor..]).nth(skip) {
+                Some(newline) => cursor + newline + 1,
+                None => content.len(),
+            };
+        }
+        cursor_line = start;
+        content[cursor..]
+            .lines()
+            .take((end - start).saturating_add(1))
+            .enumerate()
+            .map(move |(i, line)| (start - 1 + i, line))
+    })
 }
 
 /// Search lines of content for matches. Returns up to `max_results` matches.
@@ -103,80 +294,288 @@ pub fn search_lines(
     content: &str,
     search_mode: &SearchMode,
     max_results: usize,
+) -> Vec<GrepMatch> {
+    search_lines_with(
+        file_path,
+        content,
+        search_mode,
+        &SearchOptions {
+            max_results,
+            ..SearchOptions::default()
+        },
+    )
+}
+
+/// [`search_lines`] with the full set of [`SearchOptions`].
+pub fn search_lines_with(
+    file_path: &str,
+    content: &str,
+    search_mode: &SearchMode,
+    options: &SearchOptions,
 ) -> Vec<GrepMatch> {
     use memchr::memmem;
 
+    if options.normalize_nfc {
+        if let Cow::Owned(normalized) = normalize_nfc(content) {
+            let options = SearchOptions {
+                normalize_nfc: false,
+                ..options.clone()
+            };
+            return search_lines_with(file_path, &normalized, search_mode, &options);
+        }
+    }
+
     let mut results = Vec::new();
+    // Byte spans of matches in the current (original-case) line.
+    let mut spans: Vec<(usize, usize)> = Vec::new();
 
-    match search_mode {
-        SearchMode::Literal { pattern } => {
-            let finder = memmem::Finder::new(pattern.as_bytes());
-            for (line_num, line) in content.lines().enumerate() {
-                if results.len() >= max_results {
-                    break;
-                }
-                let line_bytes = line.as_bytes();
-                if let Some(start) = finder.find(line_bytes) {
-                    let end = start + pattern.len();
-                    let match_text = std::str::from_utf8(&line_bytes[start..end])
-                        .unwrap_or("")
-                        .to_string();
-                    results.push(GrepMatch {
-                        file: file_path.to_string(),
-                        line: line_num + 1,
-                        content: line.to_string(),
-                        match_text,
-                    });
-                }
-            }
+    let literal_finder = match search_mode {
+        SearchMode::Literal { pattern } => Some(memmem::Finder::new(pattern.as_bytes())),
+        SearchMode::LiteralIgnoreCase { pattern_lower }
+        | SearchMode::LiteralAsciiIgnoreCase { pattern_lower } => {
+            Some(memmem::Finder::new(pattern_lower.as_bytes()))
+        }
+        SearchMode::Regex(_) => None,
+    };
+    // Reused ASCII-folded copy of the current line.
+    let mut folded: Vec<u8> = Vec::new();
+    let ranges = normalize_line_ranges(&options.line_ranges);
+    let lines: Box<dyn Iterator<Item = (usize, &str)>> = if options.line_ranges.is_empty() {
+        Box::new(content.lines().enumerate())
+    } else {
+        Box::new(ranged_lines(content, &ranges))
+    };
+
+    for (line_num, line) in lines {
+        if results.len() >= options.max_results {
+            break;
         }
-        SearchMode::LiteralIgnoreCase { pattern_lower } => {
-            let finder = memmem::Finder::new(pattern_lower.as_bytes());
-            for (line_num, line) in content.lines().enumerate() {
-                if results.len() >= max_results {
-                    break;
+        spans.clear();
+        let line_bytes = line.as_bytes();
+        // `lines()` yields subslices of `content`, so the pointer distance
+        // is the line's byte offset (robust to both `\n` and `\r\n`).
+        let line_offset = line.as_ptr() as usize - content.as_ptr() as usize;
+
+        match search_mode {
+            SearchMode::Literal { pattern } => {
+                let finder = literal_finder.as_ref().unwrap();
+                for start in finder.find_iter(line_bytes) {
+                    spans.push((start, start + pattern.len()));
+                    if !options.only_matching {
+                        break;
+                    }
                 }
+            }
+            SearchMode::LiteralIgnoreCase { pattern_lower } => {
+                let finder = literal_finder.as_ref().unwrap();
                 let line_lower = line.to_lowercase();
-                if let Some(start) = finder.find(line_lower.as_bytes()) {
+                for start in finder.find_iter(line_lower.as_bytes()) {
                     let end = start + pattern_lower.len();
-                    let match_text = extract_original_match(line, &line_lower, start, end);
-                    results.push(GrepMatch {
-                        file: file_path.to_string(),
-                        line: line_num + 1,
-                        content: line.to_string(),
-                        match_text,
-                    });
+                    match map_lowered_span(line, &line_lower, start, end) {
+                        Some(span) => spans.push(span),
+                        // Unmappable span — still a matching line.
+                        None if !options.only_matching => spans.push((0, 0)),
+                        None => continue,
+                    }
+                    if !options.only_matching {
+                        break;
+                    }
                 }
             }
-        }
-        SearchMode::Regex(regex) => {
-            for (line_num, line) in content.lines().enumerate() {
-                if results.len() >= max_results {
-                    break;
+            SearchMode::LiteralAsciiIgnoreCase { pattern_lower } => {
+                let finder = literal_finder.as_ref().unwrap();
+                folded.clear();
+                folded.extend(line_bytes.iter().map(u8::to_ascii_lowercase));
+                for start in finder.find_iter(&folded) {
+                    spans.push((start, start + pattern_lower.len()));
+                    if !options.only_matching {
+                        break;
+                    }
                 }
-                let line_bytes = line.as_bytes();
-                if let Some(m) = regex.find(line_bytes) {
-                    let match_text = std::str::from_utf8(&line_bytes[m.start()..m.end()])
-                        .unwrap_or("")
-                        .to_string();
-                    results.push(GrepMatch {
-                        file: file_path.to_string(),
-                        line: line_num + 1,
-                        content: line.to_string(),
-                        match_text,
-                    });
+            }
+            SearchMode::Regex(regex) => {
+                for m in regex.find_iter(line_bytes) {
+                    // `rg -o` never prints empty matches; a line-mode
+                    // empty match still counts as a matching line.
+                    if options.only_matching && m.start() == m.end() {
+                        continue;
+                    }
+                    spans.push((m.start(), m.end()));
+                    if !options.only_matching {
+                        break;
+                    }
                 }
             }
         }
+
+        for &(start, end) in &spans {
+            if results.len() >= options.max_results {
+                break;
+            }
+            let match_text = std::str::from_utf8(&line_bytes[start..end])
+                .unwrap_or("")
+                .to_string();
+            let content = if options.only_matching {
+                match_text.clone()
+            } else {
+                line.to_string()
+            };
+            results.push(GrepMatch {
+                file: file_path.to_string(),
+                line: line_num + 1,
+                column: start + 1,
+                offset: line_offset + start,
+                content,
+                match_text,
+            });
+        }
     }
 
     results
 }
 
+/// Search raw file bytes, decoding them first with [`decode_text`].
+///
+/// UTF-16 content is transcoded before lines are split, so a `0x0A` byte
+/// inside a code unit never starts a new line, and a UTF-8 BOM does not
+/// shift columns on line 1. `offset` values index the decoded text.
+/// Binary content yields no matches.
+pub fn search_bytes_with(
+    file_path: &str,
+    content: &[u8],
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> Vec<GrepMatch> {
+    match decode_text(content) {
+        Some(text) => search_lines_with(file_path, &text, search_mode, options),
+        None => Vec::new(),
+    }
+}
+
+/// [`search_bytes_with`] that reports what the lenient version hides:
+/// [`SearchError::EncodingError`] for content that isn't text, and
+/// [`SearchError::Truncated`] (carrying the first `max_results` matches)
+/// when the cap cut off further matches.
+pub fn search_bytes_checked(
+    file_path: &str,
+    content: &[u8],
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> Result<Vec<GrepMatch>, SearchError> {
+    let text = decode_text(content).ok_or_else(|| SearchError::EncodingError {
+        file: file_path.to_string(),
+    })?;
+    // One extra result tells a cut-off search from one that just fit.
+    let probe = SearchOptions {
+        max_results: options.max_results.saturating_add(1),
+        ..options.clone()
+    };
+    let mut matches = search_lines_with(file_path, &text, search_mode, &probe);
+    if matches.len() > options.max_results {
+        matches.truncate(options.max_results);
+        return Err(SearchError::Truncated {
+            max_results: options.max_results,
+            matches,
+        });
+    }
+    Ok(matches)
+}
+
+/// [`search_lines_with`] over raw file bytes read as UTF-8, lossily, with
+/// each match's `offset` and `column` mapped back to byte positions in
+/// `raw`, so they stay byte-exact when invalid UTF-8 was replaced.
+#[cfg(feature = "fs")]
+pub(crate) fn search_lossy_with(
+    file_path: &str,
+    raw: &[u8],
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+) -> Vec<GrepMatch> {
+    let text = String::from_utf8_lossy(raw);
+    let mut matches = search_lines_with(file_path, &text, search_mode, options);
+    if let Cow::Owned(_) = text {
+        let lossy = LossyOffsets::new(raw);
+        for m in &mut matches {
+            let line_start = lossy.to_raw(m.offset - (m.column - 1));
+            m.offset = lossy.to_raw(m.offset);
+            m.column = m.offset - line_start + 1;
+        }
+    }
+    matches
+}
+
+/// Maps byte offsets in `String::from_utf8_lossy(raw)` back to `raw`.
+#[cfg(feature = "fs")]
+struct LossyOffsets {
+    /// `(text_start, raw_start, valid_len)` of each valid run, in order;
+    /// each run is followed in `raw` by the invalid bytes that became one
+    /// U+FFFD (3 bytes) in the text.
+    runs: Vec<(usize, usize, usize)>,
+}
+
+#[cfg(feature = "fs")]
+impl LossyOffsets {
+    fn new(raw: &[u8]) -> Self {
+        let mut runs = Vec::new();
+        let (mut text_pos, mut raw_pos) = (0, 0);
+        for chunk in raw.utf8_chunks() {
+            let valid = chunk.valid().len();
+            runs.push((text_pos, raw_pos, valid));
+            text_pos += valid;
+            raw_pos += valid;
+            if !chunk.invalid().is_empty() {
+                text_pos += char::REPLACEMENT_CHARACTER.len_utf8();
+                raw_pos += chunk.invalid().len();
+            }
+        }
+        Self { runs }
+    }
+
+    /// Raw offset of text offset `pos`. A position inside a replacement
+    /// character maps to the start of the invalid bytes it stands for.
+    fn to_raw(&self, pos: usize) -> usize {
+        let i = self.runs.partition_point(|&(text, _, _)| text <= pos) - 1;
+        let (text, raw, valid) = self.runs[i];
+        raw + (pos - text).min(valid)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn nfc_option_matches_both_forms_of_e_acute() {
+        let precomposed = "caf\u{e9} au lait";
+        let decomposed = "cafe\u{301} au lait";
+        let nfc = SearchOptions {
+            normalize_nfc: true,
+            ..SearchOptions::default()
+        };
+        for pattern in ["caf\u{e9}", "cafe\u{301}"] {
+            let plain = build_search_mode(pattern, false).unwrap();
+            let normalized = build_search_mode_nfc(pattern, false, CaseFolding::Auto).unwrap();
+            for content in [precomposed, decomposed] {
+                let same_form = content.starts_with(pattern);
+                let without =
+                    search_lines_with("a.txt", content, &plain, &SearchOptions::default());
+                assert_eq!(
+                    without.len(),
+                    usize::from(same_form),
+                    "{pattern:?} in {content:?}"
+                );
+                let with = search_lines_with("a.txt", content, &normalized, &nfc);
+                assert_eq!(with.len(), 1, "{pattern:?} in {content:?}");
+                assert_eq!(with[0].content, precomposed);
+            }
+        }
+        let regex = build_search_mode_nfc("caf(e\u{301})+", true, CaseFolding::Auto).unwrap();
+        assert_eq!(
+            search_lines_with("a.txt", "CAF\u{c9}", &regex, &nfc).len(),
+            1
+        );
+    }
+
     #[test]
     fn literal_case_sensitive() {
         let mode = build_search_mode("hello", false).unwrap();
@@ -201,6 +600,64 @@ mod tests {
         assert_eq!(results[1].line, 3);
     }
 
+    #[test]
+    fn line_ranges_limit_scanned_lines() {
+        let content = (1..=12)
+            .map(|i| format!("line {i} TODO"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mode = build_search_mode("TODO", false).unwrap();
+        let lines = |ranges: Vec<(usize, usize)>| -> Vec<usize> {
+            let options = SearchOptions {
+                line_ranges: ranges,
+                ..SearchOptions::default()
+            };
+            search_lines_with("a.txt", &content, &mode, &options)
+                .iter()
+                .map(|m| m.line)
+                .collect()
+        };
+        // Unordered, overlapping ranges; both boundary lines are included.
+        assert_eq!(lines(vec![(10, 11), (2, 3), (3, 4)]), vec![2, 3, 4, 10, 11]);
+        assert_eq!(lines(vec![(5, 5)]), vec![5]);
+        // Empty and out-of-file ranges match nothing; no ranges means all lines.
+        assert_eq!(lines(vec![(6, 5), (20, 30)]), Vec::<usize>::new());
+        assert_eq!(lines(Vec::new()).len(), 12);
+
+        let first = &search_lines_with(
+            "a.txt",
+            &content,
+            &mode,
+            &SearchOptions {
+                line_ranges: vec![(11, 12)],
+                ..SearchOptions::default()
+            },
+        )[0];
+        assert_eq!(first.content, "line 11 TODO");
+        assert_eq!(&content[first.offset..first.offset + 4], "TODO");
+    }
+
+    #[test]
+    fn ranged_lines_match_a_full_scan() {
+        let content = "a\r\n\nb\nc\r\n\nd\n";
+        let all: Vec<(usize, &str)> = content.lines().enumerate().collect();
+        for ranges in [
+            vec![(1, 1)],
+            vec![(2, 3), (5, 6)],
+            vec![(4, usize::MAX)],
+            vec![(1, 2), (6, 9)],
+            vec![(7, 8)],
+        ] {
+            let expected: Vec<_> = all
+                .iter()
+                .copied()
+                .filter(|&(i, _)| ranges.iter().any(|&(s, e)| s <= i + 1 && i < e))
+                .collect();
+            let ranges = normalize_line_ranges(&ranges);
+            assert_eq!(ranged_lines(content, &ranges).collect::<Vec<_>>(), expected);
+        }
+    }
+
     #[test]
     fn regex_search() {
         let mode = build_search_mode(r"fn\s+\w+", false).unwrap();
@@ -215,6 +672,63 @@ mod tests {
         assert_eq!(results[1].match_text, "fn helper");
     }
 
+    #[test]
+    fn offset_slices_buffer_back_to_match_text() {
+        let content = "fn main() {\r\n  let x = 1;\n}\nlet y = x;\n\u{4f60}\u{597d} let";
+        for (pattern, ignore_case) in [("let", false), ("LET", true), (r"let \w", false)] {
+            let mode = build_search_mode(pattern, ignore_case).unwrap();
+            let options = SearchOptions {
+                only_matching: true,
+                ..SearchOptions::default()
+            };
+            let results = search_lines_with("test.rs", content, &mode, &options);
+            assert!(!results.is_empty());
+            for m in &results {
+                let end = m.offset + m.match_text.len();
+                assert_eq!(&content[m.offset..end], m.match_text, "{pattern}");
+            }
+        }
+
+        let mode = build_search_mode("let", false).unwrap();
+        let offsets: Vec<usize> = search_lines("test.rs", content, &mode, 100)
+            .iter()
+            .map(|m| m.offset)
+            .collect();
+        assert_eq!(offsets, vec![15, 28, 46]);
+    }
+
+    #[test]
+    fn utf16_line_numbers_count_code_point_newlines() {
+        // U+010A and U+0A0A both carry a 0x0A byte in UTF-16LE.
+        let text = "\u{010A} head\n\u{0A0A} mid\nTODO here\n";
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+
+        let todo: Vec<u8> = "TODO"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        let at = memchr::memmem::find(&bytes, &todo).unwrap();
+        let naive_line = 1 + memchr::memchr_iter(b'\n', &bytes[..at]).count();
+        assert_eq!(naive_line, 6, "byte scan sees spurious newlines");
+
+        let mode = build_search_mode("TODO", false).unwrap();
+        let results = search_bytes_with("win.txt", &bytes, &mode, &SearchOptions::default());
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].line, 3);
+        assert_eq!(results[0].column, 1);
+        assert_eq!(results[0].content, "TODO here");
+
+        let mut bom_utf8 = b"\xEF\xBB\xBF".to_vec();
+        bom_utf8.extend(b"TODO first");
+        let results = search_bytes_with("a.txt", &bom_utf8, &mode, &SearchOptions::default());
+        assert_eq!((results[0].line, results[0].column), (1, 1));
+        assert!(
+            search_bytes_with("a.bin", b"\x7FELF\0TODO", &mode, &SearchOptions::default())
+                .is_empty()
+        );
+    }
+
     #[test]
     fn empty_content() {
         let mode = build_search_mode("hello", false).unwrap();
@@ -260,6 +774,160 @@ mod tests {
         assert_eq!(results[0].match_text, "\u{0130}B");
     }
 
+    #[test]
+    fn unicode_ignore_case_positions_when_folding_changes_length() {
+        // İ grows when lowercased, ẞ shrinks; matches after them must
+        // still report byte-accurate columns/offsets in the original.
+        let content = "x\n\u{0130}stanbul STRA\u{1E9E}E stra\u{00DF}e";
+        let options = SearchOptions {
+            only_matching: true,
+            ..SearchOptions::default()
+        };
+        let mode = build_search_mode_with("stra\u{00DF}e", true, CaseFolding::Unicode).unwrap();
+        let results = search_lines_with("t", content, &mode, &options);
+        let found: Vec<(usize, usize, &str)> = results
+            .iter()
+            .map(|m| (m.line, m.column, m.match_text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(2, 11, "STRA\u{1E9E}E"), (2, 20, "stra\u{00DF}e")]
+        );
+        for m in &results {
+            assert_eq!(
+                &content[m.offset..m.offset + m.match_text.len()],
+                m.match_text
+            );
+        }
+
+        let mode = build_search_mode("i\u{0307}stanbul", true).unwrap();
+        let results = search_lines_with("t", content, &mode, &options);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "\u{0130}stanbul");
+        assert_eq!((results[0].column, results[0].offset), (1, 2));
+    }
+
+    fn only_matching(pattern: &str, ignore_case: bool, content: &str) -> Vec<GrepMatch> {
+        let mode = build_search_mode(pattern, ignore_case).unwrap();
+        let options = SearchOptions {
+            only_matching: true,
+            ..SearchOptions::default()
+        };
+        search_lines_with("test.txt", content, &mode, &options)
+    }
+
+    #[test]
+    fn only_matching_reports_every_match_on_a_line() {
+        let results = only_matching("TODO", false, "a TODO b TODO c TODO\nnone\nTODO");
+        let found: Vec<(usize, usize, &str)> = results
+            .iter()
+            .map(|m| (m.line, m.column, m.match_text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, 3, "TODO"),
+                (1, 10, "TODO"),
+                (1, 17, "TODO"),
+                (3, 1, "TODO")
+            ]
+        );
+        assert!(results.iter().all(|m| m.content == m.match_text));
+    }
+
+    #[test]
+    fn only_matching_regex_extracts_substrings() {
+        let results = only_matching(
+            r"https?://\S+",
+            false,
+            "see http://a.io and https://b.io/x for details",
+        );
+        let urls: Vec<&str> = results.iter().map(|m| m.match_text.as_str()).collect();
+        assert_eq!(urls, vec!["http://a.io", "https://b.io/x"]);
+        assert_eq!(results[1].column, 21);
+    }
+
+    #[test]
+    fn only_matching_ignore_case_keeps_original_text() {
+        let results = only_matching("hello", true, "Hello hELLO x HELLO");
+        let texts: Vec<&str> = results.iter().map(|m| m.match_text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello", "hELLO", "HELLO"]);
+        let cols: Vec<usize> = results.iter().map(|m| m.column).collect();
+        assert_eq!(cols, vec![1, 7, 15]);
+    }
+
+    #[test]
+    fn only_matching_respects_max_results_per_match() {
+        let mode = build_search_mode("a", false).unwrap();
+        let options = SearchOptions {
+            max_results: 3,
+            only_matching: true,
+            ..SearchOptions::default()
+        };
+        let results = search_lines_with("test.txt", "aaaa\naa", &mode, &options);
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|m| m.line == 1));
+    }
+
+    #[test]
+    fn only_matching_skips_empty_regex_matches() {
+        assert!(only_matching("x*", false, "abc").is_empty());
+        // Line mode still reports the line.
+        let mode = build_search_mode("x*", false).unwrap();
+        assert_eq!(search_lines("test.txt", "abc", &mode, 10).len(), 1);
+    }
+
+    #[test]
+    fn line_mode_reports_first_match_column() {
+        let mode = build_search_mode("b", false).unwrap();
+        let results = search_lines("test.txt", "abcb", &mode, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].column, 2);
+        assert_eq!(results[0].content, "abcb");
+    }
+
+    #[test]
+    fn ascii_folding_is_selected_for_ascii_patterns() {
+        let mode = build_search_mode("Hello", true).unwrap();
+        assert!(matches!(mode, SearchMode::LiteralAsciiIgnoreCase { .. }));
+        let mode = build_search_mode("Grüße", true).unwrap();
+        assert!(matches!(mode, SearchMode::LiteralIgnoreCase { .. }));
+
+        // Same spans as the Unicode path on mixed ASCII/non-ASCII lines.
+        let content = "über HeLLo\nnone\n\u{4f60}hello HELLO\nhélLO";
+        let unicode = build_search_mode_with("hello", true, CaseFolding::Unicode).unwrap();
+        let ascii = build_search_mode_with("hello", true, CaseFolding::Ascii).unwrap();
+        let options = SearchOptions {
+            only_matching: true,
+            ..SearchOptions::default()
+        };
+        let spans = |mode: &SearchMode| -> Vec<(usize, usize, usize, String)> {
+            search_lines_with("t.txt", content, mode, &options)
+                .into_iter()
+                .map(|m| (m.line, m.column, m.offset, m.match_text))
+                .collect()
+        };
+        assert_eq!(spans(&ascii), spans(&unicode));
+        assert_eq!(spans(&ascii).len(), 3);
+    }
+
+    #[test]
+    fn unicode_folding_matches_case_pairs_ascii_folding_skips() {
+        // Kelvin sign (U+212A) lowercases to ASCII 'k'.
+        let content = "273 \u{212A}";
+        let unicode = build_search_mode_with("k", true, CaseFolding::Unicode).unwrap();
+        let results = search_lines("t.txt", content, &unicode, 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].match_text, "\u{212A}");
+
+        let auto = build_search_mode("k", true).unwrap();
+        assert!(search_lines("t.txt", content, &auto, 10).is_empty());
+
+        // Non-ASCII patterns always take the Unicode path.
+        let mode = build_search_mode("ΣΟΦΙΑ", true).unwrap();
+        assert_eq!(search_lines("t.txt", "σοφια", &mode, 10).len(), 1);
+    }
+
     #[test]
     fn unicode_ignore_case_ascii() {
         // Basic ASCII case-insensitive should still work
@@ -268,4 +936,39 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].match_text, "HELLO");
     }
+
+    #[test]
+    fn errors_distinguish_pattern_encoding_and_truncation() {
+        assert!(matches!(
+            build_search_mode("foo(", false),
+            Err(SearchError::InvalidPattern(_))
+        ));
+
+        let mode = build_search_mode("a", false).unwrap();
+        let binary = b"\x00\x01\x02a\x00\xff\x00\x00a\x00";
+        match search_bytes_checked("blob.bin", binary, &mode, &SearchOptions::default()) {
+            Err(SearchError::EncodingError { file }) => assert_eq!(file, "blob.bin"),
+            other => panic!("expected EncodingError, got {other:?}"),
+        }
+        // The lenient entry point still reports binary content as no matches.
+        assert!(search_bytes_with("blob.bin", binary, &mode, &SearchOptions::default()).is_empty());
+
+        let capped = SearchOptions {
+            max_results: 2,
+            ..SearchOptions::default()
+        };
+        match search_bytes_checked("t.txt", b"a\na\na\n", &mode, &capped) {
+            Err(SearchError::Truncated {
+                max_results,
+                matches,
+            }) => {
+                assert_eq!(max_results, 2);
+                assert_eq!(matches.len(), 2);
+            }
+            other => panic!("expected Truncated, got {other:?}"),
+        }
+        // Exactly filling the cap is not truncation.
+        let found = search_bytes_checked("t.txt", b"a\na\n", &mode, &capped).unwrap();
+        assert_eq!(found.len(), 2);
+    }
 }
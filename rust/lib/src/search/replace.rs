@@ -0,0 +1,104 @@
+//! Search-and-replace preview: rewrite buffers in memory, never on disk.
+
+use std::collections::HashMap;
+
+use super::SearchError;
+
+/// Rewritten content of one file and the number of replacements made.
+pub type ReplacePreview = (String, usize);
+
+/// Replace every match of regex `pattern` with `replacement` in each of
+/// `file_contents` (path → content), returning path → (new content,
+/// replacement count) for the files that actually changed.
+///
+/// `replacement` may reference capture groups as `$1`, `${name}`; `$$` is a
+/// literal `$`. Files with no match, or whose content comes out identical,
+/// are left out. An invalid pattern fails with
+/// [`SearchError::InvalidPattern`].
+pub fn grep_replace_preview(
+    pattern: &str,
+    replacement: &str,
+    file_contents: &HashMap<String, String>,
+    ignore_case: bool,
+) -> Result<HashMap<String, ReplacePreview>, SearchError> {
+    let regex = regex::bytes::RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()?;
+
+    let mut previews = HashMap::new();
+    for (path, content) in file_contents {
+        let count = regex.find_iter(content.as_bytes()).count();
+        if count == 0 {
+            continue;
+        }
+        let replaced = regex.replace_all(content.as_bytes(), replacement.as_bytes());
+        if *replaced == *content.as_bytes() {
+            continue;
+        }
+        // Unicode-mode matches land on char boundaries, so this only
+        // substitutes for patterns that opt out with `(?-u)`.
+        let new_content = String::from_utf8_lossy(&replaced).into_owned();
+        previews.insert(path.clone(), (new_content, count));
+    }
+    Ok(previews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(items: &[(&str, &str)]) -> HashMap<String, String> {
+        items
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn capture_references_are_expanded() {
+        let input = files(&[
+            ("a.rs", "let x = foo(1);\nlet y = foo(22);\n"),
+            ("b.rs", "no calls here\n"),
+        ]);
+        let preview =
+            grep_replace_preview(r"foo\((?P<arg>\d+)\)", "bar(${arg}, $1)", &input, false).unwrap();
+        assert_eq!(preview.len(), 1, "zero-match files are excluded");
+        let (content, count) = &preview["a.rs"];
+        assert_eq!(content, "let x = bar(1, 1);\nlet y = bar(22, 22);\n");
+        assert_eq!(*count, 2);
+        // Nothing is written back to the input.
+        assert_eq!(input["a.rs"], "let x = foo(1);\nlet y = foo(22);\n");
+    }
+
+    #[test]
+    fn unchanged_and_case_insensitive() {
+        let input = files(&[("same.txt", "keep keep"), ("mixed.txt", "Todo: x\nTODO: y")]);
+        assert!(grep_replace_preview("keep", "keep", &input, false)
+            .unwrap()
+            .is_empty());
+
+        let preview = grep_replace_preview("todo", "DONE", &input, true).unwrap();
+        assert_eq!(preview["mixed.txt"], ("DONE: x\nDONE: y".to_string(), 2));
+        assert!(grep_replace_preview("todo", "DONE", &input, false)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            grep_replace_preview("(", "x", &input, false),
+            Err(SearchError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn multibyte_content_is_preserved() {
+        let input = files(&[
+            ("ja.md", "名前: 太郎\n名前: 花子\n"),
+            ("emoji.md", "🦀 crab 🦀"),
+        ]);
+        let preview = grep_replace_preview(r"名前: (\w+)", "name=$1", &input, false).unwrap();
+        assert_eq!(preview["ja.md"], ("name=太郎\nname=花子\n".to_string(), 2));
+
+        let preview = grep_replace_preview("🦀", "🐚", &input, false).unwrap();
+        assert_eq!(preview["emoji.md"], ("🐚 crab 🐚".to_string(), 2));
+        assert!(!preview.contains_key("ja.md"));
+    }
+}
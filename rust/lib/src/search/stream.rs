@@ -0,0 +1,266 @@
+//! Windowed streaming grep over files of any size.
+//!
+//! `grep_file_streaming()` reads one file through a fixed window and hands
+//! matches to a callback as it finds them, so memory stays bounded however
+//! large the file is. Files are only ever `read()`, never mapped, so a
+//! file truncated mid-scan just ends early. Behind the `fs` feature (not
+//! WASM-safe).
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use super::grep::GrepMatch;
+use super::{search_lossy_with, SearchMode, SearchOptions};
+
+/// Default window for [`grep_file_streaming`]: 8 MiB.
+pub const STREAM_WINDOW_BYTES: usize = 8 << 20;
+
+/// Summary of a [`grep_file_streaming`] run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamedGrep {
+    /// Matches handed to the callback.
+    pub matches: usize,
+    /// Bytes read from the file before the scan finished or stopped.
+    pub bytes_read: u64,
+    /// Largest buffer held at once: a window plus the partial line
+    /// carried over from the previous one.
+    pub peak_buffer_bytes: usize,
+}
+
+/// Search one file of any size, passing each match to `on_match` as soon
+/// as its window is scanned. Equivalent to
+/// [`grep_file_streaming_windowed`] with [`STREAM_WINDOW_BYTES`].
+pub fn grep_file_streaming<P, F>(
+    path: P,
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+    on_match: F,
+) -> io::Result<StreamedGrep>
+where
+    P: AsRef<Path>,
+    F: FnMut(GrepMatch) -> bool,
+{
+    grep_file_streaming_windowed(path, STREAM_WINDOW_BYTES, search_mode, options, on_match)
+}
+
+/// [`grep_file_streaming`] reading `window_bytes` at a time.
+///
+/// The file is read, not mapped, so only the current window (plus a line
+/// cut off at its end) is ever held; a line longer than the window grows
+/// the buffer until its newline arrives. Each window is cut at its last
+/// newline and the line count is carried across, so `line` and `offset`
+/// are absolute within the file. Content is read as UTF-8, lossily, but
+/// `offset` and `column` count bytes of the file, not of the decoded
+/// text.
+///
+/// `on_match` returns `false` to stop early. `options.max_results` caps
+/// the whole file and `options.line_ranges` use absolute line numbers;
+/// `options.max_file_bytes` is not consulted, as files too big to map
+/// whole are the point.
+pub fn grep_file_streaming_windowed<P, F>(
+    path: P,
+    window_bytes: usize,
+    search_mode: &SearchMode,
+    options: &SearchOptions,
+    mut on_match: F,
+) -> io::Result<StreamedGrep>
+where
+    P: AsRef<Path>,
+    F: FnMut(GrepMatch) -> bool,
+{
+    let path = path.as_ref();
+    let name = path.to_string_lossy();
+    let mut file = File::open(path)?;
+    let window_bytes = window_bytes.max(1);
+
+    let mut summary = StreamedGrep::default();
+    let mut buf: Vec<u8> = Vec::new();
+    // Lines and bytes of the file before `buf[0]`.
+    let (mut base_line, mut base_offset) = (0usize, 0usize);
+    loop {
+        let carried = buf.len();
+        buf.reserve_exact(window_bytes);
+        buf.resize(carried + window_bytes, 0);
+        let read = read_window(&mut file, &mut buf[carried..])?;
+        buf.truncate(carried + read);
+        summary.bytes_read += read as u64;
+        summary.peak_buffer_bytes = summary.peak_buffer_bytes.max(buf.capacity());
+
+        let eof = read < window_bytes;
+        let end = if eof {
+            buf.len()
+        } else {
+            match memchr::memrchr(b'\n', &buf) {
+                Some(last_newline) => last_newline + 1,
+                // One line spans the whole window: keep reading it.
+                None => continue,
+            }
+        };
+        if end == 0 {
+            break;
+        }
+
+        let line_ranges: Vec<(usize, usize)> = options
+            .line_ranges
+            .iter()
+            .filter(|&&(_, last)| last > base_line)
+            .map(|&(first, last)| (first.saturating_sub(base_line), last - base_line))
+            .collect();
+        if !options.line_ranges.is_empty() && line_ranges.is_empty() {
+            break;
+        }
+        let window_options = SearchOptions {
+            max_results: options.max_results - summary.matches,
+            line_ranges,
+            ..options.clone()
+        };
+        let chunk = &buf[..end];
+        for mut m in search_lossy_with(&name, chunk, search_mode, &window_options) {
+            m.line += base_line;
+            m.offset += base_offset;
+            summary.matches += 1;
+            if !on_match(m) {
+                return Ok(summary);
+            }
+        }
+        if eof || summary.matches >= options.max_results {
+            break;
+        }
+
+        base_line += memchr::memchr_iter(b'\n', chunk).count();
+        base_offset += end;
+        buf.drain(..end);
+    }
+    Ok(summary)
+}
+
+/// Fill `buf` from `file`, short only at end of file.
+fn read_window(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{build_search_mode, search_lines_with};
+
+    #[test]
+    fn streaming_matches_whole_file_search_across_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("big.log");
+        let mut content = String::new();
+        for i in 0..20_000 {
+            if i % 97 == 0 {
+                content.push_str(&format!("{i} ERROR at line\r\n"));
+            } else if i % 1_000 == 0 {
+                // A line longer than the window below.
+                content.push_str(&format!("{i} {} ERROR long\n", "x".repeat(5_000)));
+            } else {
+                content.push_str(&format!("{i} info\n"));
+            }
+        }
+        content.push_str("ERROR unterminated");
+        std::fs::write(&log, &content).unwrap();
+        let mode = build_search_mode("ERROR", false).unwrap();
+        let options = SearchOptions::default();
+        let want: Vec<(usize, usize, String)> = search_lines_with("", &content, &mode, &options)
+            .into_iter()
+            .map(|m| (m.line, m.offset, m.content))
+            .collect();
+
+        for window in [7, 4_096, 65_536, STREAM_WINDOW_BYTES] {
+            let mut got = Vec::new();
+            let summary = grep_file_streaming_windowed(&log, window, &mode, &options, |m| {
+                got.push((m.line, m.offset, m.content));
+                true
+            })
+            .unwrap();
+            assert_eq!(got, want, "window {window}");
+            assert_eq!(summary.matches, want.len());
+            assert_eq!(summary.bytes_read, content.len() as u64);
+            let longest_line = content.split('\n').map(str::len).max().unwrap();
+            assert!(
+                summary.peak_buffer_bytes <= window + longest_line + 1,
+                "window {window} held {} bytes",
+                summary.peak_buffer_bytes
+            );
+        }
+        let got = &want[want.len() - 1];
+        assert_eq!((got.0, got.2.as_str()), (20_001, "ERROR unterminated"));
+    }
+
+    #[test]
+    fn offsets_index_raw_bytes_past_invalid_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        let bytes = b"bad \xff\xfe bytes\nx\xc3 ERROR one\n\xff\xff\xff ERROR two\n";
+        std::fs::write(&log, bytes).unwrap();
+        let mode = build_search_mode("ERROR", false).unwrap();
+
+        for window in [5, 4_096] {
+            let mut got = Vec::new();
+            grep_file_streaming_windowed(&log, window, &mode, &SearchOptions::default(), |m| {
+                got.push((m.offset, m.column));
+                true
+            })
+            .unwrap();
+            assert_eq!(got, [(16, 4), (30, 5)], "window {window}");
+            for (offset, _) in got {
+                assert_eq!(&bytes[offset..offset + 5], b"ERROR");
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_honours_limits_and_early_stop() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("app.log");
+        let content: String = (1..=1_000).map(|i| format!("ERROR {i}\n")).collect();
+        std::fs::write(&log, &content).unwrap();
+        let mode = build_search_mode("ERROR", false).unwrap();
+        let lines = |options: &SearchOptions| {
+            let mut lines = Vec::new();
+            grep_file_streaming_windowed(&log, 64, &mode, options, |m| {
+                lines.push(m.line);
+                true
+            })
+            .unwrap();
+            lines
+        };
+
+        let capped = SearchOptions {
+            max_results: 5,
+            ..SearchOptions::default()
+        };
+        assert_eq!(lines(&capped), [1, 2, 3, 4, 5]);
+        let ranged = SearchOptions {
+            line_ranges: vec![(500, 502), (10, 11)],
+            ..SearchOptions::default()
+        };
+        assert_eq!(lines(&ranged), [10, 11, 500, 501, 502]);
+
+        let mut seen = 0;
+        let summary = grep_file_streaming(&log, &mode, &SearchOptions::default(), |_| {
+            seen += 1;
+            seen < 3
+        })
+        .unwrap();
+        assert_eq!((seen, summary.matches), (3, 3));
+
+        let empty = dir.path().join("empty.log");
+        std::fs::write(&empty, b"").unwrap();
+        let summary =
+            grep_file_streaming(&empty, &mode, &SearchOptions::default(), |_| true).unwrap();
+        assert_eq!(summary.matches, 0);
+    }
+}
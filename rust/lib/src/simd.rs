@@ -0,0 +1,1064 @@
+//! Vector similarity kernels and top-k selection.
+//!
+//! Kernels accumulate in fixed-width lanes over `chunks_exact`, which LLVM
+//! autovectorizes to SSE/AVX/NEON/wasm-simd128 without intrinsics — so the
+//! module stays portable and WASM-safe. All functions panic if the two
+//! input vectors differ in length, except the by-name metric dispatcher
+//! [`top_k_similar_f32_metric`], which returns a [`MetricError`].
+//!
+//! The kernels are pure Rust rather than `simsimd` bindings: `simsimd` is
+//! C, so it would need a C toolchain for every target including
+//! `wasm32-unknown-unknown`, which this crate must build for by default.
+//! Everything runs on the calling thread, so top-k results (ties broken
+//! by ascending index) never depend on a parallel threshold.
+//!
+//! Half-precision (`f16`) vectors are passed as raw IEEE 754 binary16 bit
+//! patterns (`u16`) and widened to `f32` lane by lane, so no `half` crate
+//! or target `f16` support is needed.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt;
+
+/// Accumulator lanes per kernel iteration.
+const LANES: usize = 8;
+
+/// Dot product of two `f32` vectors.
+pub fn dot_f32(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len(), "vector length mismatch");
+    let mut acc = [0.0f32; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let (a_rem, b_rem) = (a_chunks.remainder(), b_chunks.remainder());
+    for (ca, cb) in a_chunks.zip(b_chunks) {
+        for ((lane, x), y) in acc.iter_mut().zip(ca).zip(cb) {
+            *lane += x * y;
+        }
+    }
+    let tail: f32 = a_rem.iter().zip(b_rem).map(|(x, y)| x * y).sum();
+    acc.iter().sum::<f32>() + tail
+}
+
+/// Squared Euclidean (L2) distance between two `f32` vectors.
+pub fn euclidean_sq_f32(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len(), "vector length mismatch");
+    let mut acc = [0.0f32; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let (a_rem, b_rem) = (a_chunks.remainder(), b_chunks.remainder());
+    for (ca, cb) in a_chunks.zip(b_chunks) {
+        for ((lane, x), y) in acc.iter_mut().zip(ca).zip(cb) {
+            let d = x - y;
+            *lane += d * d;
+        }
+    }
+    let tail: f32 = a_rem
+        .iter()
+        .zip(b_rem)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum();
+    acc.iter().sum::<f32>() + tail
+}
+
+/// Euclidean (L2) distance between two `f32` vectors.
+pub fn euclidean_f32(a: &[f32], b: &[f32]) -> f32 {
+    euclidean_sq_f32(a, b).sqrt()
+}
+
+/// Cosine similarity of two `f32` vectors.
+///
+/// Returns `0.0` if either vector has zero magnitude.
+pub fn cosine_similarity_f32(a: &[f32], b: &[f32]) -> f32 {
+    let norm = (dot_f32(a, a) * dot_f32(b, b)).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    dot_f32(a, b) / norm
+}
+
+/// Cosine distance (`1 - cosine_similarity`) of two `f32` vectors.
+///
+/// The metric form used by most ANN libraries: `0.0` for identical
+/// direction, `2.0` for opposite. Zero-magnitude inputs yield `1.0`.
+pub fn cosine_distance_f32(a: &[f32], b: &[f32]) -> f32 {
+    1.0 - cosine_similarity_f32(a, b)
+}
+
+/// Dot product of two `i8` vectors, accumulated in `i32`.
+pub fn dot_i8(a: &[i8], b: &[i8]) -> i32 {
+    assert_eq!(a.len(), b.len(), "vector length mismatch");
+    let mut acc = [0i32; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let (a_rem, b_rem) = (a_chunks.remainder(), b_chunks.remainder());
+    for (ca, cb) in a_chunks.zip(b_chunks) {
+        for ((lane, &x), &y) in acc.iter_mut().zip(ca).zip(cb) {
+            *lane += x as i32 * y as i32;
+        }
+    }
+    let tail: i32 = a_rem
+        .iter()
+        .zip(b_rem)
+        .map(|(&x, &y)| x as i32 * y as i32)
+        .sum();
+    acc.iter().sum::<i32>() + tail
+}
+
+/// Cosine similarity of two `i8` (quantized) vectors.
+///
+/// Returns `0.0` if either vector has zero magnitude.
+pub fn cosine_similarity_i8(a: &[i8], b: &[i8]) -> f32 {
+    let norm = ((dot_i8(a, a) as f64) * (dot_i8(b, b) as f64)).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    (dot_i8(a, b) as f64 / norm) as f32
+}
+
+/// Widen an IEEE 754 binary16 bit pattern to `f32` (exact).
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits & 0x8000) as u32) << 16;
+    let exp = (bits >> 10) & 0x1f;
+    let mant = (bits & 0x03ff) as u32;
+    match exp {
+        0 => {
+            // Zero or subnormal: mant * 2^-24, exact in f32.
+            let magnitude = mant as f32 * f32::from_bits(0x3380_0000);
+            f32::from_bits(sign | magnitude.to_bits())
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (mant << 13)),
+        _ => f32::from_bits(sign | ((exp as u32 + 112) << 23) | (mant << 13)),
+    }
+}
+
+/// Narrow an `f32` to an IEEE 754 binary16 bit pattern, rounding to
+/// nearest-even. Out-of-range values become infinity; NaN stays NaN.
+pub fn f32_to_f16(x: f32) -> u16 {
+    let bits = x.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x007f_ffff;
+    if exp == 0xff {
+        let nan = if mant != 0 {
+            0x0200 | (mant >> 13) as u16
+        } else {
+            0
+        };
+        return sign | 0x7c00 | nan;
+    }
+    let e = exp - 127 + 15;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+    // Rounding may carry out of the mantissa into the exponent, which is
+    // exactly the right result (up to and including infinity).
+    let round = |value: u32, rem: u32, half: u32| {
+        value + u32::from(rem > half || (rem == half && value & 1 == 1))
+    };
+    if e <= 0 {
+        if e < -10 {
+            return sign;
+        }
+        let m = mant | 0x0080_0000;
+        let shift = (14 - e) as u32;
+        let rem = m & ((1 << shift) - 1);
+        return sign | round(m >> shift, rem, 1 << (shift - 1)) as u16;
+    }
+    let value = ((e as u32) << 10) | (mant >> 13);
+    sign | round(value, mant & 0x1fff, 0x1000) as u16
+}
+
+/// Reinterpret a little-endian byte buffer as `f16` bit patterns.
+///
+/// Panics if `bytes` has odd length.
+pub fn f16_from_le_bytes(bytes: &[u8]) -> Vec<u16> {
+    assert!(bytes.len().is_multiple_of(2), "f16 buffer has odd length");
+    bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+/// Cosine similarity of two `f16` vectors (IEEE binary16 bits), computed
+/// in `f32`.
+///
+/// Returns `0.0` if either vector has zero magnitude.
+pub fn cosine_similarity_f16(a: &[u16], b: &[u16]) -> f32 {
+    assert_eq!(a.len(), b.len(), "vector length mismatch");
+    // One pass accumulating dot and both squared norms, so each element
+    // is widened once.
+    let mut dot = [0.0f32; LANES];
+    let mut aa = [0.0f32; LANES];
+    let mut bb = [0.0f32; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let (a_rem, b_rem) = (a_chunks.remainder(), b_chunks.remainder());
+    for (ca, cb) in a_chunks.zip(b_chunks) {
+        for lane in 0..LANES {
+            let (x, y) = (f16_to_f32(ca[lane]), f16_to_f32(cb[lane]));
+            dot[lane] += x * y;
+            aa[lane] += x * x;
+            bb[lane] += y * y;
+        }
+    }
+    let (mut dot, mut aa, mut bb) = (
+        dot.iter().sum::<f32>(),
+        aa.iter().sum::<f32>(),
+        bb.iter().sum::<f32>(),
+    );
+    for (&x, &y) in a_rem.iter().zip(b_rem) {
+        let (x, y) = (f16_to_f32(x), f16_to_f32(y));
+        dot += x * y;
+        aa += x * x;
+        bb += y * y;
+    }
+    let norm = (aa * bb).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    dot / norm
+}
+
+/// Cosine similarity of `query` against every vector in `vectors`.
+pub fn batch_cosine_f32(query: &[f32], vectors: &[Vec<f32>]) -> Vec<f32> {
+    vectors
+        .iter()
+        .map(|v| cosine_similarity_f32(query, v))
+        .collect()
+}
+
+/// Cosine similarity of `query` against every vector in `vectors` (`f16`).
+pub fn batch_cosine_f16(query: &[u16], vectors: &[Vec<u16>]) -> Vec<f32> {
+    vectors
+        .iter()
+        .map(|v| cosine_similarity_f16(query, v))
+        .collect()
+}
+
+/// Cosine similarity of two `f32` vectors over the dimensions where `mask`
+/// is `true`, as if the masked-out dimensions did not exist. Their values
+/// are never read into the sums, so they may hold anything (even NaN).
+///
+/// Returns `0.0` if either vector has zero magnitude on the active
+/// dimensions. Panics if `mask` differs in length from the vectors.
+pub fn cosine_similarity_masked_f32(a: &[f32], b: &[f32], mask: &[bool]) -> f32 {
+    assert_eq!(a.len(), b.len(), "vector length mismatch");
+    assert_eq!(a.len(), mask.len(), "mask length mismatch");
+    let mut dot = [0.0f32; LANES];
+    let mut aa = [0.0f32; LANES];
+    let mut bb = [0.0f32; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let m_chunks = mask.chunks_exact(LANES);
+    let (a_rem, b_rem, m_rem) = (
+        a_chunks.remainder(),
+        b_chunks.remainder(),
+        m_chunks.remainder(),
+    );
+    for ((ca, cb), cm) in a_chunks.zip(b_chunks).zip(m_chunks) {
+        for lane in 0..LANES {
+            // Select rather than multiply by the mask, so a NaN in an
+            // inactive dimension cannot leak in.
+            let (x, y) = if cm[lane] {
+                (ca[lane], cb[lane])
+            } else {
+                (0.0, 0.0)
+            };
+            dot[lane] += x * y;
+            aa[lane] += x * x;
+            bb[lane] += y * y;
+        }
+    }
+    let (mut dot, mut aa, mut bb) = (
+        dot.iter().sum::<f32>(),
+        aa.iter().sum::<f32>(),
+        bb.iter().sum::<f32>(),
+    );
+    for ((&x, &y), _) in a_rem.iter().zip(b_rem).zip(m_rem).filter(|(_, &m)| m) {
+        dot += x * y;
+        aa += x * x;
+        bb += y * y;
+    }
+    let norm = (aa * bb).sqrt();
+    if norm == 0.0 {
+        return 0.0;
+    }
+    dot / norm
+}
+
+/// Cosine similarity of `query` against every vector in `vectors`, over
+/// the dimensions where `mask` is `true` (see
+/// [`cosine_similarity_masked_f32`]). Panics if `mask` and `query` differ
+/// in length.
+pub fn batch_cosine_similarity_masked_f32(
+    query: &[f32],
+    vectors: &[Vec<f32>],
+    mask: &[bool],
+) -> Vec<f32> {
+    assert_eq!(query.len(), mask.len(), "mask length mismatch");
+    vectors
+        .iter()
+        .map(|v| cosine_similarity_masked_f32(query, v, mask))
+        .collect()
+}
+
+/// Scale `v` to unit length in place. Zero vectors are left unchanged.
+pub fn normalize_f32(v: &mut [f32]) {
+    let norm = dot_f32(v, v).sqrt();
+    if norm > 0.0 {
+        for x in v.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+/// Dot product of `query` against every vector in `vectors`, for inputs
+/// already normalized with [`normalize_f32`].
+///
+/// **Precondition:** `query` and every vector are unit length (or zero).
+/// Under that precondition the dot product *is* the cosine similarity, so
+/// this skips the two norm computations per pair that
+/// [`batch_cosine_f32`] does. Unnormalized input silently yields
+/// magnitude-scaled scores, not cosines.
+pub fn batch_dot_normalized_f32(query: &[f32], vectors: &[Vec<f32>]) -> Vec<f32> {
+    vectors.iter().map(|v| dot_f32(query, v)).collect()
+}
+
+/// The `k` best [`batch_dot_normalized_f32`] scores as `(index, score)`,
+/// ordered like [`top_k_similar_f32`]. Same unit-length precondition.
+pub fn top_k_dot_normalized_f32(
+    query: &[f32],
+    vectors: &[Vec<f32>],
+    k: usize,
+) -> Vec<(usize, f32)> {
+    top_k_by_score(batch_dot_normalized_f32(query, vectors), k)
+}
+
+/// Euclidean distance from `query` to every vector in `vectors`.
+pub fn batch_euclidean_f32(query: &[f32], vectors: &[Vec<f32>]) -> Vec<f32> {
+    vectors.iter().map(|v| euclidean_f32(query, v)).collect()
+}
+
+/// Cosine distance from `query` to every vector in `vectors`.
+pub fn batch_cosine_distance_f32(query: &[f32], vectors: &[Vec<f32>]) -> Vec<f32> {
+    vectors
+        .iter()
+        .map(|v| cosine_distance_f32(query, v))
+        .collect()
+}
+
+/// Cosine similarity of `query` against every vector in `vectors` (`i8`).
+pub fn batch_cosine_i8(query: &[i8], vectors: &[Vec<i8>]) -> Vec<f32> {
+    vectors
+        .iter()
+        .map(|v| cosine_similarity_i8(query, v))
+        .collect()
+}
+
+/// The `k` vectors most cosine-similar to `query`, as `(index, score)`.
+///
+/// Ordered by score descending, ties broken by index ascending, so the
+/// result is fully deterministic for a given input.
+pub fn top_k_similar_f32(query: &[f32], vectors: &[Vec<f32>], k: usize) -> Vec<(usize, f32)> {
+    top_k_by_score(batch_cosine_f32(query, vectors), k)
+}
+
+/// The `k` vectors most cosine-similar to `query` (`i8`). Same ordering
+/// as [`top_k_similar_f32`].
+pub fn top_k_similar_i8(query: &[i8], vectors: &[Vec<i8>], k: usize) -> Vec<(usize, f32)> {
+    top_k_by_score(batch_cosine_i8(query, vectors), k)
+}
+
+/// The `k` vectors most cosine-similar to `query` (`f16`). Same ordering
+/// as [`top_k_similar_f32`].
+pub fn top_k_similar_f16(query: &[u16], vectors: &[Vec<u16>], k: usize) -> Vec<(usize, f32)> {
+    top_k_by_score(batch_cosine_f16(query, vectors), k)
+}
+
+/// The `k` vectors most cosine-similar to `query` within each group, as
+/// `(group id, top-k)` pairs in ascending group id.
+///
+/// `group_ids[i]` is the group of `vectors[i]`. One pass keeps a bounded
+/// heap per group, so the cost is O(N log k) however many groups there
+/// are. Each group's list holds global indices ordered like
+/// [`top_k_similar_f32`] and is unaffected by other groups' scores.
+///
+/// # Panics
+///
+/// If `group_ids` and `vectors` differ in length.
+pub fn top_k_per_group_f32(
+    query: &[f32],
+    vectors: &[Vec<f32>],
+    group_ids: &[u32],
+    k: usize,
+) -> Vec<(u32, Vec<(usize, f32)>)> {
+    assert_eq!(
+        vectors.len(),
+        group_ids.len(),
+        "one group id per vector required"
+    );
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut heaps: BTreeMap<u32, BinaryHeap<Ranked>> = BTreeMap::new();
+    for (i, (v, &group)) in vectors.iter().zip(group_ids).enumerate() {
+        let entry = Ranked((i, cosine_similarity_f32(query, v)));
+        let heap = heaps.entry(group).or_default();
+        if heap.len() < k {
+            heap.push(entry);
+        } else if heap.peek().is_some_and(|worst| entry < *worst) {
+            heap.pop();
+            heap.push(entry);
+        }
+    }
+    heaps
+        .into_iter()
+        .map(|(group, heap)| {
+            let top = heap.into_sorted_vec().into_iter().map(|r| r.0).collect();
+            (group, top)
+        })
+        .collect()
+}
+
+/// Similarity metric selectable by name in [`top_k_similar_f32_metric`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Cosine similarity; higher is closer.
+    Cosine,
+    /// Raw dot product; higher is closer.
+    Dot,
+    /// Euclidean (L2) distance; lower is closer.
+    Euclidean,
+}
+
+impl Metric {
+    /// Parse `"cosine"`, `"dot"` or `"euclidean"`.
+    pub fn parse(name: &str) -> Result<Self, MetricError> {
+        match name {
+            "cosine" => Ok(Metric::Cosine),
+            "dot" => Ok(Metric::Dot),
+            "euclidean" => Ok(Metric::Euclidean),
+            _ => Err(MetricError::UnknownMetric(name.to_string())),
+        }
+    }
+
+    /// Whether scores under this metric are distances (lower is closer).
+    pub fn is_distance(self) -> bool {
+        matches!(self, Metric::Euclidean)
+    }
+
+    /// Scores of `query` against `vectors`, negated for distances so that
+    /// higher always ranks first under [`rank_order`].
+    fn rank_scores(self, query: &[f32], vectors: &[Vec<f32>]) -> Vec<f32> {
+        match self {
+            Metric::Cosine => batch_cosine_f32(query, vectors),
+            Metric::Dot => batch_dot_normalized_f32(query, vectors),
+            Metric::Euclidean => batch_euclidean_f32(query, vectors)
+                .into_iter()
+                .map(|d| -d)
+                .collect(),
+        }
+    }
+
+    /// Undo the negation applied by [`rank_scores`](Self::rank_scores).
+    fn unrank_score(self, score: f32) -> f32 {
+        if self.is_distance() {
+            -score
+        } else {
+            score
+        }
+    }
+}
+
+/// Errors from [`top_k_similar_f32_metric`], which validates its input
+/// instead of panicking like the fixed-metric kernels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetricError {
+    /// The metric name is not one of `cosine`, `dot`, `euclidean`.
+    UnknownMetric(String),
+    /// `vectors[index]` has a different dimension than the query.
+    DimensionMismatch {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for MetricError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetricError::UnknownMetric(name) => write!(
+                f,
+                "Unknown metric {:?} (expected cosine, dot or euclidean)",
+                name
+            ),
+            MetricError::DimensionMismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Vector {} has dimension {}, expected {}",
+                index, actual, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MetricError {}
+
+/// The `k` vectors closest to `query` under the metric named by `metric`
+/// (`"cosine"`, `"dot"` or `"euclidean"`), as `(index, score)`.
+///
+/// Similarities are ordered descending and distances ascending, ties
+/// broken by index ascending either way; NaN scores rank last. Errors on
+/// an unknown metric name or a vector whose dimension differs from
+/// `query`.
+pub fn top_k_similar_f32_metric(
+    query: &[f32],
+    vectors: &[Vec<f32>],
+    k: usize,
+    metric: &str,
+) -> Result<Vec<(usize, f32)>, MetricError> {
+    let metric = Metric::parse(metric)?;
+    check_dimensions(query, vectors, 0)?;
+    Ok(top_k_by_score(metric.rank_scores(query, vectors), k)
+        .into_iter()
+        .map(|(i, score)| (i, metric.unrank_score(score)))
+        .collect())
+}
+
+/// Fail on the first vector whose dimension differs from `query`;
+/// reported indices are offset by `base`.
+fn check_dimensions(query: &[f32], vectors: &[Vec<f32>], base: usize) -> Result<(), MetricError> {
+    match vectors
+        .iter()
+        .enumerate()
+        .find(|(_, v)| v.len() != query.len())
+    {
+        Some((index, v)) => Err(MetricError::DimensionMismatch {
+            index: base + index,
+            expected: query.len(),
+            actual: v.len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// `(index, rank score)` ordered so a max-heap keeps the worst entry
+/// under [`rank_order`] on top.
+#[derive(Debug, Clone, Copy)]
+struct Ranked((usize, f32));
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        rank_order(&self.0, &other.0)
+    }
+}
+
+/// Most heap slots [`TopKAccumulator::new`] reserves up front; a larger
+/// `k` (e.g. `usize::MAX` for "everything") grows the heap as batches
+/// arrive instead.
+const TOP_K_PREALLOC: usize = 1024;
+
+/// Running top-k over vectors that arrive in batches.
+///
+/// Keeps only the `k` best entries seen so far in a bounded heap, so
+/// each batch is scored once and history is never re-scored. Indices are
+/// global: the first vector of the second batch follows the last of the
+/// first. [`results`](Self::results) matches what
+/// [`top_k_similar_f32_metric`] returns over all vectors at once.
+#[derive(Debug, Clone)]
+pub struct TopKAccumulator {
+    query: Vec<f32>,
+    k: usize,
+    metric: Metric,
+    seen: usize,
+    heap: BinaryHeap<Ranked>,
+}
+
+impl TopKAccumulator {
+    pub fn new(query: Vec<f32>, k: usize, metric: Metric) -> Self {
+        Self {
+            query,
+            k,
+            metric,
+            seen: 0,
+            heap: BinaryHeap::with_capacity(k.min(TOP_K_PREALLOC)),
+        }
+    }
+
+    /// Score `vectors` and fold them into the running top-k. A batch with
+    /// a mismatched dimension is rejected whole, leaving the state as it
+    /// was.
+    pub fn push_batch(&mut self, vectors: &[Vec<f32>]) -> Result<(), MetricError> {
+        check_dimensions(&self.query, vectors, self.seen)?;
+        let scores = self.metric.rank_scores(&self.query, vectors);
+        for (offset, score) in scores.into_iter().enumerate() {
+            let entry = Ranked((self.seen + offset, score));
+            if self.heap.len() < self.k {
+                self.heap.push(entry);
+            } else if self.heap.peek().is_some_and(|worst| entry < *worst) {
+                self.heap.pop();
+                self.heap.push(entry);
+            }
+        }
+        self.seen += vectors.len();
+        Ok(())
+    }
+
+    /// Number of vectors pushed so far.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The current top-k as `(global index, score)`, ordered like
+    /// [`top_k_similar_f32_metric`].
+    pub fn results(&self) -> Vec<(usize, f32)> {
+        self.heap
+            .clone()
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Ranked((i, score))| (i, self.metric.unrank_score(score)))
+            .collect()
+    }
+}
+
+/// Total order for ranked results: higher score first, then lower index.
+///
+/// NaN scores rank below every real score so a degenerate vector can never
+/// displace a genuine match.
+pub fn rank_order(a: &(usize, f32), b: &(usize, f32)) -> Ordering {
+    let key = |s: f32| if s.is_nan() { f32::NEG_INFINITY } else { s };
+    key(b.1).total_cmp(&key(a.1)).then_with(|| a.0.cmp(&b.0))
+}
+
+/// Select the top `k` of `scores` (indexed by position) under [`rank_order`].
+pub fn top_k_by_score(scores: Vec<f32>, k: usize) -> Vec<(usize, f32)> {
+    let mut ranked: Vec<(usize, f32)> = scores.into_iter().enumerate().collect();
+    if k == 0 {
+        return Vec::new();
+    }
+    if k < ranked.len() {
+        // `rank_order` is total (no two entries compare equal), so the
+        // partition is deterministic despite being unstable.
+        ranked.select_nth_unstable_by(k - 1, rank_order);
+        ranked.truncate(k);
+    }
+    ranked.sort_unstable_by(rank_order);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn kernels_match_naive_across_lane_remainders() {
+        for len in [0, 1, 7, 8, 9, 17, 64] {
+            let a: Vec<f32> = (0..len).map(|i| i as f32 * 0.5 - 3.0).collect();
+            let b: Vec<f32> = (0..len).map(|i| 2.0 - i as f32 * 0.25).collect();
+            let dot: f32 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+            let l2: f32 = a.iter().zip(&b).map(|(x, y)| (x - y) * (x - y)).sum();
+            assert!(approx(dot_f32(&a, &b), dot), "dot len={len}");
+            assert!(approx(euclidean_sq_f32(&a, &b), l2), "l2 len={len}");
+
+            let ai: Vec<i8> = (0..len).map(|i| (i as i8).wrapping_mul(7)).collect();
+            let bi: Vec<i8> = (0..len).map(|i| 5 - i as i8).collect();
+            let doti: i32 = ai.iter().zip(&bi).map(|(&x, &y)| x as i32 * y as i32).sum();
+            assert_eq!(dot_i8(&ai, &bi), doti, "dot_i8 len={len}");
+        }
+    }
+
+    #[test]
+    fn cosine_basics() {
+        assert!(approx(cosine_similarity_f32(&[1.0, 0.0], &[2.0, 0.0]), 1.0));
+        assert!(approx(cosine_similarity_f32(&[1.0, 0.0], &[0.0, 3.0]), 0.0));
+        assert!(approx(
+            cosine_similarity_f32(&[1.0, 1.0], &[-1.0, -1.0]),
+            -1.0
+        ));
+        assert_eq!(cosine_similarity_f32(&[0.0, 0.0], &[1.0, 2.0]), 0.0);
+        assert!(approx(cosine_similarity_i8(&[10, 0], &[127, 0]), 1.0));
+        assert_eq!(cosine_similarity_i8(&[0, 0], &[1, 2]), 0.0);
+    }
+
+    #[test]
+    fn euclidean_is_sqrt_of_squared() {
+        let pairs: [(&[f32], &[f32]); 4] = [
+            (&[0.0, 0.0], &[3.0, 4.0]),
+            (&[1.5, -2.0, 7.25], &[-0.5, 3.0, 1.0]),
+            (&[1.0; 19], &[-1.0; 19]),
+            (&[], &[]),
+        ];
+        for (a, b) in pairs {
+            let expected = euclidean_sq_f32(a, b).sqrt();
+            assert!(approx(euclidean_f32(a, b), expected), "{a:?} vs {b:?}");
+        }
+        assert!(approx(euclidean_f32(&[0.0, 0.0], &[3.0, 4.0]), 5.0));
+
+        let query = [1.0, 2.0, 3.0];
+        let vectors = vec![vec![1.0, 2.0, 3.0], vec![4.0, 6.0, 3.0]];
+        assert_eq!(batch_euclidean_f32(&query, &vectors), vec![0.0, 5.0]);
+    }
+
+    #[test]
+    fn cosine_distance_is_one_minus_similarity() {
+        assert!(approx(cosine_distance_f32(&[1.0, 0.0], &[2.0, 0.0]), 0.0));
+        assert!(approx(cosine_distance_f32(&[1.0, 0.0], &[0.0, 1.0]), 1.0));
+        assert!(approx(cosine_distance_f32(&[1.0, 1.0], &[-1.0, -1.0]), 2.0));
+        assert!(approx(cosine_distance_f32(&[0.0, 0.0], &[1.0, 1.0]), 1.0));
+
+        let query = [0.3, -1.2, 2.0];
+        let vectors = vec![vec![1.0, 1.0, 1.0], vec![-0.3, 1.2, -2.0]];
+        let distances = batch_cosine_distance_f32(&query, &vectors);
+        for (d, s) in distances.iter().zip(batch_cosine_f32(&query, &vectors)) {
+            assert!(approx(*d, 1.0 - s));
+        }
+    }
+
+    #[test]
+    fn normalized_dot_matches_cosine_for_unit_vectors() {
+        let mut query = vec![0.3, -1.2, 2.0, 0.0, 5.5, -0.25, 1.0, 3.0, -2.0];
+        let mut vectors: Vec<Vec<f32>> = (0..12)
+            .map(|i| {
+                (0..9)
+                    .map(|j| ((i * 7 + j * 3) % 11) as f32 - 5.0)
+                    .collect()
+            })
+            .collect();
+        let expected_cos = batch_cosine_f32(&query, &vectors);
+        let expected_top = top_k_similar_f32(&query, &vectors, 5);
+
+        normalize_f32(&mut query);
+        for v in &mut vectors {
+            normalize_f32(v);
+            assert!(approx(dot_f32(v, v), 1.0));
+        }
+        let dots = batch_dot_normalized_f32(&query, &vectors);
+        for (d, c) in dots.iter().zip(&expected_cos) {
+            assert!(approx(*d, *c), "{d} vs {c}");
+        }
+        let top = top_k_dot_normalized_f32(&query, &vectors, 5);
+        let indices = |r: &[(usize, f32)]| r.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        assert_eq!(indices(&top), indices(&expected_top));
+    }
+
+    #[test]
+    fn normalize_leaves_zero_vector_alone() {
+        let mut zero = [0.0f32; 4];
+        normalize_f32(&mut zero);
+        assert_eq!(zero, [0.0; 4]);
+        let mut v = [3.0f32, 4.0];
+        normalize_f32(&mut v);
+        assert!(approx(v[0], 0.6) && approx(v[1], 0.8));
+    }
+
+    #[test]
+    fn f16_conversion_round_trips() {
+        for (value, bits) in [
+            (0.0f32, 0x0000u16),
+            (-0.0, 0x8000),
+            (1.0, 0x3c00),
+            (-2.5, 0xc100),
+            (65504.0, 0x7bff),
+            (f32::INFINITY, 0x7c00),
+            (2f32.powi(-24), 0x0001),
+            (2f32.powi(-14), 0x0400),
+        ] {
+            assert_eq!(f32_to_f16(value), bits, "{value}");
+            assert_eq!(f16_to_f32(bits).to_bits(), value.to_bits(), "{bits:#06x}");
+        }
+        // Every finite bit pattern survives a widen/narrow round trip.
+        for bits in (0..=u16::MAX).filter(|b| b & 0x7c00 != 0x7c00) {
+            assert_eq!(f32_to_f16(f16_to_f32(bits)), bits);
+        }
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+        // Ties round to even: 1 + 2^-11 sits halfway between 1 and 1 + 2^-10.
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(
+            f16_from_le_bytes(&[0x00, 0x3c, 0x00, 0xc1]),
+            [0x3c00, 0xc100]
+        );
+    }
+
+    #[test]
+    fn f16_cosine_tracks_f32_cosine() {
+        let source: Vec<Vec<f32>> = (0..16)
+            .map(|i| {
+                (0..37)
+                    .map(|j| (((i * 13 + j * 7) % 23) as f32 - 11.0) * 0.37)
+                    .collect()
+            })
+            .collect();
+        let to_f16 = |v: &[f32]| v.iter().map(|&x| f32_to_f16(x)).collect::<Vec<u16>>();
+        let query = &source[0];
+        let query_f16 = to_f16(query);
+        let vectors_f16: Vec<Vec<u16>> = source.iter().map(|v| to_f16(v)).collect();
+
+        let expected = batch_cosine_f32(query, &source);
+        let got = batch_cosine_f16(&query_f16, &vectors_f16);
+        for (g, e) in got.iter().zip(&expected) {
+            assert!((g - e).abs() < 1e-3, "{g} vs {e}");
+        }
+        let top = top_k_similar_f16(&query_f16, &vectors_f16, 3);
+        assert_eq!(top[0].0, 0);
+        assert_eq!(cosine_similarity_f16(&[0, 0], &[0x3c00, 0x3c00]), 0.0);
+    }
+
+    #[test]
+    fn masked_cosine_ignores_inactive_dimensions() {
+        let query = [1.0, 2.0, 0.0, 3.0, 1.0, 1.0, 1.0, 1.0, 2.0, 5.0];
+        let vectors = vec![
+            vec![1.0, 2.0, 9.0, 3.0, 1.0, 1.0, 1.0, 1.0, 2.0, -4.0],
+            vec![-1.0, 0.5, 9.0, 2.0, 0.0, 1.0, 3.0, 1.0, 1.0, f32::NAN],
+        ];
+        let all = [true; 10];
+        assert_eq!(
+            batch_cosine_similarity_masked_f32(&query, &vectors[..1], &all),
+            batch_cosine_f32(&query, &vectors[..1])
+        );
+
+        // Masking dimensions 2 and 9 (one in a full chunk, one in the
+        // tail) equals cosine over the vectors with those entries dropped.
+        let mut mask = all;
+        mask[2] = false;
+        mask[9] = false;
+        let drop = |v: &[f32]| -> Vec<f32> {
+            v.iter()
+                .enumerate()
+                .filter(|&(i, _)| mask[i])
+                .map(|(_, &x)| x)
+                .collect()
+        };
+        let masked = batch_cosine_similarity_masked_f32(&query, &vectors, &mask);
+        for (got, v) in masked.iter().zip(&vectors) {
+            assert!(approx(*got, cosine_similarity_f32(&drop(&query), &drop(v))));
+        }
+        // The first vector only differs from the query where it is masked.
+        assert!(approx(masked[0], 1.0));
+        assert!(cosine_similarity_f32(&query, &vectors[0]) < 0.9);
+
+        // No active dimensions: zero magnitude.
+        assert_eq!(
+            cosine_similarity_masked_f32(&query, &vectors[0], &[false; 10]),
+            0.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "mask length mismatch")]
+    fn masked_cosine_rejects_short_mask() {
+        batch_cosine_similarity_masked_f32(&[1.0, 2.0], &[vec![1.0, 2.0]], &[true]);
+    }
+
+    #[test]
+    #[should_panic(expected = "vector length mismatch")]
+    fn length_mismatch_panics() {
+        dot_f32(&[1.0], &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn top_k_ties_are_index_ordered() {
+        let query = [1.0, 0.0];
+        // Indices 1, 3, 4 tie at 1.0; 0 and 5 tie at 0.0.
+        let vectors = vec![
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![1.0, 1.0],
+            vec![2.0, 0.0],
+            vec![5.0, 0.0],
+            vec![0.0, 2.0],
+        ];
+        let top = top_k_similar_f32(&query, &vectors, 4);
+        let indices: Vec<usize> = top.iter().map(|&(i, _)| i).collect();
+        assert_eq!(indices, vec![1, 3, 4, 2]);
+
+        // Every cut through a tie group keeps the lowest indices.
+        for k in 0..=vectors.len() + 1 {
+            let expected: Vec<usize> = [1, 3, 4, 2, 0, 5].into_iter().take(k).collect();
+            let got: Vec<usize> = top_k_similar_f32(&query, &vectors, k)
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect();
+            assert_eq!(got, expected, "k={k}");
+        }
+    }
+
+    #[test]
+    fn top_k_all_tied_large_input_is_index_ordered() {
+        let vectors: Vec<Vec<i8>> = (0..10_000).map(|_| vec![3, 4]).collect();
+        let top = top_k_similar_i8(&[3, 4], &vectors, 50);
+        let indices: Vec<usize> = top.iter().map(|&(i, _)| i).collect();
+        assert_eq!(indices, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn top_k_ranks_nan_last() {
+        let top = top_k_by_score(vec![f32::NAN, 0.5, f32::NAN, -1.0], 3);
+        let indices: Vec<usize> = top.iter().map(|&(i, _)| i).collect();
+        assert_eq!(indices, vec![1, 3, 0]);
+    }
+
+    #[test]
+    fn metric_dispatch_matches_dedicated_rankings() {
+        let query = vec![0.5, -1.0, 2.0, 0.25];
+        let vectors: Vec<Vec<f32>> = (0..12)
+            .map(|i| {
+                let t = i as f32;
+                vec![t * 0.3 - 1.0, 2.0 - t * 0.5, (t * 0.7).sin(), t * 0.1]
+            })
+            .collect();
+
+        let cosine = top_k_similar_f32_metric(&query, &vectors, 5, "cosine").unwrap();
+        assert_eq!(cosine, top_k_similar_f32(&query, &vectors, 5));
+
+        let dot = top_k_similar_f32_metric(&query, &vectors, 5, "dot").unwrap();
+        let mut expected: Vec<(usize, f32)> = vectors
+            .iter()
+            .map(|v| dot_f32(&query, v))
+            .enumerate()
+            .collect();
+        expected.sort_by(rank_order);
+        expected.truncate(5);
+        assert_eq!(dot, expected);
+
+        let l2 = top_k_similar_f32_metric(&query, &vectors, 5, "euclidean").unwrap();
+        let mut expected: Vec<(usize, f32)> = batch_euclidean_f32(&query, &vectors)
+            .into_iter()
+            .enumerate()
+            .collect();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        expected.truncate(5);
+        assert_eq!(l2, expected);
+        assert!(l2.windows(2).all(|w| w[0].1 <= w[1].1));
+    }
+
+    #[test]
+    fn metric_dispatch_validates_input() {
+        let vectors = vec![vec![1.0, 0.0], vec![1.0, 0.0, 0.0]];
+        assert_eq!(
+            top_k_similar_f32_metric(&[1.0, 0.0], &vectors, 1, "manhattan"),
+            Err(MetricError::UnknownMetric("manhattan".to_string()))
+        );
+        assert_eq!(
+            top_k_similar_f32_metric(&[1.0, 0.0], &vectors, 1, "dot"),
+            Err(MetricError::DimensionMismatch {
+                index: 1,
+                expected: 2,
+                actual: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn accumulator_matches_one_shot_top_k() {
+        let query = vec![0.5, -1.0, 2.0, 0.25];
+        let vectors: Vec<Vec<f32>> = (0..40)
+            .map(|i| {
+                let t = i as f32;
+                vec![
+                    (t * 0.37).cos(),
+                    2.0 - t * 0.05,
+                    (t * 0.7).sin(),
+                    (i % 3) as f32,
+                ]
+            })
+            .collect();
+        for (name, metric) in [
+            ("cosine", Metric::Cosine),
+            ("dot", Metric::Dot),
+            ("euclidean", Metric::Euclidean),
+        ] {
+            for k in [0, 1, 5, 40, 100] {
+                let mut acc = TopKAccumulator::new(query.clone(), k, metric);
+                for batch in vectors.chunks(7) {
+                    acc.push_batch(batch).unwrap();
+                }
+                let expected = top_k_similar_f32_metric(&query, &vectors, k, name).unwrap();
+                assert_eq!(acc.results(), expected, "{name} k={k}");
+                assert_eq!(acc.seen(), 40);
+            }
+        }
+    }
+
+    #[test]
+    fn accumulator_rejects_bad_batch_whole() {
+        let mut acc = TopKAccumulator::new(vec![1.0, 0.0], 2, Metric::Cosine);
+        acc.push_batch(&[vec![1.0, 0.0]]).unwrap();
+        assert_eq!(
+            acc.push_batch(&[vec![0.0, 1.0], vec![1.0]]),
+            Err(MetricError::DimensionMismatch {
+                index: 2,
+                expected: 2,
+                actual: 1,
+            })
+        );
+        assert_eq!(acc.seen(), 1);
+        assert_eq!(acc.results(), vec![(0, 1.0)]);
+    }
+
+    #[test]
+    fn accumulator_accepts_unbounded_k() {
+        let mut acc = TopKAccumulator::new(vec![1.0, 0.0], usize::MAX, Metric::Cosine);
+        acc.push_batch(&[vec![0.0, 1.0], vec![1.0, 0.0]]).unwrap();
+        assert_eq!(acc.results(), vec![(1, 1.0), (0, 0.0)]);
+    }
+
+    #[test]
+    fn top_k_per_group_matches_filtered_top_k() {
+        let query = [1.0, 0.5];
+        let vectors: Vec<Vec<f32>> = (0..40)
+            .map(|i| vec![(i as f32 * 0.37).sin(), (i as f32 * 0.91).cos()])
+            .collect();
+        // Group 7 gets a single member; groups 1 and 3 split the rest.
+        let group_ids: Vec<u32> = (0..40)
+            .map(|i| if i == 25 { 7 } else { 1 + 2 * (i % 2) })
+            .collect();
+
+        let grouped = top_k_per_group_f32(&query, &vectors, &group_ids, 3);
+        let groups: Vec<u32> = grouped.iter().map(|(g, _)| *g).collect();
+        assert_eq!(groups, [1, 3, 7]);
+        for (group, top) in &grouped {
+            let members: Vec<usize> = (0..vectors.len())
+                .filter(|&i| group_ids[i] == *group)
+                .collect();
+            let subset: Vec<Vec<f32>> = members.iter().map(|&i| vectors[i].clone()).collect();
+            let expected: Vec<(usize, f32)> = top_k_similar_f32(&query, &subset, 3)
+                .into_iter()
+                .map(|(i, score)| (members[i], score))
+                .collect();
+            assert_eq!(top, &expected, "group {group}");
+        }
+        assert_eq!(grouped[2].1.len(), 1);
+
+        // Making one group's vectors score perfectly leaves the other alone.
+        let mut boosted = vectors.clone();
+        for (v, &g) in boosted.iter_mut().zip(&group_ids) {
+            if g == 3 {
+                *v = vec![1.0, 0.5];
+            }
+        }
+        let again = top_k_per_group_f32(&query, &boosted, &group_ids, 3);
+        assert_eq!(again[0], grouped[0]);
+        assert_eq!(
+            again[1].1.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            [1, 3, 5]
+        );
+        assert!(top_k_per_group_f32(&query, &vectors, &group_ids, 0).is_empty());
+    }
+}
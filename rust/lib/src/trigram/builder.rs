@@ -1,9 +1,13 @@
 //! Trigram index builder — accumulates files and their trigrams.
 
+use std::borrow::Cow;
+
 use ahash::{AHashMap, AHashSet};
 use roaring::RoaringBitmap;
 
 use super::extract::is_binary;
+use super::format::{FILE_ENTRY_SIZE, TRIGRAM_ENTRY_SIZE};
+use super::posting::PostingList;
 
 /// Maximum content size for indexing (1 GB).
 const MAX_INDEX_FILE_SIZE: usize = 1024 * 1024 * 1024;
@@ -15,10 +19,36 @@ pub struct FileEntry {
     pub path: String,
 }
 
+/// Garbage left behind by [`TrigramIndexBuilder::remove_file`], as
+/// reported by [`TrigramIndexBuilder::index_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    /// Registered files not removed.
+    pub live_docs: u32,
+    /// Removed files still holding ids and posting entries.
+    pub tombstoned_docs: u32,
+    /// Posting lists held, including ones that only name removed files.
+    pub posting_lists: u32,
+    /// File ids across all posting lists, tombstoned ones included.
+    pub posting_entries: u64,
+    /// Posting entries that name a removed file.
+    pub tombstoned_entries: u64,
+    /// Estimated bytes [`TrigramIndexBuilder::compact`] would save in
+    /// memory and in the serialized index: the shrink of every posting
+    /// list's serialized form, plus the table entries of removed files
+    /// and emptied posting lists.
+    pub reclaimable_bytes: u64,
+}
+
 /// Builder for constructing a trigram index in memory.
 ///
 /// Accumulates files and their trigrams, then serializes to the binary format
 /// via `writer::write_index()`.
+///
+/// Removing a file only tombstones its id: posting lists keep the id
+/// (masked out of every read) until [`compact`](Self::compact) rewrites
+/// them, so an index under steady add/remove churn should be compacted
+/// whenever [`index_stats`](Self::index_stats) shows enough garbage.
 #[derive(Debug)]
 pub struct TrigramIndexBuilder {
     /// Registered files in insertion order.
@@ -27,6 +57,8 @@ pub struct TrigramIndexBuilder {
     posting_lists: AHashMap<[u8; 3], RoaringBitmap>,
     /// Next file ID to assign.
     next_file_id: u32,
+    /// IDs of removed files not yet compacted away.
+    tombstones: RoaringBitmap,
 }
 
 impl TrigramIndexBuilder {
@@ -36,6 +68,7 @@ impl TrigramIndexBuilder {
             files: Vec::new(),
             posting_lists: AHashMap::new(),
             next_file_id: 0,
+            tombstones: RoaringBitmap::new(),
         }
     }
 
@@ -105,35 +138,161 @@ impl TrigramIndexBuilder {
         }
     }
 
-    /// Number of files in the index.
+    /// Tombstone the first live file registered under `path`. Returns
+    /// `false` if there is none.
+    ///
+    /// The file disappears from every read at once; its id and posting
+    /// entries linger until [`compact`](Self::compact).
+    pub fn remove_file(&mut self, path: &str) -> bool {
+        let found = self
+            .files
+            .iter()
+            .find(|f| f.path == path && !self.tombstones.contains(f.file_id));
+        match found {
+            Some(entry) => self.tombstones.insert(entry.file_id),
+            None => false,
+        }
+    }
+
+    /// Number of live (not removed) files in the index.
     pub fn file_count(&self) -> u32 {
-        self.files
+        let total: u32 = self
+            .files
             .len()
             .try_into()
-            .expect("trigram index has more than u32::MAX files")
+            .expect("trigram index has more than u32::MAX files");
+        total - self.tombstones.len() as u32
     }
 
-    /// Number of unique trigrams in the index.
+    /// Number of unique trigrams with at least one live file.
     pub fn trigram_count(&self) -> u32 {
-        self.posting_lists
-            .len()
+        let count = if self.tombstones.is_empty() {
+            self.posting_lists.len()
+        } else {
+            self.posting_lists
+                .values()
+                .filter(|files| !files.is_subset(&self.tombstones))
+                .count()
+        };
+        count
             .try_into()
             .expect("trigram index has more than u32::MAX unique trigrams")
     }
 
-    /// Get the file entries (for serialization).
+    /// All registered file entries, removed ones included.
     pub fn files(&self) -> &[FileEntry] {
         &self.files
     }
 
+    /// Live file entries in id order (for serialization).
+    pub fn live_files(&self) -> impl Iterator<Item = &FileEntry> {
+        self.files
+            .iter()
+            .filter(|f| !self.tombstones.contains(f.file_id))
+    }
+
+    /// Live files containing `trigram` (empty if it is not indexed).
+    pub fn posting_list(&self, trigram: &[u8; 3]) -> PostingList {
+        match self.posting_lists.get(trigram) {
+            Some(files) => PostingList::from_bitmap(files - &self.tombstones),
+            None => PostingList::new(),
+        }
+    }
+
     /// Get the posting lists (for serialization).
-    /// Returns entries sorted by trigram bytes for binary search.
-    pub fn sorted_posting_lists(&self) -> Vec<([u8; 3], &RoaringBitmap)> {
-        let mut entries: Vec<([u8; 3], &RoaringBitmap)> =
-            self.posting_lists.iter().map(|(k, v)| (*k, v)).collect();
+    /// Returns entries sorted by trigram bytes for binary search, with
+    /// removed files masked out and lists left empty by that dropped.
+    pub fn sorted_posting_lists(&self) -> Vec<([u8; 3], Cow<'_, RoaringBitmap>)> {
+        let mut entries: Vec<([u8; 3], Cow<'_, RoaringBitmap>)> = self
+            .posting_lists
+            .iter()
+            .filter_map(|(trigram, files)| {
+                if self.tombstones.is_disjoint(files) {
+                    Some((*trigram, Cow::Borrowed(files)))
+                } else {
+                    let live = files - &self.tombstones;
+                    (!live.is_empty()).then_some((*trigram, Cow::Owned(live)))
+                }
+            })
+            .collect();
         entries.sort_by_key(|(trigram, _)| *trigram);
         entries
     }
+
+    /// How much of the index is garbage from removed files.
+    ///
+    /// Walks every posting list, so this is a diagnostic, not something
+    /// to call per query.
+    pub fn index_stats(&self) -> IndexStats {
+        let mut stats = IndexStats {
+            live_docs: self.file_count(),
+            tombstoned_docs: self.tombstones.len() as u32,
+            posting_lists: self.posting_lists.len() as u32,
+            ..IndexStats::default()
+        };
+        for files in self.posting_lists.values() {
+            stats.posting_entries += files.len();
+            if self.tombstones.is_disjoint(files) {
+                continue;
+            }
+            let live = files - &self.tombstones;
+            stats.tombstoned_entries += files.len() - live.len();
+            stats.reclaimable_bytes += (files.serialized_size() - live.serialized_size()) as u64;
+            if live.is_empty() {
+                stats.reclaimable_bytes += TRIGRAM_ENTRY_SIZE as u64;
+            }
+        }
+        for entry in &self.files {
+            if self.tombstones.contains(entry.file_id) {
+                stats.reclaimable_bytes += (FILE_ENTRY_SIZE + entry.path.len()) as u64;
+            }
+        }
+        stats
+    }
+
+    /// Drop removed files for good: rewrite every posting list without
+    /// their ids, drop lists left empty, and renumber the remaining files
+    /// densely in their original order.
+    ///
+    /// Queries see the same live files before and after; only file ids
+    /// change, so ids from before a compaction must not be reused.
+    pub fn compact(&mut self) {
+        if self.tombstones.is_empty() {
+            return;
+        }
+        let tombstones = std::mem::take(&mut self.tombstones);
+        // Ids are assigned densely from 0 and compaction keeps them so,
+        // so a live id moves down by the number of removed ids below it.
+        let remap = |id: u32| id - tombstones.rank(id) as u32;
+        self.posting_lists.retain(|_, files| {
+            *files = RoaringBitmap::from_sorted_iter((&*files - &tombstones).iter().map(remap))
+                .expect("remapped ids stay sorted");
+            !files.is_empty()
+        });
+        self.files.retain(|f| !tombstones.contains(f.file_id));
+        for entry in &mut self.files {
+            entry.file_id = remap(entry.file_id);
+        }
+        self.next_file_id = self.files.len() as u32;
+    }
+
+    /// The `top_n` most populous posting lists as `(trigram, doc_count)`,
+    /// largest first (ties by trigram bytes).
+    ///
+    /// Diagnostic for slow queries: a few very common trigrams (runs of
+    /// whitespace, `the`) have posting lists covering most files and are
+    /// candidates for stop-trigrams.
+    pub fn posting_stats(&self, top_n: usize) -> Vec<([u8; 3], u64)> {
+        let mut stats: Vec<([u8; 3], u64)> = self
+            .posting_lists
+            .iter()
+            .map(|(trigram, files)| (*trigram, files.difference_len(&self.tombstones)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        stats.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        stats.truncate(top_n);
+        stats
+    }
 }
 
 impl Default for TrigramIndexBuilder {
@@ -203,6 +362,93 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_posting_stats_ranks_common_trigram_first() {
+        let mut builder = TrigramIndexBuilder::new();
+        for i in 0..20 {
+            // Every file has a run of spaces; the rest is unique per file.
+            builder.add_file(
+                &format!("f{i}.txt"),
+                format!("x{i:03}y    z{i:03}w").as_bytes(),
+            );
+        }
+        builder.add_file("other.txt", b"abcdef");
+
+        let stats = builder.posting_stats(5);
+        assert_eq!(stats.len(), 5);
+        assert_eq!(stats[0], (*b"   ", 20));
+        assert!(stats.windows(2).all(|w| w[0].1 >= w[1].1));
+        // Every trigram shared by all files touches the whitespace run.
+        assert!(stats
+            .iter()
+            .filter(|(_, count)| *count == 20)
+            .all(|(trigram, _)| trigram.contains(&b' ')));
+
+        assert!(builder.posting_stats(0).is_empty());
+        assert_eq!(
+            builder.posting_stats(usize::MAX).len(),
+            builder.trigram_count() as usize
+        );
+    }
+
+    #[test]
+    fn test_compact_drops_removed_files() {
+        use crate::trigram::verify::read_file_table;
+        use crate::trigram::{build_trigram_query, verify_index, write_index};
+
+        let mut builder = TrigramIndexBuilder::new();
+        for i in 0..10 {
+            let content = format!("fn shared() {{}} // unique{i:03}");
+            builder.add_file(&format!("f{i}.rs"), content.as_bytes());
+        }
+        for i in [1, 4, 5, 8] {
+            assert!(builder.remove_file(&format!("f{i}.rs")));
+        }
+        assert!(!builder.remove_file("f1.rs"));
+
+        let paths = |builder: &TrigramIndexBuilder, pattern: &str| -> Vec<String> {
+            build_trigram_query(pattern)
+                .candidates(&mut |t| builder.posting_list(t))
+                .unwrap()
+                .iter()
+                .map(|id| {
+                    let entry = builder.files().iter().find(|f| f.file_id == id);
+                    entry.unwrap().path.clone()
+                })
+                .collect()
+        };
+        let live = ["f0.rs", "f2.rs", "f3.rs", "f6.rs", "f7.rs", "f9.rs"];
+        assert_eq!(paths(&builder, "shared"), live);
+        assert!(paths(&builder, "unique004").is_empty());
+
+        let before = builder.index_stats();
+        assert_eq!((before.live_docs, before.tombstoned_docs), (6, 4));
+        assert!(before.tombstoned_entries > 0);
+        assert!(before.reclaimable_bytes > 0);
+        // Serializing already leaves the removed files out.
+        let bytes = write_index(&builder).unwrap();
+        assert!(verify_index(&bytes).is_ok());
+        assert_eq!(read_file_table(&bytes).unwrap().len(), 6);
+
+        builder.compact();
+        let after = builder.index_stats();
+        assert_eq!((after.live_docs, after.tombstoned_docs), (6, 0));
+        assert_eq!(
+            after.posting_entries,
+            before.posting_entries - before.tombstoned_entries
+        );
+        assert!(after.posting_lists < before.posting_lists);
+        assert_eq!((after.tombstoned_entries, after.reclaimable_bytes), (0, 0));
+        let ids: Vec<u32> = builder.files().iter().map(|f| f.file_id).collect();
+        assert_eq!(ids, (0..6).collect::<Vec<_>>());
+
+        assert_eq!(paths(&builder, "shared"), live);
+        assert_eq!(paths(&builder, "unique009"), ["f9.rs"]);
+        assert!(paths(&builder, "unique004").is_empty());
+        builder.add_file("f10.rs", b"fn shared() {}");
+        assert_eq!(builder.files().last().unwrap().file_id, 6);
+    }
+
     #[test]
     fn test_file_ids_sequential() {
         let mut builder = TrigramIndexBuilder::new();
@@ -10,8 +10,9 @@
 //! - **query** — Build trigram queries from patterns and regex
 //! - **posting** — Posting list operations using Roaring bitmaps
 //! - **format** — Binary index format with CRC32 integrity checks
-//! - **builder** — In-memory index construction
+//! - **builder** — In-memory index construction, removal and compaction
 //! - **writer** — Serialize index to bytes (WASM-safe, no file I/O)
+//! - **verify** — Consistency check and rebuild-from-documents repair
 //! - **error** — Error types
 //!
 //! The I/O layer (mmap, file read/write) lives in `lib::trigram`.
@@ -22,10 +23,12 @@ pub mod extract;
 pub mod format;
 pub mod posting;
 pub mod query;
+pub mod verify;
 pub mod writer;
 
 // Re-export key types for convenience.
-pub use builder::TrigramIndexBuilder;
+pub use builder::{IndexStats, TrigramIndexBuilder};
 pub use error::TrigramError;
 pub use query::{build_trigram_query, TrigramQuery};
+pub use verify::{rebuild_from_docs, verify_index, VerifyReport};
 pub use writer::write_index;
@@ -4,6 +4,7 @@
 //! extract literal substrings from regex patterns for trigram lookup.
 
 use super::extract::extract_trigrams_for_query;
+use super::posting::{intersect, union, PostingList};
 use crate::search::literal::is_literal_pattern;
 
 /// A trigram query representing the set of trigrams needed to match a pattern.
@@ -31,6 +32,35 @@ impl TrigramQuery {
             TrigramQuery::Or(queries) => queries.is_empty() || queries.iter().all(|q| q.is_empty()),
         }
     }
+
+    /// Resolve the query to candidate file IDs.
+    ///
+    /// `lookup` returns the posting list for one trigram (empty if the
+    /// trigram is not indexed). Returns `None` when the query cannot
+    /// filter and every file is a candidate.
+    pub fn candidates<F>(&self, lookup: &mut F) -> Option<PostingList>
+    where
+        F: FnMut(&[u8; 3]) -> PostingList,
+    {
+        match self {
+            TrigramQuery::All => None,
+            TrigramQuery::And(trigrams) if trigrams.is_empty() => None,
+            TrigramQuery::And(trigrams) => {
+                let lists: Vec<PostingList> = trigrams.iter().map(&mut *lookup).collect();
+                Some(intersect(&lists))
+            }
+            TrigramQuery::Or(queries) => {
+                if queries.is_empty() {
+                    return None;
+                }
+                let mut lists = Vec::with_capacity(queries.len());
+                for query in queries {
+                    lists.push(query.candidates(lookup)?);
+                }
+                Some(union(&lists))
+            }
+        }
+    }
 }
 
 /// Build a `TrigramQuery` from a search pattern.
@@ -82,72 +112,33 @@ fn extract_from_hir(hir: &regex_syntax::hir::Hir) -> TrigramQuery {
             }
         }
         HirKind::Concat(subs) => {
-            // Concatenation: collect all literal bytes, extract trigrams.
-            let mut all_bytes = Vec::new();
-            let mut _has_non_literal = false;
-
-            for sub in subs {
-                if let HirKind::Literal(lit) = sub.kind() {
-                    all_bytes.extend_from_slice(&lit.0);
-                } else {
-                    // If we accumulated enough bytes, extract trigrams from them.
-                    if all_bytes.len() >= 3 {
-                        let trigrams = extract_trigrams_for_query_bytes(&all_bytes);
-                        if !trigrams.is_empty() {
-                            // We found useful trigrams from the literal prefix/part.
-                            // Continue to collect more, but mark non-literal seen.
-                        }
-                    }
-                    _has_non_literal = true;
-                    // Try to extract from sub-expression too.
-                    all_bytes.clear();
-                }
-            }
-
-            // Collect trigrams from accumulated literal bytes.
-            let mut all_trigrams = Vec::new();
-
-            // Re-walk to get trigrams from each contiguous literal run.
+            // Every part must match, so AND the contiguous literal runs
+            // with whatever each non-literal part requires.
+            let mut query = TrigramQuery::All;
             let mut run_bytes = Vec::new();
             for sub in subs {
                 if let HirKind::Literal(lit) = sub.kind() {
                     run_bytes.extend_from_slice(&lit.0);
-                } else {
-                    if run_bytes.len() >= 3 {
-                        all_trigrams.extend(extract_trigrams_for_query_bytes(&run_bytes));
-                    }
-                    run_bytes.clear();
-
-                    // Recurse into non-literal sub-expression.
-                    let sub_query = extract_from_hir(sub);
-                    if let TrigramQuery::And(trigrams) = sub_query {
-                        all_trigrams.extend(trigrams);
-                    }
+                    continue;
                 }
+                query = conjoin(query, literal_run_query(&run_bytes));
+                run_bytes.clear();
+                query = conjoin(query, extract_from_hir(sub));
             }
-            if run_bytes.len() >= 3 {
-                all_trigrams.extend(extract_trigrams_for_query_bytes(&run_bytes));
-            }
-
-            if all_trigrams.is_empty() {
-                TrigramQuery::All
-            } else {
-                // Deduplicate.
-                all_trigrams.sort();
-                all_trigrams.dedup();
-                TrigramQuery::And(all_trigrams)
-            }
+            conjoin(query, literal_run_query(&run_bytes))
         }
         HirKind::Alternation(alts) => {
-            // OR: each alternative must have trigrams for effective filtering.
-            let sub_queries: Vec<TrigramQuery> = alts.iter().map(extract_from_hir).collect();
-
-            // If any alternative is All, the whole OR is All (can't filter).
-            if sub_queries.iter().any(|q| q.is_all()) {
-                return TrigramQuery::All;
+            // OR: a document matching any branch must stay a candidate,
+            // so one branch without trigrams makes the whole OR unfilterable.
+            let mut branches = Vec::with_capacity(alts.len());
+            for alt in alts {
+                match extract_from_hir(alt) {
+                    TrigramQuery::All => return TrigramQuery::All,
+                    TrigramQuery::Or(subs) => branches.extend(subs),
+                    and => branches.push(and),
+                }
             }
-
-            TrigramQuery::Or(sub_queries)
+            TrigramQuery::Or(branches)
         }
         HirKind::Repetition(rep) => {
             // For repetitions, we can extract from the sub-pattern.
@@ -164,6 +155,57 @@ fn extract_from_hir(hir: &regex_syntax::hir::Hir) -> TrigramQuery {
     }
 }
 
+/// Upper bound on OR branches produced by distributing AND over OR.
+///
+/// `(a|b)(c|d)(e|f)...` grows multiplicatively; past this cap the extra
+/// factor is dropped, which only widens the candidate set.
+const MAX_OR_BRANCHES: usize = 64;
+
+/// `And` of a contiguous literal run, or `All` if it is under 3 bytes.
+fn literal_run_query(bytes: &[u8]) -> TrigramQuery {
+    let trigrams = extract_trigrams_for_query_bytes(bytes);
+    if trigrams.is_empty() {
+        TrigramQuery::All
+    } else {
+        TrigramQuery::And(trigrams)
+    }
+}
+
+/// AND two queries, keeping the result an OR-of-ANDs.
+fn conjoin(a: TrigramQuery, b: TrigramQuery) -> TrigramQuery {
+    use TrigramQuery::{All, And, Or};
+
+    match (a, b) {
+        (All, q) | (q, All) => q,
+        (And(x), And(y)) => And(merge_trigrams(x, &y)),
+        (And(x), Or(branches)) | (Or(branches), And(x)) => Or(branches
+            .into_iter()
+            .map(|branch| conjoin(And(x.clone()), branch))
+            .collect()),
+        (Or(xs), Or(ys)) => {
+            if xs.len() * ys.len() > MAX_OR_BRANCHES {
+                // Keep the more selective-looking side; dropping a
+                // conjunct can only add candidates, never lose one.
+                return if xs.len() <= ys.len() { Or(xs) } else { Or(ys) };
+            }
+            let mut branches = Vec::with_capacity(xs.len() * ys.len());
+            for x in &xs {
+                for y in &ys {
+                    branches.push(conjoin(x.clone(), y.clone()));
+                }
+            }
+            Or(branches)
+        }
+    }
+}
+
+fn merge_trigrams(mut a: Vec<[u8; 3]>, b: &[[u8; 3]]) -> Vec<[u8; 3]> {
+    a.extend_from_slice(b);
+    a.sort();
+    a.dedup();
+    a
+}
+
 /// Extract trigrams from raw bytes (for regex literal extraction).
 fn extract_trigrams_for_query_bytes(bytes: &[u8]) -> Vec<[u8; 3]> {
     if bytes.len() < 3 {
@@ -254,4 +296,99 @@ mod tests {
         let query = build_trigram_query(".*");
         assert!(query.is_all());
     }
+
+    fn index(docs: &[&str]) -> impl FnMut(&[u8; 3]) -> PostingList {
+        let mut builder = crate::trigram::builder::TrigramIndexBuilder::new();
+        for (i, doc) in docs.iter().enumerate() {
+            builder.add_file(&format!("doc{i}"), doc.as_bytes());
+        }
+        let postings: ahash::AHashMap<[u8; 3], PostingList> = builder
+            .sorted_posting_lists()
+            .into_iter()
+            .map(|(t, bitmap)| (t, PostingList::from_bitmap(bitmap.into_owned())))
+            .collect();
+        move |t| postings.get(t).cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_grouped_alternation_keeps_either_branch() {
+        let docs = [
+            "has foo here",
+            "has bar here",
+            "has baz here",
+            "foo and bar",
+        ];
+        let query = build_trigram_query("(foo|bar)");
+        let candidates = query.candidates(&mut index(&docs)).unwrap();
+        assert_eq!(candidates.to_vec(), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn test_alternation_inside_concat_distributes() {
+        // "pre_(foo|bar)" → Or([And(pre_ + foo), And(pre_ + bar)])
+        let query = build_trigram_query("pre_(foo|bar)");
+        let TrigramQuery::Or(branches) = &query else {
+            panic!("Expected Or query, got {:?}", query);
+        };
+        assert_eq!(branches.len(), 2);
+        for (branch, lit) in branches.iter().zip([b"foo", b"bar"]) {
+            let TrigramQuery::And(trigrams) = branch else {
+                panic!("Expected And branch, got {:?}", branch);
+            };
+            assert!(trigrams.contains(b"pre"));
+            assert!(trigrams.contains(lit));
+        }
+
+        let docs = ["pre_foo", "pre_bar", "foo bar", "pre_baz"];
+        let candidates = query.candidates(&mut index(&docs)).unwrap();
+        assert_eq!(candidates.to_vec(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_alternation_with_unfilterable_branch_matches_all() {
+        for pattern in ["(foo|.*)", "foo|ab", "x(foo|[0-9]+)y"] {
+            let query = build_trigram_query(pattern);
+            assert!(
+                query.candidates(&mut index(&["foo"])).is_none(),
+                "{pattern} should not filter, got {query:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_alternation_never_drops_true_match() {
+        let docs = [
+            "fn main() { foo(); }",
+            "let bar = baz;",
+            "struct Quux { foo: u32 }",
+            "bar_foo_baz",
+            "nothing relevant",
+            "prefix_alpha suffix",
+            "prefix_beta_suffix",
+        ];
+        let patterns = [
+            "(foo|bar)",
+            "foo|bar|baz",
+            "(fn|let) (main|bar)",
+            "prefix_(alpha|beta)(_| )suffix",
+            "(foo|bar)_(foo|baz)",
+            "Q(uux|ux)",
+            "(struct|enum) [A-Z][a-z]+",
+        ];
+        let mut lookup = index(&docs);
+        for pattern in patterns {
+            let re = regex::Regex::new(pattern).unwrap();
+            let candidates = build_trigram_query(pattern).candidates(&mut lookup);
+            for (id, doc) in docs.iter().enumerate() {
+                if re.is_match(doc) {
+                    assert!(
+                        candidates
+                            .as_ref()
+                            .is_none_or(|c| c.to_vec().contains(&(id as u32))),
+                        "{pattern} pruned matching doc {doc:?}"
+                    );
+                }
+            }
+        }
+    }
 }
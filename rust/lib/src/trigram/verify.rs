@@ -0,0 +1,637 @@
+//! Consistency check and rebuild for serialized trigram indexes.
+//!
+//! `verify_index()` walks every structure `write_index()` emits — header,
+//! section CRC32s, table sizes, posting offsets, and posting contents —
+//! and collects every inconsistency it finds instead of stopping at the
+//! first. It never panics on hostile input, so it is safe to run on a
+//! partially-written or bit-rotted file before serving from it.
+//!
+//! When the file table (the doc-id → path map) is intact,
+//! `rebuild_from_docs()` regenerates the whole index from the original
+//! documents.
+
+use std::fmt;
+
+use ahash::AHashSet;
+use roaring::RoaringBitmap;
+
+use super::builder::{FileEntry, TrigramIndexBuilder};
+use super::error::TrigramError;
+use super::format::{
+    IndexHeader, FILE_ENTRY_SIZE, HEADER_SIZE, MAGIC, TRIGRAM_ENTRY_SIZE, VERSION,
+};
+use super::writer::write_index;
+
+/// One of the three CRC-protected sections after the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    FileTable,
+    TrigramTable,
+    Postings,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::FileTable => write!(f, "file table"),
+            Section::TrigramTable => write!(f, "trigram table"),
+            Section::Postings => write!(f, "posting section"),
+        }
+    }
+}
+
+/// A single inconsistency found by [`verify_index`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexIssue {
+    /// Input is shorter than the fixed header.
+    Truncated { len: usize },
+    /// Header does not start with `TRGM`.
+    InvalidMagic,
+    /// Header CRC32 does not match its contents.
+    HeaderCrcMismatch,
+    /// Header names a format version this code cannot read.
+    VersionMismatch { expected: u32, found: u32 },
+    /// Section offsets are out of order or past the end of the input.
+    SectionOutOfBounds {
+        section: Section,
+        start: u64,
+        end: u64,
+    },
+    /// Section size disagrees with the counts in the header.
+    SectionSizeMismatch {
+        section: Section,
+        expected: u64,
+        found: u64,
+    },
+    /// Section CRC32 does not match its contents.
+    SectionCrcMismatch { section: Section },
+    /// A file table entry's path lies outside the path bytes.
+    PathOutOfBounds { file_id: u32 },
+    /// A file table path is not valid UTF-8.
+    InvalidPath { file_id: u32 },
+    /// Two file table entries share an id.
+    DuplicateFileId { file_id: u32 },
+    /// Trigram table entries are not strictly ascending.
+    TrigramsUnsorted { index: u32 },
+    /// A posting list lies outside the posting section.
+    PostingOutOfBounds {
+        trigram: [u8; 3],
+        offset: u32,
+        len: u32,
+    },
+    /// A posting list failed to deserialize.
+    PostingUndecodable { trigram: [u8; 3] },
+    /// A posting list references an id missing from the file table.
+    UnknownFileId { trigram: [u8; 3], file_id: u32 },
+}
+
+impl fmt::Display for IndexIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexIssue::Truncated { len } => {
+                write!(
+                    f,
+                    "index is {} bytes, shorter than the {}-byte header",
+                    len, HEADER_SIZE
+                )
+            }
+            IndexIssue::InvalidMagic => write!(f, "invalid magic bytes in index header"),
+            IndexIssue::HeaderCrcMismatch => write!(f, "header CRC32 mismatch"),
+            IndexIssue::VersionMismatch { expected, found } => {
+                write!(
+                    f,
+                    "version mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            IndexIssue::SectionOutOfBounds {
+                section,
+                start,
+                end,
+            } => write!(f, "{} spans {}..{}, outside the index", section, start, end),
+            IndexIssue::SectionSizeMismatch {
+                section,
+                expected,
+                found,
+            } => write!(f, "{} is {} bytes, expected {}", section, found, expected),
+            IndexIssue::SectionCrcMismatch { section } => write!(f, "{} CRC32 mismatch", section),
+            IndexIssue::PathOutOfBounds { file_id } => {
+                write!(f, "path of file {} lies outside the file table", file_id)
+            }
+            IndexIssue::InvalidPath { file_id } => {
+                write!(f, "path of file {} is not valid UTF-8", file_id)
+            }
+            IndexIssue::DuplicateFileId { file_id } => {
+                write!(f, "file id {} appears more than once", file_id)
+            }
+            IndexIssue::TrigramsUnsorted { index } => {
+                write!(f, "trigram table entry {} is out of order", index)
+            }
+            IndexIssue::PostingOutOfBounds {
+                trigram,
+                offset,
+                len,
+            } => write!(
+                f,
+                "posting list for {:?} at {}+{} lies outside the posting section",
+                trigram, offset, len
+            ),
+            IndexIssue::PostingUndecodable { trigram } => {
+                write!(f, "posting list for {:?} failed to decode", trigram)
+            }
+            IndexIssue::UnknownFileId { trigram, file_id } => write!(
+                f,
+                "posting list for {:?} references unknown file id {}",
+                trigram, file_id
+            ),
+        }
+    }
+}
+
+/// Result of [`verify_index`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// File count from the header (0 if the header is unreadable).
+    pub file_count: u32,
+    /// Trigram count from the header (0 if the header is unreadable).
+    pub trigram_count: u32,
+    /// Whether the file table passed every check, i.e. whether
+    /// [`rebuild_from_docs`] can be used to recover.
+    pub doc_map_intact: bool,
+    /// Every inconsistency found, in file order.
+    pub issues: Vec<IndexIssue>,
+}
+
+impl VerifyReport {
+    /// True when no issues were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Check a serialized index for corruption.
+///
+/// Never panics and never fails: problems are reported in
+/// [`VerifyReport::issues`]. Checks that depend on an unreadable structure
+/// (e.g. posting contents when the trigram table is out of bounds) are
+/// skipped rather than reported twice.
+pub fn verify_index(bytes: &[u8]) -> VerifyReport {
+    let mut report = VerifyReport::default();
+    let Some(header) = check_header(bytes, &mut report.issues) else {
+        return report;
+    };
+    report.file_count = header.file_count;
+    report.trigram_count = header.trigram_count;
+
+    let Some(layout) = Layout::new(&header, bytes.len(), &mut report.issues) else {
+        return report;
+    };
+
+    let file_ids = check_file_table(
+        layout.file_table(bytes),
+        header.file_count,
+        &mut report.issues,
+    );
+    report.doc_map_intact = file_ids.is_some();
+
+    let trigram_table = layout.trigram_table(bytes);
+    let postings = layout.postings(bytes);
+    let trigram_table_ok = check_section(trigram_table, Section::TrigramTable, &mut report.issues);
+    let postings_ok = check_section(postings, Section::Postings, &mut report.issues);
+    if trigram_table_ok && postings_ok {
+        check_postings(
+            &trigram_table[..trigram_table.len() - 4],
+            &postings[..postings.len() - 4],
+            file_ids.as_ref(),
+            &mut report.issues,
+        );
+    }
+    report
+}
+
+/// Decode the file table (doc-id → path map) of a serialized index.
+///
+/// Fails unless the header and the file table both verify cleanly; the
+/// trigram table and posting lists are not consulted.
+pub fn read_file_table(bytes: &[u8]) -> Result<Vec<FileEntry>, TrigramError> {
+    let mut issues = Vec::new();
+    let entries = check_header(bytes, &mut issues)
+        .and_then(|header| {
+            let layout = Layout::new(&header, bytes.len(), &mut issues)?;
+            let table = layout.file_table(bytes);
+            check_file_table(table, header.file_count, &mut issues)?;
+            Some(decode_file_entries(table, header.file_count))
+        })
+        .filter(|_| issues.is_empty());
+    entries.ok_or_else(|| TrigramError::CorruptIndex {
+        reason: format!(
+            "file table unreadable: {}",
+            issues.first().map_or_else(String::new, ToString::to_string)
+        ),
+    })
+}
+
+/// Rebuild a damaged index from its original documents.
+///
+/// Reads the doc-id → path map from `bytes` (which must be intact, see
+/// [`VerifyReport::doc_map_intact`]) and calls `load` for each path in
+/// file-id order. Documents for which `load` returns `None` are dropped
+/// from the rebuilt index; file ids are reassigned densely.
+pub fn rebuild_from_docs<F>(bytes: &[u8], mut load: F) -> Result<Vec<u8>, TrigramError>
+where
+    F: FnMut(&str) -> Option<Vec<u8>>,
+{
+    let mut entries = read_file_table(bytes)?;
+    entries.sort_by_key(|e| e.file_id);
+    let mut builder = TrigramIndexBuilder::new();
+    for entry in &entries {
+        if let Some(content) = load(&entry.path) {
+            builder.add_file(&entry.path, &content);
+        }
+    }
+    write_index(&builder)
+}
+
+fn check_header(bytes: &[u8], issues: &mut Vec<IndexIssue>) -> Option<IndexHeader> {
+    if bytes.len() < HEADER_SIZE {
+        issues.push(IndexIssue::Truncated { len: bytes.len() });
+        return None;
+    }
+    if bytes[0..4] != MAGIC {
+        issues.push(IndexIssue::InvalidMagic);
+        return None;
+    }
+    let Some(header) = IndexHeader::from_bytes(bytes) else {
+        issues.push(IndexIssue::HeaderCrcMismatch);
+        return None;
+    };
+    if header.version != VERSION {
+        issues.push(IndexIssue::VersionMismatch {
+            expected: VERSION,
+            found: header.version,
+        });
+        return None;
+    }
+    Some(header)
+}
+
+/// Validated byte ranges of the three sections (each including its CRC).
+struct Layout {
+    file_table: (usize, usize),
+    trigram_table: (usize, usize),
+    postings: (usize, usize),
+}
+
+impl Layout {
+    fn new(header: &IndexHeader, len: usize, issues: &mut Vec<IndexIssue>) -> Option<Self> {
+        let bounds = [
+            (
+                Section::FileTable,
+                header.file_table_offset,
+                header.trigram_table_offset,
+            ),
+            (
+                Section::TrigramTable,
+                header.trigram_table_offset,
+                header.posting_offset,
+            ),
+            (Section::Postings, header.posting_offset, len as u64),
+        ];
+        let mut ranges = [(0usize, 0usize); 3];
+        let mut ok = true;
+        for (i, &(section, start, end)) in bounds.iter().enumerate() {
+            // Every section ends in a 4-byte CRC and starts after the header.
+            if start < HEADER_SIZE as u64 || end > len as u64 || end < start.saturating_add(4) {
+                issues.push(IndexIssue::SectionOutOfBounds {
+                    section,
+                    start,
+                    end,
+                });
+                ok = false;
+            } else {
+                ranges[i] = (start as usize, end as usize);
+            }
+        }
+        if !ok {
+            return None;
+        }
+
+        let trigram_size = ranges[1].1 - ranges[1].0;
+        let expected = (header.trigram_count as u64) * TRIGRAM_ENTRY_SIZE as u64 + 4;
+        if trigram_size as u64 != expected {
+            issues.push(IndexIssue::SectionSizeMismatch {
+                section: Section::TrigramTable,
+                expected,
+                found: trigram_size as u64,
+            });
+            return None;
+        }
+        let file_size = ranges[0].1 - ranges[0].0;
+        let min_file_size = (header.file_count as u64) * FILE_ENTRY_SIZE as u64 + 4;
+        if (file_size as u64) < min_file_size {
+            issues.push(IndexIssue::SectionSizeMismatch {
+                section: Section::FileTable,
+                expected: min_file_size,
+                found: file_size as u64,
+            });
+            return None;
+        }
+
+        Some(Layout {
+            file_table: ranges[0],
+            trigram_table: ranges[1],
+            postings: ranges[2],
+        })
+    }
+
+    fn file_table<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        &bytes[self.file_table.0..self.file_table.1]
+    }
+
+    fn trigram_table<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        &bytes[self.trigram_table.0..self.trigram_table.1]
+    }
+
+    fn postings<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        &bytes[self.postings.0..self.postings.1]
+    }
+}
+
+/// Check the trailing CRC32 of a section (length already validated >= 4).
+fn check_section(section: &[u8], which: Section, issues: &mut Vec<IndexIssue>) -> bool {
+    let (body, crc) = section.split_at(section.len() - 4);
+    let stored = u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]);
+    if crc32fast::hash(body) != stored {
+        issues.push(IndexIssue::SectionCrcMismatch { section: which });
+        return false;
+    }
+    true
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Validate the file table; returns the set of file ids if it is intact.
+fn check_file_table(
+    table: &[u8],
+    file_count: u32,
+    issues: &mut Vec<IndexIssue>,
+) -> Option<AHashSet<u32>> {
+    if !check_section(table, Section::FileTable, issues) {
+        return None;
+    }
+    let body = &table[..table.len() - 4];
+    let before = issues.len();
+    let mut ids = AHashSet::with_capacity(file_count as usize);
+    for i in 0..file_count as usize {
+        let at = i * FILE_ENTRY_SIZE;
+        let file_id = read_u32(body, at);
+        let path_offset = read_u32(body, at + 4) as usize;
+        let path_len = u16::from_le_bytes([body[at + 8], body[at + 9]]) as usize;
+        if !ids.insert(file_id) {
+            issues.push(IndexIssue::DuplicateFileId { file_id });
+        }
+        let path = path_offset
+            .checked_add(path_len)
+            .and_then(|end| body.get(path_offset..end));
+        match path {
+            Some(path) if std::str::from_utf8(path).is_ok() => {}
+            Some(_) => issues.push(IndexIssue::InvalidPath { file_id }),
+            None => issues.push(IndexIssue::PathOutOfBounds { file_id }),
+        }
+    }
+    (issues.len() == before).then_some(ids)
+}
+
+/// Decode entries from a file table that passed `check_file_table`.
+fn decode_file_entries(table: &[u8], file_count: u32) -> Vec<FileEntry> {
+    (0..file_count as usize)
+        .map(|i| {
+            let at = i * FILE_ENTRY_SIZE;
+            let path_offset = read_u32(table, at + 4) as usize;
+            let path_len = u16::from_le_bytes([table[at + 8], table[at + 9]]) as usize;
+            FileEntry {
+                file_id: read_u32(table, at),
+                path: String::from_utf8_lossy(&table[path_offset..path_offset + path_len])
+                    .into_owned(),
+            }
+        })
+        .collect()
+}
+
+/// Validate trigram ordering, posting bounds, and posting contents.
+/// `file_ids` is `None` when the file table is damaged, in which case
+/// posting ids are not cross-checked.
+fn check_postings(
+    trigram_table: &[u8],
+    postings: &[u8],
+    file_ids: Option<&AHashSet<u32>>,
+    issues: &mut Vec<IndexIssue>,
+) {
+    let mut previous: Option<[u8; 3]> = None;
+    for (index, entry) in trigram_table.chunks_exact(TRIGRAM_ENTRY_SIZE).enumerate() {
+        let trigram = [entry[0], entry[1], entry[2]];
+        if previous.is_some_and(|p| p >= trigram) {
+            issues.push(IndexIssue::TrigramsUnsorted {
+                index: index as u32,
+            });
+        }
+        previous = Some(trigram);
+
+        let offset = read_u32(entry, 3);
+        let len = read_u32(entry, 7);
+        let data = (offset as usize)
+            .checked_add(len as usize)
+            .and_then(|end| postings.get(offset as usize..end));
+        let Some(data) = data else {
+            issues.push(IndexIssue::PostingOutOfBounds {
+                trigram,
+                offset,
+                len,
+            });
+            continue;
+        };
+        let Ok(bitmap) = RoaringBitmap::deserialize_from(data) else {
+            issues.push(IndexIssue::PostingUndecodable { trigram });
+            continue;
+        };
+        if let Some(ids) = file_ids {
+            if let Some(file_id) = bitmap.iter().find(|id| !ids.contains(id)) {
+                issues.push(IndexIssue::UnknownFileId { trigram, file_id });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_docs() -> Vec<(&'static str, &'static [u8])> {
+        vec![
+            ("a.txt", b"hello world"),
+            ("b.txt", b"foo bar baz"),
+            ("c.txt", b"hello foo"),
+        ]
+    }
+
+    fn sample_index() -> Vec<u8> {
+        let mut builder = TrigramIndexBuilder::new();
+        for (path, content) in sample_docs() {
+            builder.add_file(path, content);
+        }
+        write_index(&builder).expect("serialize")
+    }
+
+    fn header(bytes: &[u8]) -> IndexHeader {
+        IndexHeader::from_bytes(bytes).expect("header")
+    }
+
+    /// Recompute a section CRC after a deliberate structural edit, so
+    /// checks behind the CRC are exercised.
+    fn reseal(bytes: &mut [u8], start: usize, end: usize) {
+        let crc = crc32fast::hash(&bytes[start..end - 4]);
+        bytes[end - 4..end].copy_from_slice(&crc.to_le_bytes());
+    }
+
+    #[test]
+    fn valid_index_has_clean_report() {
+        let report = verify_index(&sample_index());
+        assert!(report.is_ok(), "{:?}", report.issues);
+        assert!(report.doc_map_intact);
+        assert_eq!(report.file_count, 3);
+        assert!(report.trigram_count > 0);
+
+        let empty = write_index(&TrigramIndexBuilder::new()).unwrap();
+        assert!(verify_index(&empty).is_ok());
+    }
+
+    #[test]
+    fn flipped_posting_byte_is_section_crc_mismatch() {
+        let mut bytes = sample_index();
+        let posting_start = header(&bytes).posting_offset as usize;
+        bytes[posting_start + 2] ^= 0x40;
+
+        let report = verify_index(&bytes);
+        assert_eq!(
+            report.issues,
+            vec![IndexIssue::SectionCrcMismatch {
+                section: Section::Postings
+            }]
+        );
+        assert!(report.doc_map_intact);
+    }
+
+    #[test]
+    fn flipped_header_byte_is_header_crc_mismatch() {
+        let mut bytes = sample_index();
+        bytes[13] ^= 0x01; // file_count
+        assert_eq!(
+            verify_index(&bytes).issues,
+            vec![IndexIssue::HeaderCrcMismatch]
+        );
+
+        let mut bytes = sample_index();
+        bytes[0] = b'X';
+        assert_eq!(verify_index(&bytes).issues, vec![IndexIssue::InvalidMagic]);
+    }
+
+    #[test]
+    fn truncated_index_reports_without_panicking() {
+        let bytes = sample_index();
+        assert_eq!(
+            verify_index(&bytes[..20]).issues,
+            vec![IndexIssue::Truncated { len: 20 }]
+        );
+        let cut = header(&bytes).posting_offset as usize - 1;
+        let report = verify_index(&bytes[..cut]);
+        assert!(matches!(
+            report.issues[..],
+            [
+                IndexIssue::SectionOutOfBounds {
+                    section: Section::TrigramTable,
+                    ..
+                },
+                IndexIssue::SectionOutOfBounds {
+                    section: Section::Postings,
+                    ..
+                }
+            ]
+        ));
+    }
+
+    #[test]
+    fn posting_offset_past_section_is_reported() {
+        let mut bytes = sample_index();
+        let h = header(&bytes);
+        let (start, end) = (h.trigram_table_offset as usize, h.posting_offset as usize);
+        bytes[start + 3..start + 7].copy_from_slice(&u32::MAX.to_le_bytes());
+        reseal(&mut bytes, start, end);
+
+        let report = verify_index(&bytes);
+        assert!(matches!(
+            report.issues[..],
+            [IndexIssue::PostingOutOfBounds {
+                offset: u32::MAX,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn posting_with_unmapped_file_id_is_reported() {
+        let mut bytes = sample_index();
+        let h = header(&bytes);
+        let (start, end) = (
+            h.file_table_offset as usize,
+            h.trigram_table_offset as usize,
+        );
+        // Renumber file 0 so postings that mention it dangle.
+        bytes[start..start + 4].copy_from_slice(&99u32.to_le_bytes());
+        reseal(&mut bytes, start, end);
+
+        let report = verify_index(&bytes);
+        assert!(!report.is_ok());
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| matches!(i, IndexIssue::UnknownFileId { file_id: 0, .. })));
+    }
+
+    #[test]
+    fn rebuild_from_docs_repairs_damaged_postings() {
+        let mut bytes = sample_index();
+        let posting_start = header(&bytes).posting_offset as usize;
+        bytes[posting_start] ^= 0xFF;
+        assert!(!verify_index(&bytes).is_ok());
+
+        let docs = sample_docs();
+        let rebuilt = rebuild_from_docs(&bytes, |path| {
+            docs.iter()
+                .find(|(p, _)| *p == path)
+                .map(|(_, content)| content.to_vec())
+        })
+        .expect("doc map intact");
+
+        assert_eq!(rebuilt, sample_index());
+        assert!(verify_index(&rebuilt).is_ok());
+    }
+
+    #[test]
+    fn rebuild_requires_intact_doc_map() {
+        let mut bytes = sample_index();
+        let file_table = header(&bytes).file_table_offset as usize;
+        bytes[file_table + 12] ^= 0x01;
+
+        let report = verify_index(&bytes);
+        assert!(!report.doc_map_intact);
+        assert!(report.issues.contains(&IndexIssue::SectionCrcMismatch {
+            section: Section::FileTable
+        }));
+        assert!(matches!(
+            rebuild_from_docs(&bytes, |_| None),
+            Err(TrigramError::CorruptIndex { .. })
+        ));
+    }
+}
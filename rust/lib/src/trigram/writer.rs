@@ -2,7 +2,7 @@
 //!
 //! This module is WASM-safe: no file I/O, just byte serialization.
 
-use super::builder::TrigramIndexBuilder;
+use super::builder::{FileEntry, TrigramIndexBuilder};
 use super::error::TrigramError;
 use super::format::{IndexHeader, FILE_ENTRY_SIZE, HEADER_SIZE, TRIGRAM_ENTRY_SIZE, VERSION};
 
@@ -11,14 +11,14 @@ use super::format::{IndexHeader, FILE_ENTRY_SIZE, HEADER_SIZE, TRIGRAM_ENTRY_SIZ
 /// Returns the complete index file content as a `Vec<u8>`.
 /// The caller is responsible for writing this to disk.
 pub fn write_index(builder: &TrigramIndexBuilder) -> Result<Vec<u8>, TrigramError> {
-    let files = builder.files();
+    let files: Vec<&FileEntry> = builder.live_files().collect();
     let sorted_postings = builder.sorted_posting_lists();
 
     // Phase 1: Compute sizes for all sections.
 
     // File table: entries + concatenated path bytes.
     let mut path_bytes_total: usize = 0;
-    for f in files {
+    for f in &files {
         path_bytes_total = path_bytes_total.checked_add(f.path.len()).ok_or_else(|| {
             TrigramError::CorruptIndex {
                 reason: "File table path bytes overflow".to_string(),
@@ -121,7 +121,7 @@ pub fn write_index(builder: &TrigramIndexBuilder) -> Result<Vec<u8>, TrigramErro
                 reason: "File table entry bytes exceed u32 offset space".to_string(),
             })?;
     let mut all_paths = Vec::new();
-    for f in files {
+    for f in &files {
         let path_len: u16 = f
             .path
             .len()
@@ -33,8 +33,36 @@ pub struct ReBACTuple {
 pub struct NamespaceConfig {
     pub relations: StdHashMap<String, RelationConfig>,
     pub permissions: StdHashMap<String, Vec<String>>,
+    /// Relation or permission name → obligations a grant through it
+    /// carries (e.g. `"audit"`, `"watermark"`), reported by
+    /// `check_with_obligations`. Plain checks ignore them.
+    #[serde(default)]
+    pub obligations: StdHashMap<String, Vec<String>>,
 }
 
+/// Per-object additions to a type's [`NamespaceConfig`], keyed in
+/// [`ObjectOverrides`] by `(object_type, object_id)`.
+///
+/// Merge semantics are per key, override-wins: a relation or permission
+/// named here replaces the type-level definition of the same name for
+/// this object only (it is not unioned with it); every other name is
+/// inherited from the type. `implicit_subjects` is additive — the listed
+/// subjects hold the relation on top of whatever tuples grant it.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ObjectNamespaceOverride {
+    #[serde(default)]
+    pub relations: StdHashMap<String, RelationConfig>,
+    #[serde(default)]
+    pub permissions: StdHashMap<String, Vec<String>>,
+    /// Relation → `"type:id"` subjects that hold it on this object as if
+    /// a direct tuple existed. `"*:*"` means every subject.
+    #[serde(default)]
+    pub implicit_subjects: StdHashMap<String, Vec<String>>,
+}
+
+/// Object-scoped namespace overrides: `(object_type, object_id)` → override.
+pub type ObjectOverrides = AHashMap<(String, String), ObjectNamespaceOverride>;
+
 /// Configuration for a single relation.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(untagged)]
@@ -0,0 +1,140 @@
+//! Process-start warm-up for the hot bulk paths.
+//!
+//! [`warmup`] runs a throwaway permission check and regex search so that
+//! their code is paged in and process-wide lazies in dependencies (ahash's
+//! random seed, the allocator's arenas) are initialized at start-up rather
+//! than on the first request. Nothing is cached: each real call still
+//! builds its own graph and compiles its own regex. It is safe to call any
+//! number of times.
+
+use ahash::{AHashMap, AHashSet};
+use string_interner::DefaultStringInterner;
+
+use crate::rebac::graph::{compute_permission_interned, InternedGraph};
+use crate::rebac::{compute_permission, ReBACGraph};
+use crate::search::{build_search_mode, search_lines};
+use crate::types::*;
+
+/// Run the ReBAC (string-keyed and interned) and regex search paths once.
+pub fn warmup() {
+    warm_rebac();
+    warm_rebac_interned();
+    warm_search();
+}
+
+fn warm_namespace() -> NamespaceConfig {
+    serde_json::from_str(
+        r#"{"relations":{"owner":"direct","parent":"direct","viewer":{"union":["owner"]}},
+            "permissions":{"read":["viewer"]}}"#,
+    )
+    .expect("warm-up namespace config is valid")
+}
+
+fn warm_tuple() -> ReBACTuple {
+    ReBACTuple {
+        subject_type: "user".to_string(),
+        subject_id: "warmup".to_string(),
+        subject_relation: None,
+        relation: "owner".to_string(),
+        object_type: "file".to_string(),
+        object_id: "warmup".to_string(),
+    }
+}
+
+fn warm_rebac() {
+    let graph = ReBACGraph::from_tuples(&[warm_tuple()]);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert("file".to_string(), warm_namespace());
+    let entity = |t: &str| Entity {
+        entity_type: t.to_string(),
+        entity_id: "warmup".to_string(),
+    };
+    compute_permission(
+        &entity("user"),
+        "read",
+        &entity("file"),
+        &graph,
+        &namespaces,
+        &mut MemoCache::new(),
+        &mut AHashSet::new(),
+        0,
+    );
+}
+
+fn warm_rebac_interned() {
+    let mut interner = DefaultStringInterner::new();
+    let user = interner.get_or_intern("user");
+    let file = interner.get_or_intern("file");
+    let id = interner.get_or_intern("warmup");
+    let tuple = InternedTuple {
+        subject_type: user,
+        subject_id: id,
+        subject_relation: None,
+        relation: interner.get_or_intern("owner"),
+        object_type: file,
+        object_id: id,
+    };
+    let graph = InternedGraph::from_tuples(&[tuple], &mut interner);
+    let mut namespaces = AHashMap::new();
+    namespaces.insert(
+        file,
+        InternedNamespaceConfig::from_config(&warm_namespace(), &mut interner),
+    );
+    let read = interner.get_or_intern("read");
+    compute_permission_interned(
+        InternedEntity {
+            entity_type: user,
+            entity_id: id,
+        },
+        read,
+        InternedEntity {
+            entity_type: file,
+            entity_id: id,
+        },
+        &graph,
+        &namespaces,
+        &mut InternedMemoCache::new(),
+        &mut InternedVisitedSet::new(),
+        0,
+    );
+}
+
+fn warm_search() {
+    for (pattern, ignore_case) in [("warm(up)?", false), ("warmup", true)] {
+        if let Ok(mode) = build_search_mode(pattern, ignore_case) {
+            search_lines("warmup", "warmup\nWarmUp", &mode, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warmup_then_tiny_check_works() {
+        warmup();
+        warmup();
+
+        let graph = ReBACGraph::from_tuples(&[warm_tuple()]);
+        let mut namespaces = AHashMap::new();
+        namespaces.insert("file".to_string(), warm_namespace());
+        let entity = |t: &str| Entity {
+            entity_type: t.to_string(),
+            entity_id: "warmup".to_string(),
+        };
+        assert!(compute_permission(
+            &entity("user"),
+            "read",
+            &entity("file"),
+            &graph,
+            &namespaces,
+            &mut MemoCache::new(),
+            &mut AHashSet::new(),
+            0,
+        ));
+
+        let mode = build_search_mode("warm(up)?", false).unwrap();
+        assert_eq!(search_lines("f", "a\nwarmup\n", &mode, 10).len(), 1);
+    }
+}
@@ -77,7 +77,7 @@ pub mod zone_manager;
 pub mod blob_fetcher;
 
 #[cfg(all(feature = "grpc", has_protos))]
-pub use zone_handle::{Consistency, ZoneHandle};
+pub use zone_handle::{Consistency, TenantZoneHandle, WitnessHandle, WitnessMetrics, ZoneHandle};
 
 #[cfg(all(feature = "grpc", has_protos))]
 pub use zone_manager::{ClusterStatus, TlsFiles, ZoneManager};
@@ -151,7 +151,8 @@ pub mod prelude {
 
     pub use crate::raft::{
         Command, CommandResult, FullStateMachine, HolderInfo, LockAcquireResult, LockEntry,
-        LockInfo, LockState, RaftError, StateMachine, WitnessStateMachine,
+        LockInfo, LockPreview, LockRequest, LockState, RaftError, StateMachine, TenantScope,
+        WitnessStateMachine,
     };
 
     #[cfg(feature = "consensus")]
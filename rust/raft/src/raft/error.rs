@@ -52,10 +52,33 @@ pub enum RaftError {
     #[error("raft actor channel full (capacity {0}), driver overloaded")]
     ChannelFull(usize),
 
+    /// Zone is in read-only mode; the write was refused before proposal.
+    #[error("zone is read-only")]
+    ReadOnly,
+
     /// Transport error (gRPC forwarding failed).
     #[error("transport error: {0}")]
     Transport(String),
 
+    /// The replication WAL on disk was written in a format this build
+    /// cannot read (or is not a replication WAL at all).
+    #[error("unsupported WAL format version {found} (supported: {supported})")]
+    UnsupportedWalVersion {
+        /// Version found in the header, or 0 if the magic did not match.
+        found: u8,
+        supported: u8,
+    },
+
+    /// `ReplicationLog::append_at` was given a sequence number past the
+    /// next one; the entries in between are missing.
+    #[error("WAL gap: append at seq {seq}, expected {expected}")]
+    WalGap { seq: u64, expected: u64 },
+
+    /// `ReplicationLog::append_at` was given a sequence number that is
+    /// already taken (the log holds up to `last`).
+    #[error("WAL duplicate: seq {seq} already written (last is {last})")]
+    WalDuplicate { seq: u64, last: u64 },
+
     /// `create_zone` was called for a zone that already exists with a
     /// different peer-address-book.  Idempotency holds when the
     /// requested address book matches the existing one (same set of
@@ -37,6 +37,7 @@
 //! - [`StateMachine`]: Trait for state machine implementations
 //! - [`FullStateMachine`]: Full state machine with metadata and locks
 //! - [`WitnessStateMachine`]: Minimal state machine for witness nodes
+//! - [`TenantScope`]: Per-tenant key-space prefixing over a shared state machine
 //! - [`RaftStorage`]: Persistent Raft log storage using sled
 
 mod error;
@@ -44,6 +45,7 @@ pub mod replication_log;
 #[cfg(all(feature = "grpc", has_protos))]
 pub mod search_caps;
 mod state_machine;
+pub mod tenant_scope;
 #[cfg(all(feature = "grpc", has_protos))]
 pub mod zone_persistence;
 
@@ -55,20 +57,22 @@ mod storage;
 mod zone_registry;
 
 pub use error::{RaftError, Result};
-pub use replication_log::ReplicationLog;
+pub use replication_log::{FsyncPolicy, ReplicationLog};
 #[cfg(feature = "grpc")]
 pub use state_machine::MountApplyEvent;
 pub use state_machine::{
-    Command, CommandResult, FullStateMachine, HolderInfo, LockAcquireResult, LockEntry, LockInfo,
-    LockState, StateMachine, WitnessStateMachine, WitnessStateMachineInMemory,
+    ChangeOp, Command, CommandResult, FullStateMachine, HolderInfo, LockAcquireResult, LockEntry,
+    LockInfo, LockPreview, LockRequest, LockState, MergePrecedence, MergedEntry, MetadataChange,
+    StateMachine, WitnessStateMachine, WitnessStateMachineInMemory, MAX_ALIAS_DEPTH,
 };
+pub use tenant_scope::TenantScope;
 
 #[cfg(feature = "consensus")]
 pub use node::{NodeRole, RaftConfig, RaftMsg, ZoneConsensus, ZoneConsensusDriver};
 #[cfg(all(feature = "grpc", has_protos))]
 pub use search_caps::{read_search_caps, write_search_caps, SearchCapabilitiesInfo};
 #[cfg(feature = "consensus")]
-pub use storage::RaftStorage;
+pub use storage::{rebuild_state_machine, RaftStorage};
 #[cfg(all(feature = "grpc", has_protos))]
 pub use zone_persistence::ZonePersistence;
 #[cfg(all(feature = "grpc", has_protos))]
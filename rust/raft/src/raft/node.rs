@@ -46,7 +46,7 @@
 //! `"not leader but has new msg after advance"` panic under concurrent load.
 
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -277,6 +277,13 @@ pub enum RaftMsg {
     },
     /// Campaign to become leader.
     Campaign { tx: oneshot::Sender<Result<()>> },
+    /// Hand leadership to `transferee`, or to the most caught-up other
+    /// voter when `None`. The tx resolves with the chosen node id once
+    /// the transfer is started, not when it completes.
+    TransferLeader {
+        transferee: Option<u64>,
+        tx: oneshot::Sender<Result<u64>>,
+    },
     /// Linearizable read request (ReadIndex).
     ///
     /// The driver calls `RawNode::read_index` with a unique 8-byte
@@ -398,6 +405,9 @@ pub struct ZoneConsensus<S: StateMachine + 'static> {
     /// leave this ``None`` and ``advisory_state_blocking`` is unreachable
     /// for them.
     advisory_handle: Option<Arc<parking_lot::Mutex<super::state_machine::LockState>>>,
+    /// Node-local write gate set by [`Self::set_read_only`]. Not
+    /// replicated — each node refuses its own proposals while set.
+    read_only: Arc<AtomicBool>,
 }
 
 impl<S: StateMachine + 'static> Clone for ZoneConsensus<S> {
@@ -419,6 +429,7 @@ impl<S: StateMachine + 'static> Clone for ZoneConsensus<S> {
             #[cfg(feature = "grpc")]
             mount_apply_cb_slot: self.mount_apply_cb_slot.clone(),
             advisory_handle: self.advisory_handle.clone(),
+            read_only: self.read_only.clone(),
         }
     }
 }
@@ -664,6 +675,7 @@ impl<S: StateMachine + 'static> ZoneConsensus<S> {
             #[cfg(feature = "grpc")]
             mount_apply_cb_slot,
             advisory_handle,
+            read_only: Arc::new(AtomicBool::new(false)),
         };
 
         let driver = ZoneConsensusDriver {
@@ -746,6 +758,30 @@ impl<S: StateMachine + 'static> ZoneConsensus<S> {
         }
     }
 
+    /// Put the zone into (or take it out of) read-only mode on this node.
+    ///
+    /// While set, `propose`, `propose_ec` and `propose_ec_local` fail with
+    /// [`RaftError::ReadOnly`] before anything reaches the log; reads go
+    /// straight to the state machine and are unaffected. The flag is
+    /// node-local: a proposal forwarded from a follower is checked on
+    /// the follower and again on the leader. Membership changes are not
+    /// gated.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Release);
+    }
+
+    /// Whether this node is refusing writes (see [`Self::set_read_only`]).
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Acquire)
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(RaftError::ReadOnly);
+        }
+        Ok(())
+    }
+
     /// Block until this node becomes leader of the zone, or `timeout`
     /// elapses.  Returns `true` if leader, `false` on timeout.
     ///
@@ -1021,6 +1057,7 @@ impl<S: StateMachine + 'static> ZoneConsensus<S> {
     ///
     /// Latency: ~5-10μs (serialize + channel send).
     pub async fn propose_ec(&self, command: Command) -> Result<()> {
+        self.check_writable()?;
         let _rx = self.submit_to_channel(command)?; // drop receiver
         Ok(())
     }
@@ -1035,6 +1072,7 @@ impl<S: StateMachine + 'static> ZoneConsensus<S> {
     /// # Timeout
     /// Proposals time out after 10 seconds.
     pub async fn propose(&self, command: Command) -> Result<CommandResult> {
+        self.check_writable()?;
         match self.submit_to_channel(command.clone()) {
             Ok(rx) => {
                 // Leader path: wait for commit
@@ -1152,6 +1190,7 @@ impl<S: StateMachine + 'static> ZoneConsensus<S> {
     ///
     /// Latency: ~5-50μs (redb write, no network).
     pub async fn propose_ec_local(&self, command: Command) -> Result<u64> {
+        self.check_writable()?;
         let repl_log = self.replication_log.as_ref().ok_or_else(|| {
             RaftError::InvalidState("EC local writes require a ReplicationLog".into())
         })?;
@@ -1243,6 +1282,72 @@ impl<S: StateMachine + 'static> ZoneConsensus<S> {
         }
     }
 
+    /// Hand leadership to `transferee` (or, if `None`, the other voter with
+    /// the most replicated log) and wait until this node sees the new
+    /// leader. Returns the new leader's id.
+    ///
+    /// raft-rs aborts a transfer whose target doesn't catch up within an
+    /// election timeout; that surfaces here as [`RaftError::Timeout`].
+    pub async fn transfer_leadership(&self, transferee: Option<u64>) -> Result<u64> {
+        if !self.is_leader() {
+            return Err(RaftError::NotLeader {
+                leader_hint: self.leader_id(),
+            });
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.msg_tx
+            .try_send(RaftMsg::TransferLeader { transferee, tx })
+            .map_err(channel_try_send_err)?;
+        let target = rx.await.map_err(|_| RaftError::ProposalDropped)??;
+
+        let deadline = Instant::now() + Duration::from_secs(PROPOSAL_TIMEOUT_SECS);
+        while self.leader_id() != Some(target) {
+            if Instant::now() >= deadline {
+                return Err(RaftError::Timeout(PROPOSAL_TIMEOUT_SECS));
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(target)
+    }
+
+    /// Propose removing this node from the voter set and wait until the
+    /// removal is applied here. Returns the resulting `ConfState`.
+    ///
+    /// Unlike [`propose_conf_change`](Self::propose_conf_change) this
+    /// works on a follower — raft-rs forwards the proposal to the leader —
+    /// so a leader should [`transfer_leadership`](Self::transfer_leadership)
+    /// first rather than remove itself while leading.
+    pub async fn remove_self(&self) -> Result<ConfState> {
+        let mut cc = ConfChange::default();
+        cc.set_change_type(ConfChangeType::RemoveNode);
+        cc.node_id = self.id();
+
+        let (tx, rx) = oneshot::channel();
+        self.msg_tx
+            .try_send(RaftMsg::ProposeConfChange { change: cc, tx })
+            .map_err(channel_try_send_err)?;
+
+        match tokio::time::timeout(Duration::from_secs(PROPOSAL_TIMEOUT_SECS), rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(RaftError::ProposalDropped),
+            Err(_) => Err(RaftError::Timeout(PROPOSAL_TIMEOUT_SECS)),
+        }
+    }
+
+    /// Leave the voter set without an election: transfer leadership away
+    /// if this node leads, then [`remove_self`](Self::remove_self).
+    /// Returns the resulting `ConfState`.
+    ///
+    /// The driver and transport loop keep running; whoever owns them stops
+    /// them afterwards (see `ZoneHandle::graceful_leave`).
+    pub async fn graceful_leave(&self) -> Result<ConfState> {
+        if self.is_leader() {
+            self.transfer_leadership(None).await?;
+        }
+        self.remove_self().await
+    }
+
     /// Process a message from another node (sends through channel to driver).
     ///
     /// Uses `send().await` (blocking until space is available) instead of
@@ -1385,6 +1490,17 @@ impl<S: StateMachine + 'static> ZoneConsensusDriver<S> {
                     self.update_cached_status();
                     let _ = tx.send(result);
                 }
+                RaftMsg::TransferLeader { transferee, tx } => {
+                    let result = match transferee {
+                        Some(id) => Ok(id),
+                        None => self.pick_transferee(),
+                    };
+                    if let Ok(id) = result {
+                        tracing::info!(transferee = id, "raft.driver.transfer_leader");
+                        self.raw_node.transfer_leader(id);
+                    }
+                    let _ = tx.send(result);
+                }
                 RaftMsg::ReadIndex { tx } => {
                     // Post a ReadIndex request to raft-rs and stash
                     // the oneshot by request context. The
@@ -1792,6 +1908,27 @@ impl<S: StateMachine + 'static> ZoneConsensusDriver<S> {
         }
     }
 
+    /// The voter other than this node with the highest matched log index —
+    /// the one that can take over leadership without catching up first.
+    fn pick_transferee(&self) -> Result<u64> {
+        let voters = self
+            .raw_node
+            .store()
+            .initial_state()
+            .map_err(|e| RaftError::Storage(e.to_string()))?
+            .conf_state
+            .voters;
+        let me = self.raw_node.raft.id;
+        let prs = self.raw_node.raft.prs();
+        voters
+            .into_iter()
+            .filter(|&id| id != me)
+            .max_by_key(|&id| (prs.get(id).map_or(0, |p| p.matched), std::cmp::Reverse(id)))
+            .ok_or_else(|| {
+                RaftError::InvalidState("no other voter to transfer leadership to".into())
+            })
+    }
+
     /// Update the atomic cached status values from the current raw_node state.
     ///
     /// ``applied_index`` intentionally does NOT live here — the state
@@ -2247,6 +2384,90 @@ mod tests {
         let _ = shutdown_tx.send(true);
     }
 
+    #[tokio::test]
+    async fn test_graceful_leave_keeps_remaining_pair_serving() {
+        // Same pre-seeded 3-voter cluster as test_three_node_consensus,
+        // but each driver gets its own shutdown so the leaver can stop.
+        let mut handles = Vec::new();
+        let mut drivers = Vec::new();
+        let mut _dirs = Vec::new();
+        for id in 1..=3u64 {
+            let dir = TempDir::new().unwrap();
+            let storage = RaftStorage::open(dir.path()).unwrap();
+            let cs = ConfState {
+                voters: vec![1, 2, 3],
+                ..Default::default()
+            };
+            storage.set_conf_state(&cs).unwrap();
+            let store = RedbStore::open(dir.path().join("sm")).unwrap();
+            let state_machine = FullStateMachine::new(&store).unwrap();
+            let config = RaftConfig {
+                id,
+                peers: vec![],
+                skip_bootstrap: true,
+                tick_interval: Duration::from_millis(10),
+                ..Default::default()
+            };
+            let (handle, driver) =
+                ZoneConsensus::new(config, storage, state_machine, None).unwrap();
+            handles.push(handle);
+            drivers.push(driver);
+            _dirs.push(dir);
+        }
+        let mut shutdowns = Vec::new();
+        for (i, driver) in drivers.into_iter().enumerate() {
+            let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+            tokio::spawn(run_test_driver(driver, i, handles.clone(), shutdown_rx));
+            shutdowns.push(shutdown_tx);
+        }
+        tokio::task::yield_now().await;
+
+        handles[0].campaign().await.unwrap();
+        for _ in 0..200 {
+            if handles[0].is_leader() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(handles[0].is_leader(), "node 1 must win its campaign");
+        handles[0]
+            .propose(Command::SetMetadata {
+                key: "/before".into(),
+                value: b"1".to_vec(),
+            })
+            .await
+            .unwrap();
+
+        // Graceful leave of the leader, then stop its driver.
+        let cs = handles[0].graceful_leave().await.unwrap();
+        assert_eq!(cs.voters.len(), 2);
+        assert!(!cs.voters.contains(&1));
+        assert!(!handles[0].is_leader());
+        let _ = shutdowns[0].send(true);
+        let new_leader = handles[1].leader_id().expect("remaining pair has a leader");
+        assert_ne!(new_leader, 1);
+
+        // The remaining pair keeps the same leader and still commits.
+        let leader_idx = new_leader as usize - 1;
+        let term = handles[leader_idx].term();
+        handles[leader_idx]
+            .propose(Command::SetMetadata {
+                key: "/after".into(),
+                value: b"2".to_vec(),
+            })
+            .await
+            .expect("remaining pair must commit without the leaver");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        for handle in &handles[1..] {
+            assert_eq!(handle.leader_id(), Some(new_leader));
+            assert_eq!(handle.term(), term, "no election after the leave");
+        }
+
+        for shutdown in &shutdowns[1..] {
+            let _ = shutdown.send(true);
+        }
+    }
+
     /// Regression test: single-node ConfState must include self as voter.
     ///
     /// Before the fix, empty `config.peers` skipped ConfState bootstrap,
@@ -2395,4 +2616,69 @@ mod tests {
         assert_eq!(handle.role(), NodeRole::Leader);
         assert_eq!(handle.leader_id(), Some(1));
     }
+
+    #[tokio::test]
+    async fn test_read_only_refuses_writes_but_serves_reads() {
+        let dir = TempDir::new().unwrap();
+        let storage = RaftStorage::open(dir.path()).unwrap();
+        let store = RedbStore::open(dir.path().join("sm")).unwrap();
+        let state_machine = FullStateMachine::new(&store).unwrap();
+        let config = RaftConfig {
+            id: 1,
+            peers: vec![],
+            tick_interval: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let (handle, driver) = ZoneConsensus::new(config, storage, state_machine, None).unwrap();
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        tokio::spawn(run_test_driver(
+            driver,
+            0,
+            vec![handle.clone()],
+            shutdown_rx,
+        ));
+        handle.campaign().await.unwrap();
+        assert!(handle.is_leader());
+
+        let set = |key: &str| Command::SetMetadata {
+            key: key.into(),
+            value: b"v".to_vec(),
+        };
+        let get = |key: &'static str| handle.with_state_machine(move |sm| sm.get_metadata(key));
+        handle.propose(set("/before")).await.unwrap();
+
+        // Clones share the flag, just as every `ZoneHandle` of a zone does.
+        handle.clone().set_read_only(true);
+        assert!(handle.is_read_only());
+        for cmd in [
+            set("/during"),
+            Command::DeleteMetadata {
+                key: "/before".into(),
+            },
+            Command::AcquireLock {
+                path: "/before".into(),
+                lock_id: "l1".into(),
+                max_holders: 1,
+                ttl_secs: 30,
+                holder_info: "agent:test".into(),
+                now_secs: 1000,
+            },
+        ] {
+            let err = handle.propose(cmd).await.unwrap_err();
+            assert!(matches!(err, RaftError::ReadOnly), "got {err:?}");
+        }
+        assert!(matches!(
+            handle.propose_ec(set("/during")).await,
+            Err(RaftError::ReadOnly)
+        ));
+        assert_eq!(get("/before").await.unwrap(), Some(b"v".to_vec()));
+        assert_eq!(get("/during").await.unwrap(), None);
+
+        handle.set_read_only(false);
+        handle.propose(set("/after")).await.unwrap();
+        assert_eq!(get("/after").await.unwrap(), Some(b"v".to_vec()));
+
+        let _ = shutdown_tx.send(true);
+    }
 }
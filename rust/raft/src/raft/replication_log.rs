@@ -18,10 +18,26 @@
 //! Tokens never expire. The watermark is a single u64 comparison — O(1).
 //! This elegantly handles the "disconnected overnight" scenario: tokens stay
 //! "pending" while partitioned, flip to "committed" on reconnect.
+//!
+//! # Durability
+//!
+//! [`FsyncPolicy`] decides which appends pay for an fsync. The default
+//! (`EveryWrite`) makes every token durable before it is returned;
+//! `Batched` group-commits, and `OsBuffered` leaves it to [`ReplicationLog::flush`].
+//!
+//! # Format Header
+//!
+//! The meta tree carries an 8-byte header: magic `NXWL`, a format version,
+//! then compression-codec, checksum-type and one spare byte, all zero today.
+//! Opening a log whose header has the wrong magic or version fails with
+//! [`RaftError::UnsupportedWalVersion`](super::RaftError::UnsupportedWalVersion)
+//! instead of misreading entries. Logs written before the header existed
+//! are version 1 and get stamped on first open.
 
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use redb::ReadableDatabase;
@@ -40,6 +56,62 @@ const KEY_NEXT_SEQ: &[u8] = b"__next_seq__";
 const KEY_REPLICATED_WATERMARK: &[u8] = b"__replicated_watermark__";
 /// Key for persisted earliest sequence number (compaction lower bound).
 const KEY_EARLIEST_SEQ: &[u8] = b"__earliest_seq__";
+/// Key for the on-disk format header (see module docs).
+const KEY_FORMAT_HEADER: &[u8] = b"__format__";
+/// Header magic identifying a replication WAL.
+const WAL_MAGIC: [u8; 4] = *b"NXWL";
+/// Entry layout version: bincode `ReplicationEntry` keyed by u64 BE seq.
+const WAL_FORMAT_VERSION: u8 = 1;
+
+/// Encode the format header: magic, version, codec, checksum, reserved.
+fn format_header(version: u8) -> [u8; 8] {
+    let mut header = [0u8; 8];
+    header[..4].copy_from_slice(&WAL_MAGIC);
+    header[4] = version;
+    header
+}
+
+/// Validate the stored header, stamping the current one if absent.
+fn check_format_header(meta_tree: &RedbTree) -> Result<()> {
+    let Some(header) = meta_tree.get(KEY_FORMAT_HEADER)? else {
+        return Ok(meta_tree.set(KEY_FORMAT_HEADER, &format_header(WAL_FORMAT_VERSION))?);
+    };
+    let found = match header.get(..4) {
+        Some(magic) if magic == WAL_MAGIC && header.len() == 8 => header[4],
+        _ => 0,
+    };
+    if found != WAL_FORMAT_VERSION {
+        return Err(super::RaftError::UnsupportedWalVersion {
+            found,
+            supported: WAL_FORMAT_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// When an append is made durable (fsync'd) before returning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FsyncPolicy {
+    /// Every append is durable when it returns.
+    #[default]
+    EveryWrite,
+    /// Group commit: appends are buffered and made durable together by
+    /// one fsync once `max_entries` have accumulated or `max_interval_ms`
+    /// has passed since the last fsync, whichever comes first. The
+    /// interval is only checked on append — an idle log stays buffered
+    /// until the next append or [`ReplicationLog::flush`].
+    ///
+    /// A crash loses at most the buffered appends.
+    Batched {
+        max_entries: u32,
+        max_interval_ms: u64,
+    },
+    /// No fsync on append. Appends become durable only at the next
+    /// [`ReplicationLog::flush`] (or another durable write to the same
+    /// store); a crash can lose everything since then. Only for data
+    /// that can be re-derived, or callers that flush on their own schedule.
+    OsBuffered,
+}
 
 /// An entry in the EC replication WAL.
 ///
@@ -75,6 +147,14 @@ pub struct ReplicationLog {
     earliest_seq: AtomicU64,
     /// This node's ID (for LWW tie-breaking in ReplicationEntry).
     node_id: u64,
+    /// Durability policy for `append`.
+    fsync_policy: FsyncPolicy,
+    /// Appends committed since the last durable commit.
+    unsynced: AtomicU64,
+    /// Time of the last durable commit (for `FsyncPolicy::Batched`).
+    last_sync: Mutex<Instant>,
+    /// Durable commits issued by `append` / `flush`.
+    fsync_count: AtomicU64,
 }
 
 impl ReplicationLog {
@@ -82,9 +162,20 @@ impl ReplicationLog {
     ///
     /// Persisted state (next_seq, watermark) is restored from the meta tree.
     /// If fresh, next_seq starts at 1 (0 is reserved for "no token").
+    /// Fails with `UnsupportedWalVersion` if the format header does not match.
     pub fn new(store: &crate::storage::RedbStore, node_id: u64) -> Result<Self> {
+        Self::with_fsync_policy(store, node_id, FsyncPolicy::default())
+    }
+
+    /// [`new`](Self::new) with an explicit durability policy for `append`.
+    pub fn with_fsync_policy(
+        store: &crate::storage::RedbStore,
+        node_id: u64,
+        fsync_policy: FsyncPolicy,
+    ) -> Result<Self> {
         let log_tree = store.tree(TREE_REPLICATION_LOG)?;
         let meta_tree = store.tree(TREE_REPLICATION_META)?;
+        check_format_header(&meta_tree)?;
 
         // Restore persisted next_seq
         let next_seq = meta_tree
@@ -109,6 +200,7 @@ impl ReplicationLog {
             replicated_watermark,
             earliest_seq,
             node_id,
+            ?fsync_policy,
             "ReplicationLog initialized"
         );
 
@@ -119,9 +211,71 @@ impl ReplicationLog {
             replicated_watermark: AtomicU64::new(replicated_watermark),
             earliest_seq: AtomicU64::new(earliest_seq),
             node_id,
+            fsync_policy,
+            unsynced: AtomicU64::new(0),
+            last_sync: Mutex::new(Instant::now()),
+            fsync_count: AtomicU64::new(0),
         })
     }
 
+    pub fn fsync_policy(&self) -> FsyncPolicy {
+        self.fsync_policy
+    }
+
+    /// Number of durable (fsync'd) commits issued by `append` and `flush`.
+    pub fn fsync_count(&self) -> u64 {
+        self.fsync_count.load(Ordering::Relaxed)
+    }
+
+    /// Whether the next append must be durable under the current policy.
+    fn append_needs_sync(&self) -> bool {
+        match self.fsync_policy {
+            FsyncPolicy::EveryWrite => true,
+            FsyncPolicy::Batched {
+                max_entries,
+                max_interval_ms,
+            } => {
+                self.unsynced.load(Ordering::Relaxed) + 1 >= u64::from(max_entries)
+                    || self.last_sync.lock().elapsed() >= Duration::from_millis(max_interval_ms)
+            }
+            FsyncPolicy::OsBuffered => false,
+        }
+    }
+
+    /// Record the outcome of a commit for the batching bookkeeping.
+    fn record_commit(&self, durable: bool) {
+        if durable {
+            self.unsynced.store(0, Ordering::Relaxed);
+            *self.last_sync.lock() = Instant::now();
+            self.fsync_count.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.unsynced.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Make every prior append durable.
+    ///
+    /// A no-op when nothing is buffered; otherwise one empty durable
+    /// commit, which persists all earlier non-durable commits with it.
+    pub fn flush(&self) -> Result<()> {
+        if self.unsynced.load(Ordering::Relaxed) == 0 {
+            return Ok(());
+        }
+        let db = self.log_tree.raw_db();
+        let mut write_txn = db
+            .begin_write()
+            .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        write_txn
+            .set_durability(redb::Durability::Immediate)
+            .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        write_txn
+            .commit()
+            .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        self.record_commit(true);
+        tracing::trace!("Replication log flushed");
+        Ok(())
+    }
+
     /// Append a command to the replication log.
     ///
     /// Returns the sequence number which serves as the WriteToken.
@@ -134,7 +288,67 @@ impl ReplicationLog {
         // Relaxed: uniqueness from the atomic op; ordering from redb's
         // single-writer transaction serialization. SeqCst is unnecessary.
         let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let durable = self.write_entry(seq, command_bytes)?;
+
+        tracing::trace!(seq, durable, "EC write appended to replication log");
+        Ok(seq)
+    }
 
+    /// Append a command at an explicit sequence number, for a replicator
+    /// keeping this log aligned with a peer's.
+    ///
+    /// `seq` must be exactly the next sequence number ([`max_seq`](Self::max_seq)).
+    /// A higher `seq` fails with `WalGap` (entries are missing in between);
+    /// a lower one fails with `WalDuplicate` (the slot is already taken),
+    /// so the caller can tell a lagging log from a diverged one. Nothing
+    /// is written on either error. If the write itself fails, the claim on
+    /// `seq` is released so the caller can retry it.
+    pub fn append_at(&self, seq: u64, command_bytes: &[u8]) -> Result<()> {
+        let Some(after) = seq.checked_add(1) else {
+            return Err(super::RaftError::InvalidState(
+                "replication log sequence numbers exhausted".to_string(),
+            ));
+        };
+        // Claim `seq` only if it is the next one, so a concurrent `append`
+        // cannot take the same slot.
+        if let Err(next) =
+            self.next_seq
+                .compare_exchange(seq, after, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            return Err(if seq > next {
+                super::RaftError::WalGap {
+                    seq,
+                    expected: next,
+                }
+            } else {
+                super::RaftError::WalDuplicate {
+                    seq,
+                    last: next - 1,
+                }
+            });
+        }
+        let durable = match self.write_entry(seq, command_bytes) {
+            Ok(durable) => durable,
+            Err(e) => {
+                // Unclaim `seq` unless an `append` has already moved past
+                // it; then the slot stays a gap either way.
+                let _ = self.next_seq.compare_exchange(
+                    after,
+                    seq,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+                return Err(e);
+            }
+        };
+
+        tracing::trace!(seq, durable, "Replicated write appended at explicit seq");
+        Ok(())
+    }
+
+    /// Write the entry for an already-claimed `seq` together with the
+    /// persisted next_seq. Returns whether the commit was durable.
+    fn write_entry(&self, seq: u64, command_bytes: &[u8]) -> Result<bool> {
         let entry = ReplicationEntry {
             command: command_bytes.to_vec(),
             timestamp: SystemTime::now()
@@ -147,15 +361,22 @@ impl ReplicationLog {
         let key = seq.to_be_bytes();
         let value = bincode::serialize(&entry)?;
 
-        // Single transaction for both entry and metadata — atomic, one fsync.
+        // Single transaction for both entry and metadata — atomic, at most
+        // one fsync (skipped when the fsync policy buffers this append).
         // The max() prevents next_seq regression under concurrent appends:
         // redb serializes write transactions, so only one thread is here at a time.
         let log_table_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.log_tree.name());
         let meta_table_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.meta_tree.name());
         let db = self.log_tree.raw_db();
-        let write_txn = db
+        let mut write_txn = db
             .begin_write()
             .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        let durable = self.append_needs_sync();
+        if !durable {
+            write_txn
+                .set_durability(redb::Durability::None)
+                .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        }
         {
             let mut log_table = write_txn
                 .open_table(log_table_def)
@@ -186,9 +407,8 @@ impl ReplicationLog {
         write_txn
             .commit()
             .map_err(|e| super::RaftError::Storage(e.to_string()))?;
-
-        tracing::trace!(seq, "EC write appended to replication log");
-        Ok(seq)
+        self.record_commit(durable);
+        Ok(durable)
     }
 
     /// Remove a single WAL entry by sequence number.
@@ -388,6 +608,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_format_header_version_checked_on_open() {
+        let tmpfile = tempfile::NamedTempFile::new().unwrap();
+        let path = tmpfile.path().to_path_buf();
+        {
+            let store = RedbStore::open(&path).unwrap();
+            let log = ReplicationLog::new(&store, 1).unwrap();
+            log.append(b"cmd1").unwrap();
+        }
+
+        // Matching header: reopens and replays normally.
+        {
+            let store = RedbStore::open(&path).unwrap();
+            let log = ReplicationLog::new(&store, 1).unwrap();
+            let entries = log.drain_unreplicated().unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].1.command, b"cmd1");
+
+            // Simulate a segment written by a newer build.
+            let meta = store.tree(TREE_REPLICATION_META).unwrap();
+            meta.set(KEY_FORMAT_HEADER, &format_header(WAL_FORMAT_VERSION + 1))
+                .unwrap();
+        }
+
+        let store = RedbStore::open(&path).unwrap();
+        match ReplicationLog::new(&store, 1) {
+            Err(crate::raft::RaftError::UnsupportedWalVersion { found, supported }) => {
+                assert_eq!(found, WAL_FORMAT_VERSION + 1);
+                assert_eq!(supported, WAL_FORMAT_VERSION);
+            }
+            other => panic!("expected UnsupportedWalVersion, got {:?}", other.err()),
+        }
+
+        // Garbage where the magic should be reports version 0.
+        let meta = store.tree(TREE_REPLICATION_META).unwrap();
+        meta.set(KEY_FORMAT_HEADER, b"garbage!").unwrap();
+        assert!(matches!(
+            ReplicationLog::new(&store, 1),
+            Err(crate::raft::RaftError::UnsupportedWalVersion { found: 0, .. })
+        ));
+    }
+
     #[test]
     fn test_drain_unreplicated() {
         let store = RedbStore::open_temporary().unwrap();
@@ -611,4 +873,129 @@ mod tests {
             persisted_next
         );
     }
+
+    #[test]
+    fn test_append_at_rejects_gaps_and_duplicates() {
+        let store = RedbStore::open_temporary().unwrap();
+        let log = ReplicationLog::new(&store, 1).unwrap();
+
+        log.append_at(1, b"cmd1").unwrap();
+        log.append_at(2, b"cmd2").unwrap();
+        assert_eq!(log.max_seq(), 3);
+
+        assert!(matches!(
+            log.append_at(5, b"cmd5"),
+            Err(crate::raft::RaftError::WalGap {
+                seq: 5,
+                expected: 3
+            })
+        ));
+        assert!(matches!(
+            log.append_at(2, b"again"),
+            Err(crate::raft::RaftError::WalDuplicate { seq: 2, last: 2 })
+        ));
+        assert_eq!(log.max_seq(), 3);
+
+        // Explicit and auto-assigned appends share one sequence.
+        assert_eq!(log.append(b"cmd3").unwrap(), 3);
+        log.append_at(4, b"cmd4").unwrap();
+        let entries = log.drain_unreplicated().unwrap();
+        let seqs: Vec<u64> = entries.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4]);
+        assert_eq!(entries[1].1.command, b"cmd2");
+    }
+
+    #[test]
+    fn test_append_at_refuses_to_wrap_the_sequence() {
+        let store = RedbStore::open_temporary().unwrap();
+        let log = ReplicationLog::new(&store, 1).unwrap();
+
+        log.next_seq.store(u64::MAX, Ordering::Relaxed);
+        assert!(matches!(
+            log.append_at(u64::MAX, b"last"),
+            Err(crate::raft::RaftError::InvalidState(_))
+        ));
+        assert_eq!(log.max_seq(), u64::MAX);
+    }
+
+    #[test]
+    fn test_batched_fsync_policy_groups_commits() {
+        let store = RedbStore::open_temporary().unwrap();
+        let every = ReplicationLog::new(&store, 1).unwrap();
+        for _ in 0..50 {
+            every.append(b"cmd").unwrap();
+        }
+        assert_eq!(every.fsync_count(), 50);
+
+        let store = RedbStore::open_temporary().unwrap();
+        let batched = ReplicationLog::with_fsync_policy(
+            &store,
+            1,
+            FsyncPolicy::Batched {
+                max_entries: 10,
+                max_interval_ms: 60_000,
+            },
+        )
+        .unwrap();
+        for _ in 0..50 {
+            batched.append(b"cmd").unwrap();
+        }
+        assert_eq!(batched.fsync_count(), 5);
+        assert_eq!(batched.drain_unreplicated().unwrap().len(), 50);
+
+        let store = RedbStore::open_temporary().unwrap();
+        let buffered =
+            ReplicationLog::with_fsync_policy(&store, 1, FsyncPolicy::OsBuffered).unwrap();
+        for _ in 0..50 {
+            buffered.append(b"cmd").unwrap();
+        }
+        assert_eq!(buffered.fsync_count(), 0);
+    }
+
+    #[test]
+    fn test_batched_fsync_policy_syncs_after_interval() {
+        let store = RedbStore::open_temporary().unwrap();
+        let log = ReplicationLog::with_fsync_policy(
+            &store,
+            1,
+            FsyncPolicy::Batched {
+                max_entries: 1000,
+                max_interval_ms: 0,
+            },
+        )
+        .unwrap();
+        log.append(b"cmd1").unwrap();
+        log.append(b"cmd2").unwrap();
+        assert_eq!(log.fsync_count(), 2);
+    }
+
+    #[test]
+    fn test_flush_persists_buffered_appends() {
+        let tmpfile = tempfile::NamedTempFile::new().unwrap();
+        let path = tmpfile.path().to_path_buf();
+
+        {
+            let store = RedbStore::open(&path).unwrap();
+            let log =
+                ReplicationLog::with_fsync_policy(&store, 1, FsyncPolicy::OsBuffered).unwrap();
+            log.append(b"cmd1").unwrap();
+            log.append(b"cmd2").unwrap();
+            assert_eq!(log.fsync_count(), 0);
+
+            log.flush().unwrap();
+            assert_eq!(log.fsync_count(), 1);
+            // Nothing buffered: no extra fsync.
+            log.flush().unwrap();
+            assert_eq!(log.fsync_count(), 1);
+        }
+
+        {
+            let store = RedbStore::open(&path).unwrap();
+            let log = ReplicationLog::new(&store, 1).unwrap();
+            assert_eq!(log.max_seq(), 3);
+            let entries = log.drain_unreplicated().unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[1].1.command, b"cmd2");
+        }
+    }
 }
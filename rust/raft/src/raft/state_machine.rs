@@ -4,7 +4,8 @@
 //! For STRONG_HA zones, this includes metadata and lock operations
 //! (NOT file data - that stays in CAS/S3).
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -13,11 +14,13 @@ use parking_lot::Mutex;
 use redb::ReadableTable;
 use serde::{Deserialize, Serialize};
 
-use crate::storage::{RedbStore, RedbTree};
+use crate::storage::{PrefixScan, RedbStore, RedbTree};
 
 // Advisory lock types are the shared SSOT, defined in `contracts::lock_state`.
 // Re-exported here so callers can `use raft::{LockInfo, ...}` directly.
-pub use contracts::lock_state::{HolderInfo, LockAcquireResult, LockEntry, LockInfo, LockState};
+pub use contracts::lock_state::{
+    HolderInfo, LockAcquireResult, LockEntry, LockInfo, LockPreview, LockRequest, LockState,
+};
 
 use super::Result;
 
@@ -25,6 +28,8 @@ use super::Result;
 ///
 /// Commands are serialized and stored in the Raft log, then applied
 /// to the state machine when committed.
+///
+/// Variants are bincode-indexed: append only, never reorder.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Command {
     /// Set a key-value pair in metadata.
@@ -144,6 +149,53 @@ pub enum Command {
 
     /// No-op command (used for leader election confirmation).
     Noop,
+
+    /// Acquire several locks atomically: all of them or none.
+    ///
+    /// Applied through `LockState::apply_acquire_all`, which takes the
+    /// paths in sorted order and rolls back on the first conflict.
+    AcquireLocks {
+        requests: Vec<LockRequest>,
+        /// Wall-clock timestamp captured at proposal time (Unix secs).
+        now_secs: u64,
+    },
+
+    /// Make `alias` resolve to `target` on reads (see
+    /// [`FullStateMachine::get_metadata`]). Rejected with
+    /// `CommandResult::Error` if `alias` already holds metadata or the
+    /// link would close a cycle. While the link exists, writes to
+    /// `alias` are rejected too rather than shadowing it; `DeleteMetadata`
+    /// on `alias` removes the link only.
+    LinkMetadata { alias: String, target: String },
+
+    /// `SetMetadata` that also pushes the key's previous value (if any)
+    /// onto its history, keeping the newest `max_history` entries (see
+    /// [`FullStateMachine::get_metadata_history`]). Both writes land in
+    /// the same apply transaction.
+    SetMetadataVersioned {
+        key: String,
+        value: Vec<u8>,
+        max_history: u32,
+    },
+
+    /// Write `value` only if `key` has no metadata yet, e.g. for
+    /// content-hash keys whose value can never differ. Results in
+    /// `CommandResult::PutResult`.
+    PutIfAbsent { key: String, value: Vec<u8> },
+
+    /// `CasSetMetadata` guarded by a lock fencing token: rejected with
+    /// `CommandResult::Error` if the lock on `key` is held but no current
+    /// holder has `fencing_token`, or — once the lock is gone — if a
+    /// fenced write to `key` already carried a newer token, so a holder
+    /// whose lock expired and was reacquired cannot overwrite its
+    /// successor. Otherwise behaves like `CasSetMetadata` and, on
+    /// success, raises the key's fence to `fencing_token`.
+    CasSetMetadataFenced {
+        key: String,
+        value: Vec<u8>,
+        expected_version: u32,
+        fencing_token: u64,
+    },
 }
 
 /// Result of applying a command.
@@ -168,6 +220,55 @@ pub enum CommandResult {
 
     /// Command failed.
     Error(String),
+
+    /// Per-request results of `AcquireLocks`, in request order.
+    LockResults(Vec<LockAcquireResult>),
+
+    /// Result of `PutIfAbsent`: whether the value was written.
+    PutResult { written: bool },
+}
+
+/// Kind of metadata mutation recorded in the change log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeOp {
+    /// The key was written; `value` holds the new bytes.
+    Set,
+    /// The key was removed.
+    Delete,
+}
+
+/// One committed metadata mutation, as returned by
+/// [`FullStateMachine::changes_since`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataChange {
+    /// Log index of the entry that made the change.
+    pub index: u64,
+    pub key: String,
+    pub op: ChangeOp,
+    /// New value for `Set`, `None` for `Delete`.
+    pub value: Option<Vec<u8>>,
+}
+
+/// Which prefix's entry survives when several prefixes passed to
+/// [`FullStateMachine::list_metadata_merged`] hold the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePrecedence {
+    /// The earliest prefix in the list wins.
+    #[default]
+    FirstPrefix,
+    /// The latest prefix in the list wins, as in an overlay where later
+    /// layers are written over earlier ones.
+    LastPrefix,
+}
+
+/// One entry of a merged multi-prefix listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedEntry {
+    /// Key with its prefix stripped; the merge and dedup key.
+    pub name: String,
+    /// Index into the `prefixes` argument of the prefix it came from.
+    pub prefix: usize,
+    pub value: Vec<u8>,
 }
 
 // Advisory lock types — `HolderInfo`, `LockInfo`, `LockAcquireResult`,
@@ -512,6 +613,157 @@ const TREE_METADATA: &str = "sm_metadata";
 /// convention); values are raw bytes.
 const TREE_STREAM_ENTRIES: &str = "sm_stream_entries";
 const KEY_LAST_APPLIED: &[u8] = b"__last_applied__";
+/// Per-index record of committed metadata mutations, keyed by log index
+/// and write sequence (see `change_log_key`) so range scans come back
+/// in apply order. Feeds
+/// [`FullStateMachine::changes_since`] for incremental backups.
+const TREE_CHANGELOG: &str = "sm_changelog";
+/// Change-log key of the `seq`-th record written at log `index`: both
+/// big-endian, so records sort by index, then write order.
+fn change_log_key(index: u64, seq: u32) -> [u8; 12] {
+    let mut key = [0; 12];
+    key[..8].copy_from_slice(&index.to_be_bytes());
+    key[8..].copy_from_slice(&seq.to_be_bytes());
+    key
+}
+
+/// Lowest index `changes_since` can answer from. Lives in the metadata
+/// tree so snapshot restore resets it in the same transaction.
+const KEY_CHANGES_FLOOR: &[u8] = b"__changes_floor__";
+/// Log indices of change history `apply` keeps by default; see
+/// [`FullStateMachine::set_change_retention`].
+pub const DEFAULT_CHANGE_RETENTION: u64 = 100_000;
+/// Prefix of alias records in the metadata tree: `__alias__:{alias}` →
+/// target path. Being an internal `__` key keeps them out of listings
+/// while snapshots and the change log carry them like any other key.
+const ALIAS_KEY_PREFIX: &str = "__alias__:";
+/// Longest alias chain followed on read and accepted by `LinkMetadata`.
+pub const MAX_ALIAS_DEPTH: usize = 16;
+
+fn alias_key(path: &str) -> String {
+    format!("{ALIAS_KEY_PREFIX}{path}")
+}
+
+/// The key `command` writes a value to, for the commands that must not
+/// shadow an alias link on that key.
+fn value_write_key(command: &Command) -> Option<&str> {
+    match command {
+        Command::SetMetadata { key, .. }
+        | Command::SetMetadataVersioned { key, .. }
+        | Command::CasSetMetadata { key, .. }
+        | Command::CasSetMetadataFenced { key, .. }
+        | Command::PutIfAbsent { key, .. } => Some(key),
+        _ => None,
+    }
+}
+
+fn alias_write_error(key: &str) -> CommandResult {
+    CommandResult::Error(format!(
+        "{key} is an alias; write to its target or delete the link first"
+    ))
+}
+
+/// Prefix of version histories in the metadata tree: `__history__:{key}`
+/// → bincode `Vec<Vec<u8>>` of prior values, newest first.
+const HISTORY_KEY_PREFIX: &str = "__history__:";
+
+fn history_key(path: &str) -> String {
+    format!("{HISTORY_KEY_PREFIX}{path}")
+}
+
+/// Internal `__` key prefixes that are replicated state rather than
+/// local bookkeeping, so snapshots carry them along with user metadata.
+/// Other internal keys (`__last_applied__`, `__changes_floor__`) stay
+/// out: restore rewrites them for the receiving replica.
+const SNAPSHOT_INTERNAL_PREFIXES: &[&str] =
+    &[ALIAS_KEY_PREFIX, HISTORY_KEY_PREFIX, FENCE_KEY_PREFIX];
+
+fn is_snapshot_key(path: &str) -> bool {
+    !path.starts_with("__")
+        || SNAPSHOT_INTERNAL_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+}
+
+/// Prefix of write fences in the metadata tree: `__fence__:{key}` → u64
+/// big-endian, the highest fencing token a `CasSetMetadataFenced` on
+/// `key` has accepted. Kept across `DeleteMetadata` so a stale holder
+/// cannot resurrect a key its successor deleted.
+const FENCE_KEY_PREFIX: &str = "__fence__:";
+
+fn fence_key(path: &str) -> String {
+    format!("{FENCE_KEY_PREFIX}{path}")
+}
+
+fn decode_fence(fence: Option<&[u8]>) -> u64 {
+    fence
+        .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+        .map(u64::from_be_bytes)
+        .unwrap_or(0)
+}
+
+/// Why a fenced write to `key` with `token` must be refused, given the
+/// key's stored fence and whether a current holder of the lock on `key`
+/// has `token` (`None` if the lock is not held; see
+/// [`LockState::holds_fencing_token`]).
+fn fencing_rejection(
+    key: &str,
+    token: u64,
+    fence: Option<&[u8]>,
+    held: Option<bool>,
+) -> Option<String> {
+    match held {
+        Some(true) => None,
+        Some(false) => Some(format!(
+            "stale fencing token {token} for {key}: not held by a current holder"
+        )),
+        None => {
+            let accepted = decode_fence(fence);
+            (token < accepted)
+                .then(|| format!("stale fencing token {token} for {key}: {accepted} is newer"))
+        }
+    }
+}
+
+/// The stored `history` with `previous` pushed to the front, trimmed to
+/// `max_history` entries.
+fn pushed_history(
+    history: Option<&[u8]>,
+    previous: Option<Vec<u8>>,
+    max_history: u32,
+) -> Result<Vec<Vec<u8>>> {
+    let mut entries: Vec<Vec<u8>> = match history {
+        Some(bytes) => bincode::deserialize(bytes)?,
+        None => Vec::new(),
+    };
+    if let Some(previous) = previous {
+        entries.insert(0, previous);
+    }
+    entries.truncate(max_history as usize);
+    Ok(entries)
+}
+
+/// Why linking `alias` → `target` must be refused, given `link_of`
+/// (path → the path it aliases, if it is an alias); `None` if it's fine.
+fn alias_link_rejection(
+    alias: &str,
+    target: &str,
+    mut link_of: impl FnMut(&str) -> Result<Option<String>>,
+) -> Result<Option<String>> {
+    let mut current = target.to_string();
+    for _ in 0..MAX_ALIAS_DEPTH {
+        if current == alias {
+            return Ok(Some(format!("alias cycle: {alias} -> {target}")));
+        }
+        match link_of(&current)? {
+            Some(next) => current = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(format!(
+        "alias chain from {target} exceeds {MAX_ALIAS_DEPTH} links"
+    )))
+}
 
 // R14: Advisory locks no longer have a redb tree. The BTreeMap in
 // `Arc<Mutex<LockState>>` is the single source of truth; persistence
@@ -602,6 +854,8 @@ pub struct FullStateMachine {
     /// Distinct from ``metadata`` so WAL stream payloads never appear
     /// in file-listing scans / snapshots that walk ``sm_metadata``.
     stream_entries: RedbTree,
+    /// Change log tree — log index -> committed metadata mutation.
+    changelog: RedbTree,
     /// Advisory lock SSOT — shared with the kernel's `LockManager`.
     advisory: Arc<Mutex<LockState>>,
     /// Last applied metadata/Noop log index (persisted to redb).
@@ -658,6 +912,10 @@ pub struct FullStateMachine {
     /// apply can't be poisoned per raft's "apply must not fail" rule.
     #[allow(clippy::type_complexity)]
     invalidate_cb: Arc<parking_lot::RwLock<Vec<Arc<dyn Fn(&str) + Send + Sync>>>>,
+    /// How many log indices of change history `apply` keeps; shared so
+    /// it can be tuned after the state machine moves into
+    /// ``ZoneConsensus``.
+    change_retention: Arc<AtomicU64>,
 }
 
 impl FullStateMachine {
@@ -678,6 +936,7 @@ impl FullStateMachine {
     pub fn with_advisory(store: &RedbStore, advisory: Arc<Mutex<LockState>>) -> Result<Self> {
         let metadata = store.tree(TREE_METADATA)?;
         let stream_entries = store.tree(TREE_STREAM_ENTRIES)?;
+        let changelog = store.tree(TREE_CHANGELOG)?;
 
         // Load last_applied from metadata tree.
         let last_applied = match metadata.get(KEY_LAST_APPLIED)? {
@@ -690,14 +949,22 @@ impl FullStateMachine {
             None => 0,
         };
 
+        // Stores created before the change log existed have no history
+        // to offer: changes are only complete from the current index.
+        if metadata.get(KEY_CHANGES_FLOOR)?.is_none() {
+            metadata.set(KEY_CHANGES_FLOOR, &last_applied.to_be_bytes())?;
+        }
+
         Ok(Self {
             metadata,
             stream_entries,
+            changelog,
             advisory,
             last_applied: Arc::new(AtomicU64::new(last_applied)),
             #[cfg(feature = "grpc")]
             mount_apply_cb: Arc::new(parking_lot::RwLock::new(None)),
             invalidate_cb: Arc::new(parking_lot::RwLock::new(Vec::new())),
+            change_retention: Arc::new(AtomicU64::new(DEFAULT_CHANGE_RETENTION)),
         })
     }
 
@@ -863,7 +1130,11 @@ impl FullStateMachine {
         let key = match command {
             Command::SetMetadata { key, .. }
             | Command::CasSetMetadata { key, .. }
-            | Command::DeleteMetadata { key } => key.as_str(),
+            | Command::DeleteMetadata { key }
+            | Command::LinkMetadata { alias: key, .. }
+            | Command::SetMetadataVersioned { key, .. }
+            | Command::PutIfAbsent { key, .. }
+            | Command::CasSetMetadataFenced { key, .. } => key.as_str(),
             _ => return,
         };
         let key_owned = key.to_string();
@@ -1044,12 +1315,85 @@ impl FullStateMachine {
         (result, bytes.len())
     }
 
+    /// Apply SetMetadataVersioned command.
+    fn apply_set_metadata_versioned(
+        &self,
+        key: &str,
+        value: &[u8],
+        max_history: u32,
+    ) -> Result<CommandResult> {
+        let history_key = history_key(key);
+        let history = pushed_history(
+            self.metadata.get(history_key.as_bytes())?.as_deref(),
+            self.metadata.get(key.as_bytes())?,
+            max_history,
+        )?;
+        if history.is_empty() {
+            self.metadata.delete(history_key.as_bytes())?;
+        } else {
+            self.metadata
+                .set(history_key.as_bytes(), &bincode::serialize(&history)?)?;
+        }
+        self.metadata.set(key.as_bytes(), value)?;
+        Ok(CommandResult::Success)
+    }
+
+    /// Apply PutIfAbsent command.
+    fn apply_put_if_absent(&self, key: &str, value: &[u8]) -> Result<CommandResult> {
+        let written = self.metadata.get(key.as_bytes())?.is_none();
+        if written {
+            self.metadata.set(key.as_bytes(), value)?;
+        }
+        Ok(CommandResult::PutResult { written })
+    }
+
+    /// Run a metadata command through `execute_metadata_in_txn` in a
+    /// write transaction of its own, for commands whose check and
+    /// writes must not be split across transactions.
+    fn execute_in_own_txn(&self, command: &Command) -> Result<CommandResult> {
+        let write_txn = self
+            .metadata
+            .raw_db()
+            .begin_write()
+            .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        let result = self.execute_metadata_in_txn(&write_txn, command)?;
+        write_txn
+            .commit()
+            .map_err(|e| super::RaftError::Storage(e.to_string()))?;
+        Ok(result)
+    }
+
     /// Apply DeleteMetadata command.
     fn apply_delete_metadata(&self, key: &str) -> Result<CommandResult> {
         self.metadata.delete(key.as_bytes())?;
+        self.metadata.delete(alias_key(key).as_bytes())?;
+        self.metadata.delete(history_key(key).as_bytes())?;
+        Ok(CommandResult::Success)
+    }
+
+    /// Apply LinkMetadata command.
+    fn apply_link_metadata(&self, alias: &str, target: &str) -> Result<CommandResult> {
+        if self.metadata.get(alias.as_bytes())?.is_some() {
+            return Ok(CommandResult::Error(format!(
+                "{alias} already has metadata"
+            )));
+        }
+        if let Some(reason) = alias_link_rejection(alias, target, |path| self.alias_target(path))? {
+            return Ok(CommandResult::Error(reason));
+        }
+        self.metadata
+            .set(alias_key(alias).as_bytes(), target.as_bytes())?;
         Ok(CommandResult::Success)
     }
 
+    /// The path `path` links to, if it is an alias.
+    fn alias_target(&self, path: &str) -> Result<Option<String>> {
+        Ok(self
+            .metadata
+            .get(alias_key(path).as_bytes())?
+            .map(|target| String::from_utf8_lossy(&target).into_owned()))
+    }
+
     /// Apply AcquireLock — delegates to `LockState::apply_acquire` under
     /// the shared advisory mutex.
     ///
@@ -1069,6 +1413,13 @@ impl FullStateMachine {
         Ok(CommandResult::LockResult(result))
     }
 
+    /// Apply AcquireLocks — delegates to `LockState::apply_acquire_all`.
+    fn apply_acquire_locks(&self, requests: &[LockRequest], now: u64) -> Result<CommandResult> {
+        let mut guard = self.advisory.lock();
+        let results = guard.apply_acquire_all(requests, now);
+        Ok(CommandResult::LockResults(results))
+    }
+
     /// Apply ReleaseLock — delegates to `LockState::apply_release`.
     fn apply_release_lock(&self, path: &str, lock_id: &str) -> Result<CommandResult> {
         let mut guard = self.advisory.lock();
@@ -1110,8 +1461,38 @@ impl FullStateMachine {
     }
 
     /// Get metadata by path.
+    ///
+    /// A path with no metadata of its own that was linked with
+    /// `LinkMetadata` returns its target's metadata, following chained
+    /// aliases up to [`MAX_ALIAS_DEPTH`] links.
     pub fn get_metadata(&self, path: &str) -> Result<Option<Vec<u8>>> {
-        Ok(self.metadata.get(path.as_bytes())?)
+        let mut current = path.to_string();
+        for _ in 0..=MAX_ALIAS_DEPTH {
+            if let Some(value) = self.metadata.get(current.as_bytes())? {
+                return Ok(Some(value));
+            }
+            match self.alias_target(&current)? {
+                Some(target) => current = target,
+                None => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether `path` holds metadata of its own, without following
+    /// aliases — the check `PutIfAbsent` makes.
+    pub fn has_own_metadata(&self, path: &str) -> Result<bool> {
+        Ok(self.metadata.get(path.as_bytes())?.is_some())
+    }
+
+    /// Prior values of `key` written by `SetMetadataVersioned`, newest
+    /// first. Empty if the key has no history.
+    pub fn get_metadata_history(&self, key: &str) -> Result<Vec<Vec<u8>>> {
+        pushed_history(
+            self.metadata.get(history_key(key).as_bytes())?.as_deref(),
+            None,
+            u32::MAX,
+        )
     }
 
     /// Get metadata for multiple paths in a single call.
@@ -1137,6 +1518,348 @@ impl FullStateMachine {
         Ok(result)
     }
 
+    /// List metadata keys with prefix, skipping `offset` keys and
+    /// returning at most `limit`, in sorted order.
+    ///
+    /// Same key set as [`list_metadata`](Self::list_metadata), but values
+    /// are never copied out of the store, so directory listings stay cheap
+    /// when entries are large.
+    pub fn list_metadata_keys(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut skipped = 0;
+        self.metadata.for_each_prefix(prefix.as_bytes(), |key, _| {
+            if keys.len() >= limit {
+                return Ok(false);
+            }
+            let Ok(path) = std::str::from_utf8(key) else {
+                return Ok(true);
+            };
+            // Skip internal keys
+            if path.starts_with("__") {
+                return Ok(true);
+            }
+            if skipped < offset {
+                skipped += 1;
+            } else {
+                keys.push(path.to_string());
+            }
+            Ok(true)
+        })?;
+        Ok(keys)
+    }
+
+    /// Merge the listings under several `prefixes` into one stream sorted
+    /// by name (the key with its prefix stripped), paginated by `offset` /
+    /// `limit`.
+    ///
+    /// Each prefix is scanned lazily in store order, all from one read
+    /// snapshot, and the scans are k-way merged, so no re-sort is needed
+    /// and reading stops once the page is full. A name present under more than one
+    /// prefix appears once, taken from the prefix `precedence` selects.
+    pub fn list_metadata_merged(
+        &self,
+        prefixes: &[String],
+        limit: usize,
+        offset: usize,
+        precedence: MergePrecedence,
+    ) -> Result<Vec<MergedEntry>> {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        let prefix_bytes: Vec<&[u8]> = prefixes.iter().map(|p| p.as_bytes()).collect();
+        let mut scans = self.metadata.scan_prefixes(&prefix_bytes)?;
+        // Next listed entry of scan `i` as (name, value), skipping internal
+        // and non-UTF-8 keys.
+        let advance = |scans: &mut [PrefixScan], i: usize| -> Result<_> {
+            for item in scans[i].by_ref() {
+                let (key, value) = item?;
+                if let Ok(path) = String::from_utf8(key) {
+                    // Skip internal keys
+                    if !path.starts_with("__") {
+                        return Ok(Some((path[prefixes[i].len()..].to_string(), value)));
+                    }
+                }
+            }
+            Ok(None)
+        };
+
+        // Min-heap of (name, prefix index) over the head of every scan.
+        let mut heads: Vec<Option<Vec<u8>>> = vec![None; scans.len()];
+        let mut heap = BinaryHeap::new();
+        for (i, head) in heads.iter_mut().enumerate() {
+            if let Some((name, value)) = advance(&mut scans, i)? {
+                *head = Some(value);
+                heap.push(Reverse((name, i)));
+            }
+        }
+
+        let mut merged = Vec::new();
+        let mut skipped = 0;
+        while let Some(Reverse((name, first))) = heap.pop() {
+            if merged.len() >= limit {
+                break;
+            }
+            // Pull every other scan's entry for the same name; the heap
+            // yields them next, in prefix order.
+            let mut sources = vec![first];
+            while let Some(Reverse((next, _))) = heap.peek() {
+                if *next != name {
+                    break;
+                }
+                let Reverse((_, i)) = heap.pop().unwrap();
+                sources.push(i);
+            }
+            let winner = match precedence {
+                MergePrecedence::FirstPrefix => sources[0],
+                MergePrecedence::LastPrefix => sources[sources.len() - 1],
+            };
+            let value = heads[winner].take().unwrap_or_default();
+            for &i in &sources {
+                if let Some((next, value)) = advance(&mut scans, i)? {
+                    heads[i] = Some(value);
+                    heap.push(Reverse((next, i)));
+                }
+            }
+            if skipped < offset {
+                skipped += 1;
+            } else {
+                merged.push(MergedEntry {
+                    name,
+                    prefix: winner,
+                    value,
+                });
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Metadata mutations committed after log index `index`, in apply
+    /// order.
+    ///
+    /// Replaying them over a snapshot taken at `index` reproduces the
+    /// current metadata, internal alias, history and fence records
+    /// included, so a backup can ship this instead of a full snapshot.
+    /// Failed CAS writes record nothing, and `AdjustCounter` records the
+    /// stored result as a `Set`.
+    ///
+    /// History covers the last
+    /// [`set_change_retention`](Self::set_change_retention) indices, and
+    /// is cut shorter by [`truncate_changes`](Self::truncate_changes),
+    /// snapshot restore and EC writes. Only Raft-applied commands carry
+    /// an index to record under, so an EC write (`apply_local`,
+    /// `apply_ec_with_lww`) instead raises the floor past the current
+    /// index: a diff from before it errors rather than silently missing
+    /// it. Taking a snapshot leaves history alone, since snapshots are
+    /// also produced for follower catch-up. Errors if `index` predates the
+    /// retained history, in which case the caller needs a full snapshot.
+    pub fn changes_since(&self, index: u64) -> Result<Vec<MetadataChange>> {
+        let floor = self.changes_floor()?;
+        if index < floor {
+            return Err(super::RaftError::InvalidState(format!(
+                "change log starts at index {floor}, cannot diff from {index}"
+            )));
+        }
+        let mut changes = Vec::new();
+        let Some(next) = index.checked_add(1) else {
+            return Ok(changes);
+        };
+        let after = (Bound::Included(next.to_be_bytes()), Bound::Unbounded);
+        for item in self.changelog.range(after) {
+            let (_, value) = item?;
+            changes.push(bincode::deserialize(&value)?);
+        }
+        Ok(changes)
+    }
+
+    /// Keep change history for the last `indices` log indices (at least
+    /// 1); `apply` drops older records as it goes. Defaults to
+    /// [`DEFAULT_CHANGE_RETENTION`].
+    pub fn set_change_retention(&self, indices: u64) {
+        self.change_retention
+            .store(indices.max(1), Ordering::Relaxed);
+    }
+
+    /// Drop change-log records at or below `index` and raise the floor
+    /// of [`changes_since`](Self::changes_since) to match, ahead of the
+    /// retention window — e.g. once a backup at `index` is durable.
+    pub fn truncate_changes(&self, index: u64) -> Result<()> {
+        // Raise the floor first: a crash before the removals only
+        // leaves records that `changes_since` can no longer reach.
+        let floor = self.changes_floor()?.max(index);
+        self.metadata.set(KEY_CHANGES_FLOOR, &floor.to_be_bytes())?;
+
+        let mut batch = self.changelog.batch();
+        let through = match index.checked_add(1) {
+            Some(next) => Bound::Excluded(next.to_be_bytes()),
+            None => Bound::Unbounded,
+        };
+        for item in self.changelog.range((Bound::Unbounded, through)) {
+            let (key, _) = item?;
+            batch.remove(&key);
+        }
+        batch.apply()?;
+        Ok(())
+    }
+
+    /// Raise the change-log floor past the current index ahead of an EC
+    /// write, which has no log index to be recorded under. Raised before
+    /// the write, so a crash in between only costs history.
+    fn fence_changes_for_ec_write(&self) -> Result<()> {
+        let next = self.last_applied.load(Ordering::Acquire) + 1;
+        if self.changes_floor()? < next {
+            self.metadata.set(KEY_CHANGES_FLOOR, &next.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Drop change-log records older than the retention window ending
+    /// at `index`, inside the apply transaction.
+    fn trim_changes_in_txn(&self, txn: &redb::WriteTransaction, index: u64) -> Result<()> {
+        let retention = self.change_retention.load(Ordering::Relaxed);
+        let Some(floor) = index.checked_sub(retention) else {
+            return Ok(());
+        };
+        let meta_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.metadata.name());
+        let mut meta_table = txn
+            .open_table(meta_def)
+            .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+        let current = match meta_table
+            .get(KEY_CHANGES_FLOOR)
+            .map_err(|e| super::RaftError::Storage(format!("get changes floor: {e}")))?
+        {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .value()
+                    .try_into()
+                    .map_err(|_| super::RaftError::Storage("invalid changes floor".into()))?;
+                u64::from_be_bytes(arr)
+            }
+            None => 0,
+        };
+        if floor <= current {
+            return Ok(());
+        }
+        meta_table
+            .insert(KEY_CHANGES_FLOOR, floor.to_be_bytes().as_slice())
+            .map_err(|e| super::RaftError::Storage(format!("insert changes floor: {e}")))?;
+
+        let log_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.changelog.name());
+        let mut log_table = txn
+            .open_table(log_def)
+            .map_err(|e| super::RaftError::Storage(format!("open changelog: {e}")))?;
+        let through = (floor + 1).to_be_bytes();
+        log_table
+            .retain_in::<&[u8], _>(..through.as_slice(), |_, _| false)
+            .map_err(|e| super::RaftError::Storage(format!("trim changelog: {e}")))?;
+        Ok(())
+    }
+
+    fn changes_floor(&self) -> Result<u64> {
+        match self.metadata.get(KEY_CHANGES_FLOOR)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .try_into()
+                    .map_err(|_| super::RaftError::Storage("invalid changes floor".into()))?;
+                Ok(u64::from_be_bytes(arr))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Record the mutations `command` made (per `result`) at `index` in
+    /// the change log, inside the apply transaction. A command that
+    /// writes several keys gets one record per key under the same index,
+    /// in the order they were written.
+    fn record_change_in_txn(
+        &self,
+        txn: &redb::WriteTransaction,
+        index: u64,
+        command: &Command,
+        result: &CommandResult,
+    ) -> Result<()> {
+        let meta_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.metadata.name());
+        // Internal records the command rewrote, read back as committed
+        // by this transaction.
+        let stored = |key: String| -> Result<(String, ChangeOp, Option<Vec<u8>>)> {
+            let table = txn
+                .open_table(meta_def)
+                .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+            let value = table
+                .get(key.as_bytes())
+                .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
+                .map(|v| v.value().to_vec());
+            let op = if value.is_some() {
+                ChangeOp::Set
+            } else {
+                ChangeOp::Delete
+            };
+            Ok((key, op, value))
+        };
+        let mut writes: Vec<(String, ChangeOp, Option<Vec<u8>>)> = Vec::new();
+        match (command, result) {
+            (Command::SetMetadata { key, value }, CommandResult::Success)
+            | (
+                Command::CasSetMetadata { key, value, .. },
+                CommandResult::CasResult { success: true, .. },
+            ) => writes.push((key.clone(), ChangeOp::Set, Some(value.clone()))),
+            (Command::SetMetadataVersioned { key, value, .. }, CommandResult::Success) => {
+                writes.push(stored(history_key(key))?);
+                writes.push((key.clone(), ChangeOp::Set, Some(value.clone())));
+            }
+            (
+                Command::CasSetMetadataFenced { key, value, .. },
+                CommandResult::CasResult { success: true, .. },
+            ) => {
+                writes.push((key.clone(), ChangeOp::Set, Some(value.clone())));
+                writes.push(stored(fence_key(key))?);
+            }
+            (Command::PutIfAbsent { key, value }, CommandResult::PutResult { written: true }) => {
+                writes.push((key.clone(), ChangeOp::Set, Some(value.clone())))
+            }
+            (Command::AdjustCounter { key, .. }, CommandResult::Value(value)) => {
+                writes.push((key.clone(), ChangeOp::Set, Some(value.clone())))
+            }
+            (Command::DeleteMetadata { key }, _) => {
+                writes.push((key.clone(), ChangeOp::Delete, None));
+                // The delete also unlinks `key` if it was an alias and
+                // drops its history; its fence stays.
+                writes.push((alias_key(key), ChangeOp::Delete, None));
+                writes.push((history_key(key), ChangeOp::Delete, None));
+            }
+            // The alias record, so replaying the log re-creates the link.
+            (Command::LinkMetadata { alias, target }, CommandResult::Success) => writes.push((
+                alias_key(alias),
+                ChangeOp::Set,
+                Some(target.as_bytes().to_vec()),
+            )),
+            _ => return Ok(()),
+        }
+        let log_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.changelog.name());
+        let mut table = txn
+            .open_table(log_def)
+            .map_err(|e| super::RaftError::Storage(format!("open changelog: {e}")))?;
+        for (seq, (key, op, value)) in writes.into_iter().enumerate() {
+            let change = MetadataChange {
+                index,
+                key,
+                op,
+                value,
+            };
+            table
+                .insert(
+                    change_log_key(index, seq as u32).as_slice(),
+                    bincode::serialize(&change)?.as_slice(),
+                )
+                .map_err(|e| super::RaftError::Storage(format!("insert changelog: {e}")))?;
+        }
+        Ok(())
+    }
+
     /// Iterate every DT_MOUNT entry in this state machine, returning
     /// ``(key, target_zone_id)`` pairs.
     ///
@@ -1237,13 +1960,45 @@ impl FullStateMachine {
     pub fn list_locks(&self, prefix: &str, limit: usize) -> Result<Vec<LockInfo>> {
         Ok(self.advisory.lock().list_locks(prefix, limit))
     }
+
+    /// List locks held by holders whose `holder_info` contains
+    /// `holder_info_substr` (reads the shared advisory map).
+    pub fn list_locks_by_holder(
+        &self,
+        holder_info_substr: &str,
+        limit: usize,
+    ) -> Result<Vec<LockInfo>> {
+        Ok(self
+            .advisory
+            .lock()
+            .list_locks_by_holder(holder_info_substr, limit))
+    }
+
+    /// Preview an acquire of `path` with `max_holders` against local
+    /// state (reads the shared advisory map; no proposal).
+    pub fn peek_lock(&self, path: &str, max_holders: u32, now_secs: u64) -> Result<LockPreview> {
+        Ok(self.advisory.lock().peek(path, max_holders, now_secs))
+    }
 }
 
+/// Opens every versioned snapshot, followed by the format version (u32
+/// little-endian) and the bincode body. Legacy snapshots are a bare
+/// bincode `LegacySnapshot`, which opens with the metadata map's u64
+/// length; read that way these bytes are a length no real map reaches,
+/// so the two cannot be mistaken for each other.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"NXSMSNAP";
+/// Current snapshot format. 1 is the unversioned legacy layout.
+const SNAPSHOT_VERSION: u32 = 2;
+
 /// Snapshot format for FullStateMachine.
 ///
 /// ``stream_entries`` is serialized with a ``#[serde(default)]`` so
 /// snapshots produced before R19.1b' (no stream table) still restore —
 /// absent entries become an empty map on the target replica.
+///
+/// Serialized behind [`SNAPSHOT_MAGIC`] and [`SNAPSHOT_VERSION`]; bump
+/// the version whenever this layout (including `LockState`) changes and
+/// keep a decode path for the previous one.
 #[derive(Debug, Serialize, Deserialize)]
 struct Snapshot {
     /// All metadata entries.
@@ -1257,6 +2012,100 @@ struct Snapshot {
     last_applied: u64,
 }
 
+impl Snapshot {
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = SNAPSHOT_MAGIC.to_vec();
+        data.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut data, self)?;
+        Ok(data)
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        let Some(rest) = data.strip_prefix(SNAPSHOT_MAGIC.as_slice()) else {
+            let legacy: LegacySnapshot = bincode::deserialize(data)?;
+            return Ok(legacy.into());
+        };
+        let (version, body) = rest.split_at(rest.len().min(4));
+        let version = <[u8; 4]>::try_from(version)
+            .map(u32::from_le_bytes)
+            .map_err(|_| super::RaftError::Storage("truncated snapshot header".into()))?;
+        match version {
+            SNAPSHOT_VERSION => Ok(bincode::deserialize(body)?),
+            other => Err(super::RaftError::Storage(format!(
+                "unsupported snapshot format {other}"
+            ))),
+        }
+    }
+}
+
+/// Snapshot format 1: unversioned, written before lock holders carried
+/// fencing tokens. Decoded by [`Snapshot::decode`] only.
+#[derive(Deserialize)]
+struct LegacySnapshot {
+    metadata: HashMap<String, Vec<u8>>,
+    #[serde(default)]
+    stream_entries: HashMap<String, Vec<u8>>,
+    advisory: LegacyLockState,
+    last_applied: u64,
+}
+
+#[derive(Deserialize)]
+struct LegacyLockState {
+    locks: BTreeMap<String, LegacyLockEntry>,
+}
+
+#[derive(Deserialize)]
+struct LegacyLockEntry {
+    max_holders: u32,
+    holders: Vec<LegacyHolderInfo>,
+}
+
+#[derive(Deserialize)]
+struct LegacyHolderInfo {
+    lock_id: String,
+    holder_info: String,
+    acquired_at: u64,
+    expires_at: u64,
+}
+
+impl From<LegacySnapshot> for Snapshot {
+    /// Holders from before fencing get token 0.
+    fn from(legacy: LegacySnapshot) -> Self {
+        let locks = legacy
+            .advisory
+            .locks
+            .into_iter()
+            .map(|(path, entry)| {
+                let holders = entry
+                    .holders
+                    .into_iter()
+                    .map(|h| HolderInfo {
+                        lock_id: h.lock_id,
+                        holder_info: h.holder_info,
+                        acquired_at: h.acquired_at,
+                        expires_at: h.expires_at,
+                        fencing_token: 0,
+                    })
+                    .collect();
+                let entry = LockEntry {
+                    max_holders: entry.max_holders,
+                    holders,
+                };
+                (path, entry)
+            })
+            .collect();
+        Self {
+            metadata: legacy.metadata,
+            stream_entries: legacy.stream_entries,
+            advisory: LockState {
+                locks,
+                last_fencing_token: 0,
+            },
+            last_applied: legacy.last_applied,
+        }
+    }
+}
+
 impl FullStateMachine {
     /// Shared command dispatch — the actual redb operations.
     ///
@@ -1266,6 +2115,11 @@ impl FullStateMachine {
     /// For the Raft `apply()` path, use `execute_in_txn()` instead — it runs
     /// inside a caller-provided transaction for atomicity with `last_applied`.
     fn execute(&self, command: &Command) -> Result<CommandResult> {
+        if let Some(key) = value_write_key(command) {
+            if self.alias_target(key)?.is_some() {
+                return Ok(alias_write_error(key));
+            }
+        }
         match command {
             Command::SetMetadata { key, value } => self.apply_set_metadata(key, value),
             Command::CasSetMetadata {
@@ -1289,6 +2143,9 @@ impl FullStateMachine {
                 holder_info,
                 *now_secs,
             ),
+            Command::AcquireLocks { requests, now_secs } => {
+                self.apply_acquire_locks(requests, *now_secs)
+            }
             Command::ReleaseLock { path, lock_id } => self.apply_release_lock(path, lock_id),
             Command::ForceReleaseLock { path } => self.apply_force_release_lock(path),
             Command::ExtendLock {
@@ -1306,6 +2163,14 @@ impl FullStateMachine {
                 self.stream_entries.delete(key.as_bytes())?;
                 Ok(CommandResult::Success)
             }
+            Command::LinkMetadata { alias, target } => self.apply_link_metadata(alias, target),
+            Command::SetMetadataVersioned {
+                key,
+                value,
+                max_history,
+            } => self.apply_set_metadata_versioned(key, value, *max_history),
+            Command::PutIfAbsent { key, value } => self.apply_put_if_absent(key, value),
+            Command::CasSetMetadataFenced { .. } => self.execute_in_own_txn(command),
             Command::Noop => Ok(CommandResult::Success),
         }
     }
@@ -1326,6 +2191,19 @@ impl FullStateMachine {
     ) -> Result<CommandResult> {
         let meta_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.metadata.name());
 
+        if let Some(key) = value_write_key(command) {
+            let table = txn
+                .open_table(meta_def)
+                .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+            let linked = table
+                .get(alias_key(key).as_bytes())
+                .map_err(|e| super::RaftError::Storage(format!("get alias: {e}")))?
+                .is_some();
+            if linked {
+                return Ok(alias_write_error(key));
+            }
+        }
+
         match command {
             Command::SetMetadata { key, value } => {
                 let mut table = txn
@@ -1375,22 +2253,91 @@ impl FullStateMachine {
                 table
                     .remove(key.as_bytes())
                     .map_err(|e| super::RaftError::Storage(format!("remove metadata: {e}")))?;
+                table
+                    .remove(alias_key(key).as_bytes())
+                    .map_err(|e| super::RaftError::Storage(format!("remove alias: {e}")))?;
+                table
+                    .remove(history_key(key).as_bytes())
+                    .map_err(|e| super::RaftError::Storage(format!("remove history: {e}")))?;
                 Ok(CommandResult::Success)
             }
 
-            Command::AdjustCounter { key, delta } => {
+            Command::SetMetadataVersioned {
+                key,
+                value,
+                max_history,
+            } => {
                 let mut table = txn
                     .open_table(meta_def)
                     .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
-                let current = table
-                    .get(key.as_bytes())
-                    .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
-                    .and_then(|v| <[u8; 8]>::try_from(v.value()).ok())
-                    .map(i64::from_be_bytes)
-                    .unwrap_or(0);
-                let new_val = (current + delta).max(0);
-                table
-                    .insert(key.as_bytes(), new_val.to_be_bytes().as_slice())
+                let get = |key: &str| -> Result<Option<Vec<u8>>> {
+                    Ok(table
+                        .get(key.as_bytes())
+                        .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
+                        .map(|v| v.value().to_vec()))
+                };
+                let history_key = history_key(key);
+                let history =
+                    pushed_history(get(&history_key)?.as_deref(), get(key)?, *max_history)?;
+                if history.is_empty() {
+                    table
+                        .remove(history_key.as_bytes())
+                        .map_err(|e| super::RaftError::Storage(format!("remove history: {e}")))?;
+                } else {
+                    table
+                        .insert(
+                            history_key.as_bytes(),
+                            bincode::serialize(&history)?.as_slice(),
+                        )
+                        .map_err(|e| super::RaftError::Storage(format!("insert history: {e}")))?;
+                }
+                table
+                    .insert(key.as_bytes(), value.as_slice())
+                    .map_err(|e| super::RaftError::Storage(format!("insert metadata: {e}")))?;
+                Ok(CommandResult::Success)
+            }
+
+            Command::LinkMetadata { alias, target } => {
+                let mut table = txn
+                    .open_table(meta_def)
+                    .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+                let get = |key: &str| -> Result<Option<Vec<u8>>> {
+                    Ok(table
+                        .get(key.as_bytes())
+                        .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
+                        .map(|v| v.value().to_vec()))
+                };
+                if get(alias)?.is_some() {
+                    return Ok(CommandResult::Error(format!(
+                        "{alias} already has metadata"
+                    )));
+                }
+                let rejection = alias_link_rejection(alias, target, |path| {
+                    Ok(get(&alias_key(path))?
+                        .map(|target| String::from_utf8_lossy(&target).into_owned()))
+                })?;
+                if let Some(reason) = rejection {
+                    return Ok(CommandResult::Error(reason));
+                }
+                table
+                    .insert(alias_key(alias).as_bytes(), target.as_bytes())
+                    .map_err(|e| super::RaftError::Storage(format!("insert alias: {e}")))?;
+                Ok(CommandResult::Success)
+            }
+
+            Command::AdjustCounter { key, delta } => {
+                let mut table = txn
+                    .open_table(meta_def)
+                    .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+                let current = table
+                    .get(key.as_bytes())
+                    .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
+                    .and_then(|v| <[u8; 8]>::try_from(v.value()).ok())
+                    .map(i64::from_be_bytes)
+                    .unwrap_or(0);
+                let new_val = (current + delta).max(0);
+                table
+                    .insert(key.as_bytes(), new_val.to_be_bytes().as_slice())
                     .map_err(|e| super::RaftError::Storage(format!("insert counter: {e}")))?;
                 Ok(CommandResult::Value(new_val.to_be_bytes().to_vec()))
             }
@@ -1419,10 +2366,76 @@ impl FullStateMachine {
                 Ok(CommandResult::Success)
             }
 
+            Command::PutIfAbsent { key, value } => {
+                let mut table = txn
+                    .open_table(meta_def)
+                    .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+                let written = table
+                    .get(key.as_bytes())
+                    .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
+                    .is_none();
+                if written {
+                    table
+                        .insert(key.as_bytes(), value.as_slice())
+                        .map_err(|e| super::RaftError::Storage(format!("insert metadata: {e}")))?;
+                }
+                Ok(CommandResult::PutResult { written })
+            }
+
+            Command::CasSetMetadataFenced {
+                key,
+                value,
+                expected_version,
+                fencing_token,
+            } => {
+                let mut table = txn
+                    .open_table(meta_def)
+                    .map_err(|e| super::RaftError::Storage(format!("open metadata: {e}")))?;
+                let get = |key: &str| -> Result<Option<Vec<u8>>> {
+                    Ok(table
+                        .get(key.as_bytes())
+                        .map_err(|e| super::RaftError::Storage(format!("get metadata: {e}")))?
+                        .map(|v| v.value().to_vec()))
+                };
+                let fence_key = fence_key(key);
+                // Apply is serial, so the token cannot move between this
+                // read and the write below.
+                let held = self
+                    .advisory
+                    .lock()
+                    .holds_fencing_token(key, *fencing_token);
+                let fence = get(&fence_key)?;
+                if let Some(reason) = fencing_rejection(key, *fencing_token, fence.as_deref(), held)
+                {
+                    return Ok(CommandResult::Error(reason));
+                }
+                let current_version = get(key)?.map_or(0, |bytes| Self::extract_version(&bytes));
+                if current_version != *expected_version {
+                    return Ok(CommandResult::CasResult {
+                        success: false,
+                        current_version,
+                    });
+                }
+                table
+                    .insert(key.as_bytes(), value.as_slice())
+                    .map_err(|e| super::RaftError::Storage(format!("insert metadata: {e}")))?;
+                // A semaphore co-holder may carry an older token than
+                // the last accepted one; the fence never moves back.
+                let raised = decode_fence(fence.as_deref()).max(*fencing_token);
+                table
+                    .insert(fence_key.as_bytes(), raised.to_be_bytes().as_slice())
+                    .map_err(|e| super::RaftError::Storage(format!("insert fence: {e}")))?;
+                Ok(CommandResult::CasResult {
+                    success: true,
+                    current_version: expected_version + 1,
+                })
+            }
+
             Command::Noop => Ok(CommandResult::Success),
 
             // Lock commands never flow here.
             Command::AcquireLock { .. }
+            | Command::AcquireLocks { .. }
             | Command::ReleaseLock { .. }
             | Command::ForceReleaseLock { .. }
             | Command::ExtendLock { .. } => Err(super::RaftError::InvalidState(
@@ -1435,6 +2448,7 @@ impl FullStateMachine {
         matches!(
             command,
             Command::AcquireLock { .. }
+                | Command::AcquireLocks { .. }
                 | Command::ReleaseLock { .. }
                 | Command::ForceReleaseLock { .. }
                 | Command::ExtendLock { .. }
@@ -1447,9 +2461,13 @@ impl StateMachine for FullStateMachine {
         match command {
             Command::SetMetadata { .. }
             | Command::CasSetMetadata { .. }
-            | Command::DeleteMetadata { .. }
-            | Command::AppendStreamEntry { .. }
-            | Command::DeleteStreamEntry { .. } => self.execute(command),
+            | Command::DeleteMetadata { .. } => {
+                self.fence_changes_for_ec_write()?;
+                self.execute(command)
+            }
+            Command::AppendStreamEntry { .. } | Command::DeleteStreamEntry { .. } => {
+                self.execute(command)
+            }
             _ => Err(super::RaftError::InvalidState(
                 "Only metadata operations (set/delete) support EC local writes".into(),
             )),
@@ -1464,6 +2482,9 @@ impl StateMachine for FullStateMachine {
     ) -> Result<CommandResult> {
         match command {
             Command::SetMetadata { key, value } => {
+                if self.alias_target(key)?.is_some() {
+                    return Ok(alias_write_error(key));
+                }
                 // LWW: compare incoming vs existing modified_at (ISO 8601 lexicographic)
                 if let Some(existing) = self.metadata.get(key.as_bytes())? {
                     let incoming_ts = decode_modified_at(value);
@@ -1478,6 +2499,7 @@ impl StateMachine for FullStateMachine {
                         return Ok(CommandResult::Success);
                     }
                 }
+                self.fence_changes_for_ec_write()?;
                 self.apply_set_metadata(key, value)
             }
             Command::DeleteMetadata { key } => {
@@ -1494,6 +2516,7 @@ impl StateMachine for FullStateMachine {
                         return Ok(CommandResult::Success);
                     }
                 }
+                self.fence_changes_for_ec_write()?;
                 self.apply_delete_metadata(key)
             }
             _ => Err(super::RaftError::InvalidState(
@@ -1529,6 +2552,9 @@ impl StateMachine for FullStateMachine {
                     holder_info,
                     *now_secs,
                 )?,
+                Command::AcquireLocks { requests, now_secs } => {
+                    self.apply_acquire_locks(requests, *now_secs)?
+                }
                 Command::ReleaseLock { path, lock_id } => self.apply_release_lock(path, lock_id)?,
                 Command::ForceReleaseLock { path } => self.apply_force_release_lock(path)?,
                 Command::ExtendLock {
@@ -1627,6 +2653,19 @@ impl StateMachine for FullStateMachine {
             }
         };
 
+        // Change log entry rides the same transaction, so a backup diff
+        // never sees a mutation that didn't commit (or misses one that did).
+        if let Err(e) = self
+            .record_change_in_txn(&write_txn, index, command, &result)
+            .and_then(|()| self.trim_changes_in_txn(&write_txn, index))
+        {
+            panic!(
+                "Fatal: failed to record change in apply txn at index {}: {}. \
+                 Node must be restored from snapshot to recover.",
+                index, e
+            );
+        }
+
         // Persist last_applied in the SAME transaction — atomic with the
         // command mutation. On crash, either both are persisted or neither.
         match write_txn.open_table(meta_def) {
@@ -1684,8 +2723,8 @@ impl StateMachine for FullStateMachine {
         for item in self.metadata.iter() {
             let (key, value) = item?;
             if let Ok(path) = String::from_utf8(key) {
-                // Skip internal keys
-                if !path.starts_with("__") {
+                // Skip internal keys other than replicated ones
+                if is_snapshot_key(&path) {
                     metadata.insert(path, value);
                 }
             }
@@ -1713,12 +2752,11 @@ impl StateMachine for FullStateMachine {
             advisory,
             last_applied: self.last_applied.load(Ordering::Relaxed),
         };
-
-        Ok(bincode::serialize(&snapshot)?)
+        snapshot.encode()
     }
 
     fn restore_snapshot(&mut self, data: &[u8]) -> Result<()> {
-        let snapshot: Snapshot = bincode::deserialize(data)?;
+        let snapshot = Snapshot::decode(data)?;
 
         // Atomic restore for metadata: clear + repopulate in a single
         // redb transaction. Advisory locks are in-memory only — they
@@ -1731,6 +2769,7 @@ impl StateMachine for FullStateMachine {
         })?;
 
         let stream_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.stream_entries.name());
+        let log_def = redb::TableDefinition::<&[u8], &[u8]>::new(self.changelog.name());
 
         {
             write_txn
@@ -1752,6 +2791,18 @@ impl StateMachine for FullStateMachine {
                 )
                 .map_err(|e| super::RaftError::Storage(format!("insert last_applied: {e}")))?;
 
+            // History before the snapshot is gone: clear the change log
+            // and start it at the snapshot's index.
+            meta_table
+                .insert(
+                    KEY_CHANGES_FLOOR,
+                    snapshot.last_applied.to_be_bytes().as_slice(),
+                )
+                .map_err(|e| super::RaftError::Storage(format!("insert changes floor: {e}")))?;
+            write_txn
+                .delete_table(log_def)
+                .map_err(|e| super::RaftError::Storage(format!("delete changelog table: {e}")))?;
+
             // R19.1b': same atomic transaction restores stream_entries.
             // ``delete_table`` wipes the previous state; then reinsert
             // the snapshot contents. Pre-R19.1b' snapshots carry an
@@ -2222,8 +3273,8 @@ mod tests {
         // Snapshots must be logically identical (HashMap serialization order may vary).
         let snap1 = sm1.snapshot().unwrap();
         let snap2 = sm2.snapshot().unwrap();
-        let decoded1: Snapshot = bincode::deserialize(&snap1).unwrap();
-        let decoded2: Snapshot = bincode::deserialize(&snap2).unwrap();
+        let decoded1 = Snapshot::decode(&snap1).unwrap();
+        let decoded2 = Snapshot::decode(&snap2).unwrap();
         assert_eq!(decoded1.metadata, decoded2.metadata, "Metadata diverged");
         assert_eq!(
             decoded1.advisory.locks, decoded2.advisory.locks,
@@ -2327,6 +3378,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_peek_lock_does_not_change_holders() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+
+        let peek = sm.peek_lock("/test/sem", 2, 1000).unwrap();
+        assert!(peek.would_acquire);
+        assert_eq!(peek.available_slots, 2);
+
+        for (index, id) in [(1, "holder-1"), (2, "holder-2")] {
+            let cmd = Command::AcquireLock {
+                path: "/test/sem".into(),
+                lock_id: id.into(),
+                max_holders: 2,
+                ttl_secs: 30,
+                holder_info: "agent:test".into(),
+                now_secs: 1000,
+            };
+            sm.apply(index, &cmd).unwrap();
+        }
+
+        let peek = sm.peek_lock("/test/sem", 2, 1000).unwrap();
+        assert!(!peek.would_acquire);
+        assert_eq!((peek.current_holders, peek.available_slots), (2, 0));
+        let peek_again = sm.peek_lock("/test/sem", 2, 1000).unwrap();
+        assert_eq!(peek, peek_again);
+        assert_eq!(sm.get_lock("/test/sem").unwrap().unwrap().holders.len(), 2);
+    }
+
+    #[test]
+    fn test_list_and_release_locks_by_holder() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+
+        let mut index = 0;
+        for (path, id, holder) in [
+            ("/a", "a1", "agent:alice"),
+            ("/b", "a2", "agent:alice"),
+            ("/c", "b1", "agent:bob"),
+        ] {
+            index += 1;
+            let cmd = Command::AcquireLock {
+                path: path.into(),
+                lock_id: id.into(),
+                max_holders: 1,
+                ttl_secs: 30,
+                holder_info: holder.into(),
+                now_secs: 1000,
+            };
+            sm.apply(index, &cmd).unwrap();
+        }
+
+        let held = sm.list_locks_by_holder("agent:alice", 100).unwrap();
+        let mut paths: Vec<&str> = held.iter().map(|l| l.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/a", "/b"]);
+
+        // What ZoneHandle::release_all_for_holder proposes.
+        for lock in &held {
+            for holder in &lock.holders {
+                index += 1;
+                let cmd = Command::ReleaseLock {
+                    path: lock.path.clone(),
+                    lock_id: holder.lock_id.clone(),
+                };
+                assert!(matches!(
+                    sm.apply(index, &cmd).unwrap(),
+                    CommandResult::Success
+                ));
+            }
+        }
+
+        assert!(sm
+            .list_locks_by_holder("agent:alice", 100)
+            .unwrap()
+            .is_empty());
+        assert!(sm.get_lock("/a").unwrap().is_none());
+        assert_eq!(sm.get_lock("/c").unwrap().unwrap().holders.len(), 1);
+    }
+
     #[test]
     fn test_full_state_machine_semaphore_lock() {
         let store = RedbStore::open_temporary().unwrap();
@@ -2717,6 +3848,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_acquire_locks_conflict_leaves_no_partial_holds() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        sm.apply(1, &acquire_cmd("/rw/y", "other", 1, 1000))
+            .unwrap();
+
+        let request = |path: &str| LockRequest {
+            path: path.into(),
+            lock_id: "batch".into(),
+            max_holders: 1,
+            ttl_secs: 60,
+            holder_info: "agent:batch".into(),
+        };
+        let cmd = Command::AcquireLocks {
+            requests: vec![request("/rw/z"), request("/rw/x"), request("/rw/y")],
+            now_secs: 1000,
+        };
+        match sm.apply(2, &cmd).unwrap() {
+            CommandResult::LockResults(results) => {
+                assert_eq!(results.len(), 3);
+                assert!(results.iter().all(|r| !r.acquired));
+            }
+            _ => panic!("LockResults"),
+        }
+        assert!(sm.get_lock("/rw/x").unwrap().is_none());
+        assert!(sm.get_lock("/rw/z").unwrap().is_none());
+    }
+
     #[test]
     fn test_f4_max_holders_mismatch_rejects() {
         let store = RedbStore::open_temporary().unwrap();
@@ -3426,7 +4586,7 @@ mod tests {
         // self-consistent map — holders only on entries whose
         // `max_holders > 0`.
         for bytes in snapshots.lock().unwrap().iter() {
-            let snap: Snapshot = bincode::deserialize(bytes).unwrap();
+            let snap = Snapshot::decode(bytes).unwrap();
             for entry in snap.advisory.locks.values() {
                 if !entry.holders.is_empty() {
                     assert!(entry.max_holders > 0);
@@ -3462,6 +4622,638 @@ mod tests {
         assert!(sm.list_metadata("/__wal_stream__/").unwrap().is_empty());
     }
 
+    #[test]
+    fn test_list_metadata_keys_matches_list_metadata_and_paginates() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        for (i, key) in ["/d/c", "/d/a", "/e/x", "/d/b", "/d/e", "/d/d"]
+            .iter()
+            .enumerate()
+        {
+            let cmd = Command::SetMetadata {
+                key: key.to_string(),
+                value: vec![0u8; 4096],
+            };
+            sm.apply(i as u64 + 1, &cmd).unwrap();
+        }
+
+        let expected: Vec<String> = sm
+            .list_metadata("/d/")
+            .unwrap()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(
+            sm.list_metadata_keys("/d/", usize::MAX, 0).unwrap(),
+            expected
+        );
+        assert_eq!(expected, ["/d/a", "/d/b", "/d/c", "/d/d", "/d/e"]);
+
+        // Pages of two concatenate back to the full listing.
+        let mut paged = Vec::new();
+        for offset in (0..6).step_by(2) {
+            paged.extend(sm.list_metadata_keys("/d/", 2, offset).unwrap());
+        }
+        assert_eq!(paged, expected);
+        assert!(sm.list_metadata_keys("/d/", 2, 5).unwrap().is_empty());
+        assert!(sm.list_metadata_keys("/d/", 0, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_link_metadata_resolves_and_rejects_cycles() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let set = |key: &str, value: &[u8]| Command::SetMetadata {
+            key: key.to_string(),
+            value: value.to_vec(),
+        };
+        let link = |alias: &str, target: &str| Command::LinkMetadata {
+            alias: alias.to_string(),
+            target: target.to_string(),
+        };
+        let is_error = |result: CommandResult| matches!(result, CommandResult::Error(_));
+
+        sm.apply(1, &set("/ws/file", b"v1")).unwrap();
+        sm.apply(2, &link("/link", "/ws/file")).unwrap();
+        assert_eq!(sm.get_metadata("/link").unwrap(), Some(b"v1".to_vec()));
+
+        // The alias tracks target updates, including through a chain.
+        sm.apply(3, &set("/ws/file", b"v2")).unwrap();
+        sm.apply(4, &link("/link2", "/link")).unwrap();
+        assert_eq!(sm.get_metadata("/link").unwrap(), Some(b"v2".to_vec()));
+        assert_eq!(sm.get_metadata("/link2").unwrap(), Some(b"v2".to_vec()));
+        // Alias records stay out of listings.
+        assert_eq!(sm.list_metadata("").unwrap().len(), 1);
+
+        // Cycles, self-links and links over real metadata are refused.
+        assert!(is_error(sm.apply(5, &link("/link", "/link2")).unwrap()));
+        assert!(is_error(sm.apply(6, &link("/self", "/self")).unwrap()));
+        assert!(is_error(sm.apply(7, &link("/ws/file", "/other")).unwrap()));
+        assert_eq!(sm.get_metadata("/self").unwrap(), None);
+
+        // Writes to an alias would shadow the link and are refused.
+        let put = Command::PutIfAbsent {
+            key: "/link".to_string(),
+            value: b"shadow".to_vec(),
+        };
+        let cas = Command::CasSetMetadata {
+            key: "/link".to_string(),
+            value: b"shadow".to_vec(),
+            expected_version: 0,
+        };
+        assert!(is_error(sm.apply(8, &set("/link", b"shadow")).unwrap()));
+        assert!(is_error(sm.apply(9, &put).unwrap()));
+        assert!(is_error(sm.apply(10, &cas).unwrap()));
+        assert_eq!(sm.get_metadata("/link").unwrap(), Some(b"v2".to_vec()));
+
+        // Links travel with snapshots.
+        let other_store = RedbStore::open_temporary().unwrap();
+        let mut restored = FullStateMachine::new(&other_store).unwrap();
+        restored.restore_snapshot(&sm.snapshot().unwrap()).unwrap();
+        assert_eq!(
+            restored.get_metadata("/link2").unwrap(),
+            Some(b"v2".to_vec())
+        );
+
+        // Deleting the alias removes only the link, and the change log
+        // says so.
+        let delete = Command::DeleteMetadata {
+            key: "/link".to_string(),
+        };
+        sm.apply(11, &delete).unwrap();
+        assert_eq!(sm.get_metadata("/link").unwrap(), None);
+        assert_eq!(sm.get_metadata("/link2").unwrap(), None);
+        assert_eq!(sm.get_metadata("/ws/file").unwrap(), Some(b"v2".to_vec()));
+        let removed: Vec<String> = sm
+            .changes_since(10)
+            .unwrap()
+            .into_iter()
+            .map(|change| change.key)
+            .collect();
+        assert_eq!(removed, ["/link", "__alias__:/link", "__history__:/link"]);
+
+        // A dangling alias reads as missing.
+        sm.apply(12, &link("/dangling", "/nowhere")).unwrap();
+        assert_eq!(sm.get_metadata("/dangling").unwrap(), None);
+    }
+
+    #[test]
+    fn test_set_metadata_versioned_keeps_bounded_history() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let set = |value: &[u8]| Command::SetMetadataVersioned {
+            key: "/doc".to_string(),
+            value: value.to_vec(),
+            max_history: 2,
+        };
+
+        sm.apply(1, &set(b"v1")).unwrap();
+        assert!(sm.get_metadata_history("/doc").unwrap().is_empty());
+        for (i, value) in [b"v2", b"v3", b"v4"].iter().enumerate() {
+            sm.apply(i as u64 + 2, &set(*value)).unwrap();
+        }
+        assert_eq!(sm.get_metadata("/doc").unwrap(), Some(b"v4".to_vec()));
+        assert_eq!(
+            sm.get_metadata_history("/doc").unwrap(),
+            vec![b"v3".to_vec(), b"v2".to_vec()]
+        );
+        // History records stay out of listings.
+        assert_eq!(sm.list_metadata("").unwrap().len(), 1);
+
+        // History travels with snapshots.
+        let snapshot = sm.snapshot().unwrap();
+        let other_store = RedbStore::open_temporary().unwrap();
+        let mut restored = FullStateMachine::new(&other_store).unwrap();
+        restored.restore_snapshot(&snapshot).unwrap();
+        assert_eq!(
+            restored.get_metadata_history("/doc").unwrap(),
+            vec![b"v3".to_vec(), b"v2".to_vec()]
+        );
+
+        // Deleting the key drops its history.
+        let delete = Command::DeleteMetadata {
+            key: "/doc".to_string(),
+        };
+        sm.apply(5, &delete).unwrap();
+        assert!(sm.get_metadata_history("/doc").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_put_if_absent_writes_once() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let put = |value: &[u8]| Command::PutIfAbsent {
+            key: "/cas/abc123".to_string(),
+            value: value.to_vec(),
+        };
+
+        let first = sm.apply(1, &put(b"blob")).unwrap();
+        assert!(matches!(first, CommandResult::PutResult { written: true }));
+        let second = sm.apply(2, &put(b"other")).unwrap();
+        assert!(matches!(
+            second,
+            CommandResult::PutResult { written: false }
+        ));
+        assert_eq!(
+            sm.get_metadata("/cas/abc123").unwrap(),
+            Some(b"blob".to_vec())
+        );
+
+        // Only the write that happened reaches the change log.
+        let changes = sm.changes_since(0).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].index, 1);
+    }
+
+    #[test]
+    fn test_cas_metadata_fenced_rejects_superseded_holder() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let key = "/fenced/doc";
+        let acquire = |lock_id: &str, now_secs: u64| Command::AcquireLock {
+            path: key.to_string(),
+            lock_id: lock_id.to_string(),
+            max_holders: 1,
+            ttl_secs: 10,
+            holder_info: lock_id.to_string(),
+            now_secs,
+        };
+        let write = |value: &[u8], fencing_token: u64| Command::CasSetMetadataFenced {
+            key: key.to_string(),
+            value: value.to_vec(),
+            expected_version: 0,
+            fencing_token,
+        };
+        let token = |result: CommandResult| match result {
+            CommandResult::LockResult(r) if r.acquired => r.fencing_token,
+            other => panic!("expected an acquired lock, got {other:?}"),
+        };
+
+        let old = token(sm.apply(1, &acquire("a", 1000)).unwrap());
+        assert!(matches!(
+            sm.apply(2, &write(b"from a", old)).unwrap(),
+            CommandResult::CasResult { success: true, .. }
+        ));
+
+        // a pauses past its TTL; b takes the lock with a newer token.
+        let new = token(sm.apply(3, &acquire("b", 1100)).unwrap());
+        assert!(new > old);
+        assert!(matches!(
+            sm.apply(4, &write(b"late a", old)).unwrap(),
+            CommandResult::Error(_)
+        ));
+        assert_eq!(sm.get_metadata(key).unwrap(), Some(b"from a".to_vec()));
+
+        assert!(matches!(
+            sm.apply(5, &write(b"from b", new)).unwrap(),
+            CommandResult::CasResult { success: true, .. }
+        ));
+        // The key's fence outlives the lock entry.
+        sm.apply(
+            6,
+            &Command::ReleaseLock {
+                path: key.to_string(),
+                lock_id: "b".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            sm.apply(7, &write(b"later a", old)).unwrap(),
+            CommandResult::Error(_)
+        ));
+        assert_eq!(sm.get_metadata(key).unwrap(), Some(b"from b".to_vec()));
+
+        // So does a snapshot restore.
+        let other_store = RedbStore::open_temporary().unwrap();
+        let mut restored = FullStateMachine::new(&other_store).unwrap();
+        restored.restore_snapshot(&sm.snapshot().unwrap()).unwrap();
+        assert!(matches!(
+            restored.apply(8, &write(b"later a", old)).unwrap(),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_fenced_cas_admits_every_semaphore_holder() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let key = "/fenced/shared";
+        let acquire = |lock_id: &str| Command::AcquireLock {
+            path: key.to_string(),
+            lock_id: lock_id.to_string(),
+            max_holders: 2,
+            ttl_secs: 60,
+            holder_info: lock_id.to_string(),
+            now_secs: 1000,
+        };
+        let write = |value: &[u8], fencing_token: u64| Command::CasSetMetadataFenced {
+            key: key.to_string(),
+            value: value.to_vec(),
+            expected_version: 0,
+            fencing_token,
+        };
+        let token = |result: CommandResult| match result {
+            CommandResult::LockResult(r) if r.acquired => r.fencing_token,
+            other => panic!("expected an acquired lock, got {other:?}"),
+        };
+
+        let first = token(sm.apply(1, &acquire("r1")).unwrap());
+        let second = token(sm.apply(2, &acquire("r2")).unwrap());
+        assert!(second > first);
+        for (index, fencing_token) in [(3, second), (4, first)] {
+            assert!(matches!(
+                sm.apply(index, &write(b"shared", fencing_token)).unwrap(),
+                CommandResult::CasResult { success: true, .. }
+            ));
+        }
+
+        // Once both leave, the fence stays at the newest token.
+        for (index, lock_id) in [(5, "r1"), (6, "r2")] {
+            let release = Command::ReleaseLock {
+                path: key.to_string(),
+                lock_id: lock_id.to_string(),
+            };
+            sm.apply(index, &release).unwrap();
+        }
+        assert!(matches!(
+            sm.apply(7, &write(b"late r1", first)).unwrap(),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn test_restore_legacy_snapshot_without_fencing_tokens() {
+        type LegacyHolder = (String, String, u64, u64);
+        let metadata = HashMap::from([("/a".to_string(), b"meta".to_vec())]);
+        let locks = BTreeMap::from([(
+            "/locked".to_string(),
+            (
+                1u32,
+                vec![("lock-1".to_string(), "agent".to_string(), 1000u64, 2000u64)]
+                    as Vec<LegacyHolder>,
+            ),
+        )]);
+        let legacy =
+            bincode::serialize(&(metadata, HashMap::<String, Vec<u8>>::new(), locks, 7u64))
+                .unwrap();
+
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        sm.restore_snapshot(&legacy).unwrap();
+        assert_eq!(sm.get_metadata("/a").unwrap(), Some(b"meta".to_vec()));
+        let lock = sm.get_lock("/locked").unwrap().unwrap();
+        assert_eq!(lock.holders[0].lock_id, "lock-1");
+        assert_eq!(lock.holders[0].fencing_token, 0);
+        assert_eq!(sm.last_applied_index(), 7);
+
+        // Snapshots taken now use the versioned format.
+        assert!(sm.snapshot().unwrap().starts_with(SNAPSHOT_MAGIC));
+    }
+
+    #[test]
+    fn test_list_metadata_merged_dedups_by_precedence() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let entries = [
+            ("/base/a", "base"),
+            ("/base/c", "base"),
+            ("/base/d", "base"),
+            ("/upper/b", "upper"),
+            ("/upper/c", "upper"),
+            ("/extra/c", "extra"),
+            ("/extra/e", "extra"),
+        ];
+        for (i, (key, value)) in entries.iter().enumerate() {
+            let cmd = Command::SetMetadata {
+                key: key.to_string(),
+                value: value.as_bytes().to_vec(),
+            };
+            sm.apply(i as u64 + 1, &cmd).unwrap();
+        }
+        let prefixes: Vec<String> = ["/base/", "/upper/", "/extra/"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let summary = |merged: Vec<MergedEntry>| -> Vec<(String, usize, String)> {
+            merged
+                .into_iter()
+                .map(|e| (e.name, e.prefix, String::from_utf8(e.value).unwrap()))
+                .collect()
+        };
+        let row =
+            |name: &str, prefix: usize, value: &str| (name.to_string(), prefix, value.to_string());
+
+        let first = sm
+            .list_metadata_merged(&prefixes, usize::MAX, 0, MergePrecedence::FirstPrefix)
+            .unwrap();
+        assert_eq!(
+            summary(first),
+            vec![
+                row("a", 0, "base"),
+                row("b", 1, "upper"),
+                row("c", 0, "base"),
+                row("d", 0, "base"),
+                row("e", 2, "extra"),
+            ]
+        );
+
+        let last = sm
+            .list_metadata_merged(&prefixes, usize::MAX, 0, MergePrecedence::LastPrefix)
+            .unwrap();
+        assert_eq!(summary(last)[2], row("c", 2, "extra"));
+
+        // Pagination applies after dedup.
+        let page = sm
+            .list_metadata_merged(&prefixes, 2, 2, MergePrecedence::LastPrefix)
+            .unwrap();
+        assert_eq!(
+            summary(page),
+            vec![row("c", 2, "extra"), row("d", 0, "base")]
+        );
+        assert!(sm
+            .list_metadata_merged(&prefixes, 10, 5, MergePrecedence::FirstPrefix)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_changes_since_replays_onto_old_snapshot() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let set = |key: &str, value: &[u8]| Command::SetMetadata {
+            key: key.to_string(),
+            value: value.to_vec(),
+        };
+        sm.apply(1, &set("/a", b"a1")).unwrap();
+        sm.apply(2, &set("/b", b"b1")).unwrap();
+        sm.apply(3, &set("/c", b"c1")).unwrap();
+        let old = sm.snapshot().unwrap();
+
+        sm.apply(4, &set("/a", b"a2")).unwrap();
+        sm.apply(
+            5,
+            &Command::DeleteMetadata {
+                key: "/b".to_string(),
+            },
+        )
+        .unwrap();
+        sm.apply(
+            6,
+            &Command::AdjustCounter {
+                key: "/n".to_string(),
+                delta: 5,
+            },
+        )
+        .unwrap();
+        // Failed CAS: no mutation, nothing recorded.
+        sm.apply(
+            7,
+            &Command::CasSetMetadata {
+                key: "/c".to_string(),
+                value: b"c2".to_vec(),
+                expected_version: 99,
+            },
+        )
+        .unwrap();
+        sm.apply(8, &Command::Noop).unwrap();
+        sm.apply(9, &set("/d", b"d1")).unwrap();
+
+        let changes = sm.changes_since(3).unwrap();
+        let indices: Vec<u64> = changes.iter().map(|c| c.index).collect();
+        // The delete at 5 also records removing its alias and history.
+        assert_eq!(indices, [4, 5, 5, 5, 6, 9]);
+        assert_eq!(changes[1].op, ChangeOp::Delete);
+
+        // Old snapshot + changes == current state. Records sharing an
+        // index are replayed as entries of their own.
+        let mut restored = FullStateMachine::new(&RedbStore::open_temporary().unwrap()).unwrap();
+        restored.restore_snapshot(&old).unwrap();
+        for (i, change) in changes.iter().enumerate() {
+            let command = match change.op {
+                ChangeOp::Set => set(&change.key, change.value.as_deref().unwrap()),
+                ChangeOp::Delete => Command::DeleteMetadata {
+                    key: change.key.clone(),
+                },
+            };
+            restored.apply(4 + i as u64, &command).unwrap();
+        }
+        assert_eq!(
+            restored.list_metadata("").unwrap(),
+            sm.list_metadata("").unwrap()
+        );
+
+        // Diffing from the current index is empty; from before the
+        // restored snapshot it is an error.
+        assert!(sm.changes_since(9).unwrap().is_empty());
+        assert!(restored.changes_since(2).is_err());
+
+        sm.truncate_changes(5).unwrap();
+        assert!(sm.changes_since(4).is_err());
+        let indices: Vec<u64> = sm
+            .changes_since(5)
+            .unwrap()
+            .iter()
+            .map(|c| c.index)
+            .collect();
+        assert_eq!(indices, [6, 9]);
+    }
+
+    #[test]
+    fn test_changes_since_carries_internal_records_and_survives_snapshot() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let versioned = |value: &[u8]| Command::SetMetadataVersioned {
+            key: "/doc".to_string(),
+            value: value.to_vec(),
+            max_history: 4,
+        };
+        sm.apply(1, &versioned(b"v1")).unwrap();
+        let base = sm.snapshot().unwrap();
+
+        sm.apply(2, &versioned(b"v2")).unwrap();
+        let acquire = Command::AcquireLock {
+            path: "/locked".to_string(),
+            lock_id: "h".to_string(),
+            max_holders: 1,
+            ttl_secs: 60,
+            holder_info: "h".to_string(),
+            now_secs: 1000,
+        };
+        let token = match sm.apply(3, &acquire).unwrap() {
+            CommandResult::LockResult(r) => r.fencing_token,
+            other => panic!("expected a lock result, got {other:?}"),
+        };
+        let fenced = Command::CasSetMetadataFenced {
+            key: "/locked".to_string(),
+            value: b"guarded".to_vec(),
+            expected_version: 0,
+            fencing_token: token,
+        };
+        sm.apply(4, &fenced).unwrap();
+
+        let changes = sm.changes_since(1).unwrap();
+        let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["__history__:/doc", "/doc", "/locked", "__fence__:/locked"]
+        );
+
+        // Replaying raw key writes over the base snapshot reproduces the
+        // internal records too.
+        let mut restored = FullStateMachine::new(&RedbStore::open_temporary().unwrap()).unwrap();
+        restored.restore_snapshot(&base).unwrap();
+        for (i, change) in changes.iter().enumerate() {
+            let command = Command::SetMetadata {
+                key: change.key.clone(),
+                value: change.value.clone().unwrap(),
+            };
+            restored.apply(2 + i as u64, &command).unwrap();
+        }
+        assert_eq!(
+            restored.get_metadata_history("/doc").unwrap(),
+            sm.get_metadata_history("/doc").unwrap()
+        );
+        let metadata =
+            |sm: &FullStateMachine| Snapshot::decode(&sm.snapshot().unwrap()).unwrap().metadata;
+        assert_eq!(metadata(&restored), metadata(&sm));
+
+        // Taking snapshots left the change log alone; only an explicit
+        // truncation moves the floor.
+        assert_eq!(sm.changes_since(1).unwrap(), changes);
+        sm.truncate_changes(3).unwrap();
+        assert!(sm.changes_since(2).is_err());
+        assert_eq!(sm.changes_since(3).unwrap(), changes[2..]);
+    }
+
+    #[test]
+    fn test_change_log_keeps_only_the_retention_window() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        sm.set_change_retention(3);
+        for i in 1..=6u64 {
+            let set = Command::SetMetadata {
+                key: format!("/k{i}"),
+                value: vec![i as u8],
+            };
+            sm.apply(i, &set).unwrap();
+        }
+
+        // Applying 6 with a window of 3 dropped everything through 3.
+        assert!(sm.changes_since(2).is_err());
+        let indices: Vec<u64> = sm
+            .changes_since(3)
+            .unwrap()
+            .iter()
+            .map(|c| c.index)
+            .collect();
+        assert_eq!(indices, [4, 5, 6]);
+        assert_eq!(sm.changelog.iter().count(), 3);
+    }
+
+    #[test]
+    fn test_ec_local_write_fences_change_log() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let set = |key: &str| Command::SetMetadata {
+            key: key.to_string(),
+            value: b"v".to_vec(),
+        };
+        sm.apply(1, &set("/a")).unwrap();
+        sm.apply(2, &set("/b")).unwrap();
+        assert_eq!(sm.changes_since(1).unwrap().len(), 1);
+
+        // Stream entries are not metadata and leave the log usable.
+        sm.apply_local(&Command::AppendStreamEntry {
+            key: "/__wal_stream__/s/1".to_string(),
+            data: b"x".to_vec(),
+        })
+        .unwrap();
+        assert_eq!(sm.changes_since(1).unwrap().len(), 1);
+
+        // The EC write has no index, so no diff up to 2 can include it.
+        sm.apply_local(&set("/ec")).unwrap();
+        assert!(sm.changes_since(1).is_err());
+        assert!(sm.changes_since(2).is_err());
+
+        // Diffs from after the EC write work again.
+        sm.apply(3, &set("/c")).unwrap();
+        sm.apply(4, &set("/d")).unwrap();
+        let keys: Vec<String> = sm
+            .changes_since(3)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.key)
+            .collect();
+        assert_eq!(keys, ["/d"]);
+    }
+
+    #[cfg(feature = "grpc")]
+    #[test]
+    fn test_ec_peer_write_fences_change_log() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let set = |key: &str| Command::SetMetadata {
+            key: key.to_string(),
+            value: b"v".to_vec(),
+        };
+        sm.apply(1, &set("/a")).unwrap();
+        sm.apply(2, &set("/b")).unwrap();
+
+        sm.apply_ec_with_lww(&set("/from-peer"), 0).unwrap();
+        assert!(sm.changes_since(2).is_err());
+        sm.apply(3, &set("/c")).unwrap();
+        assert!(sm.changes_since(3).unwrap().is_empty());
+
+        sm.apply_ec_with_lww(
+            &Command::DeleteMetadata {
+                key: "/a".to_string(),
+            },
+            u64::MAX,
+        )
+        .unwrap();
+        assert!(sm.changes_since(3).is_err());
+    }
+
     /// ``DeleteStreamEntry`` removes the row; subsequent get returns
     /// ``None``.
     #[test]
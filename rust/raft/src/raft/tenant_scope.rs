@@ -0,0 +1,384 @@
+//! Tenant key-space isolation for a shared state machine.
+//!
+//! A [`TenantScope`] maps a tenant's view of the key space onto the
+//! shared one by rooting every metadata key, lock path and stream key at
+//! `{tenant_id}`, and strips that root from results. Tenant keys are
+//! absolute paths, so `/docs/a` becomes `{tenant_id}/docs/a` and the
+//! tenant's `/` is `{tenant_id}` itself — an ancestor of every tenant
+//! key, which keeps the lock hierarchy intact under scoping. Nothing in
+//! the state machine knows about tenants — scoping is purely a rewrite
+//! of [`Command`]s on the way in and of keys on the way out, and scans go
+//! through [`TenantScope::scope_prefix`] so they only ever reach the
+//! tenant's own subtree.
+
+use super::error::{RaftError, Result};
+use super::state_machine::{Command, LockInfo, LockRequest};
+
+/// Key-space rewriter for a single tenant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantScope {
+    tenant_id: String,
+    /// `"{tenant_id}/"`, cached so scoping is a single allocation.
+    prefix: String,
+}
+
+impl TenantScope {
+    /// Create a scope for `tenant_id`.
+    ///
+    /// The id must be non-empty and must not contain `/`; otherwise one
+    /// tenant's prefix could be a prefix of another's subtree. It must
+    /// not start with `__` either, which the state machine reserves for
+    /// its internal keys (`__alias__:`, `__history__:`, `__fence__:`, ...).
+    pub fn new(tenant_id: impl Into<String>) -> Result<Self> {
+        let tenant_id = tenant_id.into();
+        if tenant_id.is_empty() || tenant_id.contains('/') || tenant_id.starts_with("__") {
+            return Err(RaftError::Config(format!(
+                "invalid tenant id {tenant_id:?}: must be non-empty, contain no '/' \
+                 and not start with '__'"
+            )));
+        }
+        let prefix = format!("{tenant_id}/");
+        Ok(Self { tenant_id, prefix })
+    }
+
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// Map a tenant-relative key to its key in the shared store.
+    ///
+    /// The leading `/` is optional (`docs/a` and `/docs/a` are the same
+    /// key), and `/` or `""` is the tenant root `{tenant_id}`.
+    pub fn scope_key(&self, key: &str) -> String {
+        let rest = key.trim_start_matches('/');
+        if rest.is_empty() {
+            return self.tenant_id.clone();
+        }
+        let mut scoped = String::with_capacity(self.prefix.len() + rest.len());
+        scoped.push_str(&self.prefix);
+        scoped.push_str(rest);
+        scoped
+    }
+
+    /// Map a tenant-relative scan prefix into the shared store. Unlike
+    /// [`scope_key`](Self::scope_key), `/` maps to `{tenant_id}/`, so a
+    /// scan of the whole tenant cannot reach a tenant whose id merely
+    /// starts with this one's.
+    pub fn scope_prefix(&self, prefix: &str) -> String {
+        let rest = prefix.trim_start_matches('/');
+        let mut scoped = String::with_capacity(self.prefix.len() + rest.len());
+        scoped.push_str(&self.prefix);
+        scoped.push_str(rest);
+        scoped
+    }
+
+    /// Inverse of [`scope_key`](Self::scope_key), as an absolute
+    /// tenant-relative path; `None` if `key` belongs to another tenant
+    /// (or to no tenant at all).
+    pub fn unscope_key(&self, key: &str) -> Option<String> {
+        if key == self.tenant_id {
+            return Some("/".to_string());
+        }
+        let rest = key.strip_prefix(&self.prefix)?;
+        Some(format!("/{rest}"))
+    }
+
+    /// Whether `prefix` covers the whole tenant, root included.
+    pub fn is_root_prefix(prefix: &str) -> bool {
+        prefix.trim_start_matches('/').is_empty()
+    }
+
+    /// Rewrite every key / path carried by `cmd` into this tenant's subtree.
+    ///
+    /// The match is exhaustive on purpose: a new [`Command`] variant must
+    /// decide here how it is scoped rather than slip through unscoped.
+    pub fn scope_command(&self, cmd: Command) -> Command {
+        match cmd {
+            Command::SetMetadata { key, value } => Command::SetMetadata {
+                key: self.scope_key(&key),
+                value,
+            },
+            Command::DeleteMetadata { key } => Command::DeleteMetadata {
+                key: self.scope_key(&key),
+            },
+            Command::CasSetMetadata {
+                key,
+                value,
+                expected_version,
+            } => Command::CasSetMetadata {
+                key: self.scope_key(&key),
+                value,
+                expected_version,
+            },
+            Command::AdjustCounter { key, delta } => Command::AdjustCounter {
+                key: self.scope_key(&key),
+                delta,
+            },
+            Command::AcquireLock {
+                path,
+                lock_id,
+                max_holders,
+                ttl_secs,
+                holder_info,
+                now_secs,
+            } => Command::AcquireLock {
+                path: self.scope_key(&path),
+                lock_id,
+                max_holders,
+                ttl_secs,
+                holder_info,
+                now_secs,
+            },
+            Command::AcquireLocks { requests, now_secs } => Command::AcquireLocks {
+                requests: requests
+                    .into_iter()
+                    .map(|req| LockRequest {
+                        path: self.scope_key(&req.path),
+                        ..req
+                    })
+                    .collect(),
+                now_secs,
+            },
+            Command::ReleaseLock { path, lock_id } => Command::ReleaseLock {
+                path: self.scope_key(&path),
+                lock_id,
+            },
+            Command::ExtendLock {
+                path,
+                lock_id,
+                new_ttl_secs,
+                now_secs,
+            } => Command::ExtendLock {
+                path: self.scope_key(&path),
+                lock_id,
+                new_ttl_secs,
+                now_secs,
+            },
+            Command::ForceReleaseLock { path } => Command::ForceReleaseLock {
+                path: self.scope_key(&path),
+            },
+            Command::AppendStreamEntry { key, data } => Command::AppendStreamEntry {
+                key: self.scope_key(&key),
+                data,
+            },
+            Command::DeleteStreamEntry { key } => Command::DeleteStreamEntry {
+                key: self.scope_key(&key),
+            },
+            Command::LinkMetadata { alias, target } => Command::LinkMetadata {
+                alias: self.scope_key(&alias),
+                target: self.scope_key(&target),
+            },
+            Command::SetMetadataVersioned {
+                key,
+                value,
+                max_history,
+            } => Command::SetMetadataVersioned {
+                key: self.scope_key(&key),
+                value,
+                max_history,
+            },
+            Command::PutIfAbsent { key, value } => Command::PutIfAbsent {
+                key: self.scope_key(&key),
+                value,
+            },
+            Command::CasSetMetadataFenced {
+                key,
+                value,
+                expected_version,
+                fencing_token,
+            } => Command::CasSetMetadataFenced {
+                key: self.scope_key(&key),
+                value,
+                expected_version,
+                fencing_token,
+            },
+            Command::Noop => Command::Noop,
+        }
+    }
+
+    /// Strip the tenant prefix from `(key, value)` results, dropping any
+    /// entry outside this tenant's subtree.
+    pub fn unscope_entries<V>(&self, entries: Vec<(String, V)>) -> Vec<(String, V)> {
+        entries
+            .into_iter()
+            .filter_map(|(key, value)| Some((self.unscope_key(&key)?, value)))
+            .collect()
+    }
+
+    /// Strip the tenant prefix from a lock's path; `None` if the lock
+    /// belongs to another tenant.
+    pub fn unscope_lock(&self, mut lock: LockInfo) -> Option<LockInfo> {
+        lock.path = self.unscope_key(&lock.path)?;
+        Some(lock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raft::{CommandResult, FullStateMachine, StateMachine};
+    use crate::storage::RedbStore;
+
+    fn set(scope: &TenantScope, key: &str, value: &[u8]) -> Command {
+        scope.scope_command(Command::SetMetadata {
+            key: key.into(),
+            value: value.to_vec(),
+        })
+    }
+
+    #[test]
+    fn rejects_empty_nested_or_reserved_tenant_ids() {
+        assert!(TenantScope::new("").is_err());
+        assert!(TenantScope::new("acme/eng").is_err());
+        assert!(TenantScope::new("__alias__:x").is_err());
+        assert!(TenantScope::new("__fence__").is_err());
+        assert!(TenantScope::new("_acme").is_ok());
+        assert_eq!(TenantScope::new("acme").unwrap().tenant_id(), "acme");
+    }
+
+    #[test]
+    fn scope_round_trips_and_rejects_foreign_keys() {
+        let acme = TenantScope::new("acme").unwrap();
+        assert_eq!(acme.scope_key("/docs/a.txt"), "acme/docs/a.txt");
+        assert_eq!(acme.scope_key("docs/a.txt"), "acme/docs/a.txt");
+        assert_eq!(acme.scope_key("/"), "acme");
+        assert_eq!(acme.scope_key(""), "acme");
+        assert_eq!(acme.scope_prefix("/"), "acme/");
+        assert_eq!(acme.scope_prefix("/docs/"), "acme/docs/");
+        assert_eq!(
+            acme.unscope_key("acme/docs/a.txt").as_deref(),
+            Some("/docs/a.txt")
+        );
+        assert_eq!(acme.unscope_key("acme").as_deref(), Some("/"));
+        assert_eq!(acme.unscope_key("acmecorp/docs/a.txt"), None);
+        assert_eq!(acme.unscope_key("acmecorp"), None);
+        assert_eq!(acme.unscope_key("/docs/a.txt"), None);
+    }
+
+    #[test]
+    fn tenants_sharing_a_store_see_only_their_own_keys() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let acme = TenantScope::new("acme").unwrap();
+        let globex = TenantScope::new("globex").unwrap();
+
+        sm.apply(1, &set(&acme, "/a.txt", b"acme-a")).unwrap();
+        sm.apply(2, &set(&acme, "/b.txt", b"acme-b")).unwrap();
+        sm.apply(3, &set(&globex, "/a.txt", b"globex-a")).unwrap();
+
+        let list = |scope: &TenantScope| {
+            let raw = sm.list_metadata(&scope.scope_prefix("/")).unwrap();
+            let mut entries = scope.unscope_entries(raw);
+            entries.sort();
+            entries
+        };
+        assert_eq!(
+            list(&acme),
+            vec![
+                ("/a.txt".to_string(), b"acme-a".to_vec()),
+                ("/b.txt".to_string(), b"acme-b".to_vec()),
+            ]
+        );
+        assert_eq!(
+            list(&globex),
+            vec![("/a.txt".to_string(), b"globex-a".to_vec())]
+        );
+
+        // Same tenant-relative key, independent values.
+        let get = |sm: &FullStateMachine, scope: &TenantScope| {
+            sm.get_metadata(&scope.scope_key("/a.txt")).unwrap()
+        };
+        assert_eq!(get(&sm, &acme), Some(b"acme-a".to_vec()));
+        assert_eq!(get(&sm, &globex), Some(b"globex-a".to_vec()));
+
+        // Deleting through one scope leaves the other tenant's key alone.
+        let delete = acme.scope_command(Command::DeleteMetadata {
+            key: "/a.txt".into(),
+        });
+        sm.apply(4, &delete).unwrap();
+        assert_eq!(get(&sm, &acme), None);
+        assert_eq!(get(&sm, &globex), Some(b"globex-a".to_vec()));
+    }
+
+    #[test]
+    fn tenants_hold_independent_locks_on_the_same_path() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let acme = TenantScope::new("acme").unwrap();
+        let globex = TenantScope::new("globex").unwrap();
+
+        for (index, scope) in [(1, &acme), (2, &globex)] {
+            let cmd = scope.scope_command(Command::AcquireLock {
+                path: "/shared/file".into(),
+                lock_id: format!("{}-holder", scope.tenant_id()),
+                max_holders: 1,
+                ttl_secs: 30,
+                holder_info: "agent:test".into(),
+                now_secs: 1000,
+            });
+            sm.apply(index, &cmd).unwrap();
+        }
+
+        for scope in [&acme, &globex] {
+            let locks: Vec<LockInfo> = sm
+                .list_locks(&scope.scope_prefix("/"), 100)
+                .unwrap()
+                .into_iter()
+                .filter_map(|lock| scope.unscope_lock(lock))
+                .collect();
+            assert_eq!(locks.len(), 1);
+            assert_eq!(locks[0].path, "/shared/file");
+            assert_eq!(
+                locks[0].holders[0].lock_id,
+                format!("{}-holder", scope.tenant_id())
+            );
+        }
+    }
+
+    #[test]
+    fn scoped_locks_keep_the_path_hierarchy() {
+        let store = RedbStore::open_temporary().unwrap();
+        let mut sm = FullStateMachine::new(&store).unwrap();
+        let acme = TenantScope::new("acme").unwrap();
+        let globex = TenantScope::new("globex").unwrap();
+        let mut index = 0;
+        let mut acquire = |sm: &mut FullStateMachine, scope: &TenantScope, path: &str| {
+            index += 1;
+            let cmd = scope.scope_command(Command::AcquireLock {
+                path: path.into(),
+                lock_id: format!("holder-{index}"),
+                max_holders: 1,
+                ttl_secs: 30,
+                holder_info: "agent:test".into(),
+                now_secs: 1000,
+            });
+            match sm.apply(index, &cmd).unwrap() {
+                CommandResult::LockResult(state) => state.acquired,
+                other => panic!("unexpected result {other:?}"),
+            }
+        };
+
+        // The tenant root is an ancestor of every tenant path.
+        assert!(acquire(&mut sm, &acme, "/"));
+        assert!(!acquire(&mut sm, &acme, "/docs/a.txt"));
+        assert!(!acquire(&mut sm, &acme, "docs"));
+        // ... but not of another tenant's paths, nor of keys outside any tenant.
+        assert!(acquire(&mut sm, &globex, "/docs/a.txt"));
+        let outside = Command::AcquireLock {
+            path: "acmecorp/x".into(),
+            lock_id: "outside".into(),
+            max_holders: 1,
+            ttl_secs: 30,
+            holder_info: "agent:test".into(),
+            now_secs: 1000,
+        };
+        assert!(matches!(
+            sm.apply(100, &outside).unwrap(),
+            CommandResult::LockResult(state) if state.acquired
+        ));
+
+        // A child lock blocks the tenant root in turn.
+        assert!(!acquire(&mut sm, &globex, "/"));
+        assert!(!acquire(&mut sm, &globex, "/docs"));
+    }
+}
@@ -32,7 +32,7 @@
 mod redb_store;
 
 pub use redb_store::{
-    RedbBatch, RedbStore, RedbTree, RedbTreeBatch, StorageError as RedbStorageError,
+    PrefixScan, RedbBatch, RedbStore, RedbTree, RedbTreeBatch, StorageError as RedbStorageError,
 };
 
 // Re-export StorageError as the primary error type
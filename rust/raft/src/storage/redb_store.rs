@@ -79,6 +79,9 @@ pub enum StorageError {
 
 pub type Result<T> = std::result::Result<T, StorageError>;
 
+/// A lazy scan returned by [`RedbTree::scan_prefixes`].
+pub type PrefixScan = Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>)>>>;
+
 /// Compute the successor of a byte prefix for range scans.
 ///
 /// Returns `None` if the prefix is all 0xFF bytes (no upper bound exists).
@@ -568,6 +571,40 @@ impl RedbTree {
         self.collect_prefix(prefix).into_iter()
     }
 
+    /// Lazy prefix scans over one read snapshot, one iterator per prefix.
+    ///
+    /// Unlike `scan_prefix()`, entries are read as the iterators advance,
+    /// so a caller merging or paginating several scans can stop early.
+    /// The snapshot stays open until every iterator is dropped.
+    pub fn scan_prefixes(&self, prefixes: &[&[u8]]) -> Result<Vec<PrefixScan>> {
+        let read_txn = self.db.begin_read()?;
+        let table = match read_txn.open_table(self.table_def()) {
+            Ok(t) => t,
+            Err(redb::TableError::TableDoesNotExist(_)) => {
+                return Ok(prefixes
+                    .iter()
+                    .map(|_| Box::new(std::iter::empty()) as PrefixScan)
+                    .collect())
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut scans = Vec::with_capacity(prefixes.len());
+        for &prefix in prefixes {
+            let range = if let Some(upper) = prefix_upper_bound(prefix) {
+                table.range::<&[u8]>(prefix..upper.as_slice())
+            } else {
+                table.range::<&[u8]>(prefix..)
+            }
+            .map_err(StorageError::Storage)?;
+            scans.push(Box::new(range.map(|entry| {
+                let (k, v) = entry.map_err(StorageError::Storage)?;
+                Ok((k.value().to_vec(), v.value().to_vec()))
+            })) as PrefixScan);
+        }
+        Ok(scans)
+    }
+
     /// Internal: collect prefix scan entries.
     fn collect_prefix(&self, prefix: &[u8]) -> Vec<Result<(Vec<u8>, Vec<u8>)>> {
         let read_txn = match self.db.begin_read() {
@@ -958,6 +995,26 @@ mod tests {
         assert_eq!(users.len(), 3);
     }
 
+    #[test]
+    fn test_scan_prefixes_share_one_snapshot() {
+        let store = RedbStore::open_temporary().unwrap();
+        let tree = store.tree("prefixes_test").unwrap();
+
+        tree.set(b"user:1", b"alice").unwrap();
+        tree.set(b"item:1", b"book").unwrap();
+
+        let mut scans = tree.scan_prefixes(&[b"user:", b"item:", b"none:"]).unwrap();
+        // Writes after the scans were opened are not seen.
+        tree.set(b"user:2", b"bob").unwrap();
+
+        let keys = |scan: &mut PrefixScan| -> Vec<Vec<u8>> {
+            scan.map(|entry| entry.unwrap().0).collect()
+        };
+        assert_eq!(keys(&mut scans[0]), vec![b"user:1".to_vec()]);
+        assert_eq!(keys(&mut scans[1]), vec![b"item:1".to_vec()]);
+        assert!(keys(&mut scans[2]).is_empty());
+    }
+
     #[test]
     fn test_batch_operations() {
         let store = RedbStore::open_temporary().unwrap();
@@ -150,14 +150,14 @@ pub(crate) async fn forward_propose(
 
 /// Decode a proto `RaftResponse` back into an internal `CommandResult`.
 ///
-/// Only the variants that the server actually emits (Success / LockResult)
-/// are handled; other commands carry no typed result and collapse to
-/// `Success`, matching the old single-node path.
+/// Only the variants that the server actually emits (Success / LockResult /
+/// LockResults / PutResult) are handled; other commands carry no typed result and
+/// collapse to `Success`, matching the old single-node path.
 #[cfg(all(feature = "grpc", has_protos))]
 fn proto_result_to_command_result(
     result: Option<proto::nexus::raft::RaftResponse>,
 ) -> crate::raft::CommandResult {
-    use crate::raft::{CommandResult, HolderInfo, LockAcquireResult};
+    use crate::raft::CommandResult;
     use proto::nexus::raft::raft_response::Result as ProtoVariant;
 
     let Some(resp) = result else {
@@ -165,28 +165,48 @@ fn proto_result_to_command_result(
     };
 
     match resp.result {
-        Some(ProtoVariant::LockResult(lr)) => {
-            let holders = if lr.acquired {
-                vec![HolderInfo {
-                    lock_id: String::new(),
-                    holder_info: lr.current_holder.clone().unwrap_or_default(),
-                    acquired_at: 0,
-                    expires_at: (lr.expires_at_ms / 1000) as u64,
-                }]
-            } else {
-                Vec::new()
-            };
-            CommandResult::LockResult(LockAcquireResult {
-                acquired: lr.acquired,
-                current_holders: holders.len() as u32,
-                max_holders: 0,
-                holders,
-            })
-        }
+        Some(ProtoVariant::LockResult(lr)) => CommandResult::LockResult(proto_to_lock_result(lr)),
+        Some(ProtoVariant::LockResults(batch)) => CommandResult::LockResults(
+            batch
+                .results
+                .into_iter()
+                .map(proto_to_lock_result)
+                .collect(),
+        ),
+        Some(ProtoVariant::PutResult(put)) => CommandResult::PutResult {
+            written: put.written,
+        },
         Some(ProtoVariant::MetadataResult(_)) | None => CommandResult::Success,
     }
 }
 
+/// Decode one proto `LockResult`. Holder details beyond the counts do not
+/// survive the wire: an acquired result carries one holder without its
+/// lock id, a refused one none.
+#[cfg(all(feature = "grpc", has_protos))]
+fn proto_to_lock_result(lr: proto::nexus::raft::LockResult) -> crate::raft::LockAcquireResult {
+    use crate::raft::{HolderInfo, LockAcquireResult};
+
+    let holders = if lr.acquired {
+        vec![HolderInfo {
+            lock_id: String::new(),
+            holder_info: lr.current_holder.unwrap_or_default(),
+            acquired_at: 0,
+            expires_at: (lr.expires_at_ms / 1000) as u64,
+            fencing_token: lr.fencing_token,
+        }]
+    } else {
+        Vec::new()
+    };
+    LockAcquireResult {
+        acquired: lr.acquired,
+        current_holders: lr.current_holders,
+        max_holders: lr.max_holders,
+        holders,
+        fencing_token: lr.fencing_token,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Re-export shared transport types from transport.
 // These were previously defined locally but are now canonical in transport.
@@ -14,18 +14,19 @@ use super::proto::nexus::raft::{
     ClusterConfig as ProtoClusterConfig, DeleteZoneRequest, DeleteZoneResponse,
     GetClusterInfoRequest, GetClusterInfoResponse, GetMetadataResult, GetSearchCapabilitiesRequest,
     JoinClusterRequest, JoinClusterResponse, JoinZoneRequest, JoinZoneResponse, ListMetadataResult,
-    LockInfoResult, LockResult, NodeInfo as ProtoNodeInfo, ProposeRequest, ProposeResponse,
-    QueryRequest, QueryResponse, RaftCommand, RaftQueryResponse, RaftResponse, ReadBlobRequest,
-    ReadBlobResponse, ReplicateEntriesRequest, ReplicateEntriesResponse, SearchCapabilities,
-    StepMessageRequest, StepMessageResponse,
+    LockInfoResult, LockResult, LockResults, NodeInfo as ProtoNodeInfo, ProposeRequest,
+    ProposeResponse, PutResult, QueryRequest, QueryResponse, RaftCommand, RaftQueryResponse,
+    RaftResponse, ReadBlobRequest, ReadBlobResponse, ReplicateEntriesRequest,
+    ReplicateEntriesResponse, SearchCapabilities, StepMessageRequest, StepMessageResponse,
 };
 use super::{NodeAddress, Result, SharedPeerMap, TransportError};
 use crate::blob_fetcher::BlobFetcherSlot;
 use crate::raft::{
-    reconcile_peers_with_conf_state, Command, CommandResult, FullStateMachine, RaftError,
-    WitnessStateMachine, ZoneConsensus, ZoneRaftRegistry,
+    reconcile_peers_with_conf_state, Command, CommandResult, FullStateMachine, LockAcquireResult,
+    RaftError, WitnessStateMachine, ZoneConsensus, ZoneRaftRegistry,
 };
 use crate::storage::RedbStore;
+use crate::zone_handle::WitnessHandle;
 use bincode;
 use dashmap::DashMap;
 use prost::Message;
@@ -312,6 +313,22 @@ fn get_zone_node(
     })
 }
 
+/// Convert one lock acquire result to its proto form, reporting the first
+/// holder.
+fn lock_result_to_proto(lock_state: &LockAcquireResult) -> LockResult {
+    let first_holder = lock_state.holders.first();
+    LockResult {
+        acquired: lock_state.acquired,
+        current_holder: first_holder.map(|h| h.holder_info.clone()),
+        expires_at_ms: first_holder
+            .map(|h| (h.expires_at * 1000) as i64)
+            .unwrap_or(0),
+        fencing_token: lock_state.fencing_token,
+        current_holders: lock_state.current_holders,
+        max_holders: lock_state.max_holders,
+    }
+}
+
 /// Convert internal CommandResult to proto RaftResponse.
 fn command_result_to_proto(result: &CommandResult) -> RaftResponse {
     match result {
@@ -325,20 +342,27 @@ fn command_result_to_proto(result: &CommandResult) -> RaftResponse {
             error: None,
             result: None,
         },
-        CommandResult::LockResult(lock_state) => {
-            let first_holder = lock_state.holders.first();
-            RaftResponse {
-                success: true,
-                error: None,
-                result: Some(ProtoResponseResultVariant::LockResult(LockResult {
-                    acquired: lock_state.acquired,
-                    current_holder: first_holder.map(|h| h.holder_info.clone()),
-                    expires_at_ms: first_holder
-                        .map(|h| (h.expires_at * 1000) as i64)
-                        .unwrap_or(0),
-                })),
-            }
-        }
+        CommandResult::LockResult(lock_state) => RaftResponse {
+            success: true,
+            error: None,
+            result: Some(ProtoResponseResultVariant::LockResult(
+                lock_result_to_proto(lock_state),
+            )),
+        },
+        CommandResult::LockResults(results) => RaftResponse {
+            success: true,
+            error: None,
+            result: Some(ProtoResponseResultVariant::LockResults(LockResults {
+                results: results.iter().map(lock_result_to_proto).collect(),
+            })),
+        },
+        CommandResult::PutResult { written } => RaftResponse {
+            success: true,
+            error: None,
+            result: Some(ProtoResponseResultVariant::PutResult(PutResult {
+                written: *written,
+            })),
+        },
         CommandResult::CasResult { success, .. } => RaftResponse {
             success: *success,
             error: if *success {
@@ -1629,6 +1653,24 @@ impl WitnessZoneRegistry {
         self.zones.iter().map(|e| e.key().clone()).collect()
     }
 
+    /// Status/shutdown handle for a joined zone — the witness analogue of
+    /// `ZoneManager::get_zone`.
+    pub fn witness_handle(self: &Arc<Self>, zone_id: &str) -> Option<WitnessHandle> {
+        let node = self.get_node(zone_id)?;
+        Some(WitnessHandle::new(node, self.clone(), zone_id.to_string()))
+    }
+
+    /// Shut down one zone's transport loop and forget it. Returns `false`
+    /// if the zone is not registered.
+    pub fn remove_zone(&self, zone_id: &str) -> bool {
+        let Some((_, entry)) = self.zones.remove(zone_id) else {
+            return false;
+        };
+        let _ = entry.shutdown_tx.send(true);
+        tracing::info!("Witness zone '{}' shut down", zone_id);
+        true
+    }
+
     /// Shutdown all zones.
     pub fn shutdown_all(&self) {
         for entry in self.zones.iter() {
@@ -1775,6 +1817,42 @@ mod tests {
         assert_eq!(config.max_message_size, 64 * 1024 * 1024);
     }
 
+    #[test]
+    fn test_lock_results_keep_per_path_outcomes_over_the_wire() {
+        use crate::raft::HolderInfo;
+
+        let held = LockAcquireResult {
+            acquired: false,
+            current_holders: 2,
+            max_holders: 2,
+            holders: vec![HolderInfo {
+                lock_id: "other".into(),
+                holder_info: "agent:other".into(),
+                acquired_at: 1000,
+                expires_at: 1060,
+                fencing_token: 7,
+            }],
+            fencing_token: 0,
+        };
+        let free = LockAcquireResult {
+            acquired: false,
+            current_holders: 0,
+            max_holders: 1,
+            holders: Vec::new(),
+            fencing_token: 0,
+        };
+        let proto = command_result_to_proto(&CommandResult::LockResults(vec![held, free]));
+        let CommandResult::LockResults(decoded) =
+            super::super::proto_result_to_command_result(Some(proto))
+        else {
+            panic!("expected per-path lock results");
+        };
+        assert_eq!(decoded.len(), 2);
+        assert_eq!((decoded[0].current_holders, decoded[0].max_holders), (2, 2));
+        assert!(!decoded[0].acquired);
+        assert_eq!((decoded[1].current_holders, decoded[1].max_holders), (0, 1));
+    }
+
     #[tokio::test]
     async fn test_zone_registry_server() {
         use tempfile::TempDir;
@@ -1855,6 +1933,43 @@ mod tests {
         registry.shutdown_all();
     }
 
+    #[test]
+    fn test_witness_handle_reports_status_and_shuts_down() {
+        use crate::raft::NodeRole;
+        use tempfile::TempDir;
+
+        let tmp_dir = TempDir::new().unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let witness_id = NodeAddress::parse("witness:2126", false).unwrap().id;
+        let mut registry = WitnessZoneRegistry::new(tmp_dir.path().to_path_buf(), witness_id, None);
+        registry.set_peers(vec![
+            NodeAddress::parse("nexus-1:2126", false).unwrap(),
+            NodeAddress::parse("nexus-2:2126", false).unwrap(),
+        ]);
+        let registry = Arc::new(registry);
+        assert!(registry.witness_handle("corp").is_none());
+
+        let node = registry
+            .auto_join_zone("corp", runtime.handle())
+            .expect("witness auto-join");
+        // Joined as a voter alongside both full nodes.
+        assert!(node.is_witness());
+        assert_eq!(node.config().peers.len(), 2);
+
+        let witness = registry.witness_handle("corp").expect("handle");
+        assert_eq!(witness.zone_id(), "corp");
+        assert!(!witness.is_leader());
+        assert_eq!(witness.leader_id(), None);
+        let metrics = witness.metrics();
+        assert_eq!(metrics.node_id, witness_id);
+        assert_eq!(metrics.role, NodeRole::Follower);
+        assert_eq!(metrics.leader_id, None);
+
+        assert!(witness.shutdown());
+        assert!(!witness.shutdown());
+        assert!(registry.list_zones().is_empty());
+    }
+
     // ---------------------------------------------------------------
     // TTL conversion boundary-value tests (Issue #3031 / 11A)
     // ---------------------------------------------------------------
@@ -9,9 +9,11 @@
 use std::sync::Arc;
 
 use crate::raft::{
-    Command, CommandResult, FullStateMachine, LockAcquireResult, LockInfo, RaftError, Result,
-    ZoneConsensus,
+    Command, CommandResult, FullStateMachine, LockAcquireResult, LockInfo, LockPreview,
+    LockRequest, MergePrecedence, MergedEntry, NodeRole, RaftError, Result, TenantScope,
+    WitnessStateMachine, ZoneConsensus, ZoneRaftRegistry,
 };
+use crate::transport::WitnessZoneRegistry;
 // Bring the `StateMachine` trait into scope so the closures below can
 // call methods like `get_metadata` / `list_metadata` through the trait.
 #[allow(unused_imports)]
@@ -31,6 +33,7 @@ pub struct ZoneHandle {
     node: ZoneConsensus<FullStateMachine>,
     runtime_handle: tokio::runtime::Handle,
     zone_id: String,
+    registry: Arc<ZoneRaftRegistry>,
 }
 
 impl ZoneHandle {
@@ -38,11 +41,13 @@ impl ZoneHandle {
         node: ZoneConsensus<FullStateMachine>,
         runtime_handle: tokio::runtime::Handle,
         zone_id: String,
+        registry: Arc<ZoneRaftRegistry>,
     ) -> Arc<Self> {
         Arc::new(Self {
             node,
             runtime_handle,
             zone_id,
+            registry,
         })
     }
 
@@ -50,6 +55,19 @@ impl ZoneHandle {
         &self.zone_id
     }
 
+    /// Refuse (or re-allow) writes to this zone on this node.
+    ///
+    /// Metadata and lock writes fail with [`RaftError::ReadOnly`] while
+    /// set; `get_*` / `list_*` keep working. See
+    /// [`ZoneConsensus::set_read_only`].
+    pub fn set_read_only(&self, read_only: bool) {
+        self.node.set_read_only(read_only);
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.node.is_read_only()
+    }
+
     pub fn consensus_node(&self) -> ZoneConsensus<FullStateMachine> {
         self.node.clone()
     }
@@ -94,6 +112,45 @@ impl ZoneHandle {
         self.node.is_committed(token).map(|s| s.to_string())
     }
 
+    // ── Membership ─────────────────────────────────────────────────
+
+    /// Hand leadership to `transferee` (or the most caught-up other voter)
+    /// and wait for it to take over. See
+    /// [`ZoneConsensus::transfer_leadership`].
+    pub fn transfer_leadership(&self, transferee: Option<u64>) -> Result<u64> {
+        let node = self.node.clone();
+        self.runtime_handle
+            .block_on(async move { node.transfer_leadership(transferee).await })
+    }
+
+    /// Planned removal of this node from the zone: transfer leadership
+    /// away if leading, remove this node from the voter set, then stop the
+    /// zone's transport loop and drop it from the registry. In this order
+    /// the remaining voters never lose a leader they were following, so
+    /// there is no election storm. See [`ZoneConsensus::graceful_leave`].
+    pub fn graceful_leave(&self) -> Result<()> {
+        let node = self.node.clone();
+        let registry = Arc::clone(&self.registry);
+        let zone_id = self.zone_id.clone();
+        self.runtime_handle.block_on(async move {
+            node.graceful_leave().await?;
+            registry
+                .remove_zone(&zone_id)
+                .await
+                .map_err(|e| RaftError::Raft(format!("Failed to stop zone: {}", e)))
+        })
+    }
+
+    /// Scoped view of this zone for `tenant_id`: every metadata key and
+    /// lock path is transparently prefixed with `{tenant_id}/`, so the
+    /// tenant cannot read, list or lock another tenant's keys.
+    pub fn with_tenant(self: &Arc<Self>, tenant_id: &str) -> Result<TenantZoneHandle> {
+        Ok(TenantZoneHandle {
+            inner: Arc::clone(self),
+            scope: TenantScope::new(tenant_id)?,
+        })
+    }
+
     // ── Metadata operations ────────────────────────────────────────
 
     pub fn set_metadata(
@@ -102,17 +159,13 @@ impl ZoneHandle {
         value: Vec<u8>,
         consistency: Consistency,
     ) -> Result<Option<u64>> {
-        let cmd = Command::SetMetadata {
-            key: path.to_string(),
-            value,
-        };
-        match consistency {
-            Consistency::Ec => Ok(Some(self.propose_ec_local(cmd)?)),
-            Consistency::Sc => {
-                self.propose(cmd)?;
-                Ok(None)
-            }
-        }
+        self.submit(
+            Command::SetMetadata {
+                key: path.to_string(),
+                value,
+            },
+            consistency,
+        )
     }
 
     pub fn cas_set_metadata(
@@ -122,38 +175,37 @@ impl ZoneHandle {
         expected_version: u32,
         _consistency: Consistency,
     ) -> Result<(bool, u32)> {
-        let cmd = Command::CasSetMetadata {
+        self.propose_cas(Command::CasSetMetadata {
             key: path.to_string(),
             value,
             expected_version,
-        };
-        match self.propose_raw(cmd)? {
-            CommandResult::CasResult {
-                success,
-                current_version,
-            } => Ok((success, current_version)),
-            _ => Err(RaftError::InvalidState(
-                "Unexpected CAS result type".to_string(),
-            )),
-        }
+        })
+    }
+
+    /// `cas_set_metadata` for a writer holding the lock on `path` with
+    /// `fencing_token` (from `acquire_lock`). Fails with
+    /// `RaftError::Raft` if the caller no longer holds that lock, i.e.
+    /// it expired and someone else took it.
+    pub fn cas_metadata_fenced(
+        &self,
+        path: &str,
+        value: Vec<u8>,
+        expected_version: u32,
+        fencing_token: u64,
+    ) -> Result<(bool, u32)> {
+        self.propose_cas(Command::CasSetMetadataFenced {
+            key: path.to_string(),
+            value,
+            expected_version,
+            fencing_token,
+        })
     }
 
     pub fn adjust_counter(&self, key: &str, delta: i64) -> Result<i64> {
-        let cmd = Command::AdjustCounter {
+        self.propose_counter(Command::AdjustCounter {
             key: key.to_string(),
             delta,
-        };
-        match self.propose_raw(cmd)? {
-            CommandResult::Value(bytes) => {
-                let arr: [u8; 8] = bytes
-                    .try_into()
-                    .map_err(|_| RaftError::InvalidState("Invalid counter value".to_string()))?;
-                Ok(i64::from_be_bytes(arr))
-            }
-            _ => Err(RaftError::InvalidState(
-                "Unexpected adjust_counter result type".to_string(),
-            )),
-        }
+        })
     }
 
     pub fn get_metadata(&self, path: &str) -> Result<Option<Vec<u8>>> {
@@ -166,16 +218,63 @@ impl ZoneHandle {
     }
 
     pub fn delete_metadata(&self, path: &str, consistency: Consistency) -> Result<Option<u64>> {
-        let cmd = Command::DeleteMetadata {
+        self.submit(
+            Command::DeleteMetadata {
+                key: path.to_string(),
+            },
+            consistency,
+        )
+    }
+
+    /// Make `alias` read as `target`'s metadata. Deleting `alias` later
+    /// removes only the link. Fails if `alias` already has metadata or
+    /// the link would form a cycle.
+    pub fn link_metadata(&self, alias: &str, target: &str) -> Result<()> {
+        self.propose(Command::LinkMetadata {
+            alias: alias.to_string(),
+            target: target.to_string(),
+        })?;
+        Ok(())
+    }
+
+    /// Set `path`'s metadata, pushing the previous value onto its history
+    /// (newest `max_history` kept) in the same apply.
+    pub fn set_metadata_versioned(
+        &self,
+        path: &str,
+        value: Vec<u8>,
+        max_history: u32,
+    ) -> Result<()> {
+        self.propose(Command::SetMetadataVersioned {
             key: path.to_string(),
-        };
-        match consistency {
-            Consistency::Ec => Ok(Some(self.propose_ec_local(cmd)?)),
-            Consistency::Sc => {
-                self.propose(cmd)?;
-                Ok(None)
-            }
-        }
+            value,
+            max_history,
+        })?;
+        Ok(())
+    }
+
+    /// Write `value` at `path` only if it has no metadata yet. Returns
+    /// whether it wrote; `false` leaves the existing value untouched.
+    ///
+    /// A key already present in the local state machine returns `false`
+    /// without a proposal. That read may lag a concurrent delete, which
+    /// is harmless for the write-once keys this is meant for.
+    pub fn put_if_absent(&self, path: &str, value: Vec<u8>) -> Result<bool> {
+        self.propose_put_if_absent(Command::PutIfAbsent {
+            key: path.to_string(),
+            value,
+        })
+    }
+
+    /// Prior values of `path` written by `set_metadata_versioned`, newest
+    /// first.
+    pub fn get_metadata_history(&self, path: &str) -> Result<Vec<Vec<u8>>> {
+        let node = self.node.clone();
+        let path = path.to_string();
+        self.runtime_handle.block_on(async move {
+            node.with_state_machine(|sm: &FullStateMachine| sm.get_metadata_history(&path))
+                .await
+        })
     }
 
     pub fn list_metadata(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
@@ -187,6 +286,43 @@ impl ZoneHandle {
         })
     }
 
+    /// Sorted keys under `prefix`, paginated by `offset` / `limit`,
+    /// without transferring values.
+    pub fn list_metadata_keys(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<String>> {
+        let node = self.node.clone();
+        let prefix = prefix.to_string();
+        self.runtime_handle.block_on(async move {
+            node.with_state_machine(|sm: &FullStateMachine| {
+                sm.list_metadata_keys(&prefix, limit, offset)
+            })
+            .await
+        })
+    }
+
+    /// Listings under several prefixes merged into one name-sorted,
+    /// deduplicated, paginated stream. See
+    /// [`FullStateMachine::list_metadata_merged`].
+    pub fn list_metadata_merged(
+        &self,
+        prefixes: Vec<String>,
+        limit: usize,
+        offset: usize,
+        precedence: MergePrecedence,
+    ) -> Result<Vec<MergedEntry>> {
+        let node = self.node.clone();
+        self.runtime_handle.block_on(async move {
+            node.with_state_machine(|sm: &FullStateMachine| {
+                sm.list_metadata_merged(&prefixes, limit, offset, precedence)
+            })
+            .await
+        })
+    }
+
     pub fn get_metadata_multi(&self, paths: Vec<String>) -> Result<Vec<(String, Option<Vec<u8>>)>> {
         let node = self.node.clone();
         self.runtime_handle.block_on(async move {
@@ -222,38 +358,42 @@ impl ZoneHandle {
         ttl_secs: u32,
         holder_info: &str,
     ) -> Result<LockAcquireResult> {
-        let cmd = Command::AcquireLock {
+        self.propose_lock(Command::AcquireLock {
             path: path.to_string(),
             lock_id: lock_id.to_string(),
             max_holders,
             ttl_secs,
             holder_info: holder_info.to_string(),
             now_secs: FullStateMachine::now(),
-        };
-        match self.propose_raw(cmd)? {
-            CommandResult::LockResult(state) => Ok(state),
-            _ => Err(RaftError::InvalidState(
-                "Unexpected acquire_lock result type".to_string(),
-            )),
-        }
+        })
+    }
+
+    /// Acquire every lock in `requests` or none of them.
+    ///
+    /// Results come back in request order. When the proposal was
+    /// forwarded to a remote leader, each result keeps its outcome, counts
+    /// and fencing token but at most one holder, as for `acquire_lock`.
+    pub fn acquire_locks(&self, requests: Vec<LockRequest>) -> Result<Vec<LockAcquireResult>> {
+        self.propose_locks(Command::AcquireLocks {
+            requests,
+            now_secs: FullStateMachine::now(),
+        })
     }
 
     pub fn release_lock(&self, path: &str, lock_id: &str) -> Result<bool> {
-        let cmd = Command::ReleaseLock {
+        self.propose_succeeded(Command::ReleaseLock {
             path: path.to_string(),
             lock_id: lock_id.to_string(),
-        };
-        Ok(matches!(self.propose_raw(cmd)?, CommandResult::Success))
+        })
     }
 
     pub fn extend_lock(&self, path: &str, lock_id: &str, new_ttl_secs: u32) -> Result<bool> {
-        let cmd = Command::ExtendLock {
+        self.propose_succeeded(Command::ExtendLock {
             path: path.to_string(),
             lock_id: lock_id.to_string(),
             new_ttl_secs,
             now_secs: FullStateMachine::now(),
-        };
-        Ok(matches!(self.propose_raw(cmd)?, CommandResult::Success))
+        })
     }
 
     pub fn get_lock(&self, path: &str) -> Result<Option<LockInfo>> {
@@ -265,6 +405,21 @@ impl ZoneHandle {
         })
     }
 
+    /// Dry-run of `acquire_lock`: would a new holder get a slot right
+    /// now? Reads the local state machine only — no proposal, so it is
+    /// cheap and safe on followers, and may lag the leader slightly.
+    pub fn peek_lock(&self, path: &str, max_holders: u32) -> Result<LockPreview> {
+        let node = self.node.clone();
+        let path = path.to_string();
+        let now_secs = FullStateMachine::now();
+        self.runtime_handle.block_on(async move {
+            node.with_state_machine(|sm: &FullStateMachine| {
+                sm.peek_lock(&path, max_holders, now_secs)
+            })
+            .await
+        })
+    }
+
     pub fn list_locks(&self, prefix: &str, limit: usize) -> Result<Vec<LockInfo>> {
         let node = self.node.clone();
         let prefix = prefix.to_string();
@@ -274,7 +429,129 @@ impl ZoneHandle {
         })
     }
 
+    /// Locks held by any holder whose `holder_info` contains
+    /// `holder_info_substr` — e.g. everything a dead agent still holds.
+    /// Each `LockInfo` lists only the matching holders.
+    pub fn list_locks_by_holder(
+        &self,
+        holder_info_substr: &str,
+        limit: usize,
+    ) -> Result<Vec<LockInfo>> {
+        let node = self.node.clone();
+        let substr = holder_info_substr.to_string();
+        self.runtime_handle.block_on(async move {
+            node.with_state_machine(|sm: &FullStateMachine| sm.list_locks_by_holder(&substr, limit))
+                .await
+        })
+    }
+
+    /// Release every lock held by a holder whose `holder_info` equals
+    /// `holder_info`, one `ReleaseLock` proposal per holder. Returns the
+    /// number of holders released; locks that lapsed between the scan
+    /// and the proposal are skipped.
+    pub fn release_all_for_holder(&self, holder_info: &str) -> Result<usize> {
+        let mut released = 0;
+        for lock in self.list_locks_by_holder(holder_info, usize::MAX)? {
+            for holder in lock.holders {
+                if holder.holder_info == holder_info
+                    && self.release_lock(&lock.path, &holder.lock_id)?
+                {
+                    released += 1;
+                }
+            }
+        }
+        Ok(released)
+    }
+
     // ── Internal propose helpers ───────────────────────────────────
+    //
+    // Each public write builds its `Command` and hands it to one of these,
+    // which own the result decoding. `TenantZoneHandle` builds the same
+    // commands, scopes them with `TenantScope::scope_command` and submits
+    // them through the same helpers.
+
+    fn submit(&self, cmd: Command, consistency: Consistency) -> Result<Option<u64>> {
+        match consistency {
+            Consistency::Ec => Ok(Some(self.propose_ec_local(cmd)?)),
+            Consistency::Sc => {
+                self.propose(cmd)?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn propose_cas(&self, cmd: Command) -> Result<(bool, u32)> {
+        match self.propose_raw(cmd)? {
+            CommandResult::CasResult {
+                success,
+                current_version,
+            } => Ok((success, current_version)),
+            CommandResult::Error(e) => Err(RaftError::Raft(e)),
+            _ => Err(RaftError::InvalidState(
+                "Unexpected CAS result type".to_string(),
+            )),
+        }
+    }
+
+    fn propose_counter(&self, cmd: Command) -> Result<i64> {
+        match self.propose_raw(cmd)? {
+            CommandResult::Value(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .try_into()
+                    .map_err(|_| RaftError::InvalidState("Invalid counter value".to_string()))?;
+                Ok(i64::from_be_bytes(arr))
+            }
+            _ => Err(RaftError::InvalidState(
+                "Unexpected adjust_counter result type".to_string(),
+            )),
+        }
+    }
+
+    fn propose_put_if_absent(&self, cmd: Command) -> Result<bool> {
+        // Skip the proposal when the key already has a value. Not
+        // `get_metadata`: that follows aliases, and an alias must reach
+        // the state machine to be rejected there.
+        if let Command::PutIfAbsent { key, .. } = &cmd {
+            let node = self.node.clone();
+            let key = key.clone();
+            let present = self.runtime_handle.block_on(async move {
+                node.with_state_machine(|sm: &FullStateMachine| sm.has_own_metadata(&key))
+                    .await
+            })?;
+            if present {
+                return Ok(false);
+            }
+        }
+        match self.propose_raw(cmd)? {
+            CommandResult::PutResult { written } => Ok(written),
+            CommandResult::Error(e) => Err(RaftError::Raft(e)),
+            _ => Err(RaftError::InvalidState(
+                "Unexpected put_if_absent result type".to_string(),
+            )),
+        }
+    }
+
+    fn propose_lock(&self, cmd: Command) -> Result<LockAcquireResult> {
+        match self.propose_raw(cmd)? {
+            CommandResult::LockResult(state) => Ok(state),
+            _ => Err(RaftError::InvalidState(
+                "Unexpected acquire_lock result type".to_string(),
+            )),
+        }
+    }
+
+    fn propose_locks(&self, cmd: Command) -> Result<Vec<LockAcquireResult>> {
+        match self.propose_raw(cmd)? {
+            CommandResult::LockResults(results) => Ok(results),
+            _ => Err(RaftError::InvalidState(
+                "Unexpected acquire_locks result type".to_string(),
+            )),
+        }
+    }
+
+    fn propose_succeeded(&self, cmd: Command) -> Result<bool> {
+        Ok(matches!(self.propose_raw(cmd)?, CommandResult::Success))
+    }
 
     fn propose_ec_local(&self, cmd: Command) -> Result<u64> {
         let node = self.node.clone();
@@ -287,8 +564,10 @@ impl ZoneHandle {
             CommandResult::Success => Ok(true),
             CommandResult::Error(e) => Err(RaftError::Raft(e)),
             CommandResult::LockResult(state) => Ok(state.acquired),
+            CommandResult::LockResults(results) => Ok(results.iter().all(|r| r.acquired)),
             CommandResult::CasResult { success, .. } => Ok(success),
             CommandResult::Value(_) => Ok(true),
+            CommandResult::PutResult { written } => Ok(written),
         }
     }
 
@@ -298,3 +577,460 @@ impl ZoneHandle {
             .block_on(async move { node.propose(cmd).await })
     }
 }
+
+/// Tenant-scoped view of a [`ZoneHandle`], created by
+/// [`ZoneHandle::with_tenant`].
+///
+/// Thin wrapper: writes build the same [`Command`] as the shared handle
+/// and pass it through [`TenantScope::scope_command`] before proposing;
+/// reads scope their keys through [`TenantScope`] and strip the prefix
+/// from results.
+#[derive(Clone)]
+pub struct TenantZoneHandle {
+    inner: Arc<ZoneHandle>,
+    scope: TenantScope,
+}
+
+impl TenantZoneHandle {
+    pub fn tenant_id(&self) -> &str {
+        self.scope.tenant_id()
+    }
+
+    pub fn zone(&self) -> &Arc<ZoneHandle> {
+        &self.inner
+    }
+
+    fn scoped(&self, cmd: Command) -> Command {
+        self.scope.scope_command(cmd)
+    }
+
+    // ── Metadata operations ────────────────────────────────────────
+
+    pub fn set_metadata(
+        &self,
+        path: &str,
+        value: Vec<u8>,
+        consistency: Consistency,
+    ) -> Result<Option<u64>> {
+        let cmd = self.scoped(Command::SetMetadata {
+            key: path.to_string(),
+            value,
+        });
+        self.inner.submit(cmd, consistency)
+    }
+
+    pub fn cas_set_metadata(
+        &self,
+        path: &str,
+        value: Vec<u8>,
+        expected_version: u32,
+        _consistency: Consistency,
+    ) -> Result<(bool, u32)> {
+        self.inner.propose_cas(self.scoped(Command::CasSetMetadata {
+            key: path.to_string(),
+            value,
+            expected_version,
+        }))
+    }
+
+    pub fn cas_metadata_fenced(
+        &self,
+        path: &str,
+        value: Vec<u8>,
+        expected_version: u32,
+        fencing_token: u64,
+    ) -> Result<(bool, u32)> {
+        self.inner
+            .propose_cas(self.scoped(Command::CasSetMetadataFenced {
+                key: path.to_string(),
+                value,
+                expected_version,
+                fencing_token,
+            }))
+    }
+
+    pub fn adjust_counter(&self, key: &str, delta: i64) -> Result<i64> {
+        self.inner
+            .propose_counter(self.scoped(Command::AdjustCounter {
+                key: key.to_string(),
+                delta,
+            }))
+    }
+
+    pub fn get_metadata(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        self.inner.get_metadata(&self.scope.scope_key(path))
+    }
+
+    pub fn delete_metadata(&self, path: &str, consistency: Consistency) -> Result<Option<u64>> {
+        let cmd = self.scoped(Command::DeleteMetadata {
+            key: path.to_string(),
+        });
+        self.inner.submit(cmd, consistency)
+    }
+
+    pub fn link_metadata(&self, alias: &str, target: &str) -> Result<()> {
+        self.inner.propose(self.scoped(Command::LinkMetadata {
+            alias: alias.to_string(),
+            target: target.to_string(),
+        }))?;
+        Ok(())
+    }
+
+    pub fn set_metadata_versioned(
+        &self,
+        path: &str,
+        value: Vec<u8>,
+        max_history: u32,
+    ) -> Result<()> {
+        self.inner
+            .propose(self.scoped(Command::SetMetadataVersioned {
+                key: path.to_string(),
+                value,
+                max_history,
+            }))?;
+        Ok(())
+    }
+
+    pub fn put_if_absent(&self, path: &str, value: Vec<u8>) -> Result<bool> {
+        self.inner
+            .propose_put_if_absent(self.scoped(Command::PutIfAbsent {
+                key: path.to_string(),
+                value,
+            }))
+    }
+
+    pub fn get_metadata_history(&self, path: &str) -> Result<Vec<Vec<u8>>> {
+        self.inner.get_metadata_history(&self.scope.scope_key(path))
+    }
+
+    pub fn list_metadata(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let entries = self.inner.list_metadata(&self.scope.scope_prefix(prefix))?;
+        Ok(self.scope.unscope_entries(entries))
+    }
+
+    pub fn list_metadata_keys(
+        &self,
+        prefix: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<String>> {
+        let keys =
+            self.inner
+                .list_metadata_keys(&self.scope.scope_prefix(prefix), limit, offset)?;
+        Ok(keys
+            .iter()
+            .filter_map(|key| self.scope.unscope_key(key))
+            .collect())
+    }
+
+    pub fn list_metadata_merged(
+        &self,
+        prefixes: Vec<String>,
+        limit: usize,
+        offset: usize,
+        precedence: MergePrecedence,
+    ) -> Result<Vec<MergedEntry>> {
+        // Names are relative to each prefix, so they need no unscoping.
+        let scoped = prefixes
+            .iter()
+            .map(|p| self.scope.scope_prefix(p))
+            .collect();
+        self.inner
+            .list_metadata_merged(scoped, limit, offset, precedence)
+    }
+
+    pub fn get_metadata_multi(&self, paths: Vec<String>) -> Result<Vec<(String, Option<Vec<u8>>)>> {
+        let scoped = paths.iter().map(|p| self.scope.scope_key(p)).collect();
+        let values = self.inner.get_metadata_multi(scoped)?;
+        Ok(paths
+            .into_iter()
+            .zip(values)
+            .map(|(path, (_, value))| (path, value))
+            .collect())
+    }
+
+    pub fn batch_set_metadata(&self, items: Vec<(String, Vec<u8>)>) -> Result<usize> {
+        let count = items.len();
+        for (path, value) in items {
+            self.inner
+                .propose(self.scoped(Command::SetMetadata { key: path, value }))?;
+        }
+        Ok(count)
+    }
+
+    pub fn batch_delete_metadata(&self, keys: Vec<String>) -> Result<usize> {
+        let count = keys.len();
+        for key in keys {
+            self.inner
+                .propose(self.scoped(Command::DeleteMetadata { key }))?;
+        }
+        Ok(count)
+    }
+
+    // ── Lock operations (always SC) ────────────────────────────────
+
+    pub fn acquire_lock(
+        &self,
+        path: &str,
+        lock_id: &str,
+        max_holders: u32,
+        ttl_secs: u32,
+        holder_info: &str,
+    ) -> Result<LockAcquireResult> {
+        self.inner.propose_lock(self.scoped(Command::AcquireLock {
+            path: path.to_string(),
+            lock_id: lock_id.to_string(),
+            max_holders,
+            ttl_secs,
+            holder_info: holder_info.to_string(),
+            now_secs: FullStateMachine::now(),
+        }))
+    }
+
+    pub fn acquire_locks(&self, requests: Vec<LockRequest>) -> Result<Vec<LockAcquireResult>> {
+        self.inner.propose_locks(self.scoped(Command::AcquireLocks {
+            requests,
+            now_secs: FullStateMachine::now(),
+        }))
+    }
+
+    pub fn release_lock(&self, path: &str, lock_id: &str) -> Result<bool> {
+        self.inner
+            .propose_succeeded(self.scoped(Command::ReleaseLock {
+                path: path.to_string(),
+                lock_id: lock_id.to_string(),
+            }))
+    }
+
+    pub fn extend_lock(&self, path: &str, lock_id: &str, new_ttl_secs: u32) -> Result<bool> {
+        self.inner
+            .propose_succeeded(self.scoped(Command::ExtendLock {
+                path: path.to_string(),
+                lock_id: lock_id.to_string(),
+                new_ttl_secs,
+                now_secs: FullStateMachine::now(),
+            }))
+    }
+
+    pub fn get_lock(&self, path: &str) -> Result<Option<LockInfo>> {
+        let lock = self.inner.get_lock(&self.scope.scope_key(path))?;
+        Ok(lock.and_then(|lock| self.scope.unscope_lock(lock)))
+    }
+
+    pub fn peek_lock(&self, path: &str, max_holders: u32) -> Result<LockPreview> {
+        self.inner
+            .peek_lock(&self.scope.scope_key(path), max_holders)
+    }
+
+    pub fn list_locks(&self, prefix: &str, limit: usize) -> Result<Vec<LockInfo>> {
+        // The tenant root sits outside the `{tenant}/` scan prefix.
+        let mut locks = Vec::new();
+        if TenantScope::is_root_prefix(prefix) && limit > 0 {
+            locks.extend(self.get_lock("/")?);
+        }
+        let scanned = self.inner.list_locks(
+            &self.scope.scope_prefix(prefix),
+            limit.saturating_sub(locks.len()),
+        )?;
+        locks.extend(
+            scanned
+                .into_iter()
+                .filter_map(|lock| self.scope.unscope_lock(lock)),
+        );
+        Ok(locks)
+    }
+}
+
+/// Raft status of a witness's membership in one zone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WitnessMetrics {
+    pub node_id: u64,
+    pub role: NodeRole,
+    pub term: u64,
+    pub commit_index: u64,
+    pub last_index: u64,
+    pub leader_id: Option<u64>,
+}
+
+/// Handle to a witness node's membership in one zone, from
+/// [`WitnessZoneRegistry::witness_handle`].
+///
+/// A witness votes in elections and persists the log so a 2 + 1
+/// deployment keeps quorum, but applies no state-machine entries — so
+/// unlike [`ZoneHandle`] there are no metadata or lock operations, only
+/// status and shutdown.
+#[derive(Clone)]
+pub struct WitnessHandle {
+    node: ZoneConsensus<WitnessStateMachine>,
+    registry: Arc<WitnessZoneRegistry>,
+    zone_id: String,
+}
+
+impl WitnessHandle {
+    pub(crate) fn new(
+        node: ZoneConsensus<WitnessStateMachine>,
+        registry: Arc<WitnessZoneRegistry>,
+        zone_id: String,
+    ) -> Self {
+        Self {
+            node,
+            registry,
+            zone_id,
+        }
+    }
+
+    pub fn zone_id(&self) -> &str {
+        &self.zone_id
+    }
+
+    /// Always `false` in practice: witnesses never campaign.
+    pub fn is_leader(&self) -> bool {
+        self.node.is_leader()
+    }
+
+    /// The zone's current leader as seen by this witness.
+    pub fn leader_id(&self) -> Option<u64> {
+        self.node.leader_id()
+    }
+
+    pub fn metrics(&self) -> WitnessMetrics {
+        WitnessMetrics {
+            node_id: self.node.id(),
+            role: self.node.role(),
+            term: self.node.term(),
+            commit_index: self.node.commit_index(),
+            last_index: self.node.last_index(),
+            leader_id: self.node.leader_id(),
+        }
+    }
+
+    /// Stop this zone's transport loop and drop it from the registry.
+    /// Returns `false` if it was already shut down.
+    pub fn shutdown(&self) -> bool {
+        self.registry.remove_zone(&self.zone_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zone_manager::ZoneManager;
+    use tempfile::TempDir;
+
+    fn make_zone() -> (Arc<ZoneManager>, Arc<ZoneHandle>, TempDir) {
+        let dir = TempDir::new().expect("tempdir");
+        let zm = ZoneManager::with_node_id(
+            "test-host",
+            1,
+            dir.path().to_str().expect("utf-8"),
+            vec![],
+            "127.0.0.1:0",
+            None,
+            None,
+            None,
+        )
+        .expect("ZoneManager");
+        let zone = zm.create_zone("tenants", vec![]).expect("create_zone");
+        assert!(zone.wait_for_leader(std::time::Duration::from_secs(5)));
+        (zm, zone, dir)
+    }
+
+    #[test]
+    fn with_tenant_rejects_reserved_ids() {
+        let (_zm, zone, _dir) = make_zone();
+        assert!(zone.with_tenant("__alias__:x").is_err());
+        assert!(zone.with_tenant("acme/eng").is_err());
+        assert!(zone.with_tenant("acme").is_ok());
+    }
+
+    #[test]
+    fn tenant_writes_land_under_the_tenant_prefix() {
+        let (_zm, zone, _dir) = make_zone();
+        let acme = zone.with_tenant("acme").unwrap();
+        let globex = zone.with_tenant("globex").unwrap();
+
+        acme.set_metadata("/a.txt", b"acme-a".to_vec(), Consistency::Sc)
+            .unwrap();
+        globex
+            .set_metadata("/a.txt", b"globex-a".to_vec(), Consistency::Sc)
+            .unwrap();
+        assert!(acme.put_if_absent("/b.txt", b"acme-b".to_vec()).unwrap());
+        assert!(!acme.put_if_absent("/b.txt", b"other".to_vec()).unwrap());
+        assert_eq!(acme.adjust_counter("/n", 3).unwrap(), 3);
+
+        // The shared handle sees the scoped keys, not the tenant-relative ones.
+        assert_eq!(zone.get_metadata("/a.txt").unwrap(), None);
+        assert_eq!(
+            zone.get_metadata("acme/a.txt").unwrap(),
+            Some(b"acme-a".to_vec())
+        );
+        assert_eq!(
+            zone.get_metadata("acme/b.txt").unwrap(),
+            Some(b"acme-b".to_vec())
+        );
+
+        assert_eq!(
+            acme.get_metadata("/a.txt").unwrap(),
+            Some(b"acme-a".to_vec())
+        );
+        assert_eq!(
+            globex.list_metadata("/").unwrap(),
+            vec![("/a.txt".to_string(), b"globex-a".to_vec())]
+        );
+
+        acme.delete_metadata("/a.txt", Consistency::Sc).unwrap();
+        assert_eq!(acme.get_metadata("/a.txt").unwrap(), None);
+        assert_eq!(
+            globex.get_metadata("/a.txt").unwrap(),
+            Some(b"globex-a".to_vec())
+        );
+    }
+
+    #[test]
+    fn put_if_absent_on_an_alias_errors_like_the_state_machine() {
+        let (_zm, zone, _dir) = make_zone();
+        zone.set_metadata("/target", b"t".to_vec(), Consistency::Sc)
+            .unwrap();
+        zone.link_metadata("/alias", "/target").unwrap();
+        assert_eq!(zone.get_metadata("/alias").unwrap(), Some(b"t".to_vec()));
+
+        assert!(zone.put_if_absent("/alias", b"x".to_vec()).is_err());
+        assert!(!zone.put_if_absent("/target", b"x".to_vec()).unwrap());
+        assert_eq!(zone.get_metadata("/target").unwrap(), Some(b"t".to_vec()));
+    }
+
+    #[test]
+    fn tenants_lock_the_same_path_independently() {
+        let (_zm, zone, _dir) = make_zone();
+        let acme = zone.with_tenant("acme").unwrap();
+        let globex = zone.with_tenant("globex").unwrap();
+
+        assert!(
+            acme.acquire_lock("/f", "a1", 1, 30, "agent:a")
+                .unwrap()
+                .acquired
+        );
+        assert!(
+            globex
+                .acquire_lock("/f", "g1", 1, 30, "agent:g")
+                .unwrap()
+                .acquired
+        );
+        assert!(
+            !acme
+                .acquire_lock("/f", "a2", 1, 30, "agent:a")
+                .unwrap()
+                .acquired
+        );
+        assert!(zone.get_lock("acme/f").unwrap().is_some());
+
+        let locks = acme.list_locks("/", 10).unwrap();
+        assert_eq!(locks.len(), 1);
+        assert_eq!(locks[0].path, "/f");
+        assert_eq!(locks[0].holders[0].lock_id, "a1");
+
+        assert!(acme.release_lock("/f", "a1").unwrap());
+        assert!(acme.get_lock("/f").unwrap().is_none());
+        assert!(globex.get_lock("/f").unwrap().is_some());
+    }
+}
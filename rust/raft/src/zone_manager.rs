@@ -597,6 +597,7 @@ impl ZoneManager {
             node,
             self.rt().handle().clone(),
             zone_id.to_string(),
+            Arc::clone(&self.registry),
         ))
     }
 
@@ -630,14 +631,33 @@ impl ZoneManager {
             node,
             self.rt().handle().clone(),
             zone_id.to_string(),
+            Arc::clone(&self.registry),
         ))
     }
 
     /// Get an existing zone handle, or `None`.
     pub fn get_zone(&self, zone_id: &str) -> Option<Arc<ZoneHandle>> {
-        self.registry
+        self.registry.get_node(zone_id).map(|node| {
+            ZoneHandle::new(
+                node,
+                self.rt().handle().clone(),
+                zone_id.to_string(),
+                Arc::clone(&self.registry),
+            )
+        })
+    }
+
+    /// Put `zone_id` into (or take it out of) read-only mode on this node.
+    ///
+    /// The flag lives on the zone's consensus node, so it applies to every
+    /// handle returned by [`Self::get_zone`], including existing ones.
+    pub fn set_read_only(&self, zone_id: &str, read_only: bool) -> Result<()> {
+        let node = self
+            .registry
             .get_node(zone_id)
-            .map(|node| ZoneHandle::new(node, self.rt().handle().clone(), zone_id.to_string()))
+            .ok_or_else(|| RaftError::InvalidState(format!("Zone '{}' not found", zone_id)))?;
+        node.set_read_only(read_only);
+        Ok(())
     }
 
     /// Static Day-1 cluster formation: idempotently create raft groups
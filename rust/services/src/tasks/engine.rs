@@ -1,19 +1,39 @@
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use super::error::{Result, TaskError};
+use super::latency::{unix_millis, LatencyHistogram, LatencyHistograms};
+use super::priority::ClaimOrder;
 use super::store::TaskStore;
-use super::task::{QueueStats, TaskPriority, TaskRecord, TaskStatus};
+use super::task::{CompactionStats, QueueStats, TaskPriority, TaskRecord, TaskStatus};
 
 /// Core task queue engine. Thread-safe via fjall's internal concurrency.
 pub struct Engine {
     store: TaskStore,
     max_pending: usize,
+    /// Pending cap per priority, indexed by `TaskPriority as usize`;
+    /// 0 = no cap beyond `max_pending`.
+    max_pending_per_priority: [usize; PRIORITY_LEVELS],
     max_wait_secs: u64,
+    /// How `claim_next` / `peek_next` order due tasks; strict priority
+    /// by default.
+    claim_order: ClaimOrder,
     /// Serializes admission check + insert so max_pending is enforced under concurrency.
     submit_lock: Mutex<()>,
+    /// Signalled (under `submit_lock`) when tasks leave the pending set,
+    /// waking `submit_blocking` callers.
+    space_freed: Condvar,
+    /// Submit → claim latency of claimed tasks.
+    queue_wait: LatencyHistogram,
+    /// Claim → complete latency of completed tasks.
+    run_time: LatencyHistogram,
+    /// Default for [`Engine::complete`] / [`Engine::fail`]: fsync the
+    /// store before returning. Off by default.
+    durable: bool,
 }
 
+const PRIORITY_LEVELS: usize = TaskPriority::BestEffort as usize + 1;
+
 fn now_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -28,11 +48,75 @@ impl Engine {
         Ok(Self {
             store,
             max_pending,
+            max_pending_per_priority: [0; PRIORITY_LEVELS],
             max_wait_secs,
+            claim_order: ClaimOrder::Priority,
             submit_lock: Mutex::new(()),
+            space_freed: Condvar::new(),
+            queue_wait: LatencyHistogram::default(),
+            run_time: LatencyHistogram::default(),
+            durable: false,
         })
     }
 
+    /// Make [`Self::complete`] and [`Self::fail`] durable by default: the
+    /// store is fsynced before they return, so a task reported done
+    /// survives a crash. Without it, writes sit in fjall's journal buffer
+    /// until the next [`Self::flush`] or background persist. Per-call
+    /// overrides go through [`Self::complete_with_durability`] and
+    /// [`Self::fail_with_durability`].
+    pub fn with_durable_completions(mut self, durable: bool) -> Self {
+        self.durable = durable;
+        self
+    }
+
+    /// Order `claim_next` and `peek_next(None)` by `order`. With
+    /// [`ClaimOrder::Deadline`], a task submitted through
+    /// [`Self::submit_with_deadline`] whose deadline is near can be
+    /// claimed ahead of higher-priority work. `claim_and_lock` keeps
+    /// strict priority.
+    pub fn with_claim_order(mut self, order: ClaimOrder) -> Self {
+        self.claim_order = order;
+        self
+    }
+
+    /// Cap the pending tasks of individual priorities, so a flood of
+    /// low-priority work can't fill `max_pending` and lock out urgent
+    /// submits. A cap of 0 removes it; `max_pending` still bounds the
+    /// total.
+    pub fn with_max_pending_per_priority(mut self, caps: &[(TaskPriority, usize)]) -> Self {
+        for &(priority, cap) in caps {
+            self.max_pending_per_priority[priority as usize] = cap;
+        }
+        self
+    }
+
+    /// Why a `priority` submit can't be admitted right now, if it can't.
+    /// Caller holds `submit_lock`.
+    fn admission_error(&self, priority: TaskPriority) -> Result<Option<TaskError>> {
+        if self.max_pending > 0 {
+            let pending = self.store.count_pending()?;
+            if pending >= self.max_pending {
+                return Ok(Some(TaskError::QueueFull {
+                    pending,
+                    max_pending: self.max_pending,
+                }));
+            }
+        }
+        let cap = self.max_pending_per_priority[priority as usize];
+        if cap > 0 {
+            let pending = self.store.count_pending_with_priority(priority)?;
+            if pending >= cap {
+                return Ok(Some(TaskError::PriorityQueueFull {
+                    priority,
+                    pending,
+                    max_pending: cap,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
     /// Submit a new task. Returns the assigned task ID.
     pub fn submit(
         &self,
@@ -43,22 +127,95 @@ impl Engine {
         run_at: u64,
     ) -> Result<u64> {
         // Keep admission control and insertion atomic at the engine level.
-        let _submit_guard = self
-            .submit_lock
-            .lock()
-            .map_err(|e| TaskError::Storage(format!("submit lock poisoned: {e}")))?;
+        let _submit_guard = self.lock_submit()?;
 
         // Admission control
-        if self.max_pending > 0 {
-            let pending = self.store.count_pending()?;
-            if pending >= self.max_pending {
-                return Err(TaskError::QueueFull {
-                    pending,
-                    max_pending: self.max_pending,
-                });
+        if let Some(e) = self.admission_error(priority)? {
+            return Err(e);
+        }
+
+        self.insert_new(task_type, params, priority, max_retries, run_at, None)
+    }
+
+    /// [`Self::submit`] with an absolute `deadline` (unix secs), used to
+    /// order claims under [`ClaimOrder::Deadline`].
+    pub fn submit_with_deadline(
+        &self,
+        task_type: &str,
+        params: &[u8],
+        priority: TaskPriority,
+        max_retries: u32,
+        run_at: u64,
+        deadline: u64,
+    ) -> Result<u64> {
+        let _submit_guard = self.lock_submit()?;
+        if let Some(e) = self.admission_error(priority)? {
+            return Err(e);
+        }
+        self.insert_new(
+            task_type,
+            params,
+            priority,
+            max_retries,
+            run_at,
+            Some(deadline),
+        )
+    }
+
+    /// Submit a new task, waiting up to `timeout_secs` for room when the
+    /// queue (or the task's priority) is at its cap instead of failing
+    /// with `QueueFull` / `PriorityQueueFull`.
+    /// Woken whenever a task is claimed or cancelled; returns
+    /// `TaskError::Timeout` if the queue is still full at the deadline.
+    pub fn submit_blocking(
+        &self,
+        task_type: &str,
+        params: &[u8],
+        priority: TaskPriority,
+        max_retries: u32,
+        run_at: u64,
+        timeout_secs: u64,
+    ) -> Result<u64> {
+        let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+        let mut guard = self.lock_submit()?;
+        while self.admission_error(priority)?.is_some() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(TaskError::Timeout(timeout_secs));
             }
+            guard = self
+                .space_freed
+                .wait_timeout(guard, remaining)
+                .map_err(|e| TaskError::Storage(format!("submit lock poisoned: {e}")))?
+                .0;
         }
+        self.insert_new(task_type, params, priority, max_retries, run_at, None)
+    }
 
+    fn lock_submit(&self) -> Result<MutexGuard<'_, ()>> {
+        self.submit_lock
+            .lock()
+            .map_err(|e| TaskError::Storage(format!("submit lock poisoned: {e}")))
+    }
+
+    /// Wake blocked submitters after tasks left the pending set. Taking
+    /// `submit_lock` first means a submitter between its pending count and
+    /// its wait cannot miss the signal.
+    fn notify_space_freed(&self) {
+        drop(self.submit_lock.lock());
+        self.space_freed.notify_all();
+    }
+
+    /// Insert a fresh pending task. Caller holds `submit_lock`.
+    fn insert_new(
+        &self,
+        task_type: &str,
+        params: &[u8],
+        priority: TaskPriority,
+        max_retries: u32,
+        run_at: u64,
+        deadline: Option<u64>,
+    ) -> Result<u64> {
         let now = now_secs();
         let task_id = self.store.generate_id();
         let effective_run_at = if run_at == 0 { now } else { run_at };
@@ -81,17 +238,100 @@ impl Engine {
             completed_at: None,
             progress_pct: 0,
             progress_message: None,
+            submitted_at_ms: Some(unix_millis()),
+            claimed_at_ms: None,
+            deadline,
         };
 
         self.store.insert_task(&task)?;
         Ok(task_id)
     }
 
+    /// Record the queue wait of a just-claimed task from the stamps on its
+    /// record. Records without millisecond stamps (written by an older
+    /// version) fall back to the whole-second `created_at`.
+    fn record_claim(&self, task: &TaskRecord, now: u64) {
+        let waited_ms = match (task.submitted_at_ms, task.claimed_at_ms) {
+            (Some(submitted), Some(claimed)) => claimed.saturating_sub(submitted),
+            _ => now.saturating_sub(task.created_at).saturating_mul(1000),
+        };
+        self.queue_wait.record(waited_ms);
+    }
+
     /// Claim the next available task for a worker.
     pub fn claim_next(&self, worker_id: &str, lease_secs: u32) -> Result<Option<TaskRecord>> {
         let now = now_secs();
-        self.store
-            .claim_next(worker_id, lease_secs, now, self.max_wait_secs)
+        let claimed = self.store.claim_next_ordered(
+            worker_id,
+            lease_secs,
+            now,
+            self.max_wait_secs,
+            self.claim_order,
+        )?;
+        if let Some(task) = &claimed {
+            self.record_claim(task, now);
+            self.notify_space_freed();
+        }
+        Ok(claimed)
+    }
+
+    /// Claim the next available task of `required_type` with a custom
+    /// lease, in one step. Returns `None` if no task of that type is due;
+    /// tasks of other types are left pending rather than claimed and failed.
+    pub fn claim_and_lock(
+        &self,
+        worker_id: &str,
+        lease_secs: u32,
+        required_type: &str,
+    ) -> Result<Option<TaskRecord>> {
+        let now = now_secs();
+        let claimed = self
+            .store
+            .claim_next_of_type(worker_id, required_type, lease_secs, now)?;
+        if let Some(task) = &claimed {
+            self.record_claim(task, now);
+            self.notify_space_freed();
+        }
+        Ok(claimed)
+    }
+
+    /// The task a claim would return right now, without claiming it.
+    ///
+    /// `None` for `task_type` follows `claim_next` ordering (claim order,
+    /// priority and anti-starvation promotion); `Some(t)` follows
+    /// `claim_and_lock`.
+    /// Status and lease are left untouched, so schedulers can inspect the
+    /// head of the queue before deciding which worker to start.
+    pub fn peek_next(&self, task_type: Option<&str>) -> Result<Option<TaskRecord>> {
+        let now = now_secs();
+        match task_type {
+            Some(task_type) => self.store.peek_next_of_type(task_type, now),
+            None => self
+                .store
+                .peek_next_ordered(now, self.max_wait_secs, self.claim_order),
+        }
+    }
+
+    /// The order `claim_next` would claim a synthetic set of
+    /// `(priority, run_at, deadline)` tasks under this engine's claim order
+    /// and anti-starvation threshold, as task ids (positions in `tasks`).
+    /// See [`simulate_claims`](super::priority::simulate_claims); the store
+    /// is not touched.
+    pub fn simulate_scheduling(
+        &self,
+        tasks: &[(TaskPriority, u64, Option<u64>)],
+        now: u64,
+        secs_per_claim: u64,
+        steps: usize,
+    ) -> Vec<u64> {
+        super::priority::simulate_claims(
+            tasks,
+            now,
+            secs_per_claim,
+            self.max_wait_secs,
+            self.claim_order,
+            steps,
+        )
     }
 
     /// Update heartbeat/progress for a running task. Also renews the lease
@@ -131,18 +371,57 @@ impl Engine {
     /// Mark a task as completed with a result payload.
     /// `worker_id` must match the current owner (prevents stale workers from
     /// overwriting a re-claimed task after lease expiry).
+    /// Durable iff the engine default is (see [`Self::with_durable_completions`]).
     pub fn complete(&self, task_id: u64, result: &[u8], worker_id: &str) -> Result<()> {
+        self.complete_with_durability(task_id, result, worker_id, self.durable)
+    }
+
+    /// [`Self::complete`], fsyncing the store before returning iff `durable`.
+    pub fn complete_with_durability(
+        &self,
+        task_id: u64,
+        result: &[u8],
+        worker_id: &str,
+        durable: bool,
+    ) -> Result<()> {
         let now = now_secs();
-        self.store.complete_task(task_id, result, now, worker_id)?;
+        let task = self.store.complete_task(task_id, result, now, worker_id)?;
+        let ran_ms = match task.claimed_at_ms {
+            Some(claimed) => Some(unix_millis().saturating_sub(claimed)),
+            None => task
+                .claimed_at
+                .map(|claimed_at| now.saturating_sub(claimed_at).saturating_mul(1000)),
+        };
+        if let Some(ran_ms) = ran_ms {
+            self.run_time.record(ran_ms);
+        }
+        if durable {
+            self.store.flush()?;
+        }
         Ok(())
     }
 
     /// Mark a task as failed. Auto-retries if attempts remain; otherwise dead-letters.
-    /// `worker_id` must match the current owner.
+    /// `worker_id` must match the current owner. Durable iff the engine
+    /// default is.
     pub fn fail(&self, task_id: u64, error_message: &str, worker_id: &str) -> Result<()> {
+        self.fail_with_durability(task_id, error_message, worker_id, self.durable)
+    }
+
+    /// [`Self::fail`], fsyncing the store before returning iff `durable`.
+    pub fn fail_with_durability(
+        &self,
+        task_id: u64,
+        error_message: &str,
+        worker_id: &str,
+        durable: bool,
+    ) -> Result<()> {
         let now = now_secs();
         self.store
             .fail_task(task_id, error_message, now, worker_id)?;
+        if durable {
+            self.store.flush()?;
+        }
         Ok(())
     }
 
@@ -150,6 +429,7 @@ impl Engine {
     pub fn cancel(&self, task_id: u64) -> Result<()> {
         let now = now_secs();
         self.store.cancel_task(task_id, now)?;
+        self.notify_space_freed();
         Ok(())
     }
 
@@ -181,12 +461,30 @@ impl Engine {
         self.store.cleanup(max_completed_age_secs, now)
     }
 
+    /// Rebuild the store's indexes and compact it on disk. Submits wait
+    /// for it, and so do claims and other transitions while the indexes
+    /// are rebuilt (see [`TaskStore::compact`]).
+    pub fn compact(&self) -> Result<CompactionStats> {
+        let _submit = self.lock_submit()?;
+        self.store.compact()
+    }
+
     /// Get aggregate queue statistics.
     pub fn stats(&self) -> Result<QueueStats> {
         self.store.count_by_status()
     }
 
-    /// Persist all data to disk.
+    /// Queue-wait and run-time histograms (milliseconds) since the engine
+    /// was opened.
+    /// Counts are in-memory only and start empty on every open.
+    pub fn latency_histograms(&self) -> LatencyHistograms {
+        LatencyHistograms {
+            queue_wait: self.queue_wait.snapshot(),
+            run_time: self.run_time.snapshot(),
+        }
+    }
+
+    /// Persist all data to disk (fsync), making every prior write durable.
     pub fn flush(&self) -> Result<()> {
         self.store.flush()
     }
@@ -219,6 +517,62 @@ mod tests {
         assert_eq!(task.claimed_by.as_deref(), Some("w-0"));
     }
 
+    #[test]
+    fn test_claim_and_lock_filters_type_and_applies_lease() {
+        let (engine, _dir) = test_engine();
+        engine
+            .submit("test.other", b"x", TaskPriority::Critical, 3, 0)
+            .unwrap();
+        let tid = engine
+            .submit("test.long", b"y", TaskPriority::Normal, 3, 0)
+            .unwrap();
+
+        let task = engine
+            .claim_and_lock("w-0", 3600, "test.long")
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.task_id, tid);
+        assert_eq!(task.task_type, "test.long");
+        assert_eq!(task.lease_secs, 3600);
+        assert_eq!(task.status, TaskStatus::Running);
+
+        assert!(engine
+            .claim_and_lock("w-0", 3600, "test.long")
+            .unwrap()
+            .is_none());
+        let other = engine.claim_next("w-1", 300).unwrap().unwrap();
+        assert_eq!(other.task_type, "test.other");
+    }
+
+    #[test]
+    fn test_peek_next_matches_claim_without_claiming() {
+        let (engine, _dir) = test_engine();
+        assert!(engine.peek_next(None).unwrap().is_none());
+
+        engine.submit("low", b"", TaskPriority::Low, 0, 0).unwrap();
+        let high = engine
+            .submit("high", b"", TaskPriority::High, 0, 0)
+            .unwrap();
+
+        let peeked = engine.peek_next(None).unwrap().unwrap();
+        assert_eq!(peeked.task_id, high);
+        assert_eq!(peeked.status, TaskStatus::Pending);
+        assert!(peeked.claimed_by.is_none());
+        // Peeking twice is stable and leaves the task pending.
+        assert_eq!(engine.peek_next(None).unwrap().unwrap().task_id, high);
+        assert_eq!(engine.status(high).unwrap().unwrap().attempt, 0);
+
+        let low = engine.peek_next(Some("low")).unwrap().unwrap();
+        assert_eq!(low.task_type, "low");
+        assert!(engine.peek_next(Some("missing")).unwrap().is_none());
+
+        let claimed = engine.claim_next("w-0", 300).unwrap().unwrap();
+        assert_eq!(claimed.task_id, peeked.task_id);
+        let claimed = engine.claim_and_lock("w-0", 300, "low").unwrap().unwrap();
+        assert_eq!(claimed.task_id, low.task_id);
+        assert!(engine.peek_next(None).unwrap().is_none());
+    }
+
     #[test]
     fn test_full_lifecycle_happy_path() {
         let (engine, _dir) = test_engine();
@@ -393,6 +747,36 @@ mod tests {
         assert_eq!(stats.pending, 2);
     }
 
+    #[test]
+    fn test_submit_blocking_waits_for_claim() {
+        let dir = TempDir::new().unwrap();
+        let engine = Arc::new(Engine::open(dir.path().to_str().unwrap(), 1, 0).unwrap());
+        engine.submit("a", b"", TaskPriority::Normal, 0, 0).unwrap();
+
+        let result = engine.submit_blocking("b", b"", TaskPriority::Normal, 0, 0, 0);
+        assert!(matches!(result, Err(TaskError::Timeout(0))));
+
+        let producer = {
+            let engine = Arc::clone(&engine);
+            std::thread::spawn(move || {
+                engine.submit_blocking("b", b"", TaskPriority::Normal, 0, 0, 30)
+            })
+        };
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(
+            !producer.is_finished(),
+            "submit should block on a full queue"
+        );
+
+        let claimed = engine.claim_next("w1", 60).unwrap().unwrap();
+        assert_eq!(claimed.task_type, "a");
+        let task_id = producer.join().unwrap().unwrap();
+
+        let task = engine.status(task_id).unwrap().unwrap();
+        assert_eq!(task.task_type, "b");
+        assert_eq!(engine.stats().unwrap().pending, 1);
+    }
+
     #[test]
     fn test_submit_lock_poison_returns_error_not_panic() {
         let dir = TempDir::new().unwrap();
@@ -471,4 +855,203 @@ mod tests {
             Err(TaskError::NotFound(999))
         ));
     }
+
+    #[test]
+    fn test_latency_histograms_track_claim_and_complete() {
+        let (engine, _dir) = test_engine();
+        assert_eq!(engine.latency_histograms().queue_wait.count(), 0);
+
+        let fresh = engine
+            .submit("fresh", b"", TaskPriority::High, 0, 0)
+            .unwrap();
+        // Due 100s before it was submitted: its wait still starts at submit.
+        engine
+            .submit("overdue", b"", TaskPriority::Low, 0, now_secs() - 100)
+            .unwrap();
+        engine
+            .submit("unclaimed", b"", TaskPriority::Low, 0, now_secs() + 3600)
+            .unwrap();
+
+        std::thread::sleep(Duration::from_millis(20));
+        let task = engine.claim_next("w-0", 300).unwrap().unwrap();
+        assert_eq!(task.task_id, fresh);
+        std::thread::sleep(Duration::from_millis(20));
+        engine.complete(fresh, b"done", "w-0").unwrap();
+        let overdue = engine
+            .claim_and_lock("w-1", 300, "overdue")
+            .unwrap()
+            .unwrap();
+
+        let histograms = engine.latency_histograms();
+        assert_eq!(histograms.queue_wait.count(), 2);
+        // Millisecond samples: at least the sleeps, well under a minute.
+        let wait_p0 = histograms.queue_wait.quantile(0.0).unwrap();
+        assert!((32..60_000).contains(&wait_p0), "{wait_p0}");
+        assert_eq!(histograms.run_time.count(), 1);
+        let ran = histograms.run_time.quantile(1.0).unwrap();
+        assert!((32..60_000).contains(&ran), "{ran}");
+
+        // Failures don't count as completions.
+        engine.fail(overdue.task_id, "boom", "w-1").unwrap();
+        assert_eq!(engine.latency_histograms().run_time.count(), 1);
+    }
+
+    #[test]
+    fn test_latency_stamps_survive_reopen() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let tid = Engine::open(path, 1000, 300)
+            .unwrap()
+            .submit("later", b"", TaskPriority::Normal, 0, 0)
+            .unwrap();
+
+        // The submit stamp lives on the record, so a fresh engine still
+        // measures the wait in milliseconds rather than whole seconds.
+        std::thread::sleep(Duration::from_millis(20));
+        let engine = Engine::open(path, 1000, 300).unwrap();
+        let task = engine.claim_next("w-0", 300).unwrap().unwrap();
+        assert_eq!(task.task_id, tid);
+        let waited = engine
+            .latency_histograms()
+            .queue_wait
+            .quantile(1.0)
+            .unwrap();
+        assert!((16..60_000).contains(&waited), "{waited}");
+        assert!(task.claimed_at_ms >= task.submitted_at_ms);
+    }
+
+    #[test]
+    fn test_per_priority_cap_leaves_room_for_critical() {
+        let dir = TempDir::new().unwrap();
+        let engine = Engine::open(dir.path().to_str().unwrap(), 5, 300)
+            .unwrap()
+            .with_max_pending_per_priority(&[(TaskPriority::BestEffort, 2)]);
+
+        for _ in 0..2 {
+            engine
+                .submit("bulk", b"", TaskPriority::BestEffort, 0, 0)
+                .unwrap();
+        }
+        assert!(matches!(
+            engine.submit("bulk", b"", TaskPriority::BestEffort, 0, 0),
+            Err(TaskError::PriorityQueueFull {
+                priority: TaskPriority::BestEffort,
+                pending: 2,
+                max_pending: 2,
+            })
+        ));
+        engine
+            .submit("urgent", b"", TaskPriority::Critical, 0, 0)
+            .unwrap();
+
+        // Claiming a BestEffort task frees its band.
+        engine.claim_and_lock("w-0", 300, "bulk").unwrap().unwrap();
+        engine
+            .submit("bulk", b"", TaskPriority::BestEffort, 0, 0)
+            .unwrap();
+
+        // The global cap still bounds the total across priorities.
+        for _ in 0..2 {
+            engine
+                .submit("urgent", b"", TaskPriority::Critical, 0, 0)
+                .unwrap();
+        }
+        assert_eq!(engine.stats().unwrap().pending, 5);
+        assert!(matches!(
+            engine.submit("urgent", b"", TaskPriority::Critical, 0, 0),
+            Err(TaskError::QueueFull { .. })
+        ));
+    }
+
+    /// Copy the store directory as it is on disk right now — what a crash
+    /// at this point would leave behind — without closing the engine.
+    fn crash_image(from: &std::path::Path, to: &std::path::Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let dest = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                crash_image(&entry.path(), &dest);
+            } else {
+                std::fs::copy(entry.path(), dest).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_durable_completion_survives_crash() {
+        let dir = TempDir::new().unwrap();
+        let engine = Engine::open(dir.path().to_str().unwrap(), 1000, 300)
+            .unwrap()
+            .with_durable_completions(true);
+        let done = engine
+            .submit("test.durable", b"", TaskPriority::Normal, 0, 0)
+            .unwrap();
+        let failed = engine
+            .submit("test.durable", b"", TaskPriority::Normal, 0, 0)
+            .unwrap();
+        engine.claim_next("w-0", 300).unwrap().unwrap();
+        engine.claim_next("w-0", 300).unwrap().unwrap();
+        engine.complete(done, b"ok", "w-0").unwrap();
+        engine.fail(failed, "boom", "w-0").unwrap();
+
+        let image = TempDir::new().unwrap();
+        crash_image(dir.path(), image.path());
+        let reopened = Engine::open(image.path().to_str().unwrap(), 1000, 300).unwrap();
+        let task = reopened.status(done).unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.result.as_deref(), Some(&b"ok"[..]));
+        let task = reopened.status(failed).unwrap().unwrap();
+        assert_eq!(task.status, TaskStatus::DeadLetter);
+    }
+
+    #[test]
+    fn test_per_call_durability_overrides_engine_default() {
+        let (engine, dir) = test_engine();
+        let tid = engine
+            .submit("test.durable", b"", TaskPriority::Normal, 0, 0)
+            .unwrap();
+        engine.claim_next("w-0", 300).unwrap().unwrap();
+        engine
+            .complete_with_durability(tid, b"ok", "w-0", true)
+            .unwrap();
+
+        let image = TempDir::new().unwrap();
+        crash_image(dir.path(), image.path());
+        let reopened = Engine::open(image.path().to_str().unwrap(), 1000, 300).unwrap();
+        assert_eq!(
+            reopened.status(tid).unwrap().unwrap().status,
+            TaskStatus::Completed
+        );
+    }
+
+    #[test]
+    fn test_deadline_claim_order_prefers_imminent_deadline() {
+        let dir = TempDir::new().unwrap();
+        let engine = Engine::open(dir.path().to_str().unwrap(), 1000, 300)
+            .unwrap()
+            .with_claim_order(ClaimOrder::Deadline { horizon_secs: 60 });
+        let now = now_secs();
+        let distant = engine
+            .submit_with_deadline("distant", b"", TaskPriority::High, 3, 0, now + 3600)
+            .unwrap();
+        let imminent = engine
+            .submit_with_deadline("imminent", b"", TaskPriority::Normal, 3, 0, now + 1)
+            .unwrap();
+
+        let head = engine.peek_next(None).unwrap().unwrap();
+        assert_eq!(head.task_id, imminent);
+        assert_eq!(head.deadline, Some(now + 1));
+        let first = engine.claim_next("w-0", 300).unwrap().unwrap();
+        assert_eq!(first.task_id, imminent);
+        let second = engine.claim_next("w-0", 300).unwrap().unwrap();
+        assert_eq!(second.task_id, distant);
+
+        // The dry run follows the engine's claim order too.
+        let tasks = [
+            (TaskPriority::High, now, Some(now + 3600)),
+            (TaskPriority::Normal, now, Some(now + 1)),
+        ];
+        assert_eq!(engine.simulate_scheduling(&tasks, now, 0, 2), vec![1, 0]);
+    }
 }
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::task::TaskPriority;
+
 #[derive(Error, Debug)]
 pub enum TaskError {
     #[error("storage error: {0}")]
@@ -21,6 +23,16 @@ pub enum TaskError {
     #[error("queue full: {pending} pending tasks (max: {max_pending})")]
     QueueFull { pending: usize, max_pending: usize },
 
+    #[error("queue full for {priority:?}: {pending} pending tasks (max: {max_pending})")]
+    PriorityQueueFull {
+        priority: TaskPriority,
+        pending: usize,
+        max_pending: usize,
+    },
+
+    #[error("queue still full after waiting {0}s")]
+    Timeout(u64),
+
     #[error("task {task_id} not owned by worker {worker_id}")]
     NotOwner { task_id: u64, worker_id: String },
 }
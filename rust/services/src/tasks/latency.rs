@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of buckets. Bucket 0 holds latencies under 1ms, bucket `i`
+/// holds `[2^(i-1), 2^i)` milliseconds, and the last bucket is open-ended
+/// (from 2^30 ms ≈ 12 days).
+pub const BUCKETS: usize = 32;
+
+/// Exponential-bucket latency histogram in milliseconds. Recording is a
+/// single relaxed atomic increment: no locks, no allocation.
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+}
+
+/// Bucket index for a latency of `ms`.
+fn bucket_of(ms: u64) -> usize {
+    ((u64::BITS - ms.leading_zeros()) as usize).min(BUCKETS - 1)
+}
+
+/// Exclusive upper bound of bucket `i` in milliseconds; `u64::MAX` for
+/// the open-ended last bucket.
+pub fn bucket_upper_bound_ms(i: usize) -> u64 {
+    if i + 1 >= BUCKETS {
+        u64::MAX
+    } else {
+        1 << i
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&self, ms: u64) {
+        self.buckets[bucket_of(ms)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Point-in-time copy of the bucket counts. Buckets are read one by
+    /// one, so a snapshot taken during recording may be off by the
+    /// in-flight samples.
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        HistogramSnapshot {
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Bucket counts of a [`LatencyHistogram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HistogramSnapshot {
+    /// Sample count per bucket; see [`BUCKETS`] for the bucket bounds.
+    pub buckets: [u64; BUCKETS],
+}
+
+impl HistogramSnapshot {
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Upper bound (exclusive, in milliseconds) of the bucket holding the
+    /// `q`-quantile sample, e.g. `quantile(0.99)` for p99. `None` when the
+    /// histogram is empty.
+    pub fn quantile(&self, q: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        // Rank of the quantile sample, 1-based.
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return Some(bucket_upper_bound_ms(i));
+            }
+        }
+        Some(u64::MAX)
+    }
+}
+
+/// Wall-clock milliseconds since the Unix epoch, for the submit / claim
+/// stamps on [`TaskRecord`](super::task::TaskRecord). Task records are
+/// otherwise stamped in whole seconds, too coarse for a millisecond
+/// histogram.
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// Snapshot of the engine's latency histograms, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyHistograms {
+    /// From submit to a worker claiming the task. Includes any scheduled
+    /// delay and, for retries, the earlier attempts and their backoff.
+    pub queue_wait: HistogramSnapshot,
+    /// From claim to successful completion.
+    pub run_time: HistogramSnapshot,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_bounds() {
+        assert_eq!(bucket_of(0), 0);
+        assert_eq!(bucket_of(1), 1);
+        assert_eq!(bucket_of(3), 2);
+        assert_eq!(bucket_of(4), 3);
+        assert_eq!(bucket_of(u64::MAX), BUCKETS - 1);
+        for ms in [0, 1, 2, 7, 1000, 1 << 29] {
+            let i = bucket_of(ms);
+            assert!(ms < bucket_upper_bound_ms(i));
+            assert!(i == 0 || ms >= bucket_upper_bound_ms(i - 1));
+        }
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let histogram = LatencyHistogram::default();
+        assert_eq!(histogram.snapshot().quantile(0.5), None);
+        for _ in 0..90 {
+            histogram.record(0);
+        }
+        for _ in 0..9 {
+            histogram.record(5);
+        }
+        histogram.record(100);
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count(), 100);
+        assert_eq!(snapshot.quantile(0.5), Some(1));
+        assert_eq!(snapshot.quantile(0.95), Some(8));
+        assert_eq!(snapshot.quantile(1.0), Some(128));
+    }
+}
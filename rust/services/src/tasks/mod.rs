@@ -2,6 +2,7 @@
 //!
 //! Sub-modules:
 //!   * [`engine`] — `Engine` struct (open, submit, claim, complete, …)
+//!   * [`latency`] — lock-free queue-wait / run-time histograms
 //!   * [`store`] — fjall-backed persistence
 //!   * [`task`] — `TaskRecord`, `TaskStatus`, `TaskPriority` types
 //!   * [`priority`] — priority queue ordering
@@ -10,6 +11,7 @@
 
 pub mod engine;
 pub mod error;
+pub mod latency;
 pub mod priority;
 pub mod retry;
 pub mod store;
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use super::task::TaskPriority;
 
 /// Composite pending-queue key: [priority: 1 byte][run_at: 8 bytes BE][task_id: 8 bytes BE]
@@ -46,11 +48,202 @@ pub fn decode_running_key(key: &[u8]) -> Option<(u64, u64)> {
     Some((lease_expires, task_id))
 }
 
+/// Deadline-index key: [deadline: 8 bytes BE][task_id: 8 bytes BE]
+/// Total: 16 bytes. Sorted earliest deadline first; only pending tasks
+/// with a deadline have one.
+pub const DEADLINE_KEY_LEN: usize = 16;
+
+pub fn encode_deadline_key(deadline: u64, task_id: u64) -> [u8; DEADLINE_KEY_LEN] {
+    let mut key = [0u8; DEADLINE_KEY_LEN];
+    key[0..8].copy_from_slice(&deadline.to_be_bytes());
+    key[8..16].copy_from_slice(&task_id.to_be_bytes());
+    key
+}
+
+pub fn decode_deadline_key(key: &[u8]) -> Option<(u64, u64)> {
+    if key.len() != DEADLINE_KEY_LEN {
+        return None;
+    }
+    let deadline = u64::from_be_bytes(key[0..8].try_into().ok()?);
+    let task_id = u64::from_be_bytes(key[8..16].try_into().ok()?);
+    Some((deadline, task_id))
+}
+
+/// How `claim_next` orders due tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClaimOrder {
+    /// Highest priority band first, earliest `run_at` within a band.
+    /// Deadlines are ignored.
+    #[default]
+    Priority,
+    /// A due task whose deadline is at most `horizon_secs` away (or
+    /// already past) goes first, earliest deadline first, whatever its
+    /// priority. With none, `Priority` order (and anti-starvation)
+    /// applies. A due Critical task still preempts.
+    Deadline { horizon_secs: u64 },
+}
+
+impl ClaimOrder {
+    /// Latest deadline that counts as imminent at `now`, if deadlines are
+    /// considered at all.
+    pub fn deadline_cutoff(self, now: u64) -> Option<u64> {
+        match self {
+            ClaimOrder::Priority => None,
+            ClaimOrder::Deadline { horizon_secs } => Some(now.saturating_add(horizon_secs)),
+        }
+    }
+}
+
 /// Anti-starvation: check if the oldest task has waited beyond the threshold.
 pub fn should_promote_oldest(oldest_run_at: u64, now: u64, max_wait_secs: u64) -> bool {
     now.saturating_sub(oldest_run_at) > max_wait_secs
 }
 
+/// First pending-index key of each priority band, in key order. Lets
+/// `TaskStore` and [`simulate_claims`] pick tasks by the same rules.
+pub trait PendingBands {
+    fn band_head(&self, priority: u8) -> Option<Vec<u8>>;
+}
+
+impl PendingBands for BTreeSet<[u8; PENDING_KEY_LEN]> {
+    fn band_head(&self, priority: u8) -> Option<Vec<u8>> {
+        let mut start = [0u8; PENDING_KEY_LEN];
+        start[0] = priority;
+        self.range(start..)
+            .next()
+            .filter(|key| key[0] == priority)
+            .map(|key| key.to_vec())
+    }
+}
+
+/// Whether there is a due Critical task that must not be preempted.
+///
+/// If the first key is corrupt, conservatively treat it as due so we avoid
+/// promoting lower-priority work before self-healing the critical band.
+pub fn has_due_critical(bands: &impl PendingBands, now: u64) -> bool {
+    let Some(key_bytes) = bands.band_head(TaskPriority::Critical as u8) else {
+        return false;
+    };
+    match decode_pending_key(&key_bytes) {
+        Some((_, run_at, _)) => run_at <= now,
+        None => true,
+    }
+}
+
+/// Select the highest-priority due key in O(priority bands), or return a
+/// corrupt key candidate so the caller can self-heal the index.
+fn first_due_or_corrupt_pending_key(bands: &impl PendingBands, now: u64) -> Option<Vec<u8>> {
+    for priority in TaskPriority::Critical as u8..=TaskPriority::BestEffort as u8 {
+        let Some(key_bytes) = bands.band_head(priority) else {
+            continue;
+        };
+        match decode_pending_key(&key_bytes) {
+            Some((_, run_at, _)) if run_at <= now => return Some(key_bytes),
+            Some(_) => continue, // earliest key in this priority is future-scheduled
+            None => return Some(key_bytes),
+        }
+    }
+    None
+}
+
+/// Select a starving non-critical task for anti-starvation promotion.
+///
+/// Promotion is disabled while any due Critical task exists.
+fn select_starved_pending_key(
+    bands: &impl PendingBands,
+    now: u64,
+    max_wait_secs: u64,
+) -> Option<Vec<u8>> {
+    if max_wait_secs == 0 || has_due_critical(bands, now) {
+        return None;
+    }
+
+    // Check non-Critical priority bands from lowest (BestEffort=4)
+    // to highest (High=1). Promote the oldest starving task found.
+    for priority in (TaskPriority::High as u8..=TaskPriority::BestEffort as u8).rev() {
+        let Some(key_bytes) = bands.band_head(priority) else {
+            continue;
+        };
+        if let Some((_, run_at, _)) = decode_pending_key(&key_bytes) {
+            if should_promote_oldest(run_at, now, max_wait_secs) {
+                return Some(key_bytes);
+            }
+        }
+    }
+
+    None
+}
+
+/// The pending key `claim_next` picks at `now`: `imminent` (the
+/// [`ClaimOrder::Deadline`] candidate, if any), else a starving task, else
+/// the earliest due task of the highest band.
+pub fn select_pending_key(
+    bands: &impl PendingBands,
+    now: u64,
+    max_wait_secs: u64,
+    imminent: Option<Vec<u8>>,
+) -> Option<Vec<u8>> {
+    imminent
+        .or_else(|| select_starved_pending_key(bands, now, max_wait_secs))
+        .or_else(|| first_due_or_corrupt_pending_key(bands, now))
+}
+
+/// Dry-run of `claim_next` ordering over a synthetic pending set.
+///
+/// `tasks` are `(priority, run_at, deadline)` triples whose task ids are
+/// their positions. Each of `steps` claim attempts runs at
+/// `now + step * secs_per_claim` and picks a task with the store's own
+/// selection rules under `order` and `max_wait_secs`
+/// ([`select_pending_key`]); `max_wait_secs == 0` is strict priority.
+///
+/// Returns the claimed task ids in claim order; an attempt with nothing
+/// due claims nothing. No store is touched.
+pub fn simulate_claims(
+    tasks: &[(TaskPriority, u64, Option<u64>)],
+    now: u64,
+    secs_per_claim: u64,
+    max_wait_secs: u64,
+    order: ClaimOrder,
+    steps: usize,
+) -> Vec<u64> {
+    let mut pending: BTreeSet<[u8; PENDING_KEY_LEN]> = tasks
+        .iter()
+        .enumerate()
+        .map(|(id, &(priority, run_at, _))| encode_pending_key(priority, run_at, id as u64))
+        .collect();
+
+    let mut claimed = Vec::new();
+    for step in 0..steps as u64 {
+        let now = now.saturating_add(step.saturating_mul(secs_per_claim));
+        // Same rule as the store's deadline-index walk: the due task with
+        // the earliest deadline inside the horizon.
+        let imminent = order
+            .deadline_cutoff(now)
+            .filter(|_| !has_due_critical(&pending, now))
+            .and_then(|cutoff| {
+                pending
+                    .iter()
+                    .filter_map(|key| {
+                        let (_, run_at, task_id) = decode_pending_key(key)?;
+                        let deadline = tasks[task_id as usize].2?;
+                        (run_at <= now && deadline <= cutoff).then_some((deadline, task_id, *key))
+                    })
+                    .min()
+                    .map(|(_, _, key)| key.to_vec())
+            });
+        let Some(key) = select_pending_key(&pending, now, max_wait_secs, imminent) else {
+            continue;
+        };
+        if let Ok(key) = <[u8; PENDING_KEY_LEN]>::try_from(key.as_slice()) {
+            pending.remove(&key);
+        }
+        if let Some((_, _, task_id)) = decode_pending_key(&key) {
+            claimed.push(task_id);
+        }
+    }
+    claimed
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,6 +284,23 @@ mod tests {
         assert_eq!(task_id, 42);
     }
 
+    #[test]
+    fn test_deadline_key_roundtrip_and_order() {
+        let key = encode_deadline_key(1700000060, 42);
+        assert_eq!(decode_deadline_key(&key), Some((1700000060, 42)));
+        assert!(encode_deadline_key(100, 9) < encode_deadline_key(200, 1));
+        assert!(encode_deadline_key(100, 1) < encode_deadline_key(100, 2));
+    }
+
+    #[test]
+    fn test_claim_order_deadline_cutoff() {
+        assert_eq!(ClaimOrder::default(), ClaimOrder::Priority);
+        assert_eq!(ClaimOrder::Priority.deadline_cutoff(100), None);
+        let order = ClaimOrder::Deadline { horizon_secs: 60 };
+        assert_eq!(order.deadline_cutoff(100), Some(160));
+        assert_eq!(order.deadline_cutoff(u64::MAX), Some(u64::MAX));
+    }
+
     #[test]
     fn test_anti_starvation() {
         assert!(!should_promote_oldest(100, 200, 300)); // waited 100s < 300s threshold
@@ -102,5 +312,78 @@ mod tests {
     fn test_decode_invalid_length() {
         assert!(decode_pending_key(&[0u8; 5]).is_none());
         assert!(decode_running_key(&[0u8; 5]).is_none());
+        assert!(decode_deadline_key(&[0u8; 5]).is_none());
+    }
+
+    #[test]
+    fn test_simulate_claims_strict_priority() {
+        let tasks = [
+            (TaskPriority::Low, 100, None),
+            (TaskPriority::Critical, 150, None),
+            (TaskPriority::High, 120, None),
+            (TaskPriority::High, 110, None),
+            (TaskPriority::Normal, 900, None), // not due until t=900
+        ];
+        // Strict priority: bands in order, earliest first within a band.
+        assert_eq!(
+            simulate_claims(&tasks, 200, 0, 0, ClaimOrder::Priority, 10),
+            vec![1, 3, 2, 0]
+        );
+        // Advancing the clock lets the future task in on the last step.
+        assert_eq!(
+            simulate_claims(&tasks, 200, 200, 0, ClaimOrder::Priority, 10),
+            vec![1, 3, 2, 0, 4]
+        );
+        assert_eq!(
+            simulate_claims(&tasks, 200, 0, 0, ClaimOrder::Priority, 2),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn test_simulate_claims_anti_starvation() {
+        let tasks = [
+            (TaskPriority::High, 990, None),
+            (TaskPriority::BestEffort, 100, None), // waited 900s
+            (TaskPriority::Normal, 500, None),     // waited 500s
+            (TaskPriority::High, 995, None),
+        ];
+        // Starved tasks jump ahead, lowest band first.
+        assert_eq!(
+            simulate_claims(&tasks, 1000, 0, 300, ClaimOrder::Priority, 4),
+            vec![1, 2, 0, 3]
+        );
+        // A due Critical task still preempts promotion.
+        let mut with_critical = tasks.to_vec();
+        with_critical.push((TaskPriority::Critical, 999, None));
+        assert_eq!(
+            simulate_claims(&with_critical, 1000, 0, 300, ClaimOrder::Priority, 5),
+            vec![4, 1, 2, 0, 3]
+        );
+    }
+
+    #[test]
+    fn test_simulate_claims_deadline_order() {
+        let tasks = [
+            (TaskPriority::High, 100, Some(5000)), // deadline outside the horizon
+            (TaskPriority::Low, 100, Some(1030)),
+            (TaskPriority::Normal, 100, Some(1010)),
+            (TaskPriority::High, 2000, Some(1005)), // not due
+        ];
+        let order = ClaimOrder::Deadline { horizon_secs: 60 };
+        // Imminent deadlines first, earliest first; then priority order.
+        assert_eq!(simulate_claims(&tasks, 1000, 0, 0, order, 4), vec![2, 1, 0]);
+        // Under priority order the same set ignores deadlines.
+        assert_eq!(
+            simulate_claims(&tasks, 1000, 0, 0, ClaimOrder::Priority, 4),
+            vec![0, 2, 1]
+        );
+        // A due Critical task still preempts.
+        let mut with_critical = tasks.to_vec();
+        with_critical.push((TaskPriority::Critical, 999, None));
+        assert_eq!(
+            simulate_claims(&with_critical, 1000, 0, 0, order, 5),
+            vec![4, 2, 1, 0]
+        );
     }
 }
@@ -1,16 +1,27 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
 
 use fjall::{Database, Keyspace, KeyspaceCreateOptions, PersistMode};
 
 use super::error::{Result, TaskError};
+use super::latency::unix_millis;
 use super::priority::{
-    decode_pending_key, decode_running_key, encode_pending_key, encode_running_key,
+    decode_deadline_key, decode_pending_key, decode_running_key, encode_deadline_key,
+    encode_pending_key, encode_running_key, has_due_critical, select_pending_key, ClaimOrder,
+    PendingBands,
 };
-use super::task::{TaskPriority, TaskRecord, TaskStatus};
+use super::task::{CompactionStats, TaskPriority, TaskRecord, TaskStatus};
 
-/// Fjall-backed task storage with 5 keyspaces (column families).
+/// Number of `TaskPriority` levels.
+const PRIORITY_LEVELS: usize = TaskPriority::BestEffort as usize + 1;
+
+/// A key to write (`Some(value)`) or remove (`None`) when rebuilding an
+/// index; see `TaskStore::index_diff`.
+type IndexChange = (Vec<u8>, Option<Vec<u8>>);
+
+/// Fjall-backed task storage with 7 keyspaces (column families).
 ///
 /// Keyspaces:
 /// - `tasks`:            task_id (u64 BE)          -> TaskRecord (bincode)
@@ -18,6 +29,8 @@ use super::task::{TaskPriority, TaskRecord, TaskStatus};
 /// - `running_idx`:      [lease_expires][task_id]   -> () (empty value)
 /// - `running_task_key`: task_id (u64 BE)           -> running_idx key bytes
 /// - `dead_letter`:      task_id (u64 BE)           -> TaskRecord (bincode)
+/// - `deadline_idx`:     [deadline][task_id]        -> () (pending tasks with a deadline)
+/// - `task_deadlines`:   task_id (u64 BE)           -> deadline (u64 BE)
 pub struct TaskStore {
     db: Database,
     tasks: Keyspace,
@@ -26,17 +39,37 @@ pub struct TaskStore {
     /// Reverse lookup: task_id -> running_idx key, for O(1) removal.
     running_task_key: Keyspace,
     dead_letter: Keyspace,
+    deadline_idx: Keyspace,
+    /// Deadlines live here rather than in `TaskRecord`'s encoding, so
+    /// records written before deadlines existed still decode.
+    task_deadlines: Keyspace,
     id_counter: AtomicU64,
     /// Prevents concurrent claim_next races (Issue #3029 / Bug 2).
     claim_lock: Mutex<()>,
+    /// Held shared by every state transition other than claims (which
+    /// `claim_lock` covers) and exclusively while `compact` rebuilds the
+    /// indexes, so no transition lands mid-rebuild.
+    transition_lock: RwLock<()>,
     // Atomic status counters — O(1) stats instead of full-table scans.
     pending_count: AtomicU64,
+    /// Pending tasks per priority, indexed by `TaskPriority as usize`.
+    pending_by_priority: [AtomicU64; PRIORITY_LEVELS],
     running_count: AtomicU64,
     completed_count: AtomicU64,
     cancelled_count: AtomicU64,
     dead_letter_count: AtomicU64,
 }
 
+impl PendingBands for TaskStore {
+    fn band_head(&self, priority: u8) -> Option<Vec<u8>> {
+        self.pending_idx
+            .prefix([priority])
+            .next()
+            .and_then(|guard| guard.into_inner().ok())
+            .map(|(key, _)| key.as_ref().to_vec())
+    }
+}
+
 impl TaskStore {
     /// Open or create the task store at the given path.
     pub fn open(path: &str) -> Result<Self> {
@@ -47,6 +80,8 @@ impl TaskStore {
         let running_idx = db.keyspace("running_idx", KeyspaceCreateOptions::default)?;
         let running_task_key = db.keyspace("running_task_key", KeyspaceCreateOptions::default)?;
         let dead_letter = db.keyspace("dead_letter", KeyspaceCreateOptions::default)?;
+        let deadline_idx = db.keyspace("deadline_idx", KeyspaceCreateOptions::default)?;
+        let task_deadlines = db.keyspace("task_deadlines", KeyspaceCreateOptions::default)?;
 
         // Initialize counter from existing max task_id
         let max_id = Self::find_max_task_id(&tasks);
@@ -55,6 +90,8 @@ impl TaskStore {
         let (pending, running, completed, cancelled, dead_letter_n) =
             Self::count_all_statuses(&tasks);
 
+        let pending_by_priority = Self::count_pending_by_priority(&pending_idx);
+
         // Rebuild running_task_key reverse-lookup index from running_idx.
         // This is necessary after restart/upgrade since running_task_key is
         // an auxiliary index that may not have existed before this version.
@@ -67,9 +104,13 @@ impl TaskStore {
             running_idx,
             running_task_key,
             dead_letter,
+            deadline_idx,
+            task_deadlines,
             id_counter: AtomicU64::new(max_id + 1),
             claim_lock: Mutex::new(()),
+            transition_lock: RwLock::new(()),
             pending_count: AtomicU64::new(pending),
+            pending_by_priority: pending_by_priority.map(AtomicU64::new),
             running_count: AtomicU64::new(running),
             completed_count: AtomicU64::new(completed),
             cancelled_count: AtomicU64::new(cancelled),
@@ -106,7 +147,7 @@ impl TaskStore {
 
         for guard in tasks.iter() {
             if let Ok((_, value)) = guard.into_inner() {
-                if let Ok(record) = bincode::deserialize::<TaskRecord>(value.as_ref()) {
+                if let Ok(record) = TaskRecord::decode(value.as_ref()) {
                     match record.status {
                         TaskStatus::Pending => pending += 1,
                         TaskStatus::Running => running += 1,
@@ -122,6 +163,22 @@ impl TaskStore {
         (pending, running, completed, cancelled, dead_letter)
     }
 
+    /// Count each priority band of the pending index. Used at startup and
+    /// after rebuilding the indexes.
+    fn count_pending_by_priority(pending_idx: &Keyspace) -> [u64; PRIORITY_LEVELS] {
+        std::array::from_fn(|priority| pending_idx.prefix([priority as u8]).count() as u64)
+    }
+
+    fn pending_added(&self, priority: TaskPriority) {
+        self.pending_count.fetch_add(1, Ordering::Relaxed);
+        self.pending_by_priority[priority as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn pending_removed(&self, priority: TaskPriority) {
+        self.pending_count.fetch_sub(1, Ordering::Relaxed);
+        self.pending_by_priority[priority as usize].fetch_sub(1, Ordering::Relaxed);
+    }
+
     /// Rebuild the running_task_key reverse-lookup index by scanning running_idx.
     /// Ensures that after restart or upgrade from a version without this index,
     /// all running tasks have their reverse-lookup entry populated.
@@ -144,6 +201,7 @@ impl TaskStore {
 
     /// Insert a new task. Atomically writes to both `tasks` and `pending_idx`.
     pub fn insert_task(&self, task: &TaskRecord) -> Result<()> {
+        let _transition = self.lock_transition()?;
         let task_key = task.task_id.to_be_bytes();
         let task_value = bincode::serialize(task)?;
         let pending_key = encode_pending_key(task.priority, task.run_at, task.task_id);
@@ -151,8 +209,16 @@ impl TaskStore {
         let mut batch = self.db.batch();
         batch.insert(&self.tasks, task_key, task_value);
         batch.insert(&self.pending_idx, pending_key, vec![]);
+        if let Some(deadline) = task.deadline {
+            batch.insert(
+                &self.deadline_idx,
+                encode_deadline_key(deadline, task.task_id),
+                vec![],
+            );
+            batch.insert(&self.task_deadlines, task_key, deadline.to_be_bytes());
+        }
         batch.commit()?;
-        self.pending_count.fetch_add(1, Ordering::Relaxed);
+        self.pending_added(task.priority);
         Ok(())
     }
 
@@ -160,16 +226,25 @@ impl TaskStore {
     pub fn get_task(&self, task_id: u64) -> Result<Option<TaskRecord>> {
         let key = task_id.to_be_bytes();
         match self.tasks.get(key)? {
-            Some(bytes) => {
-                let record: TaskRecord = bincode::deserialize(bytes.as_ref())?;
-                Ok(Some(record))
-            }
+            Some(bytes) => Ok(Some(self.decode_task(bytes.as_ref())?)),
             None => Ok(None),
         }
     }
 
+    /// Decode a stored task record and fill in its deadline.
+    fn decode_task(&self, bytes: &[u8]) -> Result<TaskRecord> {
+        let mut record = TaskRecord::decode(bytes)?;
+        record.deadline = self
+            .task_deadlines
+            .get(record.task_id.to_be_bytes())?
+            .and_then(|value| <[u8; 8]>::try_from(value.as_ref()).ok())
+            .map(u64::from_be_bytes);
+        Ok(record)
+    }
+
     /// Update a task in the primary store.
     pub fn update_task(&self, task: &TaskRecord) -> Result<()> {
+        let _transition = self.lock_transition()?;
         let key = task.task_id.to_be_bytes();
         let value = bincode::serialize(task)?;
         self.tasks.insert(key, value)?;
@@ -185,68 +260,52 @@ impl TaskStore {
         }
     }
 
-    /// Return the first pending index key for a given priority, if any.
-    fn first_pending_key_for_priority(&self, priority: u8) -> Option<Vec<u8>> {
-        self.pending_idx
-            .prefix([priority])
-            .next()
-            .and_then(|guard| guard.into_inner().ok())
-            .map(|(key, _)| key.as_ref().to_vec())
-    }
-
-    /// Whether there is a due Critical task that must not be preempted.
+    /// Select the due task with the earliest deadline at or before
+    /// `cutoff`, as its pending key.
     ///
-    /// If the first key is corrupt, conservatively treat it as due so we avoid
-    /// promoting lower-priority work before self-healing the critical band.
-    fn has_due_critical(&self, now: u64) -> bool {
-        let Some(key_bytes) = self.first_pending_key_for_priority(TaskPriority::Critical as u8)
-        else {
-            return false;
-        };
-        match decode_pending_key(&key_bytes) {
-            Some((_, run_at, _)) => run_at <= now,
-            None => true,
+    /// Walks `deadline_idx` in order and stops at the first due task, so
+    /// it costs O(imminent tasks that are not yet due). Entries whose
+    /// task is gone, no longer pending or carries another deadline are
+    /// removed once the walk ends. Disabled while any due Critical task
+    /// exists.
+    fn select_deadline_pending_key(&self, now: u64, cutoff: u64) -> Result<Option<Vec<u8>>> {
+        if has_due_critical(self, now) {
+            return Ok(None);
         }
-    }
 
-    /// Select the highest-priority due key in O(priority bands), or return a
-    /// corrupt key candidate so the caller can self-heal the index.
-    fn first_due_or_corrupt_pending_key(&self, now: u64) -> Option<Vec<u8>> {
-        for priority in TaskPriority::Critical as u8..=TaskPriority::BestEffort as u8 {
-            let Some(key_bytes) = self.first_pending_key_for_priority(priority) else {
-                continue;
-            };
-            match decode_pending_key(&key_bytes) {
-                Some((_, run_at, _)) if run_at <= now => return Some(key_bytes),
-                Some(_) => continue, // earliest key in this priority is future-scheduled
-                None => return Some(key_bytes),
-            }
-        }
-        None
-    }
-
-    /// Select a starving non-critical task for anti-starvation promotion.
-    ///
-    /// Promotion is disabled while any due Critical task exists.
-    fn select_starved_pending_key(&self, now: u64, max_wait_secs: u64) -> Option<Vec<u8>> {
-        if max_wait_secs == 0 || self.has_due_critical(now) {
-            return None;
-        }
-
-        // Check non-Critical priority bands from lowest (BestEffort=4)
-        // to highest (High=1). Promote the oldest starving task found.
-        for priority in (TaskPriority::High as u8..=TaskPriority::BestEffort as u8).rev() {
-            let Some(key_bytes) = self.first_pending_key_for_priority(priority) else {
+        let upper_bound = encode_deadline_key(cutoff, u64::MAX);
+        let mut stale = Vec::new();
+        let mut selected = None;
+        for guard in self.deadline_idx.range(..=upper_bound) {
+            let (key, _) = guard
+                .into_inner()
+                .map_err(|e| TaskError::Storage(e.to_string()))?;
+            let Some((deadline, task_id)) = decode_deadline_key(key.as_ref()) else {
+                stale.push(key.as_ref().to_vec());
                 continue;
             };
-            if let Some((_, run_at, _)) = decode_pending_key(&key_bytes) {
-                if super::priority::should_promote_oldest(run_at, now, max_wait_secs) {
-                    return Some(key_bytes);
+            let task = match self.get_task(task_id)? {
+                Some(task)
+                    if task.status == TaskStatus::Pending && task.deadline == Some(deadline) =>
+                {
+                    task
+                }
+                _ => {
+                    stale.push(key.as_ref().to_vec());
+                    continue;
                 }
+            };
+            if task.run_at > now {
+                continue;
             }
+            let pending_key = encode_pending_key(task.priority, task.run_at, task_id);
+            selected = Some(pending_key.to_vec());
+            break;
         }
-
-        None
+        for key_bytes in stale {
+            self.deadline_idx.remove(key_bytes)?;
+        }
+        Ok(selected)
     }
 
     /// Claim the next pending task. Atomically moves from pending_idx to running_idx.
@@ -261,17 +320,93 @@ impl TaskStore {
         now: u64,
         max_wait_secs: u64,
     ) -> Result<Option<TaskRecord>> {
-        let _guard = self
-            .claim_lock
+        self.claim_next_ordered(
+            worker_id,
+            lease_secs,
+            now,
+            max_wait_secs,
+            ClaimOrder::Priority,
+        )
+    }
+
+    /// [`Self::claim_next`] under `order`: with [`ClaimOrder::Deadline`],
+    /// a due task with an imminent deadline is claimed before priority
+    /// and anti-starvation are considered.
+    pub fn claim_next_ordered(
+        &self,
+        worker_id: &str,
+        lease_secs: u32,
+        now: u64,
+        max_wait_secs: u64,
+        order: ClaimOrder,
+    ) -> Result<Option<TaskRecord>> {
+        let _guard = self.lock_claim()?;
+        match self.select_next(now, max_wait_secs, order)? {
+            Some((key_bytes, task)) => self
+                .mark_claimed(&key_bytes, task, worker_id, lease_secs, now)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The task `claim_next` would claim at `now`, without claiming it.
+    ///
+    /// Leaves the task's status and lease untouched. Stale index entries
+    /// found on the way are repaired exactly as `claim_next` would.
+    pub fn peek_next(&self, now: u64, max_wait_secs: u64) -> Result<Option<TaskRecord>> {
+        self.peek_next_ordered(now, max_wait_secs, ClaimOrder::Priority)
+    }
+
+    /// The task `claim_next_ordered` would claim at `now` under `order`.
+    pub fn peek_next_ordered(
+        &self,
+        now: u64,
+        max_wait_secs: u64,
+        order: ClaimOrder,
+    ) -> Result<Option<TaskRecord>> {
+        let _guard = self.lock_claim()?;
+        Ok(self
+            .select_next(now, max_wait_secs, order)?
+            .map(|(_, task)| task))
+    }
+
+    /// The task `claim_next_of_type` would claim at `now`, without claiming it.
+    pub fn peek_next_of_type(&self, task_type: &str, now: u64) -> Result<Option<TaskRecord>> {
+        let _guard = self.lock_claim()?;
+        Ok(self
+            .select_next_of_type(task_type, now)?
+            .map(|(_, task)| task))
+    }
+
+    fn lock_claim(&self) -> Result<MutexGuard<'_, ()>> {
+        self.claim_lock
             .lock()
-            .map_err(|e| TaskError::Storage(format!("claim lock poisoned: {e}")))?;
+            .map_err(|e| TaskError::Storage(format!("claim lock poisoned: {e}")))
+    }
 
+    fn lock_transition(&self) -> Result<RwLockReadGuard<'_, ()>> {
+        self.transition_lock
+            .read()
+            .map_err(|e| TaskError::Storage(format!("transition lock poisoned: {e}")))
+    }
+
+    /// Pick the next claimable task (with its pending key) under the
+    /// deadline, priority and anti-starvation rules, self-healing stale
+    /// index entries. Caller holds `claim_lock`.
+    fn select_next(
+        &self,
+        now: u64,
+        max_wait_secs: u64,
+        order: ClaimOrder,
+    ) -> Result<Option<(Vec<u8>, TaskRecord)>> {
         loop {
+            let imminent = match order.deadline_cutoff(now) {
+                Some(cutoff) => self.select_deadline_pending_key(now, cutoff)?,
+                None => None,
+            };
             // Normal path: select the first due task by checking the head entry
             // of each priority band (O(priority bands)).
-            let target_key = self
-                .select_starved_pending_key(now, max_wait_secs)
-                .or_else(|| self.first_due_or_corrupt_pending_key(now));
+            let target_key = select_pending_key(self, now, max_wait_secs, imminent);
 
             let Some(key_bytes) = target_key else {
                 return Ok(None);
@@ -284,7 +419,7 @@ impl TaskStore {
             };
 
             // Load the task record
-            let Some(mut task) = self.get_task(task_id)? else {
+            let Some(task) = self.get_task(task_id)? else {
                 // Stale index entry — remove and continue scanning.
                 self.pending_idx.remove(&key_bytes)?;
                 continue;
@@ -308,29 +443,119 @@ impl TaskStore {
                 continue;
             }
 
-            // Update the task record
-            let lease_expires = now + lease_secs as u64;
-            task.status = TaskStatus::Running;
-            task.claimed_at = Some(now);
-            task.claimed_by = Some(worker_id.to_string());
-            task.lease_secs = lease_secs;
-            task.attempt += 1;
+            return Ok(Some((key_bytes, task)));
+        }
+    }
 
-            let task_value = bincode::serialize(&task)?;
-            let running_key = encode_running_key(lease_expires, task_id);
+    /// Claim the next due pending task whose `task_type` is `task_type`,
+    /// skipping (and leaving pending) every task of another type.
+    ///
+    /// Walks each priority band in key order, so it costs O(pending tasks
+    /// of other types ahead of the match). Anti-starvation promotion does
+    /// not apply: within the requested type, strict priority order wins.
+    /// Shares `claim_next`'s lock and self-healing of stale index entries.
+    pub fn claim_next_of_type(
+        &self,
+        worker_id: &str,
+        task_type: &str,
+        lease_secs: u32,
+        now: u64,
+    ) -> Result<Option<TaskRecord>> {
+        let _guard = self.lock_claim()?;
+        match self.select_next_of_type(task_type, now)? {
+            Some((key_bytes, task)) => self
+                .mark_claimed(&key_bytes, task, worker_id, lease_secs, now)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
 
-            // Atomic: remove from pending, add to running + reverse lookup, update task
-            let mut batch = self.db.batch();
-            batch.remove(&self.pending_idx, &key_bytes);
-            batch.insert(&self.running_idx, running_key, vec![]);
-            batch.insert(&self.running_task_key, task_id.to_be_bytes(), running_key);
-            batch.insert(&self.tasks, task_id.to_be_bytes(), task_value);
-            batch.commit()?;
-            self.pending_count.fetch_sub(1, Ordering::Relaxed);
-            self.running_count.fetch_add(1, Ordering::Relaxed);
+    /// `select_next` restricted to `task_type`, without anti-starvation.
+    /// Caller holds `claim_lock`.
+    fn select_next_of_type(
+        &self,
+        task_type: &str,
+        now: u64,
+    ) -> Result<Option<(Vec<u8>, TaskRecord)>> {
+        for priority in TaskPriority::Critical as u8..=TaskPriority::BestEffort as u8 {
+            let keys: Vec<Vec<u8>> = self
+                .pending_idx
+                .prefix([priority])
+                .filter_map(|guard| guard.into_inner().ok())
+                .map(|(key, _)| key.as_ref().to_vec())
+                .collect();
+
+            for key_bytes in keys {
+                let Some((_, run_at, task_id)) = decode_pending_key(&key_bytes) else {
+                    self.pending_idx.remove(&key_bytes)?;
+                    continue;
+                };
+                // Keys within a band are ordered by run_at: the rest are future.
+                if run_at > now {
+                    break;
+                }
+                let Some(task) = self.get_task(task_id)? else {
+                    self.pending_idx.remove(&key_bytes)?;
+                    continue;
+                };
+                if task.status != TaskStatus::Pending {
+                    self.pending_idx.remove(&key_bytes)?;
+                    continue;
+                }
+                if task.task_type != task_type {
+                    continue;
+                }
+                if task.run_at > now {
+                    let corrected_key = encode_pending_key(task.priority, task.run_at, task_id);
+                    let mut repair = self.db.batch();
+                    repair.remove(&self.pending_idx, &key_bytes);
+                    repair.insert(&self.pending_idx, corrected_key, vec![]);
+                    repair.commit()?;
+                    continue;
+                }
+
+                return Ok(Some((key_bytes, task)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Move a pending task (at `pending_key`) to running, owned by
+    /// `worker_id` for `lease_secs`. Caller holds `claim_lock`.
+    fn mark_claimed(
+        &self,
+        pending_key: &[u8],
+        mut task: TaskRecord,
+        worker_id: &str,
+        lease_secs: u32,
+        now: u64,
+    ) -> Result<TaskRecord> {
+        let task_id = task.task_id;
+        let lease_expires = now + lease_secs as u64;
+        task.status = TaskStatus::Running;
+        task.claimed_at = Some(now);
+        task.claimed_at_ms = Some(unix_millis());
+        task.claimed_by = Some(worker_id.to_string());
+        task.lease_secs = lease_secs;
+        task.attempt += 1;
+
+        let task_value = bincode::serialize(&task)?;
+        let running_key = encode_running_key(lease_expires, task_id);
 
-            return Ok(Some(task));
+        // Atomic: remove from pending, add to running + reverse lookup, update task
+        let mut batch = self.db.batch();
+        batch.remove(&self.pending_idx, pending_key);
+        if let Some(deadline) = task.deadline {
+            batch.remove(&self.deadline_idx, encode_deadline_key(deadline, task_id));
         }
+        batch.insert(&self.running_idx, running_key, vec![]);
+        batch.insert(&self.running_task_key, task_id.to_be_bytes(), running_key);
+        batch.insert(&self.tasks, task_id.to_be_bytes(), task_value);
+        batch.commit()?;
+        self.pending_removed(task.priority);
+        self.running_count.fetch_add(1, Ordering::Relaxed);
+
+        Ok(task)
     }
 
     /// Move a running task to completed state. Atomically removes from running_idx
@@ -345,6 +570,7 @@ impl TaskStore {
         now: u64,
         worker_id: &str,
     ) -> Result<TaskRecord> {
+        let _transition = self.lock_transition()?;
         let mut task = self
             .get_task(task_id)?
             .ok_or(TaskError::NotFound(task_id))?;
@@ -398,6 +624,7 @@ impl TaskStore {
         now: u64,
         worker_id: &str,
     ) -> Result<(TaskRecord, bool)> {
+        let _transition = self.lock_transition()?;
         let mut task = self
             .get_task(task_id)?
             .ok_or(TaskError::NotFound(task_id))?;
@@ -441,11 +668,19 @@ impl TaskStore {
             task.status = TaskStatus::Pending;
             task.run_at = now + delay;
             task.claimed_at = None;
+            task.claimed_at_ms = None;
             task.claimed_by = None;
             let task_value = bincode::serialize(&task)?;
             let pending_key = encode_pending_key(task.priority, task.run_at, task_id);
             batch.insert(&self.tasks, task_id.to_be_bytes(), task_value);
             batch.insert(&self.pending_idx, pending_key, vec![]);
+            if let Some(deadline) = task.deadline {
+                batch.insert(
+                    &self.deadline_idx,
+                    encode_deadline_key(deadline, task_id),
+                    vec![],
+                );
+            }
         }
 
         batch.commit()?;
@@ -454,7 +689,7 @@ impl TaskStore {
         if dead_lettered {
             self.dead_letter_count.fetch_add(1, Ordering::Relaxed);
         } else {
-            self.pending_count.fetch_add(1, Ordering::Relaxed);
+            self.pending_added(task.priority);
         }
 
         Ok((task, dead_lettered))
@@ -464,6 +699,7 @@ impl TaskStore {
     /// All index updates and task writes are in a single atomic batch
     /// (Issue #3029 / Bug 3).
     pub fn cancel_task(&self, task_id: u64, now: u64) -> Result<TaskRecord> {
+        let _transition = self.lock_transition()?;
         let mut task = self
             .get_task(task_id)?
             .ok_or(TaskError::NotFound(task_id))?;
@@ -476,6 +712,9 @@ impl TaskStore {
             TaskStatus::Pending => {
                 let pending_key = encode_pending_key(task.priority, task.run_at, task_id);
                 batch.remove(&self.pending_idx, pending_key);
+                if let Some(deadline) = task.deadline {
+                    batch.remove(&self.deadline_idx, encode_deadline_key(deadline, task_id));
+                }
             }
             TaskStatus::Running => {
                 if let Some(rk) = self.find_running_key(task_id)? {
@@ -499,9 +738,7 @@ impl TaskStore {
         batch.commit()?;
 
         match prev_status {
-            TaskStatus::Pending => {
-                self.pending_count.fetch_sub(1, Ordering::Relaxed);
-            }
+            TaskStatus::Pending => self.pending_removed(task.priority),
             TaskStatus::Running => {
                 self.running_count.fetch_sub(1, Ordering::Relaxed);
             }
@@ -515,6 +752,7 @@ impl TaskStore {
     /// Requeue tasks whose leases have expired. Returns count of requeued tasks.
     /// All mutations are committed in a single batch (Issue #3029 / Issue 15).
     pub fn requeue_abandoned(&self, now: u64) -> Result<u32> {
+        let _transition = self.lock_transition()?;
         let upper_bound = encode_running_key(now, u64::MAX);
 
         // Collect expired entries first (avoid holding iterator across writes)
@@ -541,6 +779,7 @@ impl TaskStore {
 
         let mut batch = self.db.batch();
         let mut requeued = 0u32;
+        let mut requeued_by_priority = [0u64; PRIORITY_LEVELS];
 
         for (running_key, task_id) in &expired {
             let Some(mut task) = self.get_task(*task_id)? else {
@@ -559,6 +798,7 @@ impl TaskStore {
             // Re-queue as pending
             task.status = TaskStatus::Pending;
             task.claimed_at = None;
+            task.claimed_at_ms = None;
             task.claimed_by = None;
             task.error_message = Some("lease expired (abandoned)".to_string());
 
@@ -568,9 +808,17 @@ impl TaskStore {
             batch.remove(&self.running_idx, running_key);
             batch.remove(&self.running_task_key, task_id.to_be_bytes());
             batch.insert(&self.pending_idx, pending_key, vec![]);
+            if let Some(deadline) = task.deadline {
+                batch.insert(
+                    &self.deadline_idx,
+                    encode_deadline_key(deadline, *task_id),
+                    vec![],
+                );
+            }
             batch.insert(&self.tasks, task_id.to_be_bytes(), task_value);
 
             requeued += 1;
+            requeued_by_priority[task.priority as usize] += 1;
         }
 
         batch.commit()?;
@@ -580,6 +828,9 @@ impl TaskStore {
             .fetch_sub(requeued as u64, Ordering::Relaxed);
         self.pending_count
             .fetch_add(requeued as u64, Ordering::Relaxed);
+        for (counter, n) in self.pending_by_priority.iter().zip(requeued_by_priority) {
+            counter.fetch_add(n, Ordering::Relaxed);
+        }
 
         Ok(requeued)
     }
@@ -587,6 +838,7 @@ impl TaskStore {
     /// Remove completed/failed tasks older than max_age_secs. Returns count of cleaned tasks.
     /// All removals are committed in a single batch (Issue #3029 / Issue 15).
     pub fn cleanup(&self, max_age_secs: u64, now: u64) -> Result<u32> {
+        let _transition = self.lock_transition()?;
         let cutoff = now.saturating_sub(max_age_secs);
 
         // Scan all tasks and collect IDs of old terminal tasks
@@ -601,7 +853,7 @@ impl TaskStore {
                         return None;
                     }
                 };
-                let record: TaskRecord = match bincode::deserialize(value.as_ref()) {
+                let record = match TaskRecord::decode(value.as_ref()) {
                     Ok(r) => r,
                     Err(e) => {
                         tracing::warn!("skipping task entry: deserialization error: {}", e);
@@ -630,6 +882,7 @@ impl TaskStore {
         let mut batch = self.db.batch();
         for &(task_id, is_dead_letter, _) in &to_remove {
             batch.remove(&self.tasks, task_id.to_be_bytes());
+            batch.remove(&self.task_deadlines, task_id.to_be_bytes());
             if is_dead_letter {
                 batch.remove(&self.dead_letter, task_id.to_be_bytes());
             }
@@ -674,6 +927,12 @@ impl TaskStore {
         Ok(self.pending_count.load(Ordering::Relaxed) as usize)
     }
 
+    /// Count pending tasks of one priority (for per-priority admission
+    /// control). O(1) from a cached atomic counter, like `count_pending`.
+    pub fn count_pending_with_priority(&self, priority: TaskPriority) -> Result<usize> {
+        Ok(self.pending_by_priority[priority as usize].load(Ordering::Relaxed) as usize)
+    }
+
     /// List tasks with optional filters.
     pub fn list_tasks(
         &self,
@@ -689,7 +948,7 @@ impl TaskStore {
             let (_, value) = guard
                 .into_inner()
                 .map_err(|e| TaskError::Storage(e.to_string()))?;
-            let record: TaskRecord = bincode::deserialize(value.as_ref())?;
+            let record = self.decode_task(value.as_ref())?;
 
             // Apply filters
             if let Some(status) = status_filter {
@@ -726,6 +985,7 @@ impl TaskStore {
         new_lease_expires: u64,
         task: &TaskRecord,
     ) -> Result<()> {
+        let _transition = self.lock_transition()?;
         let old_key = self.find_running_key(task_id)?;
 
         let new_running_key = encode_running_key(new_lease_expires, task_id);
@@ -747,6 +1007,177 @@ impl TaskStore {
         Ok(())
     }
 
+    fn keyspaces(&self) -> [&Keyspace; 7] {
+        [
+            &self.tasks,
+            &self.pending_idx,
+            &self.running_idx,
+            &self.running_task_key,
+            &self.dead_letter,
+            &self.deadline_idx,
+            &self.task_deadlines,
+        ]
+    }
+
+    fn disk_space(&self) -> u64 {
+        self.keyspaces().iter().map(|ks| ks.disk_space()).sum()
+    }
+
+    /// Rebuild every secondary index from the `tasks` keyspace, resync the
+    /// status counters, then major-compact all keyspaces to drop the
+    /// tombstones and superseded values left by churn.
+    ///
+    /// Holds `claim_lock` throughout and `transition_lock` exclusively
+    /// for the rebuild, so every transition waits for the rebuild rather
+    /// than having its index entries reverted. Meant for maintenance
+    /// windows, not the hot path.
+    pub fn compact(&self) -> Result<CompactionStats> {
+        let _guard = self.lock_claim()?;
+
+        // Flush memtables first so both sizes cover all data.
+        for keyspace in self.keyspaces() {
+            keyspace.rotate_memtable_and_wait()?;
+        }
+        let bytes_before = self.disk_space();
+
+        let (index_entries_removed, index_entries_added) = {
+            let _transitions = self
+                .transition_lock
+                .write()
+                .map_err(|e| TaskError::Storage(format!("transition lock poisoned: {e}")))?;
+            self.rebuild_indexes()?
+        };
+
+        for keyspace in self.keyspaces() {
+            keyspace.rotate_memtable_and_wait()?;
+            keyspace.major_compact()?;
+        }
+
+        Ok(CompactionStats {
+            bytes_before,
+            bytes_after: self.disk_space(),
+            index_entries_removed,
+            index_entries_added,
+        })
+    }
+
+    /// Bring `pending_idx`, `running_idx`, `running_task_key`,
+    /// `dead_letter` and `deadline_idx` in line with the task records in
+    /// one batch, and reset the status counters. Returns (entries removed,
+    /// entries written).
+    /// Caller holds `claim_lock` and `transition_lock` exclusively.
+    fn rebuild_indexes(&self) -> Result<(u64, u64)> {
+        let mut pending = HashMap::new();
+        let mut running = HashMap::new();
+        let mut running_reverse = HashMap::new();
+        let mut dead_letter = HashMap::new();
+        let mut deadlines = HashMap::new();
+
+        for guard in self.tasks.iter() {
+            let (key, value) = guard
+                .into_inner()
+                .map_err(|e| TaskError::Storage(e.to_string()))?;
+            let (key, value): (&[u8], &[u8]) = (key.as_ref(), value.as_ref());
+            let record = match self.decode_task(value) {
+                Ok(r) => r,
+                Err(e) => {
+                    tracing::warn!("skipping task entry: deserialization error: {}", e);
+                    continue;
+                }
+            };
+            let task_id = record.task_id;
+            match record.status {
+                TaskStatus::Pending => {
+                    let pending_key = encode_pending_key(record.priority, record.run_at, task_id);
+                    pending.insert(pending_key.to_vec(), vec![]);
+                    if let Some(deadline) = record.deadline {
+                        let deadline_key = encode_deadline_key(deadline, task_id);
+                        deadlines.insert(deadline_key.to_vec(), vec![]);
+                    }
+                }
+                TaskStatus::Running => {
+                    // Keep the current lease if the reverse lookup points at
+                    // this task; otherwise recompute it from the claim time.
+                    let running_key = match self.find_running_key(task_id)? {
+                        Some(k) if decode_running_key(&k).map(|(_, id)| id) == Some(task_id) => k,
+                        _ => {
+                            let lease_expires =
+                                record.claimed_at.unwrap_or(0) + record.lease_secs as u64;
+                            encode_running_key(lease_expires, task_id).to_vec()
+                        }
+                    };
+                    running.insert(running_key.clone(), vec![]);
+                    running_reverse.insert(task_id.to_be_bytes().to_vec(), running_key);
+                }
+                TaskStatus::DeadLetter => {
+                    dead_letter.insert(key.to_vec(), value.to_vec());
+                }
+                _ => {}
+            }
+        }
+
+        let mut batch = self.db.batch();
+        let (mut removed, mut added) = (0u64, 0u64);
+        for (keyspace, expected) in [
+            (&self.pending_idx, pending),
+            (&self.running_idx, running),
+            (&self.running_task_key, running_reverse),
+            (&self.dead_letter, dead_letter),
+            (&self.deadline_idx, deadlines),
+        ] {
+            for (key, value) in Self::index_diff(keyspace, expected)? {
+                match value {
+                    Some(value) => {
+                        batch.insert(keyspace, key, value);
+                        added += 1;
+                    }
+                    None => {
+                        batch.remove(keyspace, key);
+                        removed += 1;
+                    }
+                }
+            }
+        }
+        batch.commit()?;
+
+        let (pending_n, running_n, completed, cancelled, dead_letter_n) =
+            Self::count_all_statuses(&self.tasks);
+        self.pending_count.store(pending_n, Ordering::Relaxed);
+        let by_priority = Self::count_pending_by_priority(&self.pending_idx);
+        for (counter, n) in self.pending_by_priority.iter().zip(by_priority) {
+            counter.store(n, Ordering::Relaxed);
+        }
+        self.running_count.store(running_n, Ordering::Relaxed);
+        self.completed_count.store(completed, Ordering::Relaxed);
+        self.cancelled_count.store(cancelled, Ordering::Relaxed);
+        self.dead_letter_count
+            .store(dead_letter_n, Ordering::Relaxed);
+
+        Ok((removed, added))
+    }
+
+    /// Changes that make `keyspace` hold exactly `expected`: `None` removes
+    /// a stale key, `Some` writes a missing or mismatched value.
+    fn index_diff(
+        keyspace: &Keyspace,
+        mut expected: HashMap<Vec<u8>, Vec<u8>>,
+    ) -> Result<Vec<IndexChange>> {
+        let mut changes = Vec::new();
+        for guard in keyspace.iter() {
+            let (key, value) = guard
+                .into_inner()
+                .map_err(|e| TaskError::Storage(e.to_string()))?;
+            let (key, value): (&[u8], &[u8]) = (key.as_ref(), value.as_ref());
+            match expected.remove(key) {
+                Some(want) if want == value => {}
+                Some(want) => changes.push((key.to_vec(), Some(want))),
+                None => changes.push((key.to_vec(), None)),
+            }
+        }
+        changes.extend(expected.into_iter().map(|(key, value)| (key, Some(value))));
+        Ok(changes)
+    }
+
     /// Persist all in-memory data to disk.
     pub fn flush(&self) -> Result<()> {
         self.db.persist(PersistMode::SyncAll)?;
@@ -788,6 +1219,9 @@ mod tests {
             completed_at: None,
             progress_pct: 0,
             progress_message: None,
+            submitted_at_ms: None,
+            claimed_at_ms: None,
+            deadline: None,
         }
     }
 
@@ -845,6 +1279,33 @@ mod tests {
             );
         }
 
+        // Verify each deadline entry belongs to a Pending task with that deadline
+        let mut deadline_ids: HashSet<u64> = HashSet::new();
+        for guard in store.deadline_idx.iter() {
+            if let Ok((key, _)) = guard.into_inner() {
+                let (deadline, task_id) =
+                    decode_deadline_key(key.as_ref()).expect("corrupt deadline key");
+                let task = store
+                    .get_task(task_id)
+                    .unwrap()
+                    .expect("deadline index references nonexistent task");
+                assert_eq!(task.status, TaskStatus::Pending);
+                assert_eq!(task.deadline, Some(deadline));
+                deadline_ids.insert(task_id);
+            }
+        }
+
+        // Verify the per-priority pending counters match the index bands
+        let by_priority = TaskStore::count_pending_by_priority(&store.pending_idx);
+        for (priority, expected) in by_priority.into_iter().enumerate() {
+            assert_eq!(
+                store.pending_by_priority[priority].load(Ordering::Relaxed),
+                expected,
+                "pending counter for priority {} drifted",
+                priority
+            );
+        }
+
         // Verify running_task_key reverse lookup is consistent with running_idx
         for &task_id in &running_ids {
             assert!(
@@ -857,7 +1318,7 @@ mod tests {
         // Verify no Pending tasks are missing from pending_idx
         for guard in store.tasks.iter() {
             if let Ok((_, value)) = guard.into_inner() {
-                if let Ok(record) = bincode::deserialize::<TaskRecord>(value.as_ref()) {
+                if let Ok(record) = store.decode_task(value.as_ref()) {
                     if record.status == TaskStatus::Pending && record.run_at == 0 {
                         assert!(
                             pending_ids.contains(&record.task_id),
@@ -865,6 +1326,13 @@ mod tests {
                             record.task_id
                         );
                     }
+                    if record.status == TaskStatus::Pending && record.deadline.is_some() {
+                        assert!(
+                            deadline_ids.contains(&record.task_id),
+                            "task {} has a deadline but is not in deadline_idx",
+                            record.task_id
+                        );
+                    }
                     if record.status == TaskStatus::Running {
                         assert!(
                             running_ids.contains(&record.task_id),
@@ -946,6 +1414,50 @@ mod tests {
         verify_index_consistency(&store);
     }
 
+    #[test]
+    fn test_claim_next_of_type_skips_other_types() {
+        let (store, _dir) = test_store();
+
+        let critical_other = make_task(&store, "index", TaskPriority::Critical);
+        let wanted_low = make_task(&store, "export", TaskPriority::Low);
+        let wanted_high = make_task(&store, "export", TaskPriority::High);
+        store.insert_task(&critical_other).unwrap();
+        store.insert_task(&wanted_low).unwrap();
+        store.insert_task(&wanted_high).unwrap();
+
+        let claimed = store
+            .claim_next_of_type("w-0", "export", 900, 1700000000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.task_id, wanted_high.task_id);
+        assert_eq!(claimed.lease_secs, 900);
+        assert_eq!(claimed.claimed_by.as_deref(), Some("w-0"));
+
+        let claimed = store
+            .claim_next_of_type("w-0", "export", 900, 1700000000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.task_id, wanted_low.task_id);
+
+        assert!(store
+            .claim_next_of_type("w-0", "export", 900, 1700000000)
+            .unwrap()
+            .is_none());
+        assert!(store
+            .claim_next_of_type("w-0", "missing", 900, 1700000000)
+            .unwrap()
+            .is_none());
+
+        // The other type was left pending and is still claimable.
+        let other = store
+            .claim_next("w-1", 300, 1700000000, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(other.task_id, critical_other.task_id);
+
+        verify_index_consistency(&store);
+    }
+
     #[test]
     fn test_complete_lifecycle() {
         let (store, _dir) = test_store();
@@ -1512,4 +2024,219 @@ mod tests {
 
         verify_index_consistency(&store);
     }
+
+    #[test]
+    fn test_compact_reclaims_space_and_rebuilds_indexes() {
+        let (store, _dir) = test_store();
+        let now = 1_700_000_000u64;
+
+        // Churn: tasks with bulky params that are submitted, run and cleaned up.
+        for _ in 0..500 {
+            let mut task = make_task(&store, "churn", TaskPriority::Normal);
+            task.params = vec![7; 4096];
+            store.insert_task(&task).unwrap();
+            let claimed = store.claim_next("w-0", 300, now, 0).unwrap().unwrap();
+            store
+                .complete_task(claimed.task_id, b"done", now, "w-0")
+                .unwrap();
+        }
+        assert_eq!(store.cleanup(0, now + 10).unwrap(), 500);
+
+        let running = make_task(&store, "type_b", TaskPriority::High);
+        store.insert_task(&running).unwrap();
+        store.claim_next("w-0", 300, now, 0).unwrap().unwrap();
+        let pending = make_task(&store, "type_a", TaskPriority::Normal);
+        store.insert_task(&pending).unwrap();
+
+        // Damage the indexes: a dangling pending entry and a lost reverse lookup.
+        let dangling = encode_pending_key(TaskPriority::Low, 0, u64::MAX - 1);
+        store.pending_idx.insert(dangling, vec![]).unwrap();
+        store
+            .running_task_key
+            .remove(running.task_id.to_be_bytes())
+            .unwrap();
+        store.flush().unwrap();
+
+        let stats = store.compact().unwrap();
+        assert!(
+            stats.bytes_after < stats.bytes_before,
+            "compaction did not shrink the store: {stats:?}"
+        );
+        assert_eq!(
+            stats.reclaimed_bytes(),
+            stats.bytes_before - stats.bytes_after
+        );
+        assert_eq!(stats.index_entries_removed, 1);
+        assert_eq!(stats.index_entries_added, 1);
+        verify_index_consistency(&store);
+
+        let all = store.list_tasks(None, None, 100, 0).unwrap();
+        let ids: Vec<u64> = all.iter().map(|t| t.task_id).collect();
+        assert_eq!(ids, vec![running.task_id, pending.task_id]);
+        let pending_only = store
+            .list_tasks(Some(TaskStatus::Pending), None, 100, 0)
+            .unwrap();
+        assert_eq!(pending_only.len(), 1);
+        assert_eq!(pending_only[0].task_type, "type_a");
+        let stats = store.count_by_status().unwrap();
+        assert_eq!((stats.pending, stats.running, stats.completed), (1, 1, 0));
+    }
+
+    /// Transitions racing a compaction wait for the rebuild instead of
+    /// having their index entries and counter updates reverted.
+    #[test]
+    fn test_compact_does_not_revert_concurrent_transitions() {
+        let dir = TempDir::new().unwrap();
+        let store = Arc::new(TaskStore::open(dir.path().to_str().unwrap()).unwrap());
+        let now = 1_700_000_000u64;
+        let mut claimed = Vec::new();
+        for i in 0..200 {
+            let task = make_task(&store, &format!("task-{i}"), TaskPriority::Normal);
+            store.insert_task(&task).unwrap();
+            if i % 2 == 0 {
+                claimed.push(store.claim_next("w-0", 300, now, 0).unwrap().unwrap());
+            }
+        }
+
+        let worker = {
+            let store = Arc::clone(&store);
+            std::thread::spawn(move || {
+                for task in claimed {
+                    store
+                        .complete_task(task.task_id, b"done", now, "w-0")
+                        .unwrap();
+                    let extra = make_task(&store, "extra", TaskPriority::High);
+                    store.insert_task(&extra).unwrap();
+                    store.cancel_task(extra.task_id, now).unwrap();
+                }
+            })
+        };
+        for _ in 0..5 {
+            store.compact().unwrap();
+        }
+        worker.join().unwrap();
+
+        verify_index_consistency(&store);
+        let stats = store.count_by_status().unwrap();
+        assert_eq!(
+            (
+                stats.pending,
+                stats.running,
+                stats.completed,
+                stats.cancelled
+            ),
+            (100, 0, 100, 100)
+        );
+    }
+
+    #[test]
+    fn test_deadline_order_claims_imminent_task_first() {
+        let (store, _dir) = test_store();
+        let now = 1700000000u64;
+        let order = ClaimOrder::Deadline { horizon_secs: 60 };
+
+        let mut distant = make_task(&store, "distant_high", TaskPriority::High);
+        distant.deadline = Some(now + 3600);
+        store.insert_task(&distant).unwrap();
+        let mut imminent = make_task(&store, "imminent_normal", TaskPriority::Normal);
+        imminent.deadline = Some(now + 1);
+        store.insert_task(&imminent).unwrap();
+        let mut not_due = make_task(&store, "not_due_low", TaskPriority::Low);
+        not_due.run_at = now + 100;
+        not_due.deadline = Some(now + 5);
+        store.insert_task(&not_due).unwrap();
+        let plain = make_task(&store, "plain_low", TaskPriority::Low);
+        store.insert_task(&plain).unwrap();
+        verify_index_consistency(&store);
+
+        // Strict priority ignores deadlines.
+        let head = store.peek_next(now, 0).unwrap().unwrap();
+        assert_eq!(head.task_type, "distant_high");
+
+        // Deadline order: the imminent Normal task beats the High one; the
+        // distant deadline is outside the horizon, so priority decides next.
+        let claimed: Vec<String> = (0..3)
+            .map(|_| {
+                store
+                    .claim_next_ordered("w-0", 300, now, 0, order)
+                    .unwrap()
+                    .unwrap()
+                    .task_type
+            })
+            .collect();
+        assert_eq!(claimed, ["imminent_normal", "distant_high", "plain_low"]);
+        verify_index_consistency(&store);
+
+        // An imminent deadline does not make a future task due.
+        assert!(store
+            .claim_next_ordered("w-0", 300, now, 0, order)
+            .unwrap()
+            .is_none());
+        let late = store
+            .claim_next_ordered("w-0", 300, now + 100, 0, order)
+            .unwrap()
+            .unwrap();
+        assert_eq!(late.task_type, "not_due_low");
+        verify_index_consistency(&store);
+    }
+
+    #[test]
+    fn test_deadline_order_does_not_preempt_critical() {
+        let (store, _dir) = test_store();
+        let now = 1700000000u64;
+        let order = ClaimOrder::Deadline { horizon_secs: 60 };
+
+        let mut imminent = make_task(&store, "imminent", TaskPriority::BestEffort);
+        imminent.deadline = Some(now.saturating_sub(10)); // already overdue
+        store.insert_task(&imminent).unwrap();
+        let critical = make_task(&store, "critical", TaskPriority::Critical);
+        store.insert_task(&critical).unwrap();
+        let mut cancelled = make_task(&store, "cancelled", TaskPriority::Low);
+        cancelled.deadline = Some(now);
+        store.insert_task(&cancelled).unwrap();
+        store.cancel_task(cancelled.task_id, now).unwrap();
+        verify_index_consistency(&store);
+
+        let first = store
+            .claim_next_ordered("w-0", 300, now, 0, order)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.task_type, "critical");
+        let second = store
+            .claim_next_ordered("w-0", 300, now, 0, order)
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.task_type, "imminent");
+
+        // A retried task goes back into the deadline index.
+        store.fail_task(second.task_id, "boom", now, "w-0").unwrap();
+        verify_index_consistency(&store);
+        assert_eq!(store.deadline_idx.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_deadline_is_stored_beside_the_record() {
+        let (store, _dir) = test_store();
+        let mut task = make_task(&store, "dated", TaskPriority::Normal);
+        task.deadline = Some(1700000060);
+        store.insert_task(&task).unwrap();
+
+        // The record itself keeps the pre-deadline layout.
+        let raw = store
+            .tasks
+            .get(task.task_id.to_be_bytes())
+            .unwrap()
+            .unwrap();
+        let bare: TaskRecord = bincode::deserialize(raw.as_ref()).unwrap();
+        assert_eq!(bare.deadline, None);
+
+        let loaded = store.get_task(task.task_id).unwrap().unwrap();
+        assert_eq!(loaded.deadline, Some(1700000060));
+        let listed = store.list_tasks(None, None, 10, 0).unwrap();
+        assert_eq!(listed[0].deadline, Some(1700000060));
+
+        store.compact().unwrap();
+        verify_index_consistency(&store);
+        assert_eq!(store.deadline_idx.iter().count(), 1);
+    }
 }
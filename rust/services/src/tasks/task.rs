@@ -92,6 +92,37 @@ pub struct TaskRecord {
     pub completed_at: Option<u64>,
     pub progress_pct: u8,
     pub progress_message: Option<String>,
+    /// Unix time (ms) of the first submit, for the queue-wait histogram.
+    /// `None` on records written before these stamps existed.
+    pub submitted_at_ms: Option<u64>,
+    /// Unix time (ms) of the latest claim, for the run-time histogram.
+    pub claimed_at_ms: Option<u64>,
+    /// Absolute unix time (secs) the task should be claimed by. Only
+    /// consulted under [`ClaimOrder::Deadline`](super::priority::ClaimOrder).
+    ///
+    /// Not part of the encoded record, so records keep the layout they
+    /// had before deadlines existed: the store keeps deadlines in a
+    /// keyspace of their own and fills this in on read.
+    #[serde(skip)]
+    pub deadline: Option<u64>,
+}
+
+impl TaskRecord {
+    /// Decode a stored record.
+    ///
+    /// Records written before the millisecond stamps existed end right
+    /// after `progress_message`. They decode with both stamps `None`: each
+    /// absent `Option` is a single `0` tag byte, so the legacy bytes are
+    /// retried with those appended. (Older readers ignore the trailing
+    /// stamps of newer records, since bincode allows trailing bytes.)
+    pub fn decode(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize(bytes).or_else(|err| {
+            let mut padded = Vec::with_capacity(bytes.len() + 2);
+            padded.extend_from_slice(bytes);
+            padded.extend_from_slice(&[0, 0]);
+            bincode::deserialize(&padded).map_err(|_| err)
+        })
+    }
 }
 
 /// Aggregate queue statistics.
@@ -105,6 +136,24 @@ pub struct QueueStats {
     pub cancelled: usize,
 }
 
+/// Outcome of a task store compaction pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// On-disk size of all keyspaces before and after compaction.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Stale index entries dropped while rebuilding the indexes.
+    pub index_entries_removed: u64,
+    /// Missing or mismatched index entries written while rebuilding.
+    pub index_entries_added: u64,
+}
+
+impl CompactionStats {
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,15 +212,63 @@ mod tests {
             completed_at: None,
             progress_pct: 0,
             progress_message: None,
+            submitted_at_ms: Some(1700000000123),
+            claimed_at_ms: None,
+            deadline: Some(1700000060),
         };
 
         let bytes = bincode::serialize(&record).unwrap();
-        let decoded: TaskRecord = bincode::deserialize(&bytes).unwrap();
+        let decoded = TaskRecord::decode(&bytes).unwrap();
         assert_eq!(decoded.task_id, 42);
         assert_eq!(decoded.task_type, "test.echo");
         assert_eq!(decoded.params, vec![1, 2, 3]);
         assert_eq!(decoded.priority, TaskPriority::Normal);
         assert_eq!(decoded.status, TaskStatus::Pending);
         assert_eq!(decoded.max_retries, 3);
+        assert_eq!(decoded.submitted_at_ms, Some(1700000000123));
+        // The deadline is stored outside the record.
+        assert_eq!(decoded.deadline, None);
+        let without_deadline = TaskRecord {
+            deadline: None,
+            ..record
+        };
+        assert_eq!(bincode::serialize(&without_deadline).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_decode_legacy_record_without_ms_stamps() {
+        let record = TaskRecord {
+            task_id: 7,
+            task_type: "legacy".to_string(),
+            params: vec![],
+            priority: TaskPriority::Low,
+            status: TaskStatus::Running,
+            result: None,
+            error_message: None,
+            attempt: 1,
+            max_retries: 0,
+            created_at: 1700000000,
+            run_at: 1700000000,
+            claimed_at: Some(1700000005),
+            claimed_by: Some("w-0".to_string()),
+            lease_secs: 30,
+            completed_at: None,
+            progress_pct: 40,
+            progress_message: Some("halfway".to_string()),
+            submitted_at_ms: None,
+            claimed_at_ms: None,
+            deadline: None,
+        };
+        let mut bytes = bincode::serialize(&record).unwrap();
+        // Drop the two `None` tags: the layout before the stamps existed.
+        bytes.truncate(bytes.len() - 2);
+        assert!(bincode::deserialize::<TaskRecord>(&bytes).is_err());
+
+        let decoded = TaskRecord::decode(&bytes).unwrap();
+        assert_eq!(decoded.progress_message.as_deref(), Some("halfway"));
+        assert_eq!(decoded.claimed_at, Some(1700000005));
+        assert_eq!(decoded.submitted_at_ms, None);
+        assert_eq!(decoded.claimed_at_ms, None);
+        assert!(TaskRecord::decode(&bytes[..bytes.len() - 1]).is_err());
     }
 }
@@ -8,11 +8,13 @@ with pure-Python fallback implementations or None sentinels.
 
 from __future__ import annotations
 
+import functools
 import hashlib
 import mmap
 import os
 import posixpath
 import re
+import zlib
 from typing import Any
 
 # ---------------------------------------------------------------------------
@@ -282,21 +284,164 @@ class BloomFilter:
 # File I/O — pure Python implementations
 # ---------------------------------------------------------------------------
 
+_GZIP_MAGIC = b"\x1f\x8b"
+_ZSTD_MAGIC = b"\x28\xb5\x2f\xfd"
 
-def read_file(path: str) -> bytes | None:
-    """Read a file from disk, return None if missing or error."""
+_zstd_mod: Any = None
+# A valid gzip header over a mangled deflate stream raises ``zlib.error``,
+# which is none of the other three.
+_DECOMPRESS_ERRORS: tuple[type[Exception], ...] = (OSError, EOFError, ValueError, zlib.error)
+
+try:
+    from compression import zstd as _zstd
+
+    _zstd_mod = _zstd
+    _DECOMPRESS_ERRORS += (_zstd.ZstdError,)
+except ImportError:
+    pass
+
+
+def _maybe_decompress(data: bytes) -> bytes:
+    """Decompress gzip/zstd content detected by magic bytes.
+
+    Uncompressed data, zstd without stdlib support, and corrupt streams
+    fall back to the raw bytes.
+    """
+    try:
+        if data.startswith(_GZIP_MAGIC):
+            import gzip
+
+            return gzip.decompress(data)
+        if data.startswith(_ZSTD_MAGIC) and _zstd_mod is not None:
+            result: bytes = _zstd_mod.decompress(data)
+            return result
+    except _DECOMPRESS_ERRORS:
+        pass
+    return data
+
+
+def _maybe_decompress_capped(data: bytes, limit: int) -> bytes:
+    """:func:`_maybe_decompress` that stops inflating past ``limit`` bytes.
+
+    Returns at most ``limit + 1`` bytes, so a result longer than ``limit``
+    means the content does not fit, without the rest of a high-ratio
+    stream ever being allocated. Falls back to the raw bytes like
+    :func:`_maybe_decompress`, including for truncated streams.
+    """
+    new_decompressor: Any
+    if data.startswith(_GZIP_MAGIC):
+        new_decompressor = functools.partial(zlib.decompressobj, wbits=31)
+    elif data.startswith(_ZSTD_MAGIC) and _zstd_mod is not None:
+        new_decompressor = _zstd_mod.ZstdDecompressor
+    else:
+        return data
+    out = bytearray()
+    rest = data
+    try:
+        # One decompressor per gzip member / zstd frame.
+        while rest:
+            decompressor = new_decompressor()
+            out += decompressor.decompress(rest, limit + 1 - len(out))
+            if len(out) > limit:
+                return bytes(out)
+            if not decompressor.eof:
+                return data
+            rest = decompressor.unused_data
+    except _DECOMPRESS_ERRORS:
+        return data
+    return bytes(out)
+
+
+def read_file(path: str, *, decompress: bool = False) -> bytes | None:
+    """Read a file from disk, return None if missing or error.
+
+    With ``decompress=True``, gzip/zstd files (detected by magic bytes)
+    are returned decompressed; other files are returned as-is.
+    """
     try:
         with open(path, "rb") as f:
-            return f.read()
+            data = f.read()
     except OSError:
         return None
+    return _maybe_decompress(data) if decompress else data
 
 
-def read_files_bulk(paths: list[str]) -> dict[str, bytes | None]:
-    """Read multiple files, returning {path: content_or_None}."""
-    result: dict[str, bytes | None] = {}
-    for path in paths:
-        result[path] = read_file(path)
+class BulkRead(dict[str, bytes | None]):
+    """``{path: content_or_None}`` returned by :func:`read_files_bulk`.
+
+    ``skipped`` lists, in input order, the paths left unread because the
+    ``max_total_bytes`` budget ran out; they are absent from the mapping.
+    """
+
+    def __init__(self) -> None:
+        super().__init__()
+        self.skipped: list[str] = []
+
+    @property
+    def truncated(self) -> bool:
+        """True iff the byte budget stopped the read early."""
+        return bool(self.skipped)
+
+
+def read_files_bulk(
+    paths: list[str],
+    *,
+    decompress: bool = False,
+    max_total_bytes: int | None = None,
+) -> BulkRead:
+    """Read multiple files, returning {path: content_or_None}.
+
+    ``decompress`` behaves as in :func:`read_file`. Decompression runs on
+    a thread pool — zlib and zstd release the GIL while inflating.
+
+    ``max_total_bytes`` caps the bytes returned. Files are then read one
+    at a time in input order, and reading stops at the first file that
+    would take the total over the budget (measured after decompression,
+    which stops as soon as the budget is exceeded, so a small compressed
+    file cannot inflate past it). That file and every later path are
+    reported in ``skipped``. Unreadable files map to None and spend no
+    budget.
+    """
+    if max_total_bytes is not None:
+        return _read_files_budgeted(paths, max_total_bytes, decompress)
+
+    result = BulkRead()
+    if not decompress or len(paths) < 2:
+        result.update((path, read_file(path, decompress=decompress)) for path in paths)
+        return result
+
+    from concurrent.futures import ThreadPoolExecutor
+
+    workers = min(len(paths), os.cpu_count() or 1)
+    with ThreadPoolExecutor(max_workers=workers) as pool:
+        contents = pool.map(lambda p: read_file(p, decompress=True), paths)
+        result.update(zip(paths, contents, strict=True))
+    return result
+
+
+def _read_files_budgeted(paths: list[str], max_total_bytes: int, decompress: bool) -> BulkRead:
+    if max_total_bytes < 0:
+        raise ValueError(f"max_total_bytes must be non-negative, got {max_total_bytes}")
+    result = BulkRead()
+    remaining = max_total_bytes
+    for i, path in enumerate(paths):
+        try:
+            with open(path, "rb") as f:
+                if os.fstat(f.fileno()).st_size > remaining:
+                    result.skipped = paths[i:]
+                    break
+                # One byte past the budget notices a file that grew since fstat.
+                data = f.read(remaining + 1)
+        except OSError:
+            result[path] = None
+            continue
+        if decompress:
+            data = _maybe_decompress_capped(data, remaining)
+        if len(data) > remaining:
+            result.skipped = paths[i:]
+            break
+        remaining -= len(data)
+        result[path] = data
     return result
 
 
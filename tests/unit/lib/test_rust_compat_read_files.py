@@ -0,0 +1,162 @@
+"""Unit tests for nexus._rust_compat read_file / read_files_bulk.
+
+Covers the ``decompress`` mode: gzip/zstd detection by magic bytes with a
+raw-bytes fallback for uncompressed or corrupt files, and the
+``max_total_bytes`` budget.
+"""
+
+from __future__ import annotations
+
+import gzip
+import tracemalloc
+from pathlib import Path
+
+import pytest
+
+from nexus import _rust_compat
+from nexus._rust_compat import read_file, read_files_bulk
+
+CONTENT = b"def handler():\n    return 'TODO: compress me'\n" * 50
+
+
+class TestReadFilesBulkDecompress:
+    def test_gzip_and_plain_in_one_call(self, tmp_path: Path) -> None:
+        gz = tmp_path / "a.py.gz"
+        gz.write_bytes(gzip.compress(CONTENT))
+        plain = tmp_path / "b.py"
+        plain.write_bytes(CONTENT)
+        missing = tmp_path / "missing"
+
+        result = read_files_bulk([str(gz), str(plain), str(missing)], decompress=True)
+
+        assert result == {str(gz): CONTENT, str(plain): CONTENT, str(missing): None}
+
+    def test_default_returns_raw_bytes(self, tmp_path: Path) -> None:
+        gz = tmp_path / "a.gz"
+        compressed = gzip.compress(CONTENT)
+        gz.write_bytes(compressed)
+
+        assert read_file(str(gz)) == compressed
+        assert read_files_bulk([str(gz)]) == {str(gz): compressed}
+
+    def test_corrupt_gzip_falls_back_to_raw(self, tmp_path: Path) -> None:
+        bad = tmp_path / "bad.gz"
+        raw = b"\x1f\x8b not really gzip"
+        bad.write_bytes(raw)
+
+        assert read_file(str(bad), decompress=True) == raw
+
+    def test_corrupt_deflate_body_falls_back_to_raw(self, tmp_path: Path) -> None:
+        compressed = bytearray(gzip.compress(CONTENT))
+        # Keep the magic and 10-byte header, mangle the deflate stream.
+        compressed[12:30] = b"\xff" * 18
+        bad = tmp_path / "bad.gz"
+        bad.write_bytes(bytes(compressed))
+        plain = tmp_path / "ok.txt"
+        plain.write_bytes(b"plain")
+
+        result = read_files_bulk([str(bad), str(plain)], decompress=True)
+
+        assert result == {str(bad): bytes(compressed), str(plain): b"plain"}
+
+    @pytest.mark.skipif(_rust_compat._zstd_mod is None, reason="compression.zstd unavailable")
+    def test_zstd(self, tmp_path: Path) -> None:
+        zst = tmp_path / "a.zst"
+        zst.write_bytes(_rust_compat._zstd_mod.compress(CONTENT))
+        plain = tmp_path / "b.txt"
+        plain.write_bytes(b"plain")
+
+        result = read_files_bulk([str(zst), str(plain)], decompress=True)
+
+        assert result == {str(zst): CONTENT, str(plain): b"plain"}
+
+
+class TestReadFilesBulkBudget:
+    def _write(self, tmp_path: Path, sizes: list[int]) -> list[str]:
+        paths = []
+        for i, size in enumerate(sizes):
+            path = tmp_path / f"f{i}"
+            path.write_bytes(bytes([ord("a") + i]) * size)
+            paths.append(str(path))
+        return paths
+
+    def test_budget_stops_early_and_reports_skipped(self, tmp_path: Path) -> None:
+        paths = self._write(tmp_path, [10, 20, 30, 5])
+
+        result = read_files_bulk(paths, max_total_bytes=35)
+
+        assert result == {paths[0]: b"a" * 10, paths[1]: b"b" * 20}
+        # The 5-byte file would fit, but reading stops at the first
+        # overflow so the result stays a prefix of the input.
+        assert result.truncated
+        assert result.skipped == paths[2:]
+
+        exact = read_files_bulk(paths, max_total_bytes=65)
+        assert not exact.truncated
+        assert len(exact) == 4
+
+        none = read_files_bulk(paths, max_total_bytes=0)
+        assert none == {}
+        assert none.skipped == paths
+
+    def test_unreadable_files_do_not_spend_budget(self, tmp_path: Path) -> None:
+        paths = self._write(tmp_path, [8, 8])
+        missing = str(tmp_path / "missing")
+
+        result = read_files_bulk([paths[0], missing, paths[1]], max_total_bytes=16)
+
+        assert result == {paths[0]: b"a" * 8, missing: None, paths[1]: b"b" * 8}
+        assert not result.truncated
+
+    def test_budget_counts_decompressed_bytes(self, tmp_path: Path) -> None:
+        gz = tmp_path / "a.gz"
+        gz.write_bytes(gzip.compress(CONTENT))
+        assert gz.stat().st_size < len(CONTENT)
+
+        small = read_files_bulk([str(gz)], decompress=True, max_total_bytes=len(CONTENT) - 1)
+        assert small.skipped == [str(gz)]
+
+        fits = read_files_bulk([str(gz)], decompress=True, max_total_bytes=len(CONTENT))
+        assert fits == {str(gz): CONTENT}
+
+    def test_budget_caps_decompression_memory(self, tmp_path: Path) -> None:
+        bomb = tmp_path / "bomb.gz"
+        bomb.write_bytes(gzip.compress(b"\0" * (64 << 20)))
+        assert bomb.stat().st_size < 1 << 20
+
+        tracemalloc.start()
+        try:
+            result = read_files_bulk([str(bomb)], decompress=True, max_total_bytes=1 << 20)
+            _, peak = tracemalloc.get_traced_memory()
+        finally:
+            tracemalloc.stop()
+
+        assert result.skipped == [str(bomb)]
+        assert peak < 8 << 20
+
+    def test_capped_decompression_reads_every_gzip_member(self, tmp_path: Path) -> None:
+        gz = tmp_path / "multi.gz"
+        gz.write_bytes(gzip.compress(b"one ") + gzip.compress(b"two"))
+
+        budget = gz.stat().st_size
+        result = read_files_bulk([str(gz)], decompress=True, max_total_bytes=budget)
+        assert result == {str(gz): b"one two"}
+
+        # A truncated stream falls back to the raw bytes, as without a budget.
+        cut = tmp_path / "cut.gz"
+        cut.write_bytes(gzip.compress(CONTENT)[:-12])
+        raw = cut.read_bytes()
+        assert read_files_bulk([str(cut)], decompress=True, max_total_bytes=len(CONTENT)) == {
+            str(cut): raw
+        }
+        assert read_file(str(cut), decompress=True) == raw
+
+    def test_negative_budget_is_rejected(self) -> None:
+        with pytest.raises(ValueError):
+            read_files_bulk([], max_total_bytes=-1)
+
+    def test_unbudgeted_read_is_never_truncated(self, tmp_path: Path) -> None:
+        paths = self._write(tmp_path, [4])
+        result = read_files_bulk(paths)
+        assert result == {paths[0]: b"aaaa"}
+        assert not result.truncated